@@ -28,6 +28,7 @@ from .decorators import RaisesContext as RaisesContext
 
 # Re-export reporting types
 from .reporting import CollectionError as CollectionError
+from .reporting import TeardownError as TeardownError
 
 fixture = decorators.fixture
 mark = decorators.mark
@@ -49,6 +50,7 @@ __all__ = [
     # Reporting types
     "CollectionError",
     "RunReport",
+    "TeardownError",
     "TestResult",
     # Fixture types
     "Cache",
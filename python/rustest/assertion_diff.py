@@ -0,0 +1,99 @@
+"""Structural diff of the operands of a failed ``==`` assertion.
+
+When both sides of a failing equality are dicts or lists, the runner calls
+:func:`structural_diff` and shows each difference on its own line, keyed by
+the path to it (``['user']['address']['city']``). Nesting deeper than
+``max_depth`` is compared as a whole, and the walk stops after the first
+``max_lines`` differences, so a mismatch between two large payloads stays
+readable and cheap to compute.
+"""
+
+from __future__ import annotations
+
+from collections.abc import Mapping, Sequence
+from typing import Any
+
+_MAX_REPR = 80
+
+
+def is_container(value: Any) -> bool:
+    """Whether ``value`` is diffed structurally rather than by its repr."""
+    return isinstance(value, (Mapping, list, tuple))
+
+
+def structural_diff(
+    expected: Any, actual: Any, max_depth: int = 8, max_lines: int = 20
+) -> list[str]:
+    """Return one line per difference between ``expected`` and ``actual``."""
+    collector = _Collector(max_depth, max_lines)
+    try:
+        collector.diff(expected, actual, "", 0)
+    except _LimitReached:
+        collector.lines.append("... more differences not shown")
+    return collector.lines
+
+
+class _LimitReached(Exception):
+    pass
+
+
+class _Collector:
+    def __init__(self, max_depth: int, max_lines: int) -> None:
+        self.max_depth = max_depth
+        self.max_lines = max_lines
+        self.lines: list[str] = []
+
+    def add(self, line: str) -> None:
+        if len(self.lines) == self.max_lines:
+            raise _LimitReached
+        self.lines.append(line)
+
+    def diff(self, expected: Any, actual: Any, path: str, depth: int) -> None:
+        nested = depth < self.max_depth
+        if nested and isinstance(expected, Mapping) and isinstance(actual, Mapping):
+            for key in expected:
+                child = f"{path}[{key!r}]"
+                if key in actual:
+                    self.diff(expected[key], actual[key], child, depth + 1)
+                else:
+                    self.add(f"{child}: missing, expected {_short_repr(expected[key])}")
+            for key in actual:
+                if key not in expected:
+                    self.add(f"{path}[{key!r}]: unexpected {_short_repr(actual[key])}")
+            return
+
+        if nested and _is_sequence(expected) and _is_sequence(actual):
+            for index, (left, right) in enumerate(zip(expected, actual)):
+                self.diff(left, right, f"{path}[{index}]", depth + 1)
+            if len(expected) != len(actual):
+                self.add(
+                    f"{path or '(root)'}: expected length {len(expected)}, got {len(actual)}"
+                )
+                for index in range(len(actual), len(expected)):
+                    self.add(f"{path}[{index}]: missing, expected {_short_repr(expected[index])}")
+                for index in range(len(expected), len(actual)):
+                    self.add(f"{path}[{index}]: unexpected {_short_repr(actual[index])}")
+            return
+
+        try:
+            equal = bool(expected == actual)
+        except Exception:
+            equal = False
+        if not equal:
+            self.add(
+                f"{path or '(root)'}: expected {_short_repr(expected)}, got {_short_repr(actual)}"
+            )
+
+
+def _is_sequence(value: Any) -> bool:
+    return isinstance(value, Sequence) and not isinstance(value, (str, bytes, bytearray))
+
+
+def _short_repr(value: Any) -> str:
+    try:
+        text = repr(value)
+    except Exception:
+        return "<unrepresentable>"
+    if len(text) > _MAX_REPR:
+        return text[: _MAX_REPR - 3] + "..."
+    return text
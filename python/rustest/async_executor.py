@@ -12,10 +12,13 @@ Architecture:
 - Tests with function scope: Cannot benefit from parallelism (each needs own loop)
 - Tests with class/module/session scope: Can batch within that scope
 
-Limitations:
-- stdout/stderr capture may interleave between tests since all coroutines run
-  on the same thread and output redirection uses thread-local storage. Output
-  captured per-test is best-effort when tests print at overlapping await points.
+Output capture:
+- All coroutines run on the same thread, so swapping sys.stdout per test would
+  mix the output of tests printing at overlapping await points. Instead, while
+  a batch runs, sys.stdout and sys.stderr are replaced once by streams that
+  write to the buffers of the current task, held in a context variable. Each
+  gathered test runs in its own task with its own context, so it captures only
+  its own output, including output of tasks it spawns.
 
 This module is called from Rust via PyO3 when a batch of async tests is ready.
 """
@@ -23,11 +26,44 @@ This module is called from Rust via PyO3 when a batch of async tests is ready.
 from __future__ import annotations
 
 import asyncio
-import contextlib
+import contextvars
 import io
+import sys
 import time
 import traceback
-from typing import Any, Coroutine
+from typing import Any, Coroutine, TextIO
+
+# Capture buffers (stdout, stderr) of the test running in the current task
+_task_capture: contextvars.ContextVar[tuple[io.StringIO, io.StringIO] | None] = (
+    contextvars.ContextVar("rustest_task_capture", default=None)
+)
+
+
+class _TaskRoutedStream:
+    """Stream that writes to the capture buffer of the current task.
+
+    Output written outside any gathered test goes to the stream it replaced.
+    """
+
+    def __init__(self, fallback: TextIO, index: int) -> None:
+        self._fallback = fallback
+        self._index = index
+
+    def _target(self) -> TextIO:
+        buffers = _task_capture.get()
+        return self._fallback if buffers is None else buffers[self._index]
+
+    def write(self, text: str) -> int:
+        return self._target().write(text)
+
+    def writelines(self, lines: Any) -> None:
+        self._target().writelines(lines)
+
+    def flush(self) -> None:
+        self._target().flush()
+
+    def __getattr__(self, name: str) -> Any:
+        return getattr(self._fallback, name)
 
 
 async def _wrap_test_for_gather(
@@ -60,14 +96,10 @@ async def _wrap_test_for_gather(
         coro = asyncio.wait_for(coro, timeout=timeout)
 
     try:
-        if capture_output:
-            with (
-                contextlib.redirect_stdout(stdout_capture),
-                contextlib.redirect_stderr(stderr_capture),
-            ):
-                await coro
-        else:
-            await coro
+        if stdout_capture is not None and stderr_capture is not None:
+            # Only affects this task's context, see the module docstring
+            _ = _task_capture.set((stdout_capture, stderr_capture))
+        await coro
 
         duration = time.perf_counter() - start_time
         return {
@@ -143,7 +175,16 @@ def run_coroutines_parallel(
         # unexpectedly (e.g., in the wrapper before try block)
         return await asyncio.gather(*tasks, return_exceptions=True)
 
-    raw_results = event_loop.run_until_complete(run_all())
+    if capture_output:
+        original_stdout, original_stderr = sys.stdout, sys.stderr
+        sys.stdout = _TaskRoutedStream(original_stdout, 0)
+        sys.stderr = _TaskRoutedStream(original_stderr, 1)
+        try:
+            raw_results = event_loop.run_until_complete(run_all())
+        finally:
+            sys.stdout, sys.stderr = original_stdout, original_stderr
+    else:
+        raw_results = event_loop.run_until_complete(run_all())
 
     # Convert any unexpected exceptions to result dictionaries
     results: list[dict[str, Any]] = []
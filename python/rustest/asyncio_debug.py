@@ -0,0 +1,62 @@
+"""Per-test record of asyncio warnings for ``--asyncio-debug``.
+
+The runner creates a :class:`TaskWarningRecorder` right before it drives an
+async test on its event loop and calls :meth:`TaskWarningRecorder.finish`
+once the test coroutine is done. Anything asyncio complained about in between
+is attributed to that test: tasks the test started but left pending,
+coroutines that were never awaited, and messages logged by asyncio's debug
+mode (such as slow callbacks).
+"""
+
+from __future__ import annotations
+
+import asyncio
+import gc
+import logging
+import warnings
+from typing import Any
+
+
+class _ListHandler(logging.Handler):
+    def __init__(self, messages: list[str]) -> None:
+        super().__init__(level=logging.WARNING)
+        self._messages = messages
+
+    def emit(self, record: logging.LogRecord) -> None:
+        self._messages.append(record.getMessage().splitlines()[0])
+
+
+class TaskWarningRecorder:
+    """Enable debug mode on ``loop`` and record what asyncio warns about."""
+
+    def __init__(self, loop: asyncio.AbstractEventLoop) -> None:
+        self._loop = loop
+        self._was_debug = loop.get_debug()
+        self._tasks_before = set(asyncio.all_tasks(loop))
+        self._messages: list[str] = []
+        self._handler = _ListHandler(self._messages)
+        self._logger = logging.getLogger("asyncio")
+        self._catcher = warnings.catch_warnings(record=True)
+
+        loop.set_debug(True)
+        self._logger.addHandler(self._handler)
+        recorded = self._catcher.__enter__()
+        self._recorded: list[Any] = recorded if recorded is not None else []
+        warnings.simplefilter("always")
+
+    def finish(self) -> list[str]:
+        """Stop recording and return the warnings, one line each."""
+        for task in asyncio.all_tasks(self._loop) - self._tasks_before:
+            if not task.done():
+                self._messages.append(f"Task was still pending when the test finished: {task!r}")
+        # Unawaited coroutines only warn once they are garbage collected
+        _ = gc.collect()
+        self._catcher.__exit__(None, None, None)
+        self._logger.removeHandler(self._handler)
+        self._loop.set_debug(self._was_debug)
+
+        for warning in self._recorded:
+            if issubclass(warning.category, (RuntimeWarning, ResourceWarning)):
+                first_line = str(warning.message).splitlines()[0]
+                self._messages.append(f"{warning.category.__name__}: {first_line}")
+        return self._messages
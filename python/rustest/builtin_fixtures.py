@@ -4,12 +4,15 @@
 
 from __future__ import annotations
 
+import datetime
 import importlib
+import io
 import itertools
 import os
 import shutil
 import sys
 import tempfile
+import time
 from collections.abc import Generator, MutableMapping
 from contextlib import contextmanager
 from pathlib import Path
@@ -317,6 +320,66 @@ def monkeypatch() -> Iterator[MonkeyPatch]:
         patch.undo()
 
 
+def _frozen_instant(value: Any) -> datetime.datetime:
+    """Convert a ``freeze_time`` mark argument to an aware datetime.
+
+    Accepts a datetime, an ISO 8601 string, or a Unix timestamp. Naive values
+    are taken as UTC so a frozen test behaves the same on every machine.
+    """
+    if isinstance(value, (int, float)):
+        return datetime.datetime.fromtimestamp(value, datetime.timezone.utc)
+    if isinstance(value, str):
+        value = datetime.datetime.fromisoformat(value)
+    if not isinstance(value, datetime.datetime):
+        raise TypeError(
+            f"freeze_time expects a datetime, ISO 8601 string or timestamp, got {value!r}"
+        )
+    if value.tzinfo is None:
+        value = value.replace(tzinfo=datetime.timezone.utc)
+    return value
+
+
+@fixture(scope="function")
+def freeze_time(request: Any) -> Iterator[datetime.datetime]:
+    """Freeze ``time.time()`` and ``datetime.datetime.now()`` for one test.
+
+    The instant comes from ``@mark.freeze_time(...)`` on the test, and
+    defaults to the moment the fixture is set up. Yields the frozen instant
+    as an aware UTC datetime; the real clock is restored on teardown.
+    """
+    marker = request.node.get_closest_marker("freeze_time")
+    if marker is not None and marker.args:
+        instant = _frozen_instant(marker.args[0])
+    else:
+        instant = datetime.datetime.now(datetime.timezone.utc)
+    timestamp = instant.timestamp()
+    real_datetime = datetime.datetime
+
+    class FrozenDatetime(real_datetime):
+        @classmethod
+        def now(cls, tz: datetime.tzinfo | None = None) -> datetime.datetime:
+            return real_datetime.fromtimestamp(timestamp, tz)
+
+        @classmethod
+        def utcnow(cls) -> datetime.datetime:
+            return real_datetime.fromtimestamp(timestamp, datetime.timezone.utc).replace(
+                tzinfo=None
+            )
+
+        @classmethod
+        def today(cls) -> datetime.datetime:
+            return real_datetime.fromtimestamp(timestamp)
+
+    patch = MonkeyPatch()
+    patch.setattr(time, "time", lambda: timestamp)
+    patch.setattr(time, "time_ns", lambda: int(timestamp * 1_000_000_000))
+    patch.setattr(datetime, "datetime", FrozenDatetime)
+    try:
+        yield instant
+    finally:
+        patch.undo()
+
+
 @fixture(scope="function")
 def request() -> Any:
     """Pytest-compatible request fixture for fixture parametrization.
@@ -327,12 +390,12 @@ def request() -> Any:
     **Supported:**
         - request.param: Current parameter value for parametrized fixtures
         - request.scope: Returns "function"
+        - request.addfinalizer(): Runs a callable at the requester's teardown
         - Type annotations: request: pytest.FixtureRequest
 
     **Not supported (returns None or raises NotImplementedError):**
         - request.node, function, cls, module, config
         - request.fixturename
-        - Methods: addfinalizer(), getfixturevalue()
 
     Example:
         @fixture(params=[1, 2, 3])
@@ -352,28 +415,41 @@ def request() -> Any:
     return FixtureRequest()
 
 
+# Buffers the running test's stdout and stderr are redirected to, replaced by
+# the Rust core before each test's fixtures resolve; None without capture.
+_test_capture: tuple[io.StringIO, io.StringIO] | None = None
+
+
+def _begin_test_capture(enabled: bool) -> tuple[io.StringIO, io.StringIO] | None:
+    """Create the capture buffers for the next test (called by the Rust core)."""
+    global _test_capture
+    _test_capture = (io.StringIO(), io.StringIO()) if enabled else None
+    return _test_capture
+
+
 class CaptureFixture:
     """Fixture to capture stdout and stderr.
 
-    This implements pytest's capsys fixture functionality.
+    This implements pytest's capsys fixture functionality. Given ``buffers``,
+    it reads from streams someone else redirects output to instead of
+    replacing ``sys.stdout`` and ``sys.stderr`` itself.
     """
 
-    def __init__(self) -> None:
-        import io
-
+    def __init__(self, buffers: tuple[io.StringIO, io.StringIO] | None = None) -> None:
         super().__init__()
         self._capture_out: list[str] = []
         self._capture_err: list[str] = []
         self._original_stdout = sys.stdout
         self._original_stderr = sys.stderr
-        self._capturing = False
-        self._stdout_buffer: io.StringIO = io.StringIO()
-        self._stderr_buffer: io.StringIO = io.StringIO()
+        self._shared = buffers is not None
+        self._capturing = self._shared
+        self._stdout_buffer: io.StringIO = buffers[0] if buffers else io.StringIO()
+        self._stderr_buffer: io.StringIO = buffers[1] if buffers else io.StringIO()
 
     def start_capture(self) -> None:
         """Start capturing stdout and stderr."""
-        import io
-
+        if self._shared:
+            return
         self._stdout_buffer = io.StringIO()
         self._stderr_buffer = io.StringIO()
         sys.stdout = self._stdout_buffer
@@ -382,7 +458,7 @@ class CaptureFixture:
 
     def stop_capture(self) -> None:
         """Stop capturing and restore original streams."""
-        if self._capturing:
+        if self._capturing and not self._shared:
             sys.stdout = self._original_stdout
             sys.stderr = self._original_stderr
             self._capturing = False
@@ -399,9 +475,14 @@ class CaptureFixture:
         out = self._stdout_buffer.getvalue()
         err = self._stderr_buffer.getvalue()
 
-        # Reset the buffers
-        import io
+        if self._shared:
+            # Output keeps flowing into the same streams, so empty them in place
+            for buffer in (self._stdout_buffer, self._stderr_buffer):
+                buffer.seek(0)
+                buffer.truncate()
+            return CaptureResult(out, err)
 
+        # Reset the buffers
         self._stdout_buffer = io.StringIO()
         self._stderr_buffer = io.StringIO()
         sys.stdout = self._stdout_buffer
@@ -426,18 +507,17 @@ def capsys() -> Generator[CaptureFixture, None, None]:
     returns a (out, err) tuple. out and err are strings containing the
     captured output.
 
+    It reads the same buffers rustest captures the test's output into, and
+    output it returns no longer appears in the test's reported stdout/stderr.
+    Requires output capture; raises RuntimeError under ``--no-capture``.
+
     Example:
         def test_output(capsys):
             print("hello")
             captured = capsys.readouterr()
             assert captured.out == "hello\\n"
     """
-    capture = CaptureFixture()
-    capture.start_capture()
-    try:
-        yield capture
-    finally:
-        capture.stop_capture()
+    yield _shared_capture("capsys")
 
 
 @fixture
@@ -450,12 +530,18 @@ def capfd() -> Generator[CaptureFixture, None, None]:
     """
     # For simplicity, capfd is implemented the same as capsys
     # A true file descriptor capture would require more complex handling
-    capture = CaptureFixture()
-    capture.start_capture()
-    try:
-        yield capture
-    finally:
-        capture.stop_capture()
+    yield _shared_capture("capfd")
+
+
+def _shared_capture(name: str) -> CaptureFixture:
+    """A CaptureFixture over the running test's capture buffers."""
+    if _test_capture is None:
+        msg = (
+            f"The '{name}' fixture requires output capture, which is disabled "
+            "(--no-capture / capture_output=False)."
+        )
+        raise RuntimeError(msg)
+    return CaptureFixture(_test_capture)
 
 
 class LogRecord(NamedTuple):
@@ -470,6 +556,34 @@ class LogRecord(NamedTuple):
     exc_info: Any
 
 
+# Level caplog captures at unless a test changes it; 0 means DEBUG
+_caplog_level: int = 0
+
+
+def _configure_log_capture(level: str | None) -> None:
+    """Set the default caplog level for the run (called by the Rust core)."""
+    global _caplog_level
+    if level is None:
+        _caplog_level = 0
+        return
+    _caplog_level = _parse_log_level(level)
+
+
+def _parse_log_level(level: int | str) -> int:
+    """Convert a level name or number (``"INFO"``, ``"20"``, ``20``) to a number."""
+    import logging
+
+    if isinstance(level, int):
+        return level
+    if level.strip().isdigit():
+        return int(level)
+    number = logging.getLevelName(level.strip().upper())
+    if not isinstance(number, int):
+        msg = f"Unknown log level: {level!r}"
+        raise ValueError(msg)
+    return number
+
+
 class LogCaptureFixture:
     """Fixture to capture logging output.
 
@@ -501,11 +615,11 @@ class LogCaptureFixture:
                 self.records.append(record)
 
         self._handler = ListHandler(self._records)
-        self._handler.setLevel(logging.DEBUG)
+        self._handler.setLevel(logging.NOTSET)
         self._logger.addHandler(self._handler)
         self._old_level = self._logger.level
-        # Set to DEBUG to capture all messages
-        self._logger.setLevel(logging.DEBUG)
+        # Capture from the run's --log-level, or every message by default
+        self._logger.setLevel(_caplog_level or logging.DEBUG)
 
     def stop_capture(self) -> None:
         """Stop capturing log messages."""
@@ -1251,6 +1365,7 @@ __all__ = [
     "caplog",
     "capsys",
     "capfd",
+    "freeze_time",
     "mocker",
     "monkeypatch",
     "pytestconfig",
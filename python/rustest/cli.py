@@ -7,6 +7,7 @@ import os
 from collections.abc import Sequence
 
 from .core import run
+from .reporting import RunReport
 
 
 def is_ci_environment() -> bool:
@@ -49,6 +50,41 @@ def is_ci_environment() -> bool:
     return any(os.getenv(var) for var in ci_vars)
 
 
+def failure_ratio(value: str) -> float:
+    """Parse a failure ratio between 0 and 1 for ``--allow-failure-ratio``."""
+    try:
+        ratio = float(value)
+    except ValueError:
+        raise argparse.ArgumentTypeError(f"invalid ratio: {value!r}") from None
+    if not 0.0 <= ratio <= 1.0:
+        raise argparse.ArgumentTypeError(f"ratio must be between 0 and 1, got {value}")
+    return ratio
+
+
+def exit_code(report: RunReport, allow_failure_ratio: float | None = None) -> int:
+    """Exit code for a finished run, matching pytest.
+
+    0 = all tests passed
+    1 = some tests failed, errored during fixture setup, or a fixture
+        teardown raised
+    2 = collection errors (syntax errors, import errors, etc.) or the run
+        was interrupted with Ctrl+C
+
+    With ``allow_failure_ratio``, failures and errors make up at most that
+    share of the tests still exit 0; they are reported all the same.
+    """
+    if len(report.collection_errors) > 0 or report.interrupted:
+        return 2
+    if report.teardown_errors:
+        return 1
+    failures = report.failed + report.errors
+    if failures == 0:
+        return 0
+    if allow_failure_ratio is not None and failures / report.total <= allow_failure_ratio:
+        return 0
+    return 1
+
+
 def build_parser() -> argparse.ArgumentParser:
     parser = argparse.ArgumentParser(
         prog="rustest",
@@ -63,7 +99,10 @@ def build_parser() -> argparse.ArgumentParser:
     _ = parser.add_argument(
         "-k",
         "--pattern",
-        help="Substring to filter tests by (case insensitive).",
+        help=(
+            "Keyword expression to filter tests by (case insensitive), "
+            'e.g. "login", "login and not slow".'
+        ),
     )
     _ = parser.add_argument(
         "-m",
@@ -75,7 +114,13 @@ def build_parser() -> argparse.ArgumentParser:
         "-n",
         "--workers",
         type=int,
-        help="Number of worker slots to use (experimental).",
+        help="Run tests in this many worker processes, each taking whole files (default: in-process).",
+    )
+    _ = parser.add_argument(
+        "--maxprocesses",
+        dest="max_processes",
+        type=int,
+        help="Upper bound on the number of worker slots, including auto-detected ones.",
     )
     _ = parser.add_argument(
         "--no-capture",
@@ -89,6 +134,11 @@ def build_parser() -> argparse.ArgumentParser:
         action="store_true",
         help="Show verbose output with hierarchical test structure.",
     )
+    _ = parser.add_argument(
+        "--compact",
+        action="store_true",
+        help="Print one summary line per test file (useful for CI logs).",
+    )
     _ = parser.add_argument(
         "--ascii",
         action="store_true",
@@ -98,7 +148,16 @@ def build_parser() -> argparse.ArgumentParser:
         "--color",
         choices=["auto", "always", "never"],
         default="auto",
-        help="When to use colored output: auto (default, detect CI), always, or never.",
+        help=(
+            "When to use colored output: auto (default, detect a terminal, "
+            "off in CI unless FORCE_COLOR is set), always, or never."
+        ),
+    )
+    _ = parser.add_argument(
+        "--max-name-width",
+        type=int,
+        help="Truncate long test names in the middle to this many characters "
+        "(defaults to the terminal width).",
     )
     _ = parser.add_argument(
         "--no-codeblocks",
@@ -137,6 +196,267 @@ def build_parser() -> argparse.ArgumentParser:
             "from pytest."
         ),
     )
+    _ = parser.add_argument(
+        "--trace-config",
+        action="store_true",
+        dest="trace_config",
+        help="Log which files were considered during discovery and why, plus sys.path additions.",
+    )
+    _ = parser.add_argument(
+        "--max-file-size",
+        type=int,
+        help="Skip collecting test files larger than this many bytes, with a warning.",
+    )
+    _ = parser.add_argument(
+        "--full-trace",
+        action="store_true",
+        dest="full_trace",
+        help="Show library (stdlib and site-packages) frames in failure tracebacks.",
+    )
+    _ = parser.add_argument(
+        "--continue-on-collection-errors",
+        action="store_true",
+        dest="continue_on_collection_errors",
+        help="Run the collected tests even if some files failed to collect (default).",
+    )
+    _ = parser.add_argument(
+        "--no-continue-on-collection-errors",
+        action="store_false",
+        dest="continue_on_collection_errors",
+        help="Abort before running any test if a file failed to collect.",
+    )
+    _ = parser.add_argument(
+        "--check",
+        action="store_true",
+        dest="check",
+        help=(
+            "Validate the fixture graph of every collected test (missing fixtures, "
+            "cycles, scope violations) without running anything."
+        ),
+    )
+    _ = parser.add_argument(
+        "--sample",
+        type=int,
+        metavar="N",
+        help=(
+            "Run a random sample of up to N collected tests. Samples can split a "
+            "class; fixtures of every scope still work for the tests that run."
+        ),
+    )
+    _ = parser.add_argument(
+        "--shuffle",
+        action="store_true",
+        dest="shuffle",
+        help=(
+            "Run files, and the tests within each file, in a random order. Tests from "
+            "different files never interleave. Reproduce an order with --shuffle-seed."
+        ),
+    )
+    _ = parser.add_argument(
+        "--shuffle-seed",
+        type=int,
+        metavar="SEED",
+        help="Seed for --sample and --shuffle, to reproduce a previous run.",
+    )
+    _ = parser.add_argument(
+        "--github-annotations",
+        action="store_true",
+        dest="github_annotations",
+        help="Print failures as GitHub Actions annotations so they show inline on pull requests.",
+    )
+    _ = parser.add_argument(
+        "--tap",
+        dest="tap_output",
+        metavar="PATH",
+        help="Write a TAP version 13 report to PATH after the run ('-' for stdout).",
+    )
+    _ = parser.add_argument(
+        "--wrap-failures",
+        action="store_true",
+        dest="wrap_failures",
+        help="Wrap long lines in failure messages to the terminal width.",
+    )
+    _ = parser.add_argument(
+        "--passed-only",
+        dest="passed_only",
+        metavar="REPORT",
+        help="Run only the tests that passed in a previous JSON report.",
+    )
+    _ = parser.add_argument(
+        "--discovery-threads",
+        type=int,
+        metavar="N",
+        help="Read test files ahead on N threads during discovery (helps on slow file systems).",
+    )
+    _ = parser.add_argument(
+        "--run-slow",
+        action="store_true",
+        dest="run_slow",
+        help="Run tests marked 'slow', which are skipped by default unless -m is given.",
+    )
+    _ = parser.add_argument(
+        "--enrich-exclude",
+        action="append",
+        dest="enrich_exclude",
+        metavar="GLOB",
+        help=(
+            "Show plain tracebacks for assertion failures in files matching GLOB, "
+            "without evaluating Expected/Received values. May be repeated."
+        ),
+    )
+    _ = parser.add_argument(
+        "--resume",
+        action="store_true",
+        dest="resume",
+        help="Continue an interrupted run, skipping tests it already completed.",
+    )
+    _ = parser.add_argument(
+        "--xfail-list",
+        dest="xfail_list",
+        metavar="FILE",
+        help="Treat failures of the node ids listed in FILE as expected (xfail).",
+    )
+    _ = parser.add_argument(
+        "--line-trace",
+        action="store_true",
+        dest="line_trace",
+        help="Record the lines each test executes to .rustest_cache/line_trace.json (slow).",
+    )
+    _ = parser.add_argument(
+        "--sqlite",
+        dest="sqlite_path",
+        metavar="PATH",
+        help="Append test results to the SQLite database at PATH.",
+    )
+    _ = parser.add_argument(
+        "--changed-lines",
+        dest="changed_lines",
+        nargs="?",
+        const="HEAD",
+        metavar="REV",
+        help="Run only tests whose lines, or the code they import, changed since REV (default: HEAD).",
+    )
+    _ = parser.add_argument(
+        "--slowest-files",
+        dest="slowest_files",
+        type=int,
+        metavar="N",
+        help="Print the N test files that took longest in total after the run.",
+    )
+    _ = parser.add_argument(
+        "--asyncio-debug",
+        action="store_true",
+        dest="asyncio_debug",
+        help="Enable asyncio debug mode for async tests and report pending-task warnings.",
+    )
+    _ = parser.add_argument(
+        "--trace-json",
+        dest="trace_json",
+        metavar="PATH",
+        help="Write collection, test and fixture timings as a Chrome trace to PATH.",
+    )
+    _ = parser.add_argument(
+        "--group-failures",
+        action="store_true",
+        dest="group_failures",
+        help="Print identical failures once in the summary, listing every affected test.",
+    )
+    _ = parser.add_argument(
+        "--no-loop-scope-strict",
+        action="store_false",
+        dest="loop_scope_strict",
+        help="Widen a too-narrow explicit loop_scope to what the test's async fixtures need, with a warning, instead of failing the test.",
+    )
+    _ = parser.add_argument(
+        "--notebooks",
+        action="store_true",
+        dest="notebooks",
+        help="Also collect tests from test_*.ipynb and *_test.ipynb notebooks.",
+    )
+    _ = parser.add_argument(
+        "--junit-xml",
+        dest="junit_xml_path",
+        metavar="PATH",
+        help="Write a JUnit XML report of the run to PATH.",
+    )
+    _ = parser.add_argument(
+        "--json-report",
+        dest="json_report_path",
+        metavar="PATH",
+        help="Write a JSON report of the run to PATH.",
+    )
+    _ = parser.add_argument(
+        "--allow-failure-ratio",
+        type=failure_ratio,
+        dest="allow_failure_ratio",
+        metavar="RATIO",
+        help="Exit 0 when at most this share (0-1) of the tests failed or errored.",
+    )
+    _ = parser.add_argument(
+        "--durations",
+        dest="durations",
+        type=int,
+        metavar="N",
+        help="Print the N slowest tests after the run (0 for all).",
+    )
+    _ = parser.add_argument(
+        "--failure-exception",
+        action="append",
+        dest="failure_exceptions",
+        metavar="NAME",
+        help=(
+            "Count tests raising exception class NAME (or a subclass) as failed and any "
+            "other exception as an error. AssertionError always counts as a failure. "
+            "May be repeated."
+        ),
+    )
+    _ = parser.add_argument(
+        "--log-level",
+        dest="log_level",
+        metavar="LEVEL",
+        help="Default level the caplog fixture captures at (e.g. INFO or 20).",
+    )
+    _ = parser.add_argument(
+        "--run-skipped",
+        action="store_true",
+        dest="run_skipped",
+        help=(
+            "Run skipped tests anyway and report their real outcome, to check "
+            "whether the skips are still needed."
+        ),
+    )
+    _ = parser.add_argument(
+        "--import-timeout",
+        type=float,
+        metavar="SECONDS",
+        dest="import_timeout",
+        help=(
+            "Report a test file that takes longer than SECONDS to import as a "
+            "collection error instead of waiting for it."
+        ),
+    )
+    _ = parser.add_argument(
+        "--show-test-fixtures",
+        action="store_true",
+        dest="show_test_fixtures",
+        help="With -v, list the fixtures each test used under its result line.",
+    )
+    _ = parser.add_argument(
+        "--collapse-param-failures",
+        action="store_true",
+        dest="collapse_param_failures",
+        help=(
+            "Show the full traceback for the first failing case of a parametrized "
+            "test and one line for each later case failing the same way."
+        ),
+    )
+    _ = parser.add_argument(
+        "--maxfail",
+        type=int,
+        metavar="N",
+        dest="max_failures",
+        help="Stop after N failed or erroring tests (-x is --maxfail=1).",
+    )
     parser.set_defaults(
         capture_output=True,
         enable_codeblocks=True,
@@ -144,6 +464,14 @@ def build_parser() -> argparse.ArgumentParser:
         failed_first=False,
         fail_fast=False,
         pytest_compat=False,
+        trace_config=False,
+        full_trace=False,
+        continue_on_collection_errors=True,
+        check=False,
+        github_annotations=False,
+        wrap_failures=False,
+        run_slow=False,
+        resume=False,
     )
     return parser
 
@@ -162,12 +490,12 @@ def main(argv: Sequence[str] | None = None) -> int:
 
     # Determine color mode
     if args.color == "auto":
-        # Auto-detect: colors enabled locally, disabled in CI
-        use_color = not is_ci_environment()
-    elif args.color == "always":
-        use_color = True
-    else:  # "never"
-        use_color = False
+        # Auto-detect: colors disabled in CI unless FORCE_COLOR asks for
+        # them; otherwise the renderer checks NO_COLOR, FORCE_COLOR and
+        # whether stderr is a terminal
+        no_color = is_ci_environment() and os.environ.get("FORCE_COLOR", "") in ("", "0")
+    else:
+        no_color = args.color == "never"
 
     report = run(
         paths=list(args.paths),
@@ -181,18 +509,49 @@ def main(argv: Sequence[str] | None = None) -> int:
         pytest_compat=args.pytest_compat,
         verbose=args.verbose,
         ascii=args.ascii,
-        no_color=not use_color,
+        no_color=no_color,
+        compact=args.compact,
+        force_color=args.color == "always",
+        max_name_width=args.max_name_width,
+        max_processes=args.max_processes,
+        trace_config=args.trace_config,
+        max_file_size=args.max_file_size,
+        filter_traceback=not args.full_trace,
+        continue_on_collection_errors=args.continue_on_collection_errors,
+        check=args.check,
+        sample=args.sample,
+        shuffle_seed=args.shuffle_seed,
+        github_annotations=args.github_annotations,
+        tap_output=args.tap_output,
+        wrap_failures=args.wrap_failures,
+        passed_only=args.passed_only,
+        discovery_threads=args.discovery_threads,
+        run_slow=args.run_slow,
+        enrich_exclude=args.enrich_exclude,
+        resume=args.resume,
+        xfail_list=args.xfail_list,
+        line_trace=args.line_trace,
+        sqlite_path=args.sqlite_path,
+        changed_lines=args.changed_lines,
+        slowest_files=args.slowest_files,
+        asyncio_debug=args.asyncio_debug,
+        trace_json=args.trace_json,
+        group_failures=args.group_failures,
+        loop_scope_strict=args.loop_scope_strict,
+        notebooks=args.notebooks,
+        junit_xml_path=args.junit_xml_path,
+        json_report_path=args.json_report_path,
+        durations=args.durations,
+        failure_exceptions=args.failure_exceptions,
+        log_level=args.log_level,
+        shuffle=args.shuffle,
+        run_skipped=args.run_skipped,
+        import_timeout=args.import_timeout,
+        show_test_fixtures=args.show_test_fixtures,
+        collapse_param_failures=args.collapse_param_failures,
+        max_failures=args.max_failures,
     )
     # Note: Rust now handles all output rendering with real-time progress
     # The Python _print_report() function is no longer called
 
-    # Exit codes match pytest:
-    # 0 = all tests passed
-    # 1 = some tests failed
-    # 2 = collection errors (syntax errors, import errors, etc.)
-    if len(report.collection_errors) > 0:
-        return 2
-    elif report.failed > 0:
-        return 1
-    else:
-        return 0
+    return exit_code(report, args.allow_failure_ratio)
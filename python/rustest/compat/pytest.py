@@ -375,9 +375,8 @@ class _PluginManagerStub:
 class FixtureRequest:
     """Pytest-compatible FixtureRequest for fixture parametrization.
 
-    Supports: param, scope, node, config, getfixturevalue().
-    Not implemented: function, cls, module, fixturename (always None),
-    addfinalizer() (raises NotImplementedError).
+    Supports: param, scope, node, config, getfixturevalue(), addfinalizer().
+    Not implemented: function, cls, module, fixturename (always None).
     """
 
     def __init__(
@@ -420,21 +419,29 @@ class FixtureRequest:
         # Cache for executed fixtures (per-test)
         self._executed_fixtures: dict[str, Any] = {}
 
+        # Callables registered with addfinalizer(), run in reverse order
+        self._finalizers: list[Callable[[], None]] = []
+
     def addfinalizer(self, finalizer: Callable[[], None]) -> None:
-        """Not supported — use yield-based fixture teardown instead. Always raises NotImplementedError."""
-        msg = (
-            "request.addfinalizer() is not supported in rustest pytest-compat mode.\n"
-            "\n"
-            "Workaround: Use fixture teardown with yield:\n"
-            "  @pytest.fixture\n"
-            "  def my_fixture():\n"
-            "      resource = setup()\n"
-            "      yield resource\n"
-            "      teardown(resource)  # Runs after test\n"
-            "\n"
-            "For full pytest features, use pytest directly or migrate to native rustest."
-        )
-        raise NotImplementedError(msg)
+        """Register a callable to run when the requesting fixture or test is torn down.
+
+        Finalizers run in reverse registration order, after the teardown of a
+        yield fixture that registered them.
+        """
+        self._finalizers.append(finalizer)
+
+    def _run_finalizers(self) -> None:
+        """Run registered finalizers last-in first-out, re-raising the first error."""
+        error: BaseException | None = None
+        while self._finalizers:
+            finalizer = self._finalizers.pop()
+            try:
+                finalizer()
+            except Exception as exc:
+                if error is None:
+                    error = exc
+        if error is not None:
+            raise error
 
     def getfixturevalue(self, name: str) -> Any:
         """Get the value of another fixture by name, resolving dependencies recursively."""
@@ -681,13 +688,16 @@ class _PytestMarkCompat:
         """Alias for @pytest.mark.parametrize (same as top-level parametrize)."""
         return _rustest_mark.parametrize
 
-    def skip(self, reason: str | None = None) -> Callable[[F], F]:
+    def skip(self, reason: Any = None) -> Any:
         """Mark test as skipped.
 
         This is the @pytest.mark.skip() decorator which should skip the test.
-        Maps to rustest's skip_decorator().
+        Maps to rustest's skip_decorator(). Used bare (``@pytest.mark.skip`` or
+        ``marks=pytest.mark.skip``) it receives the test itself.
         """
-        return _rustest_skip_decorator(reason=reason)  # type: ignore[return-value]
+        if callable(reason):
+            return _rustest_skip_decorator()(reason)
+        return _rustest_skip_decorator(reason=reason)
 
     @property
     def skipif(self) -> Any:
@@ -724,24 +734,12 @@ def param(*values: Any, id: str | None = None, marks: Any = None, **kwargs: Any)
     Args:
         *values: The parameter values for this test case
         id: Optional custom test ID for this parameter set
-        marks: Optional marks to apply (currently ignored with a warning)
+        marks: A mark, or a list of marks, applied to this case only, e.g.
+            ``pytest.mark.xfail(reason="...")`` or ``pytest.mark.skip``
 
     Returns:
         A ParameterSet object that will be handled by parametrize
-
-    Note:
-        The 'marks' parameter is accepted but not yet functional.
-        Tests with marks will run normally but marks won't be applied.
     """
-    if marks is not None:
-        import warnings
-
-        warnings.warn(
-            "pytest.param() marks are not yet supported in rustest pytest-compat mode. The test will run but marks will be ignored.",
-            UserWarning,
-            stacklevel=2,
-        )
-
     return ParameterSet(values=values, id=id, marks=marks)
 
 
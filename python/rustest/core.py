@@ -2,9 +2,10 @@
 
 from __future__ import annotations
 
+import asyncio
 import os
 import sys
-from collections.abc import Sequence
+from collections.abc import Callable, Sequence
 from pathlib import Path
 
 from rich.console import Console
@@ -89,6 +90,49 @@ def run(
     verbose: bool = False,
     ascii: bool = False,
     no_color: bool = False,
+    compact: bool = False,
+    force_color: bool = False,
+    max_name_width: int | None = None,
+    max_processes: int | None = None,
+    trace_config: bool = False,
+    max_file_size: int | None = None,
+    event_loop_policy: asyncio.AbstractEventLoopPolicy | None = None,
+    filter_traceback: bool = True,
+    continue_on_collection_errors: bool = True,
+    check: bool = False,
+    status_symbols: dict[str, str] | None = None,
+    sample: int | None = None,
+    shuffle_seed: int | None = None,
+    github_annotations: bool = False,
+    tap_output: str | os.PathLike[str] | None = None,
+    wrap_failures: bool = False,
+    passed_only: str | os.PathLike[str] | None = None,
+    discovery_threads: int | None = None,
+    run_slow: bool = False,
+    enrich_exclude: Sequence[str] | None = None,
+    resume: bool = False,
+    xfail_list: str | os.PathLike[str] | None = None,
+    line_trace: bool = False,
+    sqlite_path: str | os.PathLike[str] | None = None,
+    changed_lines: str | None = None,
+    slowest_files: int | None = None,
+    asyncio_debug: bool = False,
+    trace_json: str | None = None,
+    group_failures: bool = False,
+    loop_scope_strict: bool = True,
+    notebooks: bool = False,
+    junit_xml_path: str | None = None,
+    json_report_path: str | None = None,
+    durations: int | None = None,
+    on_complete: Callable[[RunReport, list[str]], object] | None = None,
+    failure_exceptions: Sequence[str] | None = None,
+    log_level: str | None = None,
+    shuffle: bool = False,
+    run_skipped: bool = False,
+    import_timeout: float | None = None,
+    show_test_fixtures: bool = False,
+    collapse_param_failures: bool = False,
+    max_failures: int | None = None,
 ) -> RunReport:
     """Execute tests and return a rich report.
 
@@ -96,7 +140,9 @@ def run(
         paths: Files or directories to collect tests from
         pattern: Substring to filter tests by (case insensitive)
         mark_expr: Mark expression to filter tests (e.g., "slow", "not slow", "slow and integration")
-        workers: Number of worker slots to use (experimental)
+        workers: Number of worker processes to split test files across. ``None``
+            or ``1`` runs everything in this process. Session-scoped fixtures
+            are set up once per worker.
         capture_output: Whether to capture stdout/stderr during test execution
         enable_codeblocks: Whether to enable code block tests from markdown files
         last_failed_mode: Last failed mode: "none", "only", or "first"
@@ -105,6 +151,125 @@ def run(
         verbose: Show verbose output with hierarchical test structure
         ascii: Use ASCII characters instead of Unicode symbols for output
         no_color: Disable colored output
+        compact: Print one summary line per file (e.g. for CI logs)
+        force_color: Force colored output even when not writing to a terminal
+        max_name_width: Truncate long test names in the middle to this width
+            (defaults to the terminal width)
+        max_processes: Upper bound on the number of workers, whether set
+            explicitly or auto-detected
+        trace_config: Log discovery decisions and sys.path additions to stderr
+        max_file_size: Skip collecting test files larger than this many bytes
+        event_loop_policy: asyncio event loop policy (e.g. ``uvloop.EventLoopPolicy()``)
+            used for every loop created during the run; the previous policy is
+            restored afterwards
+        filter_traceback: Hide stdlib and site-packages frames from failure tracebacks
+        continue_on_collection_errors: Run collected tests even if some files failed
+            to collect; when False, any collection error aborts the run
+        check: Validate every test's fixture graph (missing fixtures, cycles,
+            scope violations) and report all problems without running tests.
+        status_symbols: Replace the symbol or word shown for an outcome, keyed by
+            ``passed``, ``failed``, ``skipped``, ``xfailed`` or ``xpassed``.
+        sample: Run a random sample of up to this many collected tests, for a quick
+            smoke check. Combine with ``shuffle_seed`` to repeat a sample.
+        shuffle_seed: Seed for ``sample`` and ``shuffle``. When
+            omitted a fresh seed is chosen and printed so the run can be repeated.
+        github_annotations: Print each failure as a GitHub Actions ``::error``
+            workflow command so it is shown inline on pull requests.
+        tap_output: Write a TAP (Test Anything Protocol) version 13 report to this
+            path after the run, or to stdout when it is ``"-"``.
+        wrap_failures: Soft-wrap long lines in failure messages to the terminal width,
+            keeping code indentation with a hanging indent.
+        passed_only: Path to a prior JSON report. Only tests that passed in it are
+            run, matched by node id - the inverse of ``last_failed``.
+        discovery_threads: Number of threads reading test files ahead of the imports
+            during discovery. Imports still run one at a time, so this helps when
+            collection is waiting on slow or cold file systems. Defaults to reading
+            files as they are imported.
+        run_slow: Run tests marked ``slow``. They are deselected by default unless
+            ``mark_expr`` is given, which then decides on its own.
+        enrich_exclude: Glob patterns of files whose assertion failures are shown as
+            plain tracebacks, skipping the Expected/Received enrichment (which
+            re-evaluates parts of the failing assertion).
+        resume: Continue a run that was interrupted (e.g. by a crash). Tests recorded
+            in the progress journal are not run again; their recorded outcomes are
+            merged into the report.
+        xfail_list: Path to a file of known-failing node ids, one per line (``#``
+            starts a comment, which is used as the reason). Listed tests that fail
+            are reported as expected failures, and listed tests that pass as
+            unexpected passes, without touching the test code.
+        line_trace: Record which lines each test executes using ``sys.settrace``
+            and write them to ``.rustest_cache/line_trace.json``. This slows
+            tests down considerably, so it is off by default.
+        sqlite_path: Append every test result (node id, status, duration,
+            timestamp and run id) to a ``test_results`` table in this SQLite
+            database, creating it if needed. Useful for tracking flakiness
+            across runs.
+        changed_lines: Git revision (e.g. ``"HEAD"`` or ``"main"``) to diff the
+            working tree against. Only tests whose own lines changed, or whose
+            module imports changed code, are run.
+        slowest_files: After the run, print this many test files that took
+            longest in total, slowest first. Useful to find files with
+            heavy fixture setup.
+        asyncio_debug: Run async tests with ``loop.set_debug(True)`` and record
+            the asyncio warnings each test leaves behind (tasks still pending,
+            coroutines never awaited). They are listed in a warnings summary
+            and on ``RunReport.warnings``, next to other per-test warnings
+            such as a test returning a value.
+        trace_json: Write a Chrome tracing (``chrome://tracing``, Perfetto)
+            JSON file to this path with spans for collection, each test and
+            each fixture setup and teardown.
+        group_failures: In the failures summary, print failures whose final
+            exception line matches (ignoring memory addresses, paths and line
+            numbers) once, followed by the other affected node ids.
+        loop_scope_strict: Fail a test whose explicit ``loop_scope`` is narrower
+            than its async fixtures require. When False, the loop scope is widened
+            to the required scope and a warning is listed in the summary instead.
+        notebooks: Also collect Jupyter notebooks named ``test_*.ipynb`` or
+            ``*_test.ipynb``. Their code cells are run as one module and test
+            functions defined in them are collected like in a ``.py`` file.
+        junit_xml_path: Write a JUnit XML report to this path when the run
+            finishes, with one ``<testcase>`` per test (the file path as
+            ``classname``) and captured output for failures.
+        json_report_path: Write a JSON report to this path when the run
+            finishes: totals, duration and every result, under a versioned
+            ``"schema_version"`` key.
+        durations: After the run, print this many slowest tests with their
+            durations, slowest first (``0`` prints every test). Equal
+            durations are listed by node id.
+        on_complete: Called once when the run finishes with the report and
+            the paths of the report files it wrote (TAP, JUnit XML, JSON,
+            trace and SQLite), e.g. to upload them from CI. An exception
+            raised by the callback is printed and the report still returned.
+        failure_exceptions: Exception class names (e.g. ``"AssertionError"``)
+            that count as test failures. When given, any other exception
+            raised by a test body is reported as an error instead; subclasses
+            match, and ``AssertionError`` and ``fail()`` always count as
+            failures.
+        log_level: Default level the ``caplog`` fixture captures at, as a
+            level name (``"INFO"``) or number. Records below it are
+            dropped unless a test lowers the level with ``caplog.set_level``
+            or ``caplog.at_level``. Defaults to capturing every record.
+        shuffle: Run test files, and the tests within each file, in a random
+            order. Tests from different files never interleave, so module- and
+            class-scoped fixtures are still set up once per file or class.
+            Reproduce an order with ``shuffle_seed``.
+        run_skipped: Run tests that are skipped by ``@skip``, a ``skip`` mark or
+            a true ``skipif`` condition, and report their real outcome. Each
+            such result keeps the skip reason in ``TestResult.was_skipped``,
+            to audit whether the skips are still needed.
+        import_timeout: Seconds a test file may take to import. A file still
+            importing after that is reported as a collection error instead of
+            stalling discovery. ``None`` (the default) waits indefinitely.
+        show_test_fixtures: In verbose output, list the fixtures each test used
+            (explicit, autouse and their dependencies) under its result line.
+            ``TestResult.fixtures`` is recorded either way.
+        collapse_param_failures: In the failures summary, print the full
+            traceback only for the first failing case of a parametrized test;
+            later cases failing the same way get a one-line entry. Ignored
+            with ``group_failures``, which already merges them.
+        max_failures: Stop the run once this many tests have failed or errored.
+            ``fail_fast`` is the same as ``max_failures=1``. ``None`` or ``0``
+            runs every test.
     """
     # Store runtime configuration for fixtures to access
     try:
@@ -133,7 +298,19 @@ def run(
 
     # Set up event routing with rich terminal renderer
     router = EventRouter()
-    rich_renderer = RichRenderer(use_colors=not no_color, use_ascii=ascii)
+    # Without --color always or never the renderer detects colors itself,
+    # like the Rust renderers do
+    use_colors = False if no_color else True if force_color else None
+    rich_renderer = RichRenderer(
+        use_colors=use_colors,
+        use_ascii=ascii,
+        status_symbols=status_symbols,
+        wrap_failures=wrap_failures,
+        verbose=verbose,
+        group_failures=group_failures,
+        collapse_param_failures=collapse_param_failures,
+        show_test_fixtures=show_test_fixtures,
+    )
     router.subscribe(rich_renderer)
 
     # Read asyncio loop scope defaults from pyproject.toml
@@ -156,9 +333,57 @@ def run(
             verbose=verbose,
             ascii=ascii,
             no_color=no_color,
-            event_callback=router.emit,
+            # Compact output is rendered directly by the Rust core
+            event_callback=None if compact else router.emit,
             default_test_loop_scope=default_test_loop_scope,
             default_fixture_loop_scope=default_fixture_loop_scope,
+            compact=compact,
+            force_color=force_color,
+            max_name_width=max_name_width,
+            max_processes=max_processes,
+            trace_config=trace_config,
+            max_file_size=max_file_size,
+            event_loop_policy=event_loop_policy,
+            filter_traceback=filter_traceback,
+            continue_on_collection_errors=continue_on_collection_errors,
+            check=check,
+            status_symbols=status_symbols,
+            sample=sample,
+            shuffle_seed=shuffle_seed,
+            github_annotations=github_annotations,
+            tap_output=tap_output,
+            wrap_failures=wrap_failures,
+            passed_only=passed_only,
+            discovery_threads=discovery_threads,
+            run_slow=run_slow,
+            enrich_exclude=enrich_exclude,
+            resume=resume,
+            xfail_list=xfail_list,
+            line_trace=line_trace,
+            sqlite_path=sqlite_path,
+            changed_lines=changed_lines,
+            slowest_files=slowest_files,
+            asyncio_debug=asyncio_debug,
+            trace_json=trace_json,
+            group_failures=group_failures,
+            loop_scope_strict=loop_scope_strict,
+            notebooks=notebooks,
+            junit_xml_path=junit_xml_path,
+            json_report_path=json_report_path,
+            durations=durations,
+            on_complete=(
+                None
+                if on_complete is None
+                else lambda raw, artifacts: on_complete(RunReport.from_py(raw), artifacts)
+            ),
+            failure_exceptions=failure_exceptions,
+            log_level=log_level,
+            shuffle=shuffle,
+            run_skipped=run_skipped,
+            import_timeout=import_timeout,
+            show_test_fixtures=show_test_fixtures,
+            collapse_param_failures=collapse_param_failures,
+            max_failures=max_failures,
         )
     finally:
         if previous_running is None:
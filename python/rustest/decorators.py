@@ -4,6 +4,7 @@ from __future__ import annotations
 
 from collections.abc import Callable, Mapping, Sequence
 import inspect
+import re
 import sys
 from typing import Any, ParamSpec, TypeVar, overload, cast
 
@@ -28,7 +29,30 @@ class ParameterSet:
         super().__init__()
         self.values = values
         self.id = id
-        self.marks = marks  # Currently not used, but stored for future support
+        self.marks = marks
+
+    def mark_payloads(self) -> list[dict[str, Any]]:
+        """The case's marks as the ``{"name", "args", "kwargs"}`` dicts tests carry.
+
+        ``marks`` may be one mark decorator or a sequence of them. Each is
+        applied to a placeholder function, so anything that works as a test
+        decorator (``mark.xfail(...)``, ``skip(...)``, ``pytest.mark.skip``)
+        works here too.
+        """
+        if self.marks is None:
+            return []
+        decorators = self.marks if isinstance(self.marks, (list, tuple)) else [self.marks]
+
+        def carrier() -> None:
+            pass
+
+        for decorator in cast(Sequence[Callable[..., Any]], decorators):
+            decorator(carrier)
+        payloads: list[dict[str, Any]] = list(getattr(carrier, "__rustest_marks__", []))
+        skip_reason = getattr(carrier, "__rustest_skip__", None)
+        if skip_reason is not None:
+            payloads.append({"name": "skip", "args": (), "kwargs": {"reason": skip_reason}})
+        return payloads
 
     def __repr__(self) -> str:
         return f"ParameterSet(values={self.values!r}, id={self.id!r})"
@@ -301,7 +325,11 @@ def _cross_product_cases(
             # Combine the IDs with a hyphen separator
             combined_id = f"{existing_case['id']}-{new_case['id']}"
 
-            combined.append({"id": combined_id, "values": combined_values})
+            case: dict[str, object] = {"id": combined_id, "values": combined_values}
+            marks = [*existing_case.get("marks", ()), *new_case.get("marks", ())]  # type: ignore[misc]
+            if marks:
+                case["marks"] = marks
+            combined.append(case)
 
     return tuple(combined)
 
@@ -313,6 +341,7 @@ def parametrize(
     argvalues: Sequence[Sequence[object] | Mapping[str, object] | ParameterSet] | None = None,
     ids: Sequence[str] | Callable[[Any], str | None] | None = None,
     indirect: bool | Sequence[str] | str = False,
+    scope: str | None = None,
 ) -> Callable[[Callable[Q, S]], Callable[Q, S]]:
     """Parametrise a test function.
 
@@ -339,7 +368,16 @@ def parametrize(
                 @parametrize("my_data", [42, 100], indirect=True)
                 def test_example(my_data):
                     assert my_data["value"] in (42, 100)
+        scope: How long a fixture built from an indirect parameter is kept.
+            Defaults to the fixture's own scope. With ``scope="module"``, each
+            parameter value builds the fixture once per module, shared by every
+            test parametrized with that value.
     """
+    if scope is not None and scope not in VALID_SCOPES:
+        valid = ", ".join(sorted(VALID_SCOPES))
+        msg = f"Invalid parametrize scope '{scope}'. Must be one of: {valid}"
+        raise ValueError(msg)
+
     # Support both 'values' (rustest style) and 'argvalues' (pytest style)
     actual_values = argvalues if argvalues is not None else values
     if actual_values is None:
@@ -356,6 +394,11 @@ def parametrize(
         existing_cases = getattr(func, "__rustest_parametrization__", None)
 
         if existing_cases:
+            already = set(existing_cases[0]["values"]).intersection(normalized_names)
+            if already:
+                names = ", ".join(repr(name) for name in sorted(already))
+                msg = f"parametrize() argument {names} is already parametrized by another decorator"
+                raise ValueError(msg)
             # Create cross-product of existing and new cases
             combined_cases = _cross_product_cases(existing_cases, new_cases)
             setattr(func, "__rustest_parametrization__", combined_cases)
@@ -369,6 +412,11 @@ def parametrize(
             combined_indirect = list(existing_indirect) + normalized_indirect
             setattr(func, "__rustest_parametrization_indirect__", combined_indirect)
 
+        if scope is not None:
+            scopes = dict(getattr(func, "__rustest_parametrization_scope__", {}))
+            scopes.update(dict.fromkeys(normalized_names, scope))
+            setattr(func, "__rustest_parametrization_scope__", scopes)
+
         return func
 
     return decorator
@@ -435,9 +483,11 @@ def _build_cases(
     for index, case in enumerate(values):
         # Handle ParameterSet objects (from pytest.param())
         param_set_id: str | None = None
+        case_marks: list[dict[str, Any]] = []
         actual_case: Any = case
         if isinstance(case, ParameterSet):
             param_set_id = case.id
+            case_marks = case.mark_payloads()
             actual_case = case.values  # Extract the actual values
             # If it's a single value tuple, unwrap it for consistency
             if len(actual_case) == 1:
@@ -476,7 +526,10 @@ def _build_cases(
             index=index,
         )
 
-        case_payloads.append({"id": case_id, "values": data})
+        payload: dict[str, object] = {"id": case_id, "values": data}
+        if case_marks:
+            payload["marks"] = case_marks
+        case_payloads.append(payload)
     return tuple(case_payloads)
 
 
@@ -494,11 +547,17 @@ class MarkDecorator:
         # Get existing marks or create a new list
         existing_marks: list[dict[str, Any]] = getattr(func, "__rustest_marks__", [])
 
+        kwargs = self.kwargs
+        if self.name == "skipif" and self.args and kwargs.get("reason") is None:
+            # Like pytest, an expression condition doubles as the default reason
+            if isinstance(self.args[0], str):
+                kwargs = {**kwargs, "reason": f"condition: {self.args[0]}"}
+
         # Add this mark to the list
         mark_data = {
             "name": self.name,
             "args": self._normalize_args(func),
-            "kwargs": self.kwargs,
+            "kwargs": kwargs,
         }
         existing_marks.append(mark_data)
 
@@ -806,6 +865,22 @@ class ExceptionInfo:
     def __repr__(self) -> str:
         return f"<ExceptionInfo {self.type.__name__}({self.value!r})>"
 
+    def match(self, regexp: str | re.Pattern[str]) -> bool:
+        """Assert that ``regexp`` matches the string form of the exception.
+
+        Uses ``re.search``, so the pattern may match anywhere in the message.
+        Returns True so it can be used in an ``assert`` statement.
+
+        Raises:
+            AssertionError: If the pattern does not match.
+        """
+        message = str(self.value)
+        if not re.search(regexp, message):
+            pattern = regexp.pattern if isinstance(regexp, re.Pattern) else regexp
+            msg = f"Regex pattern {pattern!r} does not match {message!r}."
+            raise AssertionError(msg)
+        return True
+
 
 class RaisesContext:
     """Context manager for asserting that code raises a specific exception.
@@ -870,8 +945,6 @@ class RaisesContext:
 
         # Check if the message matches the pattern (if provided)
         if self.match_pattern is not None:
-            import re
-
             exc_message = str(exc_val)
             if not re.search(self.match_pattern, exc_message):
                 msg = (
@@ -906,6 +979,13 @@ class RaisesContext:
             raise AttributeError(msg)
         return self.excinfo.type
 
+    def match(self, regexp: str | re.Pattern[str]) -> bool:
+        """Assert that ``regexp`` matches the caught exception's message."""
+        if self.excinfo is None:
+            msg = "No exception was caught"
+            raise AttributeError(msg)
+        return self.excinfo.match(regexp)
+
 
 def raises(
     exc_type: type[BaseException] | tuple[type[BaseException], ...],
@@ -8,11 +8,14 @@ from __future__ import annotations
 
 import inspect
 import threading
+from collections.abc import Generator
 from typing import Any
 
 _registry_lock = threading.Lock()
 _fixture_registry: dict[str, Any] = {}
 _fixture_cache: dict[str, Any] = {}
+# Generator fixtures entered by resolve_fixture(), awaiting teardown by the runner
+_pending_teardowns: list[Generator[Any, None, None]] = []
 
 
 def register_fixtures(fixtures: dict[str, Any]) -> None:
@@ -33,6 +36,18 @@ def clear_registry() -> None:
         _fixture_cache.clear()
 
 
+def take_pending_teardowns() -> list[Generator[Any, None, None]]:
+    """Return and forget the generator fixtures awaiting teardown.
+
+    The runner finalizes them together with the requesting test's
+    function-scoped fixtures, in reverse order of setup.
+    """
+    with _registry_lock:
+        pending = list(_pending_teardowns)
+        _pending_teardowns.clear()
+        return pending
+
+
 def get_fixture(name: str) -> Any:
     """Get a fixture callable by name.
 
@@ -127,8 +142,10 @@ def resolve_fixture(
 
     # Handle generator fixtures
     if inspect.isgenerator(result):
-        result = next(result)
-        # TODO: Store generator for teardown
+        generator = result
+        result = next(generator)
+        with _registry_lock:
+            _pending_teardowns.append(generator)
 
     # Cache the result
     _executed_fixtures[name] = result
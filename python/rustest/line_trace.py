@@ -0,0 +1,75 @@
+"""Per-test record of executed lines for ``--line-trace``.
+
+The runner calls :func:`start` before each test body and :func:`stop` right
+after it, and :func:`write` once the run is over. This is a lightweight "which
+lines ran" map, not coverage: there is no branch data and no source analysis,
+and ``sys.settrace`` slows every traced test down considerably.
+"""
+
+from __future__ import annotations
+
+import json
+import os
+import sys
+from pathlib import Path
+from types import FrameType
+from typing import Any, Callable
+
+_PACKAGE_DIR = os.path.dirname(os.path.abspath(__file__))
+
+# node id -> file -> executed line numbers
+_hits: dict[str, dict[str, set[int]]] = {}
+
+# Tracer that was active before start(), e.g. coverage.py or a debugger
+_previous_tracer: Callable[..., Any] | None = None
+
+
+def reset() -> None:
+    """Forget lines recorded by a previous run."""
+    _hits.clear()
+
+
+def start(node_id: str) -> None:
+    """Install a tracer recording the lines run on behalf of ``node_id``.
+
+    Any tracer already installed (coverage.py, a debugger) is paused until
+    :func:`stop` puts it back.
+    """
+    global _previous_tracer
+    _previous_tracer = sys.gettrace()
+    files = _hits.setdefault(node_id, {})
+
+    def trace_lines(frame: FrameType, event: str, _arg: Any) -> Callable[..., Any] | None:
+        if event == "line":
+            files.setdefault(frame.f_code.co_filename, set()).add(frame.f_lineno)
+        return trace_lines
+
+    def trace_calls(frame: FrameType, _event: str, _arg: Any) -> Callable[..., Any] | None:
+        # rustest's own helpers (fixture registry, approx, ...) are not of interest
+        if frame.f_code.co_filename.startswith(_PACKAGE_DIR):
+            return None
+        return trace_lines
+
+    sys.settrace(trace_calls)
+
+
+def stop() -> None:
+    """Remove the tracer installed by :func:`start`, restoring the previous one."""
+    global _previous_tracer
+    sys.settrace(_previous_tracer)
+    _previous_tracer = None
+
+
+def recorded() -> dict[str, dict[str, list[int]]]:
+    """Return the lines recorded so far, sorted for stable output."""
+    return {
+        node_id: {filename: sorted(lines) for filename, lines in sorted(files.items())}
+        for node_id, files in _hits.items()
+    }
+
+
+def write(path: str | os.PathLike[str]) -> None:
+    """Write ``{node_id: {file: [line, ...]}}`` as JSON to ``path``."""
+    destination = Path(path)
+    destination.parent.mkdir(parents=True, exist_ok=True)
+    _ = destination.write_text(json.dumps(recorded(), indent=2) + "\n", encoding="utf-8")
@@ -5,11 +5,16 @@ Provides beautiful, real-time progress display using the rich library.
 
 from __future__ import annotations
 
+import math
+import os
 import sys
-from typing import TYPE_CHECKING
+import textwrap
+import time
+from typing import TYPE_CHECKING, TextIO
 
 from rich.console import Console
 from rich.live import Live
+from rich.markup import escape
 from rich.progress import BarColumn, Progress, SpinnerColumn, TaskID, TextColumn, TimeElapsedColumn
 
 if TYPE_CHECKING:
@@ -37,6 +42,123 @@ if TYPE_CHECKING:
         | CollectionCompletedEvent
     )
 
+# Symbols shown for each outcome, as in the Rust renderers' StatusSymbols
+DEFAULT_SYMBOLS = {"passed": "✓", "failed": "✗", "skipped": "⊘", "xfailed": "x", "xpassed": "X"}
+ASCII_SYMBOLS = {
+    "passed": "PASS",
+    "failed": "FAIL",
+    "skipped": "SKIP",
+    "xfailed": "XFAIL",
+    "xpassed": "XPASS",
+}
+
+# Extra indentation for the continuation of a wrapped failure line
+HANGING_INDENT = 4
+
+
+def wrap_line(line: str, width: int) -> list[str]:
+    """Soft-wrap one line to ``width`` columns, like the Rust renderers.
+
+    Continuation lines keep the line's own indentation plus a hanging indent,
+    so wrapped code and tracebacks stay readable. Lines break at the last
+    space that fits, or mid-word when there is none.
+    """
+    if len(line) <= width:
+        return [line]
+    content = line.lstrip(" ")
+    indent = " " * (len(line) - len(content))
+    # Keep at least half the width for content on continuation lines
+    hanging = " " * min(len(indent) + HANGING_INDENT, width // 2)
+    return textwrap.wrap(
+        content,
+        width,
+        initial_indent=indent,
+        subsequent_indent=hanging,
+        break_on_hyphens=False,
+    ) or [line]
+
+
+def progress_prefix(completed: int, total: int) -> str:
+    """Completion percentage column for verbose lines, like pytest's ``[ 42%]``.
+
+    Without a known total the column keeps its width but shows ``?``. Reruns
+    can complete more tests than were collected, so it is capped at 100.
+    """
+    if total == 0:
+        return "[   ?]"
+    return f"[{min(completed * 100 // total, 100):>3}%]"
+
+
+def estimate_remaining(completed: int, total: int, elapsed: float) -> float | None:
+    """Estimated seconds left in the run, from the average time per completed test.
+
+    Returns ``None`` until 5% of the tests have completed, since earlier
+    estimates swing too much to be useful, and once every test is done.
+    """
+    if total == 0 or completed >= total or completed * 20 < total:
+        return None
+    return elapsed * (total - completed) / completed
+
+
+def group_failures(
+    failures: list[tuple[str, str, str]], fingerprints: dict[str, int]
+) -> list[tuple[tuple[str, str, str], list[tuple[str, str, str]]]]:
+    """Group failures by fingerprint, keeping the order of first occurrence.
+
+    Each group is its first failure, printed in full, and the other failures
+    with the same fingerprint. Failures without a fingerprint stay alone.
+    """
+    groups: list[tuple[tuple[str, str, str], list[tuple[str, str, str]]]] = []
+    index_by_fingerprint: dict[int, int] = {}
+    for failure in failures:
+        fingerprint = fingerprints.get(failure[0])
+        if fingerprint is not None and fingerprint in index_by_fingerprint:
+            groups[index_by_fingerprint[fingerprint]][1].append(failure)
+            continue
+        if fingerprint is not None:
+            index_by_fingerprint[fingerprint] = len(groups)
+        groups.append((failure, []))
+    return groups
+
+
+def collapse_param_failures(
+    failures: list[tuple[str, str, str]], fingerprints: dict[str, int]
+) -> list[tuple[tuple[str, str, str], str | None]]:
+    """Pair each failure with the id of the earlier case of its test it repeats.
+
+    Cases repeat the first case of the same parametrized test that failed the
+    same way. The first case of each group is paired with ``None`` and keeps its full
+    traceback. Tests that aren't parametrized never repeat another failure.
+    """
+    first_cases: dict[tuple[str, str, int], str] = {}
+    paired: list[tuple[tuple[str, str, str], str | None]] = []
+    for failure in failures:
+        test_id, file_path, _ = failure
+        fingerprint = fingerprints.get(test_id)
+        base, bracket, _ = test_id.rpartition("::")[2].partition("[")
+        if fingerprint is None or not bracket or not test_id.endswith("]"):
+            paired.append((failure, None))
+            continue
+        key = (file_path, base, fingerprint)
+        paired.append((failure, first_cases.get(key)))
+        first_cases.setdefault(key, test_id)
+    return paired
+
+
+def detect_colors(stream: TextIO) -> bool:
+    """Decide whether to color output when no --color choice was made.
+
+    Same precedence as the Rust renderers: NO_COLOR (any non-empty value)
+    disables colors, FORCE_COLOR (any non-empty value other than ``0``)
+    enables them, and otherwise colors are used when ``stream`` is a terminal.
+    """
+    if os.environ.get("NO_COLOR"):
+        return False
+    force_color = os.environ.get("FORCE_COLOR")
+    if force_color:
+        return force_color != "0"
+    return stream.isatty()
+
 
 class RichRenderer:
     """Real-time terminal renderer using rich library.
@@ -49,17 +171,50 @@ class RichRenderer:
     but calls are serialized by the GIL. Rich's Live is also thread-safe.
     """
 
-    def __init__(self, *, use_colors: bool = True, use_ascii: bool = False) -> None:
+    def __init__(
+        self,
+        *,
+        use_colors: bool | None = None,
+        use_ascii: bool = False,
+        status_symbols: dict[str, str] | None = None,
+        wrap_failures: bool = False,
+        verbose: bool = False,
+        group_failures: bool = False,
+        collapse_param_failures: bool = False,
+        show_test_fixtures: bool = False,
+    ) -> None:
         """Initialize the rich renderer.
 
         Args:
-            use_colors: Whether to use colored output
+            use_colors: Whether to use colored output; ``None`` detects it from
+                NO_COLOR, FORCE_COLOR and whether stderr is a terminal
             use_ascii: Whether to use ASCII characters instead of Unicode symbols
+            status_symbols: Symbols replacing the defaults, keyed by outcome
+            wrap_failures: Soft-wrap failure messages to the terminal width
+            verbose: Print one line per test, prefixed with the run's
+                completion percentage
+            group_failures: Print failures that share a final exception line
+                once, followed by the other affected tests
+            collapse_param_failures: Print one line instead of the traceback
+                for a parametrized case that failed like an earlier case
+            show_test_fixtures: List the fixtures each test used under its
+                verbose line
         """
         super().__init__()
+        if use_colors is None:
+            use_colors = detect_colors(sys.stderr)
         self.console = Console(force_terminal=use_colors, file=sys.stderr)
         self.use_colors = use_colors
         self.use_ascii = use_ascii
+        self.symbols = {
+            **(ASCII_SYMBOLS if use_ascii else DEFAULT_SYMBOLS),
+            **(status_symbols or {}),
+        }
+        self.wrap_failures = wrap_failures
+        self.verbose = verbose
+        self.group_failures = group_failures
+        self.collapse_param_failures = collapse_param_failures
+        self.show_test_fixtures = show_test_fixtures
 
         # Progress bar for file execution
         self.progress = Progress(
@@ -83,8 +238,9 @@ class RichRenderer:
             console=self.console,
         )
 
-        # Map file paths to progress task IDs
+        # Map file paths to progress task IDs and their shortened display names
         self.file_tasks: dict[str, TaskID] = {}
+        self.file_names: dict[str, str] = {}
 
         # Collection phase state
         self._collection_task: TaskID | None = None
@@ -95,12 +251,16 @@ class RichRenderer:
 
         # Overall statistics
         self.total_tests = 0
+        self.completed = 0
+        # When the suite started, for the ETA shown next to the running file
+        self._suite_started_at: float | None = None
         self.passed = 0
         self.failed = 0
         self.skipped = 0
 
         # Collect failures to display at the end
         self.failures: list[tuple[str, str, str]] = []  # (test_id, file_path, message)
+        self.fingerprints: dict[str, int] = {}  # test_id -> failure fingerprint
 
         # Collect collection errors
         self.collection_errors: list[tuple[str, str]] = []  # (path, message)
@@ -225,6 +385,18 @@ class RichRenderer:
     def _handle_suite_started(self, event: SuiteStartedEvent) -> None:
         """Handle suite start event."""
         self.total_tests = event.total_tests
+        self._suite_started_at = time.monotonic()
+
+        # Print the session header before the live display starts
+        self.console.print(
+            f"[dim]rustest {event.rustest_version} -- Python {event.python_version} "
+            + f"on {event.platform}[/dim]"
+        )
+        self.console.print(f"[dim]rootdir: {event.rootdir}[/dim]")
+        if event.configfile is not None:
+            self.console.print(f"[dim]configfile: {event.configfile}[/dim]")
+        self.console.print()
+
         self._ensure_started()
 
     def _handle_file_started(self, event: FileStartedEvent) -> None:
@@ -233,42 +405,74 @@ class RichRenderer:
 
         # Add progress bar for this file
         task_id = self.progress.add_task(
-            f"[cyan]{event.file_path}[/cyan]",
+            f"[cyan]{escape(event.display_name)}[/cyan]",
             total=event.total_tests,
         )
         self.file_tasks[event.file_path] = task_id
+        self.file_names[event.file_path] = event.display_name
 
     def _handle_test_completed(self, event: TestCompletedEvent) -> None:
         """Handle test completion event."""
-        # Update the progress bar for this file
+        self.completed += 1
+
+        # Update the progress bar for this file and refresh the run's ETA on it
         task_id = self.file_tasks.get(event.file_path)
 
         if task_id is not None:
             self.progress.update(task_id, advance=1)
+            eta = self._eta()
+            if eta is not None:
+                name = escape(self.file_names.get(event.file_path, event.file_path))
+                self.progress.update(
+                    task_id, description=f"[cyan]{name}[/cyan] [dim]ETA ~{math.ceil(eta)}s[/dim]"
+                )
+
+        if self.verbose:
+            self.console.print(self._test_line(event))
+            if self.show_test_fixtures and event.fixtures:
+                fixtures = escape(", ".join(event.fixtures))
+                self.console.print(f"[dim]       fixtures: {fixtures}[/dim]")
 
         # Update overall stats
         if event.status == "passed":
             self.passed += 1
-        elif event.status == "failed":
+        elif event.status in ("failed", "error"):
             self.failed += 1
             # Store failure for later display
             if event.message:
                 self.failures.append((event.test_id, event.file_path, event.message))
+                if event.failure_fingerprint is not None:
+                    self.fingerprints[event.test_id] = event.failure_fingerprint
         elif event.status == "skipped":
             self.skipped += 1
 
+    def _eta(self) -> float | None:
+        """Estimated seconds left in the run, once there is enough to go on."""
+        if self._suite_started_at is None:
+            return None
+        elapsed = time.monotonic() - self._suite_started_at
+        return estimate_remaining(self.completed, self.total_tests, elapsed)
+
+    def _test_line(self, event: TestCompletedEvent) -> str:
+        """Verbose line for a completed test, e.g. ``[ 42%] ✓ tests/test_a.py::test_one``."""
+        if event.status == "passed":
+            color = "green"
+        elif event.status in ("skipped", "xfailed", "xpassed"):
+            color = "yellow"
+        else:
+            color = "red"
+        symbol = escape(self.symbols.get(event.status, self.symbols["failed"]))
+        prefix = escape(progress_prefix(self.completed, self.total_tests))
+        name = escape(event.display_name)
+        return f"[dim]{prefix}[/dim] [{color}]{symbol}[/{color}] {name}"
+
     def _handle_file_completed(self, event: FileCompletedEvent) -> None:
         """Handle file completion event."""
         task_id = self.file_tasks.get(event.file_path)
 
         if task_id is not None:
-            # Select symbols based on ASCII mode
-            if self.use_ascii:
-                pass_symbol = "PASS"
-                fail_symbol = "FAIL"
-            else:
-                pass_symbol = "✓"
-                fail_symbol = "✗"
+            pass_symbol = escape(self.symbols["passed"])
+            fail_symbol = escape(self.symbols["failed"])
 
             # Update description to show completion status
             if event.failed > 0:
@@ -284,9 +488,10 @@ class RichRenderer:
             else:
                 duration_str = f"{event.duration:.2f}s"
 
+            name = escape(self.file_names.get(event.file_path, event.file_path))
             self.progress.update(
                 task_id,
-                description=f"{symbol} [{color}]{event.file_path}[/{color}] [dim]({duration_str})[/dim]",
+                description=f"{symbol} [{color}]{name}[/{color}] [dim]({duration_str})[/dim]",
                 completed=event.passed + event.failed + event.skipped,
             )
 
@@ -294,6 +499,42 @@ class RichRenderer:
         """Handle collection error event."""
         self.collection_errors.append((event.path, event.message))
 
+    def _wrap_message(self, message: str) -> str:
+        """Wrap a failure message when enabled and writing to a terminal."""
+        if not self.wrap_failures or not self.console.is_terminal:
+            return message
+        width = self.console.width
+        return "\n".join(
+            wrapped for line in message.splitlines() for wrapped in wrap_line(line, width)
+        )
+
+    def _print_failure(self, test_id: str, file_path: str, message: str, separator: str) -> None:
+        """Print one failure of the summary with its message."""
+        # Extract test name from test_id
+        test_name = test_id.split("::")[-1] if "::" in test_id else test_id
+
+        self.console.print(f"[bold]{test_name}[/bold] [dim]({file_path})[/dim]")
+        self.console.print(f"[dim]{separator}[/dim]")
+        self.console.print(self._wrap_message(message))
+        self.console.print()
+
+    def _group_others(self, others: list[tuple[str, str, str]]) -> str:
+        """Lines listing the other tests of a group, after its first failure."""
+        noun = "test" if len(others) == 1 else "tests"
+        lines = [f"Same failure in {len(others)} more {noun}:"]
+        lines.extend(f"  {escape(test_id)}" for test_id, _, _ in others)
+        return "\n".join(lines)
+
+    def _repeated_failure(self, failure: tuple[str, str, str], first: str) -> str:
+        """One-line entry for a case that failed like an earlier case of its test."""
+        test_id, _, message = failure
+        # The assertion enrichment block trails the traceback
+        traceback = message.split("__RUSTEST_ASSERTION_VALUES__")[0]
+        lines = [line.strip() for line in traceback.splitlines() if line.strip()]
+        exception_line = lines[-1] if lines else ""
+        first_name = first.rpartition("::")[2]
+        return escape(f"{test_id} - {exception_line} (same as {first_name})")
+
     def _handle_suite_completed(self, event: SuiteCompletedEvent) -> None:
         """Handle suite completion event."""
         # Stop the live display
@@ -301,17 +542,11 @@ class RichRenderer:
             self.live.stop()
             self._started = False
 
-        # Select symbols and separators based on ASCII mode
-        if self.use_ascii:
-            separator = "-" * 70
-            pass_symbol = "PASS"
-            fail_symbol = "FAIL"
-            skip_symbol = "SKIP"
-        else:
-            separator = "─" * 70
-            pass_symbol = "✓"
-            fail_symbol = "✗"
-            skip_symbol = "⊘"
+        # Select separators based on ASCII mode
+        separator = "-" * 70 if self.use_ascii else "─" * 70
+        pass_symbol = escape(self.symbols["passed"])
+        fail_symbol = escape(self.symbols["failed"])
+        skip_symbol = escape(self.symbols["skipped"])
 
         # Print collection errors
         if self.collection_errors:
@@ -331,14 +566,21 @@ class RichRenderer:
             self.console.print("[bold red]FAILURES[/bold red]")
             self.console.print()
 
-            for test_id, file_path, message in self.failures:
-                # Extract test name from test_id
-                test_name = test_id.split("::")[-1] if "::" in test_id else test_id
-
-                self.console.print(f"[bold]{test_name}[/bold] [dim]({file_path})[/dim]")
-                self.console.print(f"[dim]{separator}[/dim]")
-                self.console.print(message)
-                self.console.print()
+            if self.group_failures:
+                for failure, others in group_failures(self.failures, self.fingerprints):
+                    self._print_failure(*failure, separator)
+                    if others:
+                        self.console.print(self._group_others(others))
+                        self.console.print()
+            elif self.collapse_param_failures:
+                for failure, first in collapse_param_failures(self.failures, self.fingerprints):
+                    if first is None:
+                        self._print_failure(*failure, separator)
+                    else:
+                        self.console.print(f"[dim]{self._repeated_failure(failure, first)}[/dim]")
+            else:
+                for test_id, file_path, message in self.failures:
+                    self._print_failure(test_id, file_path, message, separator)
 
         # Print summary
         self.console.print()
@@ -359,6 +601,10 @@ class RichRenderer:
             parts.append(f"[yellow]{skip_symbol} {event.skipped} skipped[/yellow]")
         if event.errors > 0:
             parts.append(f"[red]{event.errors} error[/red]")
+        if event.xfailed > 0:
+            parts.append(f"[yellow]{event.xfailed} xfailed[/yellow]")
+        if event.xpassed > 0:
+            parts.append(f"[yellow]{event.xpassed} xpassed[/yellow]")
 
         if not parts:
             parts.append("0 tests")
@@ -3,7 +3,7 @@
 from __future__ import annotations
 
 from collections.abc import Iterable
-from dataclasses import dataclass
+from dataclasses import dataclass, field
 
 from . import rust
 
@@ -21,6 +21,11 @@ class TestResult:
     message: str | None
     stdout: str | None
     stderr: str | None
+    stdout_bytes: int = 0
+    stderr_bytes: int = 0
+    reruns: int = 0
+    was_skipped: str | None = None
+    fixtures: tuple[str, ...] = ()
 
     @classmethod
     def from_py(cls, result: rust.PyTestResult) -> "TestResult":
@@ -32,24 +37,63 @@ class TestResult:
             message=result.message,
             stdout=result.stdout,
             stderr=result.stderr,
+            stdout_bytes=result.stdout_bytes,
+            stderr_bytes=result.stderr_bytes,
+            reruns=result.reruns,
+            was_skipped=result.was_skipped,
+            fixtures=tuple(result.fixtures),
         )
 
 
+@dataclass(slots=True)
+class CollectionFrame:
+    """One traceback frame of a collection error."""
+
+    filename: str
+    lineno: int
+    name: str
+
+    @classmethod
+    def from_py(cls, frame: rust.CollectionFrame) -> "CollectionFrame":
+        return cls(filename=frame.filename, lineno=frame.lineno, name=frame.name)
+
+
 @dataclass(slots=True)
 class CollectionError:
     """Error that occurred during test collection (e.g., syntax error, import error)."""
 
     path: str
     message: str
+    error_type: str | None = None
+    filename: str | None = None
+    lineno: int | None = None
+    frames: tuple[CollectionFrame, ...] = ()
 
     @classmethod
     def from_py(cls, error: rust.CollectionError) -> "CollectionError":
         return cls(
             path=error.path,
             message=error.message,
+            error_type=error.error_type,
+            filename=error.filename,
+            lineno=error.lineno,
+            frames=tuple(CollectionFrame.from_py(frame) for frame in error.frames),
         )
 
 
+@dataclass(slots=True)
+class TeardownError:
+    """A fixture teardown that raised after the tests using it finished."""
+
+    fixture: str
+    scope: str
+    message: str
+
+    @classmethod
+    def from_py(cls, error: rust.TeardownError) -> "TeardownError":
+        return cls(fixture=error.fixture, scope=error.scope, message=error.message)
+
+
 @dataclass(slots=True)
 class RunReport:
     """Aggregate statistics for an entire test session."""
@@ -61,6 +105,16 @@ class RunReport:
     duration: float
     results: tuple[TestResult, ...]
     collection_errors: tuple[CollectionError, ...]
+    errors: int = 0
+    xfailed: int = 0
+    xpassed: int = 0
+    by_marker: dict[str, dict[str, int]] = field(default_factory=dict)
+    fixture_setups: int = 0
+    fixture_teardowns: int = 0
+    file_durations: tuple[tuple[str, float], ...] = ()
+    warnings: tuple[tuple[str, str], ...] = ()
+    teardown_errors: tuple[TeardownError, ...] = ()
+    interrupted: bool = False
 
     @classmethod
     def from_py(cls, report: rust.PyRunReport) -> "RunReport":
@@ -74,9 +128,33 @@ class RunReport:
             collection_errors=tuple(
                 CollectionError.from_py(error) for error in report.collection_errors
             ),
+            errors=report.errors,
+            xfailed=report.xfailed,
+            xpassed=report.xpassed,
+            by_marker={mark: dict(counts) for mark, counts in report.by_marker.items()},
+            fixture_setups=report.fixture_setups,
+            fixture_teardowns=report.fixture_teardowns,
+            file_durations=tuple(report.file_durations),
+            warnings=tuple(report.warnings),
+            teardown_errors=tuple(
+                TeardownError.from_py(error) for error in report.teardown_errors
+            ),
+            interrupted=report.interrupted,
         )
 
     def iter_status(self, status: str) -> Iterable[TestResult]:
         """Yield results with the requested status."""
 
         return (result for result in self.results if result.status == status)
+
+    def slowest(self, count: int = 0) -> list[TestResult]:
+        """Return the ``count`` slowest results, slowest first (``0`` for all).
+
+        Equal durations are ordered by node id, matching ``--durations``.
+        """
+
+        ordered = sorted(
+            self.results,
+            key=lambda result: (-result.duration, f"{result.path}::{result.name}"),
+        )
+        return ordered[:count] if count > 0 else ordered
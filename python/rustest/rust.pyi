@@ -2,7 +2,9 @@
 
 from __future__ import annotations
 
-from typing import Sequence
+import asyncio
+
+from typing import Callable, Mapping, Sequence
 
 # Event classes
 class SuiteStartedEvent:
@@ -10,6 +12,11 @@ class SuiteStartedEvent:
 
     total_files: int
     total_tests: int
+    rootdir: str
+    configfile: str | None
+    rustest_version: str
+    python_version: str
+    platform: str
     timestamp: float
 
 class SuiteCompletedEvent:
@@ -19,6 +26,8 @@ class SuiteCompletedEvent:
     failed: int
     skipped: int
     errors: int
+    xfailed: int
+    xpassed: int
     duration: float
     timestamp: float
 
@@ -26,6 +35,7 @@ class FileStartedEvent:
     """Event emitted when a test file starts."""
 
     file_path: str
+    display_name: str
     total_tests: int
     timestamp: float
 
@@ -45,9 +55,12 @@ class TestCompletedEvent:
     test_id: str
     file_path: str
     test_name: str
+    display_name: str
     status: str
     duration: float
     message: str | None
+    failure_fingerprint: int | None
+    fixtures: list[str]
     timestamp: float
 
 class CollectionErrorEvent:
@@ -55,6 +68,10 @@ class CollectionErrorEvent:
 
     path: str
     message: str
+    error_type: str | None
+    filename: str | None
+    lineno: int | None
+    frames: list[CollectionFrame]
     timestamp: float
 
 class CollectionStartedEvent:
@@ -88,12 +105,43 @@ class PyTestResult:
     message: str | None
     stdout: str | None
     stderr: str | None
+    stdout_bytes: int
+    stderr_bytes: int
+    reruns: int
+    was_skipped: str | None
+    fixtures: list[str]
+
+class CollectionFrame:
+    """One traceback frame of a collection error."""
+
+    filename: str
+    lineno: int
+    name: str
 
 class CollectionError:
     """Error that occurred during test collection (e.g., syntax error, import error)."""
 
     path: str
     message: str
+    error_type: str | None
+    filename: str | None
+    lineno: int | None
+    frames: list[CollectionFrame]
+
+class TeardownError:
+    """A fixture teardown that raised."""
+
+    fixture: str
+    scope: str
+    message: str
+
+class MarkerInfo:
+    """A mark known to the registry, used by collected tests, or both."""
+
+    name: str
+    description: str | None
+    registered: bool
+    tests: int
 
 class PyRunReport:
     """Test run report from the Rust extension."""
@@ -102,9 +150,21 @@ class PyRunReport:
     passed: int
     failed: int
     skipped: int
+    errors: int
+    xfailed: int
+    xpassed: int
     duration: float
     results: list[PyTestResult]
     collection_errors: list[CollectionError]
+    by_marker: dict[str, dict[str, int]]
+    fixture_setups: int
+    fixture_teardowns: int
+    file_durations: list[tuple[str, float]]
+    warnings: list[tuple[str, str]]
+    teardown_errors: list[TeardownError]
+    interrupted: bool
+
+    def slowest(self, count: int = 0) -> list[PyTestResult]: ...
 
 def run(
     paths: Sequence[str],
@@ -122,10 +182,62 @@ def run(
     event_callback: object | None = ...,
     default_test_loop_scope: str = ...,
     default_fixture_loop_scope: str = ...,
+    compact: bool = ...,
+    force_color: bool = ...,
+    max_name_width: int | None = ...,
+    max_processes: int | None = ...,
+    trace_config: bool = ...,
+    max_file_size: int | None = ...,
+    event_loop_policy: asyncio.AbstractEventLoopPolicy | None = ...,
+    filter_traceback: bool = ...,
+    continue_on_collection_errors: bool = ...,
+    check: bool = ...,
+    status_symbols: dict[str, str] | None = ...,
+    sample: int | None = ...,
+    shuffle_seed: int | None = ...,
+    github_annotations: bool = ...,
+    tap_output: str | os.PathLike[str] | None = ...,
+    wrap_failures: bool = ...,
+    passed_only: str | os.PathLike[str] | None = ...,
+    discovery_threads: int | None = ...,
+    run_slow: bool = ...,
+    enrich_exclude: Sequence[str] | None = ...,
+    resume: bool = ...,
+    xfail_list: str | os.PathLike[str] | None = ...,
+    line_trace: bool = ...,
+    sqlite_path: str | os.PathLike[str] | None = ...,
+    changed_lines: str | None = ...,
+    slowest_files: int | None = ...,
+    asyncio_debug: bool = ...,
+    trace_json: str | None = ...,
+    group_failures: bool = ...,
+    loop_scope_strict: bool = ...,
+    notebooks: bool = ...,
+    junit_xml_path: str | None = ...,
+    json_report_path: str | None = ...,
+    durations: int | None = ...,
+    on_complete: Callable[[PyRunReport, list[str]], object] | None = ...,
+    failure_exceptions: Sequence[str] | None = ...,
+    log_level: str | None = ...,
+    shuffle: bool = ...,
+    run_skipped: bool = ...,
+    import_timeout: float | None = ...,
+    show_test_fixtures: bool = ...,
+    collapse_param_failures: bool = ...,
+    max_failures: int | None = ...,
+    node_ids: Sequence[str] | None = ...,
+    worker: bool = ...,
 ) -> PyRunReport:
     """Execute tests and return a report."""
     ...
 
+def list_markers(
+    paths: Sequence[str],
+    markers: Mapping[str, str] | None = ...,
+) -> list[MarkerInfo]:
+    """List built-in, registered and used marks for the tests under ``paths``."""
+    ...
+
 def getfixturevalue(name: str) -> object:
     """Resolve a fixture through the active test resolver."""
     ...
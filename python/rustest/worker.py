@@ -0,0 +1,123 @@
+"""Persistent worker process for running batches of tests.
+
+Started as ``python -m rustest.worker`` by the Rust worker pool. The process
+stays alive for the whole run, reading batches of node ids from stdin and
+writing their results to stdout. Each message is a 4-byte big-endian length
+followed by UTF-8 JSON; closing stdin tells the worker to exit.
+
+A batch is run as one session, so session-scoped fixtures are shared by the
+batch's tests but not with other workers.
+"""
+
+from __future__ import annotations
+
+import json
+import os
+import struct
+import sys
+from collections import defaultdict
+from typing import IO, Any
+
+_HEADER = struct.Struct(">I")
+
+
+def read_message(stream: IO[bytes]) -> Any | None:
+    """Read one framed message, or return None at end of stream."""
+    header = stream.read(_HEADER.size)
+    if len(header) < _HEADER.size:
+        return None
+    (length,) = _HEADER.unpack(header)
+    payload = stream.read(length)
+    if len(payload) < length:
+        raise EOFError("stream ended mid-message")
+    return json.loads(payload)
+
+
+def write_message(stream: IO[bytes], message: Any) -> None:
+    """Write one framed message and flush it."""
+    payload = json.dumps(message).encode("utf-8")
+    stream.write(_HEADER.pack(len(payload)) + payload)
+    stream.flush()
+
+
+def run_batch(
+    node_ids: list[str], options: dict[str, Any] | None = None
+) -> dict[str, Any]:
+    """Run the given ``path::name`` node ids and return their results.
+
+    Only these tests run; ``options`` are passed through to
+    ``rustest.rust.run``. Besides ``results``, the response carries what the
+    batch's session reported: warnings, teardown errors and fixture counts.
+    The batch runs as a worker session: the parent run keeps the last-failed
+    cache and journal, and has already applied ``RUSTEST_ADDOPTS``.
+    """
+    from .rust import run
+
+    wanted: dict[str, set[str]] = defaultdict(set)
+    paths: dict[str, str] = {}
+    for node_id in node_ids:
+        path, _, name = node_id.partition("::")
+        wanted[os.path.realpath(path)].add(name)
+        paths[os.path.realpath(path)] = path
+
+    results: dict[str, dict[str, Any]] = {}
+    session: dict[str, Any] = {}
+    if wanted:
+        report = run(
+            list(paths.values()),
+            event_callback=lambda _event: None,
+            node_ids=node_ids,
+            worker=True,
+            **(options or {}),
+        )
+        for result in report.results:
+            real_path = os.path.realpath(result.path)
+            if result.name in wanted.get(real_path, ()):
+                node_id = f"{paths[real_path]}::{result.name}"
+                results[node_id] = {
+                    "node_id": node_id,
+                    "status": result.status,
+                    "duration": result.duration,
+                    "message": result.message,
+                    "stdout": result.stdout,
+                    "stderr": result.stderr,
+                    "fixtures": list(result.fixtures),
+                }
+        session = {
+            "warnings": [list(warning) for warning in report.warnings],
+            "teardown_errors": [
+                [error.fixture, error.scope, error.message] for error in report.teardown_errors
+            ],
+            "fixture_setups": report.fixture_setups,
+            "fixture_teardowns": report.fixture_teardowns,
+        }
+
+    return {
+        "results": [
+            results.get(node_id)
+            or {
+                "node_id": node_id,
+                "status": "error",
+                "duration": 0.0,
+                "message": f"Test {node_id} was not collected by the worker",
+            }
+            for node_id in node_ids
+        ],
+        **session,
+    }
+
+
+def main() -> int:
+    stdin, stdout = sys.stdin.buffer, sys.stdout.buffer
+    # Test output must not corrupt the protocol stream
+    sys.stdout = sys.stderr
+    while True:
+        request = read_message(stdin)
+        if request is None:
+            return 0
+        response = run_batch(request["node_ids"], request.get("options"))
+        write_message(stdout, {"batch_id": request["batch_id"], **response})
+
+
+if __name__ == "__main__":
+    raise SystemExit(main())
@@ -0,0 +1,51 @@
+"""Tests for the structural diff shown under failed container comparisons."""
+
+from __future__ import annotations
+
+from rustest.assertion_diff import is_container, structural_diff
+
+
+def test_nested_dicts_pinpoint_the_differing_path() -> None:
+    expected = {"user": {"name": "Ada", "address": {"city": "Paris", "zip": "69001"}}}
+    actual = {"user": {"name": "Ada", "address": {"city": "Lyon", "zip": "69001"}}}
+
+    assert structural_diff(expected, actual) == [
+        "['user']['address']['city']: expected 'Paris', got 'Lyon'"
+    ]
+
+
+def test_missing_and_unexpected_keys() -> None:
+    assert structural_diff({"a": 1, "b": 2}, {"b": 2, "c": 3}) == [
+        "['a']: missing, expected 1",
+        "['c']: unexpected 3",
+    ]
+
+
+def test_lists_report_indices_and_length() -> None:
+    assert structural_diff([1, 2, 3], [1, 5]) == [
+        "[1]: expected 2, got 5",
+        "(root): expected length 3, got 2",
+        "[2]: missing, expected 3",
+    ]
+
+
+def test_depth_limit_compares_deeper_values_whole() -> None:
+    expected = {"a": {"b": {"c": 1}}}
+    actual = {"a": {"b": {"c": 2}}}
+
+    assert structural_diff(expected, actual, max_depth=1) == [
+        "['a']: expected {'b': {'c': 1}}, got {'b': {'c': 2}}"
+    ]
+
+
+def test_line_limit_truncates_the_diff() -> None:
+    lines = structural_diff(list(range(100)), list(range(1, 101)), max_lines=5)
+
+    assert len(lines) == 6
+    assert lines[-1] == "... more differences not shown"
+
+
+def test_strings_are_not_containers() -> None:
+    assert is_container({"a": 1})
+    assert is_container([1])
+    assert not is_container("abc")
@@ -56,6 +56,46 @@ class TestCli:
                 verbose=False,
                 ascii=False,
                 no_color=False,
+                compact=False,
+                force_color=False,
+                max_name_width=None,
+                max_processes=None,
+                trace_config=False,
+                max_file_size=None,
+                filter_traceback=True,
+                continue_on_collection_errors=True,
+                check=False,
+                sample=None,
+                shuffle_seed=None,
+                github_annotations=False,
+                tap_output=None,
+                wrap_failures=False,
+                passed_only=None,
+                discovery_threads=None,
+                run_slow=False,
+                enrich_exclude=None,
+                resume=False,
+                xfail_list=None,
+                line_trace=False,
+                sqlite_path=None,
+                changed_lines=None,
+                slowest_files=None,
+                asyncio_debug=False,
+                trace_json=None,
+                group_failures=False,
+                loop_scope_strict=True,
+                notebooks=False,
+                junit_xml_path=None,
+                json_report_path=None,
+                durations=None,
+                failure_exceptions=None,
+                log_level=None,
+                shuffle=False,
+                run_skipped=False,
+                import_timeout=None,
+                show_test_fixtures=False,
+                collapse_param_failures=False,
+                max_failures=None,
             )
             assert exit_code == 0
 
@@ -89,6 +129,18 @@ class TestCliArguments:
         args = parser.parse_args(["--ascii"])
         assert args.ascii is True
 
+    def test_compact_flag(self) -> None:
+        """Test --compact flag is parsed correctly."""
+        parser = cli.build_parser()
+        args = parser.parse_args(["--compact"])
+        assert args.compact is True
+
+    def test_max_name_width_flag(self) -> None:
+        """Test --max-name-width is parsed as an integer."""
+        parser = cli.build_parser()
+        args = parser.parse_args(["--max-name-width", "60"])
+        assert args.max_name_width == 60
+
     def test_color_auto_by_default(self) -> None:
         """Test color is auto by default."""
         parser = cli.build_parser()
@@ -187,6 +239,26 @@ class TestCIDetection:
             # Should have no_color=True in CI
             assert mock_run.call_args.kwargs["no_color"] is True
 
+    def test_force_color_enables_colors_in_ci(self) -> None:
+        """Test that FORCE_COLOR keeps auto mode from disabling colors in CI."""
+        report = RunReport(
+            total=0,
+            passed=0,
+            failed=0,
+            skipped=0,
+            duration=0.0,
+            results=(),
+            collection_errors=(),
+        )
+
+        with patch.dict(os.environ, {"CI": "true", "FORCE_COLOR": "1"}):
+            with patch("rustest.cli.run", return_value=report) as mock_run:
+                cli.main([])
+
+            # The renderers see FORCE_COLOR themselves
+            assert mock_run.call_args.kwargs["no_color"] is False
+            assert mock_run.call_args.kwargs["force_color"] is False
+
     def test_color_enabled_locally_by_default(self) -> None:
         """Test that colors are enabled locally when not explicitly set."""
         report = RunReport(
@@ -226,6 +298,7 @@ class TestCIDetection:
 
             # Should have no_color=False even in CI when --color always is passed
             assert mock_run.call_args.kwargs["no_color"] is False
+            assert mock_run.call_args.kwargs["force_color"] is True
 
 
 class TestCliEdgeCases:
@@ -285,6 +358,13 @@ class TestCliEdgeCases:
         args = parser.parse_args([])
         assert args.workers is None
 
+    def test_maxprocesses_flag(self) -> None:
+        """Test --maxprocesses is parsed into max_processes."""
+        parser = cli.build_parser()
+        args = parser.parse_args(["-n", "8", "--maxprocesses", "4"])
+        assert args.workers == 8
+        assert args.max_processes == 4
+
     def test_last_failed_flag(self) -> None:
         """Test --lf/--last-failed flag."""
         parser = cli.build_parser()
@@ -315,6 +395,234 @@ class TestCliEdgeCases:
         args = parser.parse_args(["--pytest-compat"])
         assert args.pytest_compat is True
 
+    def test_trace_config_flag(self) -> None:
+        """Test --trace-config flag."""
+        parser = cli.build_parser()
+        args = parser.parse_args(["--trace-config"])
+        assert args.trace_config is True
+
+    def test_max_file_size_flag(self) -> None:
+        """Test --max-file-size is parsed as a byte count."""
+        parser = cli.build_parser()
+        args = parser.parse_args(["--max-file-size", "1048576"])
+        assert args.max_file_size == 1048576
+
+    def test_full_trace_flag(self) -> None:
+        """Test --full-trace flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).full_trace is False
+        assert parser.parse_args(["--full-trace"]).full_trace is True
+
+    def test_continue_on_collection_errors_flags(self) -> None:
+        """Test collection errors are tolerated unless disabled."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).continue_on_collection_errors is True
+        args = parser.parse_args(["--no-continue-on-collection-errors"])
+        assert args.continue_on_collection_errors is False
+
+    def test_check_flag(self) -> None:
+        """Test --check flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).check is False
+        assert parser.parse_args(["--check"]).check is True
+
+    def test_sample_flag(self) -> None:
+        """Test --sample is parsed as a test count."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).sample is None
+        assert parser.parse_args(["--sample", "25"]).sample == 25
+
+    def test_shuffle_seed_flag(self) -> None:
+        """Test --shuffle-seed is parsed as an integer."""
+        parser = cli.build_parser()
+        assert parser.parse_args(["--shuffle-seed", "1234"]).shuffle_seed == 1234
+
+    def test_github_annotations_flag(self) -> None:
+        """Test --github-annotations flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).github_annotations is False
+        assert parser.parse_args(["--github-annotations"]).github_annotations is True
+
+    def test_tap_flag(self) -> None:
+        """Test --tap takes a report path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).tap_output is None
+        assert parser.parse_args(["--tap", "report.tap"]).tap_output == "report.tap"
+
+    def test_wrap_failures_flag(self) -> None:
+        """Test --wrap-failures flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).wrap_failures is False
+        assert parser.parse_args(["--wrap-failures"]).wrap_failures is True
+
+    def test_passed_only_flag(self) -> None:
+        """Test --passed-only takes a report path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).passed_only is None
+        args = parser.parse_args(["--passed-only", "report.json"])
+        assert args.passed_only == "report.json"
+
+    def test_discovery_threads_flag(self) -> None:
+        """Test --discovery-threads takes a thread count."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).discovery_threads is None
+        assert parser.parse_args(["--discovery-threads", "4"]).discovery_threads == 4
+
+    def test_run_slow_flag(self) -> None:
+        """Test --run-slow flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).run_slow is False
+        assert parser.parse_args(["--run-slow"]).run_slow is True
+
+    def test_enrich_exclude_flag(self) -> None:
+        """Test --enrich-exclude can be repeated."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).enrich_exclude is None
+        args = parser.parse_args(
+            ["--enrich-exclude", "tests/legacy/*", "--enrich-exclude", "**/test_hw.py"]
+        )
+        assert args.enrich_exclude == ["tests/legacy/*", "**/test_hw.py"]
+
+    def test_resume_flag(self) -> None:
+        """Test --resume flag."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).resume is False
+        assert parser.parse_args(["--resume"]).resume is True
+
+    def test_xfail_list_flag(self) -> None:
+        """Test --xfail-list takes a file path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).xfail_list is None
+        args = parser.parse_args(["--xfail-list", "known-failures.txt"])
+        assert args.xfail_list == "known-failures.txt"
+
+    def test_line_trace_flag(self) -> None:
+        """Test --line-trace is opt-in."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).line_trace is False
+        assert parser.parse_args(["--line-trace"]).line_trace is True
+
+    def test_sqlite_flag(self) -> None:
+        """Test --sqlite takes a database path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).sqlite_path is None
+        args = parser.parse_args(["--sqlite", "results.db"])
+        assert args.sqlite_path == "results.db"
+
+    def test_changed_lines_flag(self) -> None:
+        """Test --changed-lines defaults its revision to HEAD."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).changed_lines is None
+        assert parser.parse_args(["--changed-lines"]).changed_lines == "HEAD"
+        assert parser.parse_args(["--changed-lines", "main"]).changed_lines == "main"
+
+    def test_slowest_files_flag(self) -> None:
+        """Test --slowest-files takes a file count."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).slowest_files is None
+        assert parser.parse_args(["--slowest-files", "3"]).slowest_files == 3
+
+    def test_asyncio_debug_flag(self) -> None:
+        """Test --asyncio-debug toggles asyncio debug mode."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).asyncio_debug is False
+        assert parser.parse_args(["--asyncio-debug"]).asyncio_debug is True
+
+    def test_trace_json_flag(self) -> None:
+        """Test --trace-json takes an output path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).trace_json is None
+        assert parser.parse_args(["--trace-json", "trace.json"]).trace_json == "trace.json"
+
+    def test_group_failures_flag(self) -> None:
+        """Test --group-failures toggles failure grouping."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).group_failures is False
+        assert parser.parse_args(["--group-failures"]).group_failures is True
+
+    def test_no_loop_scope_strict_flag(self) -> None:
+        """Test --no-loop-scope-strict turns loop scope mismatches into warnings."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).loop_scope_strict is True
+        assert parser.parse_args(["--no-loop-scope-strict"]).loop_scope_strict is False
+
+    def test_notebooks_flag(self) -> None:
+        """Test --notebooks enables notebook collection."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).notebooks is False
+        assert parser.parse_args(["--notebooks"]).notebooks is True
+
+    def test_junit_xml_flag(self) -> None:
+        """Test --junit-xml takes an output path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).junit_xml_path is None
+        assert parser.parse_args(["--junit-xml", "report.xml"]).junit_xml_path == "report.xml"
+
+    def test_json_report_flag(self) -> None:
+        """Test --json-report takes an output path."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).json_report_path is None
+        assert parser.parse_args(["--json-report", "report.json"]).json_report_path == "report.json"
+
+    def test_durations_flag(self) -> None:
+        """Test --durations takes a test count."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).durations is None
+        assert parser.parse_args(["--durations", "5"]).durations == 5
+        assert parser.parse_args(["--durations=0"]).durations == 0
+
+    def test_failure_exception_flag(self) -> None:
+        """Test --failure-exception can be repeated."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).failure_exceptions is None
+        args = parser.parse_args(
+            ["--failure-exception", "AssertionError", "--failure-exception", "TimeoutError"]
+        )
+        assert args.failure_exceptions == ["AssertionError", "TimeoutError"]
+
+    def test_log_level_flag(self) -> None:
+        """Test --log-level sets the default caplog level."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).log_level is None
+        assert parser.parse_args(["--log-level", "INFO"]).log_level == "INFO"
+
+    def test_shuffle_flag(self) -> None:
+        """Test --shuffle enables shuffling."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).shuffle is False
+        assert parser.parse_args(["--shuffle"]).shuffle is True
+
+    def test_run_skipped_flag(self) -> None:
+        """Test --run-skipped enables running skipped tests."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).run_skipped is False
+        assert parser.parse_args(["--run-skipped"]).run_skipped is True
+
+    def test_import_timeout_flag(self) -> None:
+        """Test --import-timeout parses seconds."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).import_timeout is None
+        assert parser.parse_args(["--import-timeout", "2.5"]).import_timeout == 2.5
+
+    def test_show_test_fixtures_flag(self) -> None:
+        """Test --show-test-fixtures enables the per-test fixture list."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).show_test_fixtures is False
+        assert parser.parse_args(["--show-test-fixtures"]).show_test_fixtures is True
+
+    def test_collapse_param_failures_flag(self) -> None:
+        """Test --collapse-param-failures enables collapsed parametrized failures."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).collapse_param_failures is False
+        assert parser.parse_args(["--collapse-param-failures"]).collapse_param_failures is True
+
+    def test_maxfail_flag(self) -> None:
+        """Test --maxfail parses the failure limit."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).max_failures is None
+        assert parser.parse_args(["--maxfail", "5"]).max_failures == 5
+        assert parser.parse_args(["--maxfail=2"]).max_failures == 2
+
     def test_no_codeblocks_flag(self) -> None:
         """Test --no-codeblocks flag."""
         parser = cli.build_parser()
@@ -455,6 +763,34 @@ class TestCliReturnCodes:
 
         assert exit_code == 2
 
+    def test_returns_one_on_teardown_errors(self) -> None:
+        """Test exit code is 1 when a fixture teardown raised."""
+        from rustest import TeardownError
+
+        report = RunReport(
+            total=0,
+            passed=0,
+            failed=0,
+            skipped=0,
+            duration=0.1,
+            results=(),
+            collection_errors=(),
+            teardown_errors=(
+                TeardownError(
+                    fixture="database",
+                    scope="module",
+                    message="RuntimeError: still open",
+                ),
+            ),
+        )
+
+        ci_vars = ["CI", "GITHUB_ACTIONS", "GITLAB_CI", "JENKINS_HOME"]
+        with patch.dict(os.environ, {var: "" for var in ci_vars}, clear=True):
+            with patch("rustest.cli.run", return_value=report):
+                exit_code = cli.main(["tests"])
+
+        assert exit_code == 1
+
     def test_returns_zero_with_only_skipped(self) -> None:
         """Test exit code is 0 when all tests are skipped."""
         result = TestResult(
@@ -483,6 +819,61 @@ class TestCliReturnCodes:
 
         assert exit_code == 0
 
+    @staticmethod
+    def _report_with_failures(failed: int, total: int) -> RunReport:
+        results = tuple(
+            TestResult(
+                name=f"test_{index}",
+                path="tests/test_explore.py",
+                status="failed" if index < failed else "passed",
+                duration=0.1,
+                message="AssertionError" if index < failed else None,
+                stdout=None,
+                stderr=None,
+            )
+            for index in range(total)
+        )
+        return RunReport(
+            total=total,
+            passed=total - failed,
+            failed=failed,
+            skipped=0,
+            duration=1.0,
+            results=results,
+            collection_errors=(),
+        )
+
+    def test_failures_within_allowed_ratio_exit_zero(self) -> None:
+        """Test 1 of 10 failing passes the run with a 0.2 failure ratio."""
+        report = self._report_with_failures(1, 10)
+
+        ci_vars = ["CI", "GITHUB_ACTIONS", "GITLAB_CI", "JENKINS_HOME"]
+        with patch.dict(os.environ, {var: "" for var in ci_vars}, clear=True):
+            with patch("rustest.cli.run", return_value=report):
+                exit_code = cli.main(["tests", "--allow-failure-ratio", "0.2"])
+
+        assert exit_code == 0
+        assert len(report.results) == 10
+
+    def test_failures_above_allowed_ratio_exit_one(self) -> None:
+        """Test 3 of 10 failing still fails the run with a 0.2 failure ratio."""
+        report = self._report_with_failures(3, 10)
+
+        ci_vars = ["CI", "GITHUB_ACTIONS", "GITLAB_CI", "JENKINS_HOME"]
+        with patch.dict(os.environ, {var: "" for var in ci_vars}, clear=True):
+            with patch("rustest.cli.run", return_value=report):
+                exit_code = cli.main(["tests", "--allow-failure-ratio", "0.2"])
+
+        assert exit_code == 1
+
+    def test_allowed_failure_ratio_must_be_a_fraction(self) -> None:
+        """Test --allow-failure-ratio rejects values outside 0-1."""
+        parser = cli.build_parser()
+        assert parser.parse_args([]).allow_failure_ratio is None
+        assert parser.parse_args(["--allow-failure-ratio", "0.25"]).allow_failure_ratio == 0.25
+        with pytest.raises(SystemExit):
+            parser.parse_args(["--allow-failure-ratio", "1.5"])
+
 
 class TestCliOutput:
     """Test CLI output formatting."""
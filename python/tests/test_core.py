@@ -17,15 +17,30 @@ class TestCoreRun:
             message=None,
             stdout=None,
             stderr=None,
+            stdout_bytes=0,
+            stderr_bytes=0,
+            reruns=0,
+            was_skipped=None,
+            fixtures=[],
         )
         dummy_report = SimpleNamespace(
             total=1,
             passed=1,
             failed=0,
             skipped=0,
+            errors=0,
+            xfailed=0,
+            xpassed=0,
             duration=0.05,
             results=[dummy_result],
             collection_errors=[],
+            by_marker={},
+            fixture_setups=0,
+            fixture_teardowns=0,
+            file_durations=[],
+            warnings=[],
+            teardown_errors=[],
+            interrupted=False,
         )
 
         captured_args: dict[str, object] = {}
@@ -46,6 +61,49 @@ class TestCoreRun:
             event_callback,
             default_test_loop_scope="function",
             default_fixture_loop_scope="function",
+            compact=False,
+            force_color=False,
+            max_name_width=None,
+            max_processes=None,
+            trace_config=False,
+            max_file_size=None,
+            event_loop_policy=None,
+            filter_traceback=True,
+            continue_on_collection_errors=True,
+            check=False,
+            status_symbols=None,
+            sample=None,
+            shuffle_seed=None,
+            github_annotations=False,
+            tap_output=None,
+            wrap_failures=False,
+            passed_only=None,
+            discovery_threads=None,
+            run_slow=False,
+            enrich_exclude=None,
+            resume=False,
+            xfail_list=None,
+            line_trace=False,
+            sqlite_path=None,
+            changed_lines=None,
+            slowest_files=None,
+            asyncio_debug=False,
+            trace_json=None,
+            group_failures=False,
+            loop_scope_strict=True,
+            notebooks=False,
+            junit_xml_path=None,
+            json_report_path=None,
+            durations=None,
+            on_complete=None,
+            failure_exceptions=None,
+            log_level=None,
+            shuffle=False,
+            run_skipped=False,
+            import_timeout=None,
+            show_test_fixtures=False,
+            collapse_param_failures=False,
+            max_failures=None,
         ):  # type: ignore[no-untyped-def]
             captured_args["paths"] = paths
             captured_args["pattern"] = pattern
@@ -60,6 +118,49 @@ class TestCoreRun:
             captured_args["ascii"] = ascii
             captured_args["no_color"] = no_color
             captured_args["event_callback"] = event_callback
+            captured_args["compact"] = compact
+            captured_args["force_color"] = force_color
+            captured_args["max_name_width"] = max_name_width
+            captured_args["max_processes"] = max_processes
+            captured_args["trace_config"] = trace_config
+            captured_args["max_file_size"] = max_file_size
+            captured_args["event_loop_policy"] = event_loop_policy
+            captured_args["filter_traceback"] = filter_traceback
+            captured_args["continue_on_collection_errors"] = continue_on_collection_errors
+            captured_args["check"] = check
+            captured_args["status_symbols"] = status_symbols
+            captured_args["sample"] = sample
+            captured_args["shuffle_seed"] = shuffle_seed
+            captured_args["github_annotations"] = github_annotations
+            captured_args["tap_output"] = tap_output
+            captured_args["wrap_failures"] = wrap_failures
+            captured_args["passed_only"] = passed_only
+            captured_args["discovery_threads"] = discovery_threads
+            captured_args["run_slow"] = run_slow
+            captured_args["enrich_exclude"] = enrich_exclude
+            captured_args["resume"] = resume
+            captured_args["xfail_list"] = xfail_list
+            captured_args["line_trace"] = line_trace
+            captured_args["sqlite_path"] = sqlite_path
+            captured_args["changed_lines"] = changed_lines
+            captured_args["slowest_files"] = slowest_files
+            captured_args["asyncio_debug"] = asyncio_debug
+            captured_args["trace_json"] = trace_json
+            captured_args["group_failures"] = group_failures
+            captured_args["loop_scope_strict"] = loop_scope_strict
+            captured_args["notebooks"] = notebooks
+            captured_args["junit_xml_path"] = junit_xml_path
+            captured_args["json_report_path"] = json_report_path
+            captured_args["durations"] = durations
+            captured_args["on_complete"] = on_complete
+            captured_args["failure_exceptions"] = failure_exceptions
+            captured_args["log_level"] = log_level
+            captured_args["shuffle"] = shuffle
+            captured_args["run_skipped"] = run_skipped
+            captured_args["import_timeout"] = import_timeout
+            captured_args["show_test_fixtures"] = show_test_fixtures
+            captured_args["collapse_param_failures"] = collapse_param_failures
+            captured_args["max_failures"] = max_failures
             return dummy_report
 
         with stub_rust_module(run=fake_run):
@@ -85,5 +186,48 @@ class TestCoreRun:
         assert captured_args["no_color"] is False
         assert captured_args["event_callback"] is not None  # EventRouter.emit callback
         assert callable(captured_args["event_callback"])
+        assert captured_args["compact"] is False
+        assert captured_args["force_color"] is False
+        assert captured_args["max_name_width"] is None
+        assert captured_args["max_processes"] is None
+        assert captured_args["trace_config"] is False
+        assert captured_args["max_file_size"] is None
+        assert captured_args["event_loop_policy"] is None
+        assert captured_args["filter_traceback"] is True
+        assert captured_args["continue_on_collection_errors"] is True
+        assert captured_args["check"] is False
+        assert captured_args["status_symbols"] is None
+        assert captured_args["sample"] is None
+        assert captured_args["shuffle_seed"] is None
+        assert captured_args["github_annotations"] is False
+        assert captured_args["tap_output"] is None
+        assert captured_args["wrap_failures"] is False
+        assert captured_args["passed_only"] is None
+        assert captured_args["discovery_threads"] is None
+        assert captured_args["run_slow"] is False
+        assert captured_args["enrich_exclude"] is None
+        assert captured_args["resume"] is False
+        assert captured_args["xfail_list"] is None
+        assert captured_args["line_trace"] is False
+        assert captured_args["sqlite_path"] is None
+        assert captured_args["changed_lines"] is None
+        assert captured_args["slowest_files"] is None
+        assert captured_args["asyncio_debug"] is False
+        assert captured_args["trace_json"] is None
+        assert captured_args["group_failures"] is False
+        assert captured_args["loop_scope_strict"] is True
+        assert captured_args["notebooks"] is False
+        assert captured_args["junit_xml_path"] is None
+        assert captured_args["json_report_path"] is None
+        assert captured_args["durations"] is None
+        assert captured_args["on_complete"] is None
+        assert captured_args["failure_exceptions"] is None
+        assert captured_args["log_level"] is None
+        assert captured_args["shuffle"] is False
+        assert captured_args["run_skipped"] is False
+        assert captured_args["import_timeout"] is None
+        assert captured_args["show_test_fixtures"] is False
+        assert captured_args["collapse_param_failures"] is False
+        assert captured_args["max_failures"] is None
         assert report.total == 1
         assert report.passed == 1
@@ -75,6 +75,37 @@ class TestParametrizeDecorator:
             def _(_: int) -> None:
                 raise AssertionError("should not run")
 
+    def test_parametrize_records_scope_per_argument(self) -> None:
+        @parametrize("backend", ["sqlite"], indirect=True, scope="module")
+        @parametrize("size", [1, 2])
+        def test_func(backend: str, size: int) -> None:
+            pass
+
+        scopes = getattr(test_func, "__rustest_parametrization_scope__")
+        assert scopes == {"backend": "module"}
+
+    def test_stacked_parametrize_varies_the_last_decorator_fastest(self) -> None:
+        @parametrize("x", [0, 1])
+        @parametrize("y", [2, 3])
+        def test_func(x: int, y: int) -> None:
+            pass
+
+        cases = getattr(test_func, "__rustest_parametrization__")
+        assert [case["id"] for case in cases] == ["2-0", "2-1", "3-0", "3-1"]
+        assert cases[1]["values"] == {"y": 2, "x": 1}
+
+    def test_stacked_parametrize_rejects_a_repeated_argument(self) -> None:
+        with pytest.raises(ValueError, match="'x' is already parametrized"):
+
+            @parametrize("x", [1])
+            @parametrize(("x", "y"), [(2, 3)])
+            def _(_: int, __: int) -> None:
+                raise AssertionError("should not run")
+
+    def test_parametrize_rejects_unknown_scope(self) -> None:
+        with pytest.raises(ValueError, match="Invalid parametrize scope"):
+            parametrize("value", [1], scope="galaxy")
+
 
 class TestMarkDecorator:
     def test_mark_attaches_single_mark(self) -> None:
@@ -381,6 +381,17 @@ class TestRaises:
         assert "ExceptionInfo" in repr_str
         assert "ValueError" in repr_str
 
+    def test_raises_excinfo_match(self) -> None:
+        """Test ExceptionInfo.match() searches the exception message."""
+        with raises(ValueError) as exc_info:
+            raise ValueError("invalid literal for int()")
+
+        assert exc_info.match(r"literal for \w+")
+        assert exc_info.excinfo is not None
+        assert exc_info.excinfo.match("invalid")
+        with pytest.raises(AssertionError, match="does not match"):
+            exc_info.match("^literal")
+
     def test_raises_with_partial_match(self) -> None:
         """Test that match does partial matching, not exact."""
         with raises(ValueError, match="invalid"):
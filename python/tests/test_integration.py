@@ -231,6 +231,33 @@ def test_success():
         assert failed_tests[0].name == "test_fail"
         assert skipped_tests[0].name == "test_skip"
 
+    def test_run_report_slowest(self) -> None:
+        """Test the slowest results come first, ties ordered by node id."""
+
+        def result(name: str, duration: float) -> TestResult:
+            return TestResult(
+                name=name,
+                path="/test.py",
+                status="passed",
+                duration=duration,
+                message=None,
+                stdout=None,
+                stderr=None,
+            )
+
+        report = RunReport(
+            total=3,
+            passed=3,
+            failed=0,
+            skipped=0,
+            duration=1.0,
+            results=(result("test_b", 0.2), result("test_fast", 0.01), result("test_a", 0.2)),
+            collection_errors=(),
+        )
+
+        assert [r.name for r in report.slowest(2)] == ["test_a", "test_b"]
+        assert [r.name for r in report.slowest()] == ["test_a", "test_b", "test_fast"]
+
     def test_run_with_worker_count(self, tmp_path: Path) -> None:
         """Test running tests with specific worker count."""
         self._write_test_file(
@@ -0,0 +1,25 @@
+"""Tests for the ``--line-trace`` recorder."""
+
+from __future__ import annotations
+
+import sys
+from types import FrameType
+from typing import Any
+
+from rustest import line_trace
+
+
+class TestLineTrace:
+    def test_stop_restores_the_previous_tracer(self) -> None:
+        def previous(_frame: FrameType, _event: str, _arg: Any) -> None:
+            return None
+
+        original = sys.gettrace()
+        sys.settrace(previous)
+        try:
+            line_trace.start("tests/test_a.py::test_one")
+            line_trace.stop()
+            assert sys.gettrace() is previous
+        finally:
+            sys.settrace(original)
+            line_trace.reset()
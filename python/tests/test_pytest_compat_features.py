@@ -22,9 +22,9 @@ from rustest.compat.pytest import (
     importorskip,
     FixtureRequest,
 )
-from rustest.decorators import parametrize, ParameterSet, _build_cases
+from rustest.decorators import mark, parametrize, ParameterSet, _build_cases
 from rustest.builtin_fixtures import CaptureFixture
-from rustest.fixture_registry import register_fixtures, clear_registry
+from rustest.fixture_registry import register_fixtures, clear_registry, take_pending_teardowns
 
 
 # =============================================================================
@@ -219,14 +219,25 @@ class TestPytestParam:
         assert result.id == "test_case"
         assert result.values == (1, 2)
 
-    def test_param_with_marks_warns(self):
-        """Test that param() with marks emits a warning."""
-        with warnings.catch_warnings(record=True) as w:
-            warnings.simplefilter("always")
-            _ = param(1, marks="some_mark")
+    def test_param_marks_are_attached_to_their_case(self):
+        """Test param() marks end up on that case only."""
 
-            assert len(w) == 1
-            assert "marks are not yet supported" in str(w[0].message)
+        @parametrize(
+            "x",
+            [
+                1,
+                param(2, marks=mark.xfail(reason="bug")),
+                param(3, marks=[mark.skip, mark.slow]),
+            ],
+        )
+        def dummy_test(x):
+            pass
+
+        cases = dummy_test.__rustest_parametrization__
+        assert "marks" not in cases[0]
+        assert [m["name"] for m in cases[1]["marks"]] == ["xfail"]
+        assert cases[1]["marks"][0]["kwargs"]["reason"] == "bug"
+        assert [m["name"] for m in cases[2]["marks"]] == ["skip", "slow"]
 
     def test_param_in_parametrize(self):
         """Test that param() works with parametrize decorator."""
@@ -779,3 +790,27 @@ class TestFixtureRequestFallback:
             assert result is request
         finally:
             clear_registry()
+
+    def test_getfixturevalue_defers_generator_teardown(self):
+        """Generator fixtures stay open until the runner takes their teardowns."""
+        events: list[str] = []
+
+        def resource():
+            events.append("enter")
+            yield "resource"
+            events.append("exit")
+
+        register_fixtures({"resource": resource})
+        try:
+            request = FixtureRequest()
+            assert request.getfixturevalue("resource") == "resource"
+            assert events == ["enter"]
+
+            pending = take_pending_teardowns()
+            assert len(pending) == 1
+            with pytest.raises(StopIteration):
+                next(pending[0])
+            assert events == ["enter", "exit"]
+            assert take_pending_teardowns() == []
+        finally:
+            clear_registry()
@@ -18,15 +18,30 @@ class TestReportingConversion:
             message=None,
             stdout="output",
             stderr=None,
+            stdout_bytes=6,
+            stderr_bytes=0,
+            reruns=0,
+            was_skipped=None,
+            fixtures=[],
         )
         py_report = SimpleNamespace(
             total=1,
             passed=1,
             failed=0,
             skipped=0,
+            errors=0,
+            xfailed=0,
+            xpassed=0,
             duration=0.123,
             results=[py_result],
             collection_errors=[],
+            by_marker={},
+            fixture_setups=0,
+            fixture_teardowns=0,
+            file_durations=[],
+            warnings=[],
+            teardown_errors=[],
+            interrupted=False,
         )
 
         report = RunReport.from_py(py_report)
@@ -38,6 +53,47 @@ class TestReportingConversion:
         assert isinstance(result, TestResult)
         assert result.name == "test_sample"
         assert result.stdout == "output"
+        assert result.stdout_bytes == 6
+        assert result.stderr_bytes == 0
+
+    def test_from_py_converts_structured_collection_errors(self) -> None:
+        frame = SimpleNamespace(filename="/src/tests/helpers.py", lineno=3, name="<module>")
+        py_error = SimpleNamespace(
+            path="tests/test_broken.py",
+            message="Traceback ...\nNameError: name 'x' is not defined",
+            error_type="NameError",
+            filename="/src/tests/helpers.py",
+            lineno=3,
+            frames=[frame],
+        )
+        py_report = SimpleNamespace(
+            total=0,
+            passed=0,
+            failed=0,
+            skipped=0,
+            errors=0,
+            xfailed=0,
+            xpassed=0,
+            duration=0.0,
+            results=[],
+            collection_errors=[py_error],
+            by_marker={},
+            fixture_setups=0,
+            fixture_teardowns=0,
+            file_durations=[],
+            warnings=[],
+            teardown_errors=[],
+            interrupted=False,
+        )
+
+        report = RunReport.from_py(py_report)
+
+        (error,) = report.collection_errors
+        assert error.error_type == "NameError"
+        assert (error.filename, error.lineno) == ("/src/tests/helpers.py", 3)
+        assert [(f.filename, f.lineno, f.name) for f in error.frames] == [
+            ("/src/tests/helpers.py", 3, "<module>")
+        ]
 
     def test_iter_status_filters_results(self) -> None:
         passed = TestResult(
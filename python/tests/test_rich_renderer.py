@@ -0,0 +1,279 @@
+"""Tests for the rich terminal renderer used by the default output."""
+
+from __future__ import annotations
+
+import io
+import os
+import sys
+from types import SimpleNamespace
+from unittest.mock import patch
+
+from rustest.renderers.rich_renderer import (
+    RichRenderer,
+    estimate_remaining,
+    progress_prefix,
+    wrap_line,
+)
+
+
+class TestColorDetection:
+    """Colors are decided like the Rust renderers when no --color choice was made."""
+
+    def test_ci_log_without_force_color_has_no_escape_codes(self) -> None:
+        stream = io.StringIO()
+        with patch.dict(os.environ, {"CI": "true"}, clear=True):
+            with patch.object(sys, "stderr", stream):
+                renderer = RichRenderer()
+                renderer.console.print("[red]✗ failed[/red]")
+
+        assert renderer.use_colors is False
+        assert "\x1b[" not in stream.getvalue()
+
+    def test_force_color_enables_colors_off_a_terminal(self) -> None:
+        with patch.dict(os.environ, {"FORCE_COLOR": "1"}, clear=True):
+            with patch.object(sys, "stderr", io.StringIO()):
+                assert RichRenderer().use_colors is True
+
+    def test_no_color_beats_force_color(self) -> None:
+        with patch.dict(os.environ, {"NO_COLOR": "1", "FORCE_COLOR": "1"}, clear=True):
+            with patch.object(sys, "stderr", io.StringIO()):
+                assert RichRenderer().use_colors is False
+
+    def test_explicit_choice_skips_detection(self) -> None:
+        with patch.dict(os.environ, {"NO_COLOR": "1"}, clear=True):
+            assert RichRenderer(use_colors=True).use_colors is True
+
+
+class TestNameWidth:
+    """Names are shown as the Rust core shortened them for display."""
+
+    def test_progress_rows_show_the_shortened_file_name(self) -> None:
+        renderer = RichRenderer(use_colors=False)
+        event = SimpleNamespace(
+            file_path="tests/" + "nested/" * 12 + "test_long.py",
+            display_name="tests/nest...ed/test_long.py",
+            total_tests=2,
+            timestamp=0.0,
+        )
+
+        with patch.object(renderer, "progress") as progress:
+            with patch.object(renderer, "_ensure_started"):
+                renderer._handle_file_started(event)  # type: ignore[arg-type]
+
+        description = progress.add_task.call_args.args[0]
+        assert "tests/nest...ed/test_long.py" in description
+        assert event.file_path not in description
+
+
+class TestStatusSymbols:
+    """Configured status symbols replace the built-in glyphs."""
+
+    def test_file_rows_use_configured_symbols(self) -> None:
+        renderer = RichRenderer(use_colors=False, status_symbols={"passed": "OK", "failed": "KO"})
+        renderer.file_tasks["tests/test_a.py"] = 0  # type: ignore[assignment]
+        event = SimpleNamespace(
+            file_path="tests/test_a.py", duration=0.005, passed=1, failed=1, skipped=0
+        )
+
+        with patch.object(renderer, "progress") as progress:
+            renderer._handle_file_completed(event)  # type: ignore[arg-type]
+
+        description = progress.update.call_args.kwargs["description"]
+        assert description.startswith("KO ")
+
+    def test_summary_uses_configured_symbols(self) -> None:
+        renderer = RichRenderer(use_colors=False, use_ascii=True, status_symbols={"skipped": "~"})
+        event = SimpleNamespace(
+            total=2, passed=1, failed=0, skipped=1, errors=0, xfailed=0, xpassed=0, duration=0.1
+        )
+
+        with patch.object(renderer.console, "print") as mock_print:
+            renderer._handle_suite_completed(event)  # type: ignore[arg-type]
+
+        summary = mock_print.call_args_list[-1].args[0]
+        assert "PASS 1 passed" in summary
+        assert "~ 1 skipped" in summary
+
+
+class TestWrapFailures:
+    """Failure messages can be soft-wrapped with a hanging indent."""
+
+    def test_wrap_line_keeps_indentation_with_a_hanging_indent(self) -> None:
+        line = "    assert compute(alpha, beta, gamma) == expected_value"
+
+        assert wrap_line(line, 30) == [
+            "    assert compute(alpha,",
+            "        beta, gamma) ==",
+            "        expected_value",
+        ]
+
+    def test_short_lines_are_left_alone(self) -> None:
+        assert wrap_line("    x = 1", 30) == ["    x = 1"]
+
+    def test_failures_are_wrapped_to_the_terminal_width(self) -> None:
+        renderer = RichRenderer(use_colors=False, wrap_failures=True)
+        renderer.failures.append(
+            ("tests/test_a.py::test_long", "tests/test_a.py", "E   " + "word " * 20)
+        )
+        event = SimpleNamespace(
+            total=1, passed=0, failed=1, skipped=0, errors=0, xfailed=0, xpassed=0, duration=0.1
+        )
+
+        with patch.object(renderer, "console") as console:
+            console.is_terminal = True
+            console.width = 40
+            renderer._handle_suite_completed(event)  # type: ignore[arg-type]
+
+        printed = [call.args[0] for call in console.print.call_args_list if call.args]
+        message = next(text for text in printed if text.startswith("E   "))
+        assert all(len(line) <= 40 for line in message.splitlines())
+        assert message.splitlines()[1].startswith("    word")
+
+
+class TestVerboseProgress:
+    """Verbose lines carry the run's completion percentage."""
+
+    def test_percentage_reaches_100_on_the_last_test(self) -> None:
+        renderer = RichRenderer(use_colors=False, use_ascii=True, verbose=True)
+        renderer.total_tests = 3
+
+        with patch.object(renderer.console, "print") as mock_print:
+            for name in ("test_a", "test_b", "test_c"):
+                renderer._handle_test_completed(  # type: ignore[arg-type]
+                    SimpleNamespace(
+                        test_id=f"tests/test_progress.py::{name}",
+                        file_path="tests/test_progress.py",
+                        display_name=f"tests/test_progress.py::{name}",
+                        status="passed",
+                        message=None,
+                    )
+                )
+
+        lines = [call.args[0] for call in mock_print.call_args_list]
+        assert lines == [
+            "[dim][ 33%][/dim] [green]PASS[/green] tests/test_progress.py::test_a",
+            "[dim][ 66%][/dim] [green]PASS[/green] tests/test_progress.py::test_b",
+            "[dim][100%][/dim] [green]PASS[/green] tests/test_progress.py::test_c",
+        ]
+
+    def test_unknown_total_keeps_the_column_width(self) -> None:
+        assert progress_prefix(1, 0) == "[   ?]"
+        assert progress_prefix(5, 4) == "[100%]"
+
+    def test_fixtures_are_listed_under_the_line(self) -> None:
+        renderer = RichRenderer(
+            use_colors=False, use_ascii=True, verbose=True, show_test_fixtures=True
+        )
+        renderer.total_tests = 1
+
+        with patch.object(renderer.console, "print") as mock_print:
+            renderer._handle_test_completed(  # type: ignore[arg-type]
+                SimpleNamespace(
+                    test_id="tests/test_db.py::test_query",
+                    file_path="tests/test_db.py",
+                    display_name="tests/test_db.py::test_query",
+                    status="passed",
+                    message=None,
+                    fixtures=["db", "tmp_path"],
+                )
+            )
+
+        lines = [call.args[0] for call in mock_print.call_args_list]
+        assert lines == [
+            "[dim][100%][/dim] [green]PASS[/green] tests/test_db.py::test_query",
+            "[dim]       fixtures: db, tmp_path[/dim]",
+        ]
+
+
+class TestEta:
+    """The running file's row shows an estimate of the time left."""
+
+    def test_estimate_waits_for_five_percent_of_the_tests(self) -> None:
+        assert estimate_remaining(4, 100, 10.0) is None
+        assert estimate_remaining(5, 100, 10.0) == 190.0
+        assert estimate_remaining(50, 100, 10.0) == 10.0
+        assert estimate_remaining(100, 100, 10.0) is None
+        assert estimate_remaining(3, 0, 10.0) is None
+
+    def test_running_file_row_shows_the_eta(self) -> None:
+        renderer = RichRenderer(use_colors=False)
+        renderer.total_tests = 100
+        renderer.completed = 49
+        renderer._suite_started_at = 100.0
+        renderer.file_tasks["tests/test_a.py"] = 0  # type: ignore[assignment]
+        renderer.file_names["tests/test_a.py"] = "tests/test_a.py"
+        event = SimpleNamespace(
+            test_id="tests/test_a.py::test_x",
+            file_path="tests/test_a.py",
+            display_name="tests/test_a.py::test_x",
+            status="passed",
+            message=None,
+        )
+
+        with patch.object(renderer, "progress") as progress:
+            with patch("rustest.renderers.rich_renderer.time.monotonic", return_value=110.0):
+                renderer._handle_test_completed(event)  # type: ignore[arg-type]
+
+        description = progress.update.call_args.kwargs["description"]
+        assert description == "[cyan]tests/test_a.py[/cyan] [dim]ETA ~10s[/dim]"
+
+
+class TestGroupFailures:
+    """Failures that share a fingerprint are printed once with the other tests."""
+
+    def test_identical_failures_form_one_entry(self) -> None:
+        renderer = RichRenderer(use_colors=False, group_failures=True)
+        for name, fingerprint in (("test_read", 7), ("test_sum", 3), ("test_write", 7)):
+            renderer._handle_test_completed(  # type: ignore[arg-type]
+                SimpleNamespace(
+                    test_id=f"tests/test_db.py::{name}",
+                    file_path="tests/test_db.py",
+                    display_name=f"tests/test_db.py::{name}",
+                    status="failed",
+                    message=f"RuntimeError: {name}",
+                    failure_fingerprint=fingerprint,
+                )
+            )
+        event = SimpleNamespace(
+            total=3, passed=0, failed=3, skipped=0, errors=0, xfailed=0, xpassed=0, duration=0.1
+        )
+
+        with patch.object(renderer.console, "print") as mock_print:
+            renderer._handle_suite_completed(event)  # type: ignore[arg-type]
+
+        printed = [call.args[0] for call in mock_print.call_args_list if call.args]
+        assert "RuntimeError: test_write" not in printed
+        assert "Same failure in 1 more test:\n  tests/test_db.py::test_write" in printed
+        assert printed.index("RuntimeError: test_read") < printed.index("RuntimeError: test_sum")
+
+
+class TestCollapseParamFailures:
+    """Later cases failing like the first case of their test get one line."""
+
+    def test_repeated_cases_are_shown_as_one_line(self) -> None:
+        renderer = RichRenderer(use_colors=False, collapse_param_failures=True)
+        message = "Traceback:\n  File \"calc.py\", line 4\nZeroDivisionError: division by zero"
+        for case, fingerprint in (("1-0", 5), ("2-0", 5), ("3-1", 9), ("4-0", 5)):
+            renderer._handle_test_completed(  # type: ignore[arg-type]
+                SimpleNamespace(
+                    test_id=f"tests/test_calc.py::test_div[{case}]",
+                    file_path="tests/test_calc.py",
+                    display_name=f"tests/test_calc.py::test_div[{case}]",
+                    status="failed",
+                    message=message,
+                    failure_fingerprint=fingerprint,
+                )
+            )
+        event = SimpleNamespace(
+            total=4, passed=0, failed=4, skipped=0, errors=0, xfailed=0, xpassed=0, duration=0.1
+        )
+
+        with patch.object(renderer.console, "print") as mock_print:
+            renderer._handle_suite_completed(event)  # type: ignore[arg-type]
+
+        printed = [call.args[0] for call in mock_print.call_args_list if call.args]
+        assert printed.count(message) == 2
+        assert (
+            "[dim]tests/test_calc.py::test_div[4-0] - ZeroDivisionError: division by zero "
+            "(same as test_div[1-0])[/dim]"
+        ) in printed
@@ -0,0 +1,91 @@
+from __future__ import annotations
+
+import io
+import struct
+from types import SimpleNamespace
+from typing import Any
+
+import pytest
+
+from rustest import rust
+from rustest.worker import read_message, run_batch, write_message
+
+
+class TestWorkerFraming:
+    def test_messages_round_trip(self) -> None:
+        stream = io.BytesIO()
+        write_message(stream, {"batch_id": 1, "node_ids": ["tests/test_a.py::test_x"]})
+        write_message(stream, {"batch_id": 2, "node_ids": []})
+        stream.seek(0)
+
+        assert read_message(stream) == {"batch_id": 1, "node_ids": ["tests/test_a.py::test_x"]}
+        assert read_message(stream) == {"batch_id": 2, "node_ids": []}
+        assert read_message(stream) is None
+
+    def test_length_prefix_is_big_endian(self) -> None:
+        stream = io.BytesIO()
+        write_message(stream, [])
+
+        assert stream.getvalue() == struct.pack(">I", 2) + b"[]"
+
+    def test_truncated_message_raises(self) -> None:
+        stream = io.BytesIO(struct.pack(">I", 10) + b"[1, 2")
+
+        with pytest.raises(EOFError):
+            read_message(stream)
+
+
+class TestRunBatch:
+    def test_runs_whole_batch_in_one_session(self, monkeypatch: pytest.MonkeyPatch) -> None:
+        calls: list[tuple[list[str], dict[str, Any]]] = []
+
+        def fake_run(paths: list[str], **kwargs: Any) -> SimpleNamespace:
+            calls.append((paths, kwargs))
+            results = [
+                SimpleNamespace(
+                    path=path,
+                    name=name,
+                    status="passed",
+                    duration=0.5,
+                    message=None,
+                    stdout=None,
+                    stderr=None,
+                    fixtures=(),
+                )
+                for path in paths
+                for name in ("test_x", "test_unrequested")
+            ]
+            return SimpleNamespace(
+                results=results,
+                warnings=[("tests/test_a.py::test_x", "DeprecationWarning: old")],
+                teardown_errors=[
+                    SimpleNamespace(fixture="db", scope="session", message="boom")
+                ],
+                fixture_setups=3,
+                fixture_teardowns=2,
+            )
+
+        monkeypatch.setattr(rust, "run", fake_run)
+
+        response = run_batch(
+            ["tests/test_a.py::test_x", "tests/test_b.py::test_x", "tests/test_b.py::test_gone"],
+            {"capture_output": False},
+        )
+
+        assert len(calls) == 1
+        paths, kwargs = calls[0]
+        assert paths == ["tests/test_a.py", "tests/test_b.py"]
+        assert kwargs["capture_output"] is False
+        assert kwargs["node_ids"] == [
+            "tests/test_a.py::test_x",
+            "tests/test_b.py::test_x",
+            "tests/test_b.py::test_gone",
+        ]
+        assert [(r["node_id"], r["status"]) for r in response["results"]] == [
+            ("tests/test_a.py::test_x", "passed"),
+            ("tests/test_b.py::test_x", "passed"),
+            ("tests/test_b.py::test_gone", "error"),
+        ]
+        assert response["warnings"] == [["tests/test_a.py::test_x", "DeprecationWarning: old"]]
+        assert response["teardown_errors"] == [["db", "session", "boom"]]
+        assert (response["fixture_setups"], response["fixture_teardowns"]) == (3, 2)
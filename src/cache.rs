@@ -1,17 +1,38 @@
+use crate::model::PyTestResult;
+use indexmap::IndexMap;
 use pyo3::PyResult;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 const CACHE_DIR: &str = ".rustest_cache";
 const LAST_FAILED_FILE: &str = "lastfailed";
+const JOURNAL_FILE: &str = "journal";
+const LINE_TRACE_FILE: &str = "line_trace.json";
 
 #[derive(Debug, Serialize, Deserialize)]
 struct LastFailedCache {
     failed: HashSet<String>,
 }
 
+/// The parts of a prior JSON report needed to select tests from it
+#[derive(Debug, Deserialize)]
+struct PriorReport {
+    results: Vec<PriorResult>,
+}
+
+/// One test outcome from a prior report, identified either by `node_id`
+/// or by its `path` and `name`
+#[derive(Debug, Deserialize)]
+struct PriorResult {
+    node_id: Option<String>,
+    path: Option<String>,
+    name: Option<String>,
+    status: String,
+}
+
 /// Get the path to the cache directory
 fn get_cache_dir() -> PathBuf {
     PathBuf::from(CACHE_DIR)
@@ -22,6 +43,16 @@ fn get_last_failed_path() -> PathBuf {
     get_cache_dir().join(LAST_FAILED_FILE)
 }
 
+/// Default location of the progress journal
+pub fn default_journal_path() -> PathBuf {
+    get_cache_dir().join(JOURNAL_FILE)
+}
+
+/// Default location of the `--line-trace` output
+pub fn default_line_trace_path() -> PathBuf {
+    get_cache_dir().join(LINE_TRACE_FILE)
+}
+
 /// Ensure the cache directory exists
 fn ensure_cache_dir() -> std::io::Result<()> {
     let cache_dir = get_cache_dir();
@@ -77,6 +108,161 @@ pub fn write_last_failed(failed_tests: &HashSet<String>) -> PyResult<()> {
     Ok(())
 }
 
+/// Read the node ids of tests that passed in a prior JSON report
+pub fn read_passed_from_report(report_path: &Path) -> PyResult<HashSet<String>> {
+    let content = fs::read_to_string(report_path).map_err(|e| {
+        pyo3::exceptions::PyIOError::new_err(format!(
+            "Failed to read report {}: {}",
+            report_path.display(),
+            e
+        ))
+    })?;
+    parse_passed_from_report(&content).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Failed to parse report {}: {}",
+            report_path.display(),
+            e
+        ))
+    })
+}
+
+fn parse_passed_from_report(content: &str) -> serde_json::Result<HashSet<String>> {
+    let report: PriorReport = serde_json::from_str(content)?;
+    Ok(report
+        .results
+        .into_iter()
+        .filter(|result| result.status == "passed")
+        .filter_map(|result| match (result.node_id, result.path, result.name) {
+            (Some(node_id), _, _) => Some(node_id),
+            (None, Some(path), Some(name)) => Some(format!("{}::{}", path, name)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// One completed test, as recorded in the progress journal
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub path: String,
+    pub name: String,
+    pub status: String,
+    pub duration: f64,
+    pub message: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn node_id(&self) -> String {
+        format!("{}::{}", self.path, self.name)
+    }
+
+    /// Rebuild the recorded outcome for the report of a resumed run.
+    pub fn into_result(self) -> PyTestResult {
+        let mut result =
+            PyTestResult::passed(self.name, self.path, self.duration, None, None, Vec::new());
+        result.status = self.status;
+        result.message = self.message;
+        result
+    }
+}
+
+/// Append-only record of completed tests, one JSON object per line, so a
+/// run that crashes part way can be resumed with `--resume`.
+///
+/// Journaling is best effort: if the file can't be written the run carries
+/// on without it.
+pub struct Journal {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl Journal {
+    /// Open the journal at `path`. A resumed run appends to the entries of
+    /// the interrupted one; any other run starts a fresh journal.
+    pub fn start(path: &Path, resume: bool) -> Self {
+        let opened = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(resume)
+                    .write(true)
+                    .truncate(!resume)
+                    .open(path)
+            });
+        let file = match opened {
+            Ok(file) => Some(file),
+            Err(e) => {
+                eprintln!(
+                    "Warning: Not journaling progress to {}: {}",
+                    path.display(),
+                    e
+                );
+                None
+            }
+        };
+        Self {
+            path: path.to_path_buf(),
+            file,
+        }
+    }
+
+    /// Record a completed test. Each entry is written straight through, so
+    /// it survives the process dying right after.
+    pub fn record(&mut self, result: &PyTestResult) {
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+        let entry = JournalEntry {
+            path: result.path.clone(),
+            name: result.name.clone(),
+            status: result.status.clone(),
+            duration: result.duration,
+            message: result.message.clone(),
+        };
+        let written = serde_json::to_string(&entry)
+            .map_err(std::io::Error::other)
+            .and_then(|line| file.write_all(format!("{}\n", line).as_bytes()));
+        if let Err(e) = written {
+            eprintln!(
+                "Warning: Stopped journaling progress to {}: {}",
+                self.path.display(),
+                e
+            );
+            self.file = None;
+        }
+    }
+
+    /// The run finished cleanly, so there is nothing left to resume.
+    pub fn finish(self) {
+        drop(self.file);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Read the entries recorded by an interrupted run, keyed by node id.
+///
+/// A missing journal means there is nothing to resume. A line cut short by
+/// the crash is ignored.
+pub fn read_journal(path: &Path) -> PyResult<IndexMap<String, JournalEntry>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(IndexMap::new()),
+        Err(e) => {
+            return Err(pyo3::exceptions::PyIOError::new_err(format!(
+                "Failed to read journal {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+        .map(|entry| (entry.node_id(), entry))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,4 +278,27 @@ mod tests {
 
         assert_eq!(failed, read_failed);
     }
+
+    fn ids(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_passed_from_report_keeps_only_passed() {
+        let report = r#"{
+            "results": [
+                {"node_id": "tests/test_a.py::test_ok", "status": "passed"},
+                {"node_id": "tests/test_a.py::test_bad", "status": "failed"},
+                {"node_id": "tests/test_a.py::test_later", "status": "skipped"},
+                {"path": "tests/test_b.py", "name": "test_also_ok", "status": "passed"},
+                {"path": "tests/test_b.py", "name": "test_broken", "status": "error"}
+            ]
+        }"#;
+
+        assert_eq!(
+            parse_passed_from_report(report).unwrap(),
+            ids(&["tests/test_a.py::test_ok", "tests/test_b.py::test_also_ok"])
+        );
+        assert!(parse_passed_from_report("{}").is_err());
+    }
 }
@@ -0,0 +1,182 @@
+//! Changed line ranges from `git diff`, for `--changed-lines`
+//!
+//! `git diff -U0` hunks are parsed into the new-side line ranges of each
+//! changed file. A file whose hunks can't be read (binary files, pure
+//! renames, an unexpected header) is kept as changed in full, so selection
+//! falls back to the whole file instead of missing it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// New-side line ranges of one file; `None` when the whole file changed
+type FileRanges = Option<Vec<(usize, usize)>>;
+
+/// Lines changed relative to a git revision, keyed by absolute file path
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChangedLines {
+    files: HashMap<PathBuf, FileRanges>,
+}
+
+impl ChangedLines {
+    /// Diff the working tree against `revision` in the repository containing `cwd`.
+    pub fn from_git(revision: &str, cwd: &Path) -> Result<Self, String> {
+        let root = git(cwd, &["rev-parse", "--show-toplevel"])?;
+        let root = PathBuf::from(root.trim());
+        // Test paths are compared canonicalized
+        let root = root.canonicalize().unwrap_or(root);
+        let diff = git(
+            cwd,
+            &["diff", "-U0", "--no-color", "--no-ext-diff", revision, "--"],
+        )?;
+        Ok(Self::parse(&diff, &root))
+    }
+
+    /// Parse `git diff -U0` output; paths are resolved against `root`.
+    pub fn parse(diff: &str, root: &Path) -> Self {
+        let mut files = HashMap::new();
+        let mut current: Option<(PathBuf, FileRanges)> = None;
+
+        let mut finish = |current: Option<(PathBuf, FileRanges)>| {
+            if let Some((path, ranges)) = current {
+                // A file with no readable hunks is changed in full
+                let ranges = ranges.filter(|ranges| !ranges.is_empty());
+                files.insert(path, ranges);
+            }
+        };
+
+        for line in diff.lines() {
+            if let Some(rest) = line.strip_prefix("diff --git ") {
+                finish(current.take());
+                // Until a `+++` line names the new file, use the `b/` side here
+                current = rest
+                    .split_once(" b/")
+                    .map(|(_, path)| (root.join(path), Some(Vec::new())));
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                match (path.strip_prefix("b/"), current.as_mut()) {
+                    (Some(path), Some(current)) => current.0 = root.join(path),
+                    // Deleted files have no tests left to select
+                    (None, _) if path == "/dev/null" => current = None,
+                    _ => {}
+                }
+            } else if line.starts_with("@@") {
+                if let Some((_, ranges)) = current.as_mut() {
+                    match (ranges.as_mut(), parse_hunk_header(line)) {
+                        (Some(list), Some(range)) => list.push(range),
+                        _ => *ranges = None,
+                    }
+                }
+            }
+        }
+        finish(current);
+
+        Self { files }
+    }
+
+    /// Whether `path` changed at all.
+    pub fn touches_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Whether any changed line of `path` falls within `start..=end`.
+    pub fn touches(&self, path: &Path, start: usize, end: usize) -> bool {
+        match self.files.get(path) {
+            None => false,
+            Some(None) => true,
+            Some(Some(ranges)) => ranges.iter().any(|&(s, e)| s <= end && start <= e),
+        }
+    }
+
+    /// Changed ranges of `path`, or `None` when the whole file counts as changed.
+    pub fn ranges(&self, path: &Path) -> Option<&[(usize, usize)]> {
+        self.files.get(path).and_then(|ranges| ranges.as_deref())
+    }
+}
+
+/// New-side line range of a `@@ -a,b +c,d @@` header.
+///
+/// A pure deletion (`d == 0`) is attributed to the line it follows.
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let new_side = line
+        .strip_prefix("@@ ")?
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let (start, count) = match new_side[1..].split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new_side[1..].parse::<usize>().ok()?, 1),
+    };
+    if count == 0 {
+        let line = start.max(1);
+        Some((line, line))
+    } else {
+        Some((start, start + count - 1))
+    }
+}
+
+fn git(cwd: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/tests/test_math.py b/tests/test_math.py
+index 1111111..2222222 100644
+--- a/tests/test_math.py
++++ b/tests/test_math.py
+@@ -4,2 +4,3 @@ def test_add():
++    assert add(2, 2) == 4
+@@ -20 +21 @@ def test_sub():
+-    x = 1
++    x = 2
+@@ -30,2 +30,0 @@ def test_mul():
+diff --git a/src/lib.py b/src/lib.py
+deleted file mode 100644
+--- a/src/lib.py
++++ /dev/null
+@@ -1,3 +0,0 @@
+diff --git a/data.bin b/data.bin
+index 3333333..4444444 100644
+Binary files a/data.bin and b/data.bin differ
+diff --git a/tests/test_odd.py b/tests/test_odd.py
+--- a/tests/test_odd.py
++++ b/tests/test_odd.py
+@@ garbled @@
+";
+
+    #[test]
+    fn parses_new_side_hunk_ranges() {
+        let root = Path::new("/repo");
+        let changed = ChangedLines::parse(DIFF, root);
+        let math = root.join("tests/test_math.py");
+
+        assert_eq!(
+            changed.ranges(&math),
+            Some(&[(4, 6), (21, 21), (30, 30)][..])
+        );
+        assert!(changed.touches(&math, 1, 4));
+        assert!(!changed.touches(&math, 7, 20));
+        assert!(changed.touches(&math, 30, 35));
+
+        // Deleted files are dropped
+        assert!(!changed.touches_file(&root.join("src/lib.py")));
+        // Hunks that can't be read fall back to the whole file
+        assert!(changed.touches_file(&root.join("data.bin")));
+        assert_eq!(changed.ranges(&root.join("data.bin")), None);
+        assert!(changed.touches(&root.join("tests/test_odd.py"), 1000, 1000));
+    }
+}
@@ -8,6 +8,8 @@
 use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use indexmap::IndexMap;
@@ -19,13 +21,19 @@ use rayon::prelude::*;
 use walkdir::WalkDir;
 
 use crate::cache;
+use crate::changed_lines::ChangedLines;
+use crate::keyword_expr::KeywordExpr;
 use crate::mark_expr::MarkExpr;
 use crate::model::{
-    invalid_test_definition, to_relative_path, CollectionError, Fixture, FixtureParam,
-    FixtureScope, LastFailedMode, Mark, ModuleIdGenerator, ParameterMap, RunConfiguration,
-    TestCase, TestModule,
+    invalid_test_definition, to_relative_path, AutouseExclusion, CollectionError, CollectionFrame,
+    Discovery, Fixture, FixtureParam, FixtureScope, LastFailedMode, Mark, ParameterMap,
+    RunConfiguration, TestCase, TestModule, XUNIT_FIXTURE_PREFIX,
+};
+use crate::notebook::notebook_source;
+use crate::output::{
+    emit_collection_completed, emit_collection_progress, emit_collection_started, record_span,
+    start_trace,
 };
-use crate::output::{emit_collection_completed, emit_collection_progress, emit_collection_started};
 use crate::python_support::{setup_python_path, PyPaths};
 
 /// Inject the pytest compatibility shim into sys.modules.
@@ -116,6 +124,25 @@ fn should_exclude_dir(entry: &walkdir::DirEntry) -> bool {
 enum FileType {
     Python,
     Markdown,
+    Notebook,
+}
+
+/// Collected file type for `path`, or `None` if no glob matches it.
+fn classify_file(
+    path: &Path,
+    py_glob: &GlobSet,
+    md_glob: Option<&GlobSet>,
+    nb_glob: Option<&GlobSet>,
+) -> Option<FileType> {
+    if py_glob.is_match(path) {
+        Some(FileType::Python)
+    } else if md_glob.is_some_and(|glob| glob.is_match(path)) {
+        Some(FileType::Markdown)
+    } else if nb_glob.is_some_and(|glob| glob.is_match(path)) {
+        Some(FileType::Notebook)
+    } else {
+        None
+    }
 }
 
 /// Discover all test files in parallel using rayon.
@@ -128,6 +155,7 @@ fn discover_files_parallel(
     paths: &[PathBuf],
     py_glob: &GlobSet,
     md_glob: Option<&GlobSet>,
+    nb_glob: Option<&GlobSet>,
 ) -> Vec<(PathBuf, FileType)> {
     // First, collect all directories to walk
     let mut dirs_to_walk: Vec<PathBuf> = Vec::new();
@@ -137,12 +165,8 @@ fn discover_files_parallel(
         if path.is_dir() {
             dirs_to_walk.push(path.clone());
         } else if path.is_file() {
-            if py_glob.is_match(path) {
-                direct_files.push((path.clone(), FileType::Python));
-            } else if let Some(md_glob_set) = md_glob {
-                if md_glob_set.is_match(path) {
-                    direct_files.push((path.clone(), FileType::Markdown));
-                }
+            if let Some(file_type) = classify_file(path, py_glob, md_glob, nb_glob) {
+                direct_files.push((path.clone(), file_type));
             }
         }
     }
@@ -159,12 +183,8 @@ fn discover_files_parallel(
             {
                 let file = entry.into_path();
                 if file.is_file() {
-                    if py_glob.is_match(&file) {
-                        files.push((file, FileType::Python));
-                    } else if let Some(md_glob_set) = md_glob {
-                        if md_glob_set.is_match(&file) {
-                            files.push((file, FileType::Markdown));
-                        }
+                    if let Some(file_type) = classify_file(&file, py_glob, md_glob, nb_glob) {
+                        files.push((file, file_type));
                     }
                 }
             }
@@ -178,6 +198,153 @@ fn discover_files_parallel(
     all_files
 }
 
+/// How many files past the one being imported are read ahead, per thread
+const PREFETCH_WINDOW_PER_THREAD: usize = 4;
+
+/// Reads upcoming test files on background threads while the import loop
+/// works through them in order.
+///
+/// Imports have to run one at a time under the GIL, but the file reads they
+/// wait on don't. Each file is read a few places ahead of the import loop, so
+/// slow or cold file systems overlap with `exec_module` instead of stalling it.
+/// Python files are still loaded through importlib (keeping its bytecode
+/// cache); the read ahead warms the OS cache for them. Markdown and notebook
+/// contents are used directly.
+struct SourcePrefetch {
+    /// Indices of files to read; dropping it lets the reader threads exit
+    jobs: mpsc::Sender<usize>,
+    results: mpsc::Receiver<(usize, std::io::Result<String>)>,
+    /// Reads that finished ahead of the file being asked for
+    ready: HashMap<usize, std::io::Result<String>>,
+    window: usize,
+    dispatched: usize,
+    total: usize,
+}
+
+impl SourcePrefetch {
+    fn start(paths: Vec<PathBuf>, threads: usize) -> Self {
+        let (job_sender, job_receiver) = mpsc::channel::<usize>();
+        let (result_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let paths = Arc::new(paths);
+
+        for _ in 0..threads {
+            let job_receiver = Arc::clone(&job_receiver);
+            let result_sender = result_sender.clone();
+            let paths = Arc::clone(&paths);
+            std::thread::spawn(move || loop {
+                let next = job_receiver.lock().map(|jobs| jobs.recv());
+                let Ok(Ok(index)) = next else {
+                    break;
+                };
+                let read = std::fs::read_to_string(&paths[index]);
+                if result_sender.send((index, read)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        let mut prefetch = Self {
+            jobs: job_sender,
+            results,
+            ready: HashMap::new(),
+            window: threads * PREFETCH_WINDOW_PER_THREAD,
+            dispatched: 0,
+            total: paths.len(),
+        };
+        prefetch.dispatch_up_to(prefetch.window);
+        prefetch
+    }
+
+    fn dispatch_up_to(&mut self, end: usize) {
+        let end = end.min(self.total);
+        while self.dispatched < end && self.jobs.send(self.dispatched).is_ok() {
+            self.dispatched += 1;
+        }
+    }
+
+    /// Wait for the contents of file `index`, and queue reads further ahead.
+    ///
+    /// Returns `None` if the file couldn't be read; the caller's own read then
+    /// reports the error as usual.
+    fn take(&mut self, index: usize) -> Option<String> {
+        self.dispatch_up_to(index + 1 + self.window);
+        loop {
+            if let Some(read) = self.ready.remove(&index) {
+                return read.ok();
+            }
+            let (done, read) = self.results.recv().ok()?;
+            self.ready.insert(done, read);
+        }
+    }
+}
+
+/// Prints discovery decisions to stderr when `--trace-config` is enabled.
+struct DiscoveryTrace {
+    enabled: bool,
+}
+
+impl DiscoveryTrace {
+    fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    fn log(&self, message: impl AsRef<str>) {
+        if self.enabled {
+            eprintln!("[trace-config] {}", message.as_ref());
+        }
+    }
+}
+
+/// Describe how each candidate file under `paths` is treated by the file globs.
+///
+/// Walks the same directories as [`discover_files_parallel`], but sequentially
+/// and only for `--trace-config`, reporting matched files, Python files that
+/// don't match any glob, and directories pruned by the norecursedirs rules.
+fn trace_candidate_files(
+    paths: &[PathBuf],
+    py_glob: &GlobSet,
+    md_glob: Option<&GlobSet>,
+    nb_glob: Option<&GlobSet>,
+) -> Vec<String> {
+    let describe = |file: &Path| -> Option<String> {
+        if py_glob.is_match(file) {
+            Some(format!("matched test file glob: {}", file.display()))
+        } else if md_glob.is_some_and(|glob| glob.is_match(file)) {
+            Some(format!("matched markdown glob: {}", file.display()))
+        } else if nb_glob.is_some_and(|glob| glob.is_match(file)) {
+            Some(format!("matched notebook glob: {}", file.display()))
+        } else if file.extension().is_some_and(|ext| ext == "py") {
+            Some(format!("ignored (no glob match): {}", file.display()))
+        } else {
+            None
+        }
+    };
+
+    let mut lines = Vec::new();
+    for path in paths {
+        if path.is_file() {
+            lines.extend(describe(path));
+            continue;
+        }
+
+        let mut entries = WalkDir::new(path).into_iter();
+        while let Some(entry) = entries.next() {
+            let Ok(entry) = entry else { continue };
+            if should_exclude_dir(&entry) {
+                lines.push(format!(
+                    "skipped directory (norecursedirs): {}",
+                    entry.path().display()
+                ));
+                entries.skip_current_dir();
+            } else if entry.file_type().is_file() {
+                lines.extend(describe(entry.path()));
+            }
+        }
+    }
+    lines
+}
+
 /// Discover all conftest.py files in parallel.
 ///
 /// This collects all conftest.py paths first using parallel file system traversal,
@@ -328,24 +495,40 @@ fn file_contains_pytest_import(content: &str) -> bool {
 /// corresponding Python file.  This makes it straightforward for the execution
 /// pipeline to run tests while still having quick access to fixtures.
 ///
-/// Returns a tuple of (modules, collection_errors) where collection_errors
-/// contains any errors that occurred during test collection (e.g., syntax errors).
+/// The [`Discovery`] also carries the errors that occurred while collecting
+/// (e.g., syntax errors), so a run can report them alongside the results.
 pub fn discover_tests(
     py: Python<'_>,
     paths: &PyPaths,
     config: &RunConfiguration,
-) -> PyResult<(Vec<TestModule>, Vec<CollectionError>)> {
+) -> PyResult<Discovery> {
     let collection_start = std::time::Instant::now();
+    if config.trace_json.is_some() {
+        start_trace();
+    }
 
     // Emit collection started event
     if let Some(ref callback) = config.event_callback {
         emit_collection_started(callback);
     }
 
+    // Reject malformed -k / -m expressions once instead of per collected file
+    if let Some(pattern) = &config.pattern {
+        KeywordExpr::parse(pattern)
+            .map_err(|e| invalid_test_definition(format!("Invalid -k expression: {}", e)))?;
+    }
+    if let Some(mark_expr) = &config.mark_expr {
+        MarkExpr::parse(mark_expr)
+            .map_err(|e| invalid_test_definition(format!("Invalid mark expression: {}", e)))?;
+    }
+
     let canonical_paths = paths.materialise()?;
+    let trace = DiscoveryTrace::new(config.trace_config);
 
     // Setup sys.path to enable imports like pytest does
-    setup_python_path(py, &canonical_paths)?;
+    for added in setup_python_path(py, &canonical_paths)? {
+        trace.log(format!("sys.path += {}", added.display()));
+    }
 
     // If pytest compatibility mode is enabled, inject the pytest shim
     if config.pytest_compat {
@@ -360,9 +543,13 @@ pub fn discover_tests(
     } else {
         None
     };
+    let nb_glob = if config.notebooks {
+        Some(build_notebook_glob()?)
+    } else {
+        None
+    };
     let mut modules = Vec::new();
     let mut collection_errors = Vec::new();
-    let module_ids = ModuleIdGenerator::default();
     let mut files_collected: usize = 0;
 
     // OPTIMIZATION: Discover all conftest paths in parallel first
@@ -375,7 +562,7 @@ pub fn discover_tests(
         let conftest_path = dir.join("conftest.py");
         if conftest_path.is_file() && !conftest_fixtures.contains_key(dir) {
             let (fixtures, pytest_names) =
-                load_conftest_fixtures(py, &conftest_path, &module_ids, config.pytest_compat)?;
+                load_conftest_fixtures(py, &conftest_path, config.pytest_compat)?;
             if !pytest_names.is_empty() {
                 detected_pytest_fixtures.push((conftest_path.clone(), pytest_names));
             }
@@ -384,7 +571,22 @@ pub fn discover_tests(
     }
 
     // OPTIMIZATION: Discover all test files in parallel
-    let test_files = discover_files_parallel(&canonical_paths, &py_glob, md_glob.as_ref());
+    let test_files = discover_files_parallel(
+        &canonical_paths,
+        &py_glob,
+        md_glob.as_ref(),
+        nb_glob.as_ref(),
+    );
+    if trace.enabled {
+        for line in trace_candidate_files(
+            &canonical_paths,
+            &py_glob,
+            md_glob.as_ref(),
+            nb_glob.as_ref(),
+        ) {
+            trace.log(line);
+        }
+    }
 
     // Fast text scan for pytest imports — done before Python module loading.
     // We defer emitting the message until after module processing so that the
@@ -392,30 +594,56 @@ pub fn discover_tests(
     let has_pytest_imports =
         !config.pytest_compat && detect_pytest_imports(&test_files, &conftest_dirs);
 
+    // Read files ahead on background threads when asked to
+    let mut prefetch = config
+        .discovery_threads
+        .filter(|&threads| threads > 1)
+        .map(|threads| {
+            let paths = test_files.iter().map(|(file, _)| file.clone()).collect();
+            SourcePrefetch::start(paths, threads)
+        });
+
     // Process test files sequentially (Python imports require GIL)
-    for (file, file_type) in test_files {
+    for (index, (file, file_type)) in test_files.into_iter().enumerate() {
+        let source = prefetch.as_mut().and_then(|p| p.take(index));
+
         // Ensure parent conftest fixtures are loaded (they should already be, but check)
         discover_parent_conftest_files(
             py,
             &file,
             &mut conftest_fixtures,
-            &module_ids,
             config.pytest_compat,
             &mut detected_pytest_fixtures,
         )?;
 
         match file_type {
-            FileType::Python => {
-                match collect_from_file(
-                    py,
-                    &file,
-                    config,
-                    &module_ids,
-                    &conftest_fixtures,
-                    &mut detected_pytest_fixtures,
-                ) {
+            FileType::Python | FileType::Notebook => {
+                let collected = if matches!(file_type, FileType::Notebook) {
+                    collect_from_notebook(
+                        py,
+                        &file,
+                        source,
+                        config,
+                        &conftest_fixtures,
+                        &mut detected_pytest_fixtures,
+                    )
+                } else {
+                    collect_from_file(
+                        py,
+                        &file,
+                        config,
+                        &conftest_fixtures,
+                        &mut detected_pytest_fixtures,
+                    )
+                };
+                match collected {
                     Ok(Some(module)) => {
                         let tests_in_file = module.tests.len();
+                        trace.log(format!(
+                            "collected {} tests: {}",
+                            tests_in_file,
+                            file.display()
+                        ));
                         modules.push(module);
                         files_collected += 1;
                         if let Some(ref callback) = config.event_callback {
@@ -427,18 +655,24 @@ pub fn discover_tests(
                             );
                         }
                     }
-                    Ok(None) => {}
+                    Ok(None) => {
+                        trace.log(format!("no tests collected: {}", file.display()));
+                    }
                     Err(err) => {
-                        let error_msg = format_collection_error(py, &err);
-                        collection_errors
-                            .push(CollectionError::new(to_relative_path(&file), error_msg));
+                        trace.log(format!("error collecting {}: {}", file.display(), err));
+                        collection_errors.push(collection_error(py, to_relative_path(&file), &err));
                     }
                 }
             }
             FileType::Markdown => {
-                match collect_from_markdown(py, &file, config, &conftest_fixtures) {
+                match collect_from_markdown(py, &file, source, config, &conftest_fixtures) {
                     Ok(Some(module)) => {
                         let tests_in_file = module.tests.len();
+                        trace.log(format!(
+                            "collected {} code blocks: {}",
+                            tests_in_file,
+                            file.display()
+                        ));
                         modules.push(module);
                         files_collected += 1;
                         if let Some(ref callback) = config.event_callback {
@@ -450,11 +684,12 @@ pub fn discover_tests(
                             );
                         }
                     }
-                    Ok(None) => {}
+                    Ok(None) => {
+                        trace.log(format!("no code blocks collected: {}", file.display()));
+                    }
                     Err(err) => {
-                        let error_msg = format_collection_error(py, &err);
-                        collection_errors
-                            .push(CollectionError::new(to_relative_path(&file), error_msg));
+                        trace.log(format!("error collecting {}: {}", file.display(), err));
+                        collection_errors.push(collection_error(py, to_relative_path(&file), &err));
                     }
                 }
             }
@@ -463,12 +698,78 @@ pub fn discover_tests(
 
     // Apply last-failed filtering if configured
     if config.last_failed_mode != LastFailedMode::None {
-        apply_last_failed_filter(&mut modules, config)?;
+        let failed_ids = cache::read_last_failed()?;
+        let rerun = apply_last_failed_filter(&mut modules, config.last_failed_mode, &failed_ids);
+        if rerun == 0 && config.last_failed_mode == LastFailedMode::OnlyFailed {
+            eprintln!("No previously failed tests, running all tests (--lf)");
+        }
+    }
+
+    // Keep only tests that passed in a prior report if requested
+    if let Some(ref report_path) = config.passed_only {
+        let passed_ids = cache::read_passed_from_report(report_path)?;
+        for module in modules.iter_mut() {
+            module
+                .tests
+                .retain(|test| passed_ids.contains(&test.unique_id()));
+        }
+        modules.retain(|m| !m.tests.is_empty());
+    }
+
+    // Worker processes run exactly the tests their parent selected
+    if let Some(ref node_ids) = config.node_ids {
+        select_node_ids(&mut modules, node_ids);
+    }
+
+    // Keep only tests touched by the diff against a git revision
+    if let Some(ref revision) = config.changed_lines {
+        let cwd = std::env::current_dir()?;
+        let changed = ChangedLines::from_git(revision, &cwd)
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+        let before: usize = modules.iter().map(|m| m.tests.len()).sum();
+        select_changed_tests(py, &mut modules, &changed);
+        let after: usize = modules.iter().map(|m| m.tests.len()).sum();
+        eprintln!(
+            "Selected {} of {} tests changed since {}",
+            after, before, revision
+        );
+    }
+
+    // Slow tests only run when asked for, by --run-slow or an explicit -m
+    if !config.run_slow && config.mark_expr.is_none() && config.node_ids.is_none() {
+        let deselected = deselect_slow_tests(&mut modules);
+        if deselected > 0 {
+            eprintln!(
+                "Deselected {} slow test{} (use --run-slow to run them)",
+                deselected,
+                if deselected == 1 { "" } else { "s" }
+            );
+        }
+    }
+
+    // Narrow to a random smoke-test sample and shuffle the run order if requested
+    let seed = config.shuffle_seed.unwrap_or_else(time_seed);
+    if let Some(sample) = config.sample {
+        let (sampled, total) = apply_sample(&mut modules, sample, seed);
+        eprintln!(
+            "Sampled {} of {} tests (--shuffle-seed {})",
+            sampled, total, seed
+        );
+    }
+    if config.shuffle {
+        apply_shuffle(&mut modules, seed);
+        eprintln!("Shuffled test order (--shuffle-seed {})", seed);
     }
 
     // Calculate total tests and emit collection completed event
     let total_tests: usize = modules.iter().map(|m| m.tests.len()).sum();
     let collection_duration = collection_start.elapsed().as_secs_f64();
+    record_span(
+        "collection",
+        "collection",
+        collection_start,
+        serde_json::json!({ "files": files_collected, "tests": total_tests }),
+    );
     if let Some(ref callback) = config.event_callback {
         emit_collection_completed(callback, files_collected, total_tests, collection_duration);
     }
@@ -494,7 +795,77 @@ pub fn discover_tests(
         );
     }
 
-    Ok((modules, collection_errors))
+    Ok(Discovery {
+        modules,
+        collection_errors,
+    })
+}
+
+/// Build a collection error for `path` from the exception raised collecting it.
+fn collection_error(py: Python<'_>, path: String, error: &PyErr) -> CollectionError {
+    let mut collection_error = CollectionError::new(path, format_collection_error(py, error));
+    collection_error.error_type = error
+        .get_type(py)
+        .name()
+        .and_then(|name| name.extract())
+        .ok();
+    collection_error.frames = error
+        .traceback(py)
+        .and_then(|tb| extract_collection_frames(py, tb.as_any()).ok())
+        .unwrap_or_default();
+
+    // A SyntaxError points at the broken line itself; anything else at the
+    // innermost frame that raised it
+    let value = error.value(py);
+    let syntax_error_location = error
+        .is_instance_of::<pyo3::exceptions::PySyntaxError>(py)
+        .then(|| {
+            let filename: Option<String> = value.getattr("filename").ok()?.extract().ok()?;
+            let lineno: Option<usize> = value.getattr("lineno").ok()?.extract().ok()?;
+            Some((filename, lineno))
+        })
+        .flatten();
+    match syntax_error_location {
+        Some((filename, lineno)) => {
+            collection_error.filename = filename;
+            collection_error.lineno = lineno;
+        }
+        None => {
+            if let Some(frame) = collection_error.frames.last() {
+                collection_error.filename = Some(frame.filename.clone());
+                collection_error.lineno = Some(frame.lineno);
+            }
+        }
+    }
+
+    collection_error
+}
+
+/// Frames of an import traceback, without the interpreter's frozen importlib frames.
+fn extract_collection_frames(
+    py: Python<'_>,
+    traceback: &Bound<'_, PyAny>,
+) -> PyResult<Vec<CollectionFrame>> {
+    let summaries = py
+        .import("traceback")?
+        .call_method1("extract_tb", (traceback,))?;
+    let mut frames = Vec::new();
+    for summary in summaries.try_iter()? {
+        let summary = summary?;
+        let filename: String = summary.getattr("filename")?.extract()?;
+        if filename.starts_with("<frozen ") {
+            continue;
+        }
+        frames.push(CollectionFrame {
+            filename,
+            lineno: summary
+                .getattr("lineno")?
+                .extract::<Option<usize>>()?
+                .unwrap_or(0),
+            name: summary.getattr("name")?.extract()?,
+        });
+    }
+    Ok(frames)
 }
 
 /// Format a collection error for display.
@@ -530,7 +901,6 @@ fn discover_parent_conftest_files(
     py: Python<'_>,
     test_file: &Path,
     conftest_map: &mut HashMap<PathBuf, IndexMap<String, Fixture>>,
-    module_ids: &ModuleIdGenerator,
     pytest_compat: bool,
     detected_pytest_fixtures: &mut Vec<(PathBuf, Vec<String>)>,
 ) -> PyResult<()> {
@@ -547,7 +917,7 @@ fn discover_parent_conftest_files(
             // Only load if we haven't already loaded it
             if !conftest_map.contains_key(current_dir) {
                 let (fixtures, pytest_names) =
-                    load_conftest_fixtures(py, &conftest_path, module_ids, pytest_compat)?;
+                    load_conftest_fixtures(py, &conftest_path, pytest_compat)?;
                 if !pytest_names.is_empty() {
                     detected_pytest_fixtures.push((conftest_path.clone(), pytest_names));
                 }
@@ -711,11 +1081,10 @@ fn detect_pytest_fixtures(
 fn load_conftest_fixtures(
     py: Python<'_>,
     path: &Path,
-    module_ids: &ModuleIdGenerator,
     pytest_compat: bool,
 ) -> PyResult<(IndexMap<String, Fixture>, Vec<String>)> {
-    let (module_name, package_name) = infer_module_names(path, module_ids.next());
-    let module = load_python_module(py, path, &module_name, package_name.as_deref())?;
+    let (module_name, package_name) = infer_module_names(path);
+    let module = load_python_module(py, path, &module_name, package_name.as_deref(), None)?;
     let module_dict: Bound<'_, PyDict> = module.getattr("__dict__")?.cast_into()?;
 
     // OPTIMIZATION: Cache type object once for the entire module
@@ -767,6 +1136,8 @@ fn load_conftest_fixtures(
 /// Merge conftest fixtures for a test file with the file's own fixtures.
 /// Conftest fixtures from parent directories are merged from farthest to nearest,
 /// and the test file's own fixtures override any conftest fixtures with the same name.
+/// Only ancestor conftests are merged, which is what limits a conftest's autouse
+/// fixtures to tests under its directory.
 fn merge_conftest_fixtures(
     py: Python<'_>,
     test_path: &Path,
@@ -850,6 +1221,20 @@ fn build_file_glob() -> PyResult<GlobSet> {
         .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()))
 }
 
+/// Build the glob set matching test notebooks (`test_*.ipynb`, `*_test.ipynb`).
+fn build_notebook_glob() -> PyResult<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in ["**/test_*.ipynb", "**/*_test.ipynb"] {
+        builder.add(
+            Glob::new(pattern)
+                .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()))?,
+        );
+    }
+    builder
+        .build()
+        .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()))
+}
+
 /// Build the glob set matching markdown files (*.md).
 fn build_markdown_glob() -> PyResult<GlobSet> {
     let mut builder = GlobSetBuilder::new();
@@ -862,17 +1247,122 @@ fn build_markdown_glob() -> PyResult<GlobSet> {
         .map_err(|err| PyErr::new::<pyo3::exceptions::PyValueError, _>(err.to_string()))
 }
 
+/// Warning for a file exceeding `max_file_size`, or `None` if it may be collected.
+fn oversized_file_warning(path: &Path, max_file_size: Option<u64>) -> Option<String> {
+    let max_size = max_file_size?;
+    let size = std::fs::metadata(path).ok()?.len();
+    (size > max_size).then(|| {
+        format!(
+            "Warning: Skipping {} ({} bytes exceeds --max-file-size of {} bytes)",
+            to_relative_path(path),
+            size,
+            max_size
+        )
+    })
+}
+
 /// Load a module from `path` and extract fixtures and tests.
 fn collect_from_file(
     py: Python<'_>,
     path: &Path,
     config: &RunConfiguration,
-    module_ids: &ModuleIdGenerator,
     conftest_map: &HashMap<PathBuf, IndexMap<String, Fixture>>,
     detected_pytest_fixtures: &mut Vec<(PathBuf, Vec<String>)>,
 ) -> PyResult<Option<TestModule>> {
-    let (module_name, package_name) = infer_module_names(path, module_ids.next());
-    let module = load_python_module(py, path, &module_name, package_name.as_deref())?;
+    // Check the size before importing: oversized generated files are slow to load
+    if let Some(warning) = oversized_file_warning(path, config.max_file_size) {
+        eprintln!("{}", warning);
+        return Ok(None);
+    }
+
+    let (module_name, package_name) = infer_module_names(path);
+    let module = load_python_module(
+        py,
+        path,
+        &module_name,
+        package_name.as_deref(),
+        config.import_timeout,
+    )?;
+    collect_from_module(
+        py,
+        path,
+        &module,
+        config,
+        conftest_map,
+        detected_pytest_fixtures,
+    )
+}
+
+/// Run a test notebook's code cells as a module and collect its tests.
+///
+/// Node ids keep the notebook path, so results point at the `.ipynb` file.
+fn collect_from_notebook(
+    py: Python<'_>,
+    path: &Path,
+    source: Option<String>,
+    config: &RunConfiguration,
+    conftest_map: &HashMap<PathBuf, IndexMap<String, Fixture>>,
+    detected_pytest_fixtures: &mut Vec<(PathBuf, Vec<String>)>,
+) -> PyResult<Option<TestModule>> {
+    if let Some(warning) = oversized_file_warning(path, config.max_file_size) {
+        eprintln!("{}", warning);
+        return Ok(None);
+    }
+
+    // Read the notebook, unless it was already read ahead
+    let content = match source {
+        Some(content) => content,
+        None => std::fs::read_to_string(path).map_err(|e| {
+            invalid_test_definition(format!("Failed to read {}: {}", path.display(), e))
+        })?,
+    };
+    let code = notebook_source(&content).map_err(|e| {
+        invalid_test_definition(format!("Invalid notebook {}: {}", path.display(), e))
+    })?;
+
+    let (module_name, package_name) = infer_module_names(path);
+    ensure_parent_packages_loaded(py, path)?;
+    let module = py
+        .import("types")?
+        .getattr("ModuleType")?
+        .call1((module_name.as_str(),))?;
+    let path_str = path.to_string_lossy();
+    module.setattr("__file__", path_str.as_ref())?;
+    if let Some(package_name) = package_name {
+        module.setattr("__package__", package_name)?;
+    }
+    let sys = py.import("sys")?;
+    let modules: Bound<'_, PyDict> = sys.getattr("modules")?.cast_into()?;
+    modules.set_item(&module_name, &module)?;
+
+    // Compile against the notebook path so tracebacks name the notebook
+    let builtins = py.import("builtins")?;
+    let compiled = builtins
+        .getattr("compile")?
+        .call1((code, path_str.as_ref(), "exec"))?;
+    builtins
+        .getattr("exec")?
+        .call1((compiled, module.getattr("__dict__")?))?;
+
+    collect_from_module(
+        py,
+        path,
+        &module,
+        config,
+        conftest_map,
+        detected_pytest_fixtures,
+    )
+}
+
+/// Extract fixtures and tests from a module loaded from `path`.
+fn collect_from_module(
+    py: Python<'_>,
+    path: &Path,
+    module: &Bound<'_, PyAny>,
+    config: &RunConfiguration,
+    conftest_map: &HashMap<PathBuf, IndexMap<String, Fixture>>,
+    detected_pytest_fixtures: &mut Vec<(PathBuf, Vec<String>)>,
+) -> PyResult<Option<TestModule>> {
     let module_dict: Bound<'_, PyDict> = module.getattr("__dict__")?.cast_into()?;
 
     let (module_fixtures, tests, pytest_names) =
@@ -901,7 +1391,9 @@ fn collect_from_file(
     let mut tests = expand_tests_for_parametrized_fixtures(py, tests, &fixtures)?;
 
     if let Some(pattern) = &config.pattern {
-        tests.retain(|case| test_matches_pattern(case, pattern));
+        let keyword_expr = KeywordExpr::parse(pattern)
+            .map_err(|e| invalid_test_definition(format!("Invalid -k expression: {}", e)))?;
+        tests.retain(|case| keyword_expr.matches(case));
     }
 
     // Apply mark filtering if specified
@@ -927,13 +1419,17 @@ fn collect_from_file(
 fn collect_from_markdown(
     py: Python<'_>,
     path: &Path,
+    source: Option<String>,
     config: &RunConfiguration,
     conftest_map: &HashMap<PathBuf, IndexMap<String, Fixture>>,
 ) -> PyResult<Option<TestModule>> {
-    // Read the markdown file
-    let content = std::fs::read_to_string(path).map_err(|e| {
-        invalid_test_definition(format!("Failed to read {}: {}", path.display(), e))
-    })?;
+    // Read the markdown file, unless it was already read ahead
+    let content = match source {
+        Some(content) => content,
+        None => std::fs::read_to_string(path).map_err(|e| {
+            invalid_test_definition(format!("Failed to read {}: {}", path.display(), e))
+        })?,
+    };
 
     // Parse Python code blocks
     let mut tests = Vec::new();
@@ -979,13 +1475,17 @@ fn collect_from_markdown(
             class_name: None,
             fixture_param_indices: IndexMap::new(),
             indirect_params: Vec::new(),
+            param_scopes: HashMap::new(),
             has_patches: false,
+            excluded_autouse: AutouseExclusion::default(),
         });
     }
 
     // Apply pattern filtering if specified
     if let Some(pattern) = &config.pattern {
-        tests.retain(|case| test_matches_pattern(case, pattern));
+        let keyword_expr = KeywordExpr::parse(pattern)
+            .map_err(|e| invalid_test_definition(format!("Invalid -k expression: {}", e)))?;
+        tests.retain(|case| keyword_expr.matches(case));
     }
 
     // Apply mark filtering if specified
@@ -1112,21 +1612,6 @@ def run_codeblock():
     Ok(run_codeblock.unbind())
 }
 
-/// Determine whether a test case should be kept for the provided pattern.
-fn test_matches_pattern(test_case: &TestCase, pattern: &str) -> bool {
-    let pattern_lower = pattern.to_ascii_lowercase();
-    test_case
-        .display_name
-        .to_ascii_lowercase()
-        .contains(&pattern_lower)
-        || test_case
-            .path
-            .display()
-            .to_string()
-            .to_ascii_lowercase()
-            .contains(&pattern_lower)
-}
-
 /// Return type for `inspect_module`: (fixtures, test cases, detected pytest fixture names).
 type InspectModuleResult = (IndexMap<String, Fixture>, Vec<TestCase>, Vec<String>);
 
@@ -1171,7 +1656,7 @@ fn inspect_module(
                 continue;
             }
 
-            if !name.starts_with("test") {
+            if !name.starts_with("test") || opts_out_of_collection(&value)? {
                 continue;
             }
 
@@ -1191,7 +1676,12 @@ fn inspect_module(
 
             let param_cases = collect_parametrization(py, &value)?;
             let marks = collect_marks(&value)?;
+            if skip_reason.is_none() {
+                skip_reason = mark_skip_reason(py, &marks, &value)?;
+            }
+            let excluded_autouse = AutouseExclusion::from_marks(py, &marks)?;
             let indirect_params = extract_indirect_params(&value)?;
+            let param_scopes = extract_param_scopes(&value)?;
 
             if param_cases.is_empty() {
                 tests.push(TestCase {
@@ -1206,30 +1696,43 @@ fn inspect_module(
                     class_name: None,
                     fixture_param_indices: IndexMap::new(),
                     indirect_params: indirect_params.clone(),
+                    param_scopes: param_scopes.clone(),
                     has_patches,
+                    excluded_autouse: excluded_autouse.clone(),
                 });
             } else {
-                for (case_id, values) in param_cases {
-                    let display_name = format!("{}[{}]", name, case_id);
+                for case in param_cases {
+                    let display_name = format!("{}[{}]", name, case.id);
+                    let skip_reason = match &skip_reason {
+                        Some(reason) => Some(reason.clone()),
+                        None => mark_skip_reason(py, &case.marks, &value)?,
+                    };
+                    let mut case_marks = marks.clone();
+                    case_marks.extend(case.marks);
                     tests.push(TestCase {
                         name: name.clone(),
                         display_name,
                         path: path.to_path_buf(),
                         callable: value.clone().unbind(),
                         parameters: parameters.clone(),
-                        parameter_values: values,
-                        skip_reason: skip_reason.clone(),
-                        marks: marks.clone(),
+                        parameter_values: case.values,
+                        skip_reason,
+                        marks: case_marks,
                         class_name: None,
                         fixture_param_indices: IndexMap::new(),
                         indirect_params: indirect_params.clone(),
+                        param_scopes: param_scopes.clone(),
                         has_patches,
+                        excluded_autouse: excluded_autouse.clone(),
                     });
                 }
             }
         }
         // Check if it's a class (both unittest.TestCase and plain test classes)
         else if is_class(&value, &type_type)? {
+            if opts_out_of_collection(&value)? {
+                continue;
+            }
             if is_test_case_class(py, &value)? {
                 // unittest.TestCase support
                 let class_tests = discover_unittest_class_tests(py, path, &name, &value)?;
@@ -1361,7 +1864,9 @@ fn expand_tests_for_parametrized_fixtures(
                 class_name: test.class_name.clone(),
                 fixture_param_indices,
                 indirect_params: test.indirect_params.clone(),
+                param_scopes: test.param_scopes.clone(),
                 has_patches: test.has_patches,
+                excluded_autouse: test.excluded_autouse.clone(),
             });
         }
     }
@@ -1408,6 +1913,15 @@ fn is_plain_test_class(name: &str) -> bool {
     name.starts_with("Test")
 }
 
+/// Check whether an object sets a falsy `__test__`, as pytest honours, to stay
+/// out of collection even though its name looks like a test.
+fn opts_out_of_collection(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
+    match obj.getattr_opt("__test__")? {
+        Some(flag) => Ok(!flag.is_truthy()?),
+        None => Ok(false),
+    }
+}
+
 /// Check if a class is a unittest.TestCase subclass.
 fn is_test_case_class(py: Python<'_>, cls: &Bound<'_, PyAny>) -> PyResult<bool> {
     let unittest = py.import("unittest")?;
@@ -1444,7 +1958,7 @@ fn discover_unittest_class_tests(
         let method = member.get_item(1)?;
 
         // Check if it's a method and starts with "test"
-        if name.starts_with("test") && is_callable(&method)? {
+        if name.starts_with("test") && is_callable(&method)? && !opts_out_of_collection(&method)? {
             let display_name = format!("{}::{}", class_name, name);
 
             // Create a callable that properly instantiates and runs the test
@@ -1462,7 +1976,9 @@ fn discover_unittest_class_tests(
                 class_name: Some(class_name.to_string()),
                 fixture_param_indices: IndexMap::new(),
                 indirect_params: Vec::new(),
+                param_scopes: HashMap::new(),
                 has_patches: false,
+                excluded_autouse: AutouseExclusion::default(),
             });
         }
     }
@@ -1482,56 +1998,31 @@ fn discover_unittest_class_tests(
 /// - Result: [(x=1,y=10), (x=1,y=20), (x=2,y=10), (x=2,y=20)]
 fn combine_parametrizations(
     py: Python<'_>,
-    class_params: &[(String, ParameterMap)],
-    method_params: &[(String, ParameterMap)],
-) -> PyResult<Vec<(String, ParameterMap)>> {
-    // If neither has parametrizations, return empty
-    if class_params.is_empty() && method_params.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    // If only class has parametrizations, return them
-    if method_params.is_empty() {
-        let mut result = Vec::new();
-        for (class_id, class_values) in class_params {
-            let mut cloned_values = ParameterMap::new();
-            for (key, value) in class_values {
-                cloned_values.insert(key.clone(), value.clone_ref(py));
-            }
-            result.push((class_id.clone(), cloned_values));
-        }
-        return Ok(result);
-    }
-
-    // If only method has parametrizations, return them
-    if class_params.is_empty() {
-        let mut result = Vec::new();
-        for (method_id, method_values) in method_params {
-            let mut cloned_values = ParameterMap::new();
-            for (key, value) in method_values {
-                cloned_values.insert(key.clone(), value.clone_ref(py));
-            }
-            result.push((method_id.clone(), cloned_values));
-        }
-        return Ok(result);
+    class_params: &[ParamCase],
+    method_params: &[ParamCase],
+) -> PyResult<Vec<ParamCase>> {
+    // If only one of them has parametrizations, return those
+    if method_params.is_empty() || class_params.is_empty() {
+        return Ok(class_params
+            .iter()
+            .chain(method_params)
+            .map(|case| case.clone_ref(py))
+            .collect());
     }
 
     // Both have parametrizations - create Cartesian product
     let mut result = Vec::new();
-    for (class_id, class_values) in class_params {
-        for (method_id, method_values) in method_params {
-            // Combine the parameter values
-            let mut combined_values = ParameterMap::new();
-            for (key, value) in class_values {
-                combined_values.insert(key.clone(), value.clone_ref(py));
-            }
-            for (key, value) in method_values {
-                combined_values.insert(key.clone(), value.clone_ref(py));
-            }
+    for class_case in class_params {
+        for method_case in method_params {
+            // Combine the parameter values and the per-case marks
+            let mut combined = class_case.clone_ref(py);
+            let method_case = method_case.clone_ref(py);
+            combined.values.extend(method_case.values);
+            combined.marks.extend(method_case.marks);
 
             // Combine the IDs
-            let combined_id = format!("{}-{}", class_id, method_id);
-            result.push((combined_id, combined_values));
+            combined.id = format!("{}-{}", class_case.id, method_case.id);
+            result.push(combined);
         }
     }
 
@@ -1549,7 +2040,6 @@ fn discover_plain_class_tests_and_fixtures(
 ) -> PyResult<(IndexMap<String, Fixture>, Vec<TestCase>)> {
     let mut fixtures = IndexMap::new();
     let mut tests = Vec::new();
-    let inspect = py.import("inspect")?;
 
     // Create a shared namespace for this class so that fixture and test wrappers
     // share the same instance cache.  This is critical for class-method autouse
@@ -1558,21 +2048,20 @@ fn discover_plain_class_tests_and_fixtures(
     let class_namespace = PyDict::new(py);
     class_namespace.set_item("test_class", cls)?;
     class_namespace.set_item("_instance_cache", PyDict::new(py))?;
+    fixtures.extend(create_xunit_fixtures(
+        py,
+        class_name,
+        cls,
+        &class_namespace,
+    )?);
 
     // Extract class-level parametrization (if any)
     let class_param_cases = collect_parametrization(py, cls)?;
     let class_indirect_params = extract_indirect_params(cls)?;
+    let class_param_scopes = extract_param_scopes(cls)?;
 
-    // Process all members
-    let members = inspect.call_method1("getmembers", (cls,))?;
-
-    for member in members.try_iter()? {
-        let member = member?;
-
-        // Each member is a tuple (name, value)
-        let name: String = member.get_item(0)?.extract()?;
-        let method = member.get_item(1)?;
-
+    // Process all members, including test methods that only exist on instances
+    for (name, method) in collect_class_members(cls)? {
         // Skip special methods (like __init__, __str__, etc.)
         if name.starts_with("__") {
             continue;
@@ -1614,7 +2103,7 @@ fn discover_plain_class_tests_and_fixtures(
         }
 
         // Check if it's a test method
-        if name.starts_with("test") && is_callable(&method)? {
+        if name.starts_with("test") && is_callable(&method)? && !opts_out_of_collection(&method)? {
             let display_name = format!("{}::{}", class_name, name);
 
             // Detect @patch decorators on the method
@@ -1638,8 +2127,14 @@ fn discover_plain_class_tests_and_fixtures(
             }
 
             let marks = collect_marks(&method)?;
+            if skip_reason.is_none() {
+                skip_reason = mark_skip_reason(py, &marks, &method)?;
+            }
+            let excluded_autouse = AutouseExclusion::from_marks(py, &marks)?;
             let method_param_cases = collect_parametrization(py, &method)?;
             let method_indirect_params = extract_indirect_params(&method)?;
+            let mut param_scopes = class_param_scopes.clone();
+            param_scopes.extend(extract_param_scopes(&method)?);
 
             // Merge class-level and method-level indirect params
             let mut indirect_params = class_indirect_params.clone();
@@ -1671,25 +2166,35 @@ fn discover_plain_class_tests_and_fixtures(
                     class_name: Some(class_name.to_string()),
                     fixture_param_indices: IndexMap::new(),
                     indirect_params: indirect_params.clone(),
+                    param_scopes: param_scopes.clone(),
                     has_patches,
+                    excluded_autouse: excluded_autouse.clone(),
                 });
             } else {
                 // Handle parametrized test methods
-                for (case_id, values) in combined_param_cases {
-                    let param_display_name = format!("{}::{}[{}]", class_name, name, case_id);
+                for case in combined_param_cases {
+                    let param_display_name = format!("{}::{}[{}]", class_name, name, case.id);
+                    let skip_reason = match &skip_reason {
+                        Some(reason) => Some(reason.clone()),
+                        None => mark_skip_reason(py, &case.marks, &method)?,
+                    };
+                    let mut case_marks = marks.clone();
+                    case_marks.extend(case.marks);
                     tests.push(TestCase {
                         name: name.clone(),
                         display_name: param_display_name,
                         path: path.to_path_buf(),
                         callable: test_callable.clone_ref(py),
                         parameters: parameters.clone(),
-                        parameter_values: values,
-                        skip_reason: skip_reason.clone(),
-                        marks: marks.clone(),
+                        parameter_values: case.values,
+                        skip_reason,
+                        marks: case_marks,
                         class_name: Some(class_name.to_string()),
                         fixture_param_indices: IndexMap::new(),
                         indirect_params: indirect_params.clone(),
+                        param_scopes: param_scopes.clone(),
                         has_patches,
+                        excluded_autouse: excluded_autouse.clone(),
                     });
                 }
             }
@@ -1699,6 +2204,73 @@ fn discover_plain_class_tests_and_fixtures(
     Ok((fixtures, tests))
 }
 
+/// List `(name, value)` members of a test class, sorted by name.
+///
+/// Members are found with `dir()` + `getattr()` on the class. This picks up
+/// test methods that frameworks attach dynamically (decorators, metaclasses
+/// or `__init_subclass__`). Attributes whose lookup raises, such as
+/// properties that fail on the bare class, are skipped.
+///
+/// Methods assigned in `__init__` only exist on an instance, so a class
+/// whose `__init__` stores a `test*` attribute is also instantiated without
+/// arguments and its instance searched. Other classes are never constructed
+/// during collection, so their `__init__` side effects don't run there.
+fn collect_class_members<'py>(
+    cls: &Bound<'py, PyAny>,
+) -> PyResult<Vec<(String, Bound<'py, PyAny>)>> {
+    let mut members: IndexMap<String, Bound<'py, PyAny>> = IndexMap::new();
+    for name in cls.dir()?.iter() {
+        let name: String = name.extract()?;
+        if let Ok(value) = cls.getattr(name.as_str()) {
+            members.insert(name, value);
+        }
+    }
+
+    // Instance-only attributes are only interesting as test methods
+    let instance = init_assigns_test_attributes(cls)
+        .then(|| cls.call0().ok())
+        .flatten();
+    if let Some(instance) = instance {
+        if let Ok(names) = instance.dir() {
+            for name in names.iter() {
+                let name: String = name.extract()?;
+                if !name.starts_with("test") || members.contains_key(&name) {
+                    continue;
+                }
+                if let Ok(value) = instance.getattr(name.as_str()) {
+                    if is_callable(&value)? {
+                        members.insert(name, value);
+                    }
+                }
+            }
+        }
+    }
+
+    members.sort_keys();
+    Ok(members.into_iter().collect())
+}
+
+/// Whether the class's `__init__` is Python code that names a `test*`
+/// attribute, e.g. `self.test_generated = ...`.
+///
+/// Names set through `setattr()` with a computed string aren't seen.
+fn init_assigns_test_attributes(cls: &Bound<'_, PyAny>) -> bool {
+    let Ok(names) = cls
+        .getattr("__init__")
+        .and_then(|init| init.getattr("__code__"))
+        .and_then(|code| code.getattr("co_names"))
+    else {
+        // `object.__init__` and other built-in initializers
+        return false;
+    };
+    names.try_iter().is_ok_and(|names| {
+        names.flatten().any(|name| {
+            name.extract::<String>()
+                .is_ok_and(|name| name.starts_with("test"))
+        })
+    })
+}
+
 /// Check if an object is callable.
 fn is_callable(obj: &Bound<'_, PyAny>) -> PyResult<bool> {
     let builtins = obj.py().import("builtins")?;
@@ -1789,16 +2361,10 @@ def {func_name}(*args, **kwargs):
     if 'instance' not in _instance_cache:
         _instance_cache['instance'] = test_class()
     instance = _instance_cache['instance']
-    _setup = getattr(instance, 'setup_method', None)
-    if _setup is not None:
-        _setup()
     try:
         test_method = getattr(instance, '{method_name}')
         return test_method(*args, **kwargs)
     finally:
-        _teardown = getattr(instance, 'teardown_method', None)
-        if _teardown is not None:
-            _teardown()
         _instance_cache.clear()
 "#,
         func_name = func_name,
@@ -1814,6 +2380,106 @@ def {func_name}(*args, **kwargs):
     Ok(wrapper.unbind())
 }
 
+/// Create autouse fixtures for the xunit-style hooks a plain test class defines.
+///
+/// As in pytest, `setup_class`/`teardown_class` become a class-scoped generator
+/// fixture and `setup_method`/`teardown_method` a function-scoped one, both
+/// limited to the class. An error in a setup hook is then a fixture error of
+/// each affected test, and an error in a teardown hook is reported like any
+/// other teardown error. The method hooks share `_instance_cache` with the
+/// test wrapper and receive the test method when they take an argument.
+fn create_xunit_fixtures(
+    py: Python<'_>,
+    class_name: &str,
+    cls: &Bound<'_, PyAny>,
+    class_namespace: &Bound<'_, PyDict>,
+) -> PyResult<Vec<(String, Fixture)>> {
+    let defines = |hooks: [&str; 2]| hooks.iter().any(|hook| cls.hasattr(*hook).unwrap_or(false));
+    let class_hooks = defines(["setup_class", "teardown_class"]);
+    let method_hooks = defines(["setup_method", "teardown_method"]);
+    if !class_hooks && !method_hooks {
+        return Ok(Vec::new());
+    }
+
+    let code = r#"
+import inspect as _inspect
+
+def _call_xunit(hook, arg):
+    try:
+        takes_arg = bool(_inspect.signature(hook).parameters)
+    except (TypeError, ValueError):
+        takes_arg = False
+    return hook(arg) if takes_arg else hook()
+
+def _xunit_class_hooks():
+    _setup = getattr(test_class, 'setup_class', None)
+    if _setup is not None:
+        _call_xunit(_setup, test_class)
+    yield
+    _teardown = getattr(test_class, 'teardown_class', None)
+    if _teardown is not None:
+        _call_xunit(_teardown, test_class)
+
+def _xunit_method_hooks(request):
+    if 'instance' not in _instance_cache:
+        _instance_cache['instance'] = test_class()
+    instance = _instance_cache['instance']
+    method_name = request.node.name.rsplit('::', 1)[-1].split('[', 1)[0]
+    method = getattr(instance, method_name, None)
+    _setup = getattr(instance, 'setup_method', None)
+    if _setup is not None:
+        try:
+            _call_xunit(_setup, method)
+        except BaseException:
+            _instance_cache.clear()
+            raise
+    yield
+    _teardown = getattr(instance, 'teardown_method', None)
+    if _teardown is not None:
+        _call_xunit(_teardown, method)
+"#;
+    let code_cstr = CString::new(code).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid code string: {}", e))
+    })?;
+    py.run(&code_cstr, Some(class_namespace), Some(class_namespace))?;
+
+    let mut fixtures = Vec::new();
+    let hooks = [
+        (class_hooks, "class", FixtureScope::Class, Vec::new()),
+        (
+            method_hooks,
+            "method",
+            FixtureScope::Function,
+            vec!["request".to_string()],
+        ),
+    ];
+    for (defined, kind, scope, parameters) in hooks {
+        if !defined {
+            continue;
+        }
+        // Class fixtures share the module's fixture map, so the name includes the class
+        let name = format!("{}{}_{}", XUNIT_FIXTURE_PREFIX, kind, class_name);
+        let callable = class_namespace
+            .get_item(format!("_xunit_{}_hooks", kind))?
+            .expect("defined above");
+        fixtures.push((
+            name.clone(),
+            Fixture::new(
+                name,
+                callable.unbind(),
+                parameters,
+                scope,
+                true,
+                false,
+                false,
+                true,
+                Some(class_name.to_string()),
+            ),
+        ));
+    }
+    Ok(fixtures)
+}
+
 /// Check if a Python object is a function.
 ///
 /// OPTIMIZATION: This uses direct type checking with a cached FunctionType object,
@@ -2221,11 +2887,34 @@ fn extract_parameters_inner(py: Python<'_>, value: &Bound<'_, PyAny>) -> PyResul
     Ok(names)
 }
 
+/// One parametrized case of a test: its id, argument values and the marks
+/// given to it with `pytest.param(..., marks=...)`.
+struct ParamCase {
+    id: String,
+    values: ParameterMap,
+    marks: Vec<Mark>,
+}
+
+impl ParamCase {
+    fn clone_ref(&self, py: Python<'_>) -> Self {
+        Self {
+            id: self.id.clone(),
+            values: self
+                .values
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone_ref(py)))
+                .collect(),
+            marks: self
+                .marks
+                .iter()
+                .map(|mark| mark.clone_with_py(py))
+                .collect(),
+        }
+    }
+}
+
 /// Collect parameterisation information attached to a test function.
-fn collect_parametrization(
-    _py: Python<'_>,
-    value: &Bound<'_, PyAny>,
-) -> PyResult<Vec<(String, ParameterMap)>> {
+fn collect_parametrization(py: Python<'_>, value: &Bound<'_, PyAny>) -> PyResult<Vec<ParamCase>> {
     let mut parametrized = Vec::new();
     let Ok(attr) = value.getattr("__rustest_parametrization__") else {
         return Ok(parametrized);
@@ -2247,11 +2936,80 @@ fn collect_parametrization(
             let key: String = key.extract()?;
             parameters.insert(key, value.unbind());
         }
-        parametrized.push((case_id, parameters));
+        let mut marks = Vec::new();
+        if let Some(case_marks) = case.get_item("marks")? {
+            for mark in case_marks.try_iter()? {
+                marks.push(mark_from_dict(py, &mark?.cast_into()?)?);
+            }
+        }
+        parametrized.push(ParamCase {
+            id: case_id,
+            values: parameters,
+            marks,
+        });
     }
     Ok(parametrized)
 }
 
+/// Skip reason from the `skip` and `skipif` marks among `marks`, if one applies.
+///
+/// `skipif` conditions are normally evaluated by the decorator already; one
+/// still given as a string is evaluated here against the module globals of
+/// `target`. Several `skipif` marks combine with OR: the first true one gives
+/// the reason, which defaults to the condition text.
+fn mark_skip_reason(
+    py: Python<'_>,
+    marks: &[Mark],
+    target: &Bound<'_, PyAny>,
+) -> PyResult<Option<String>> {
+    let text_arg = |mark: &Mark, key: &str, index: usize| -> Option<String> {
+        mark.get_kwarg(py, key)
+            .filter(|value| !value.is_none(py))
+            .or_else(|| mark.args.bind(py).get_item(index).ok().map(Bound::unbind))
+            .and_then(|value| value.bind(py).extract::<String>().ok())
+    };
+    if let Some(mark) = marks.iter().find(|mark| mark.is_named("skip")) {
+        let reason = text_arg(mark, "reason", 0);
+        return Ok(Some(
+            reason.unwrap_or_else(|| "skipped via rustest.skip".to_string()),
+        ));
+    }
+
+    for mark in marks.iter().filter(|mark| mark.is_named("skipif")) {
+        let (condition, text) = match mark.args.bind(py).get_item(0) {
+            Err(_) => (true, String::new()),
+            Ok(condition) => match condition.extract::<String>() {
+                Ok(expression) => {
+                    let globals = target
+                        .getattr("__globals__")
+                        .ok()
+                        .and_then(|globals| globals.cast_into::<PyDict>().ok())
+                        .unwrap_or_else(|| PyDict::new(py));
+                    let value = py
+                        .import("builtins")?
+                        .call_method1("eval", (expression.as_str(), globals))
+                        .and_then(|value| value.is_truthy())
+                        .map_err(|e| {
+                            invalid_test_definition(format!(
+                                "Failed to evaluate skipif condition '{}': {}",
+                                expression, e
+                            ))
+                        })?;
+                    (value, expression)
+                }
+                Err(_) => (condition.is_truthy()?, condition.repr()?.to_string()),
+            },
+        };
+        if condition {
+            let reason = text_arg(mark, "reason", 1);
+            return Ok(Some(
+                reason.unwrap_or_else(|| format!("condition: {}", text)),
+            ));
+        }
+    }
+    Ok(None)
+}
+
 /// Extract the list of indirect parameters from a test function.
 /// Returns parameter names that should be resolved as fixture references.
 fn extract_indirect_params(value: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
@@ -2268,6 +3026,21 @@ fn extract_indirect_params(value: &Bound<'_, PyAny>) -> PyResult<Vec<String>> {
     Ok(indirect_params)
 }
 
+/// Read the per-argument scopes set by `@parametrize(..., scope=...)`.
+fn extract_param_scopes(value: &Bound<'_, PyAny>) -> PyResult<HashMap<String, FixtureScope>> {
+    let Ok(attr) = value.getattr("__rustest_parametrization_scope__") else {
+        return Ok(HashMap::new());
+    };
+    let scopes: HashMap<String, String> = attr.extract()?;
+    scopes
+        .into_iter()
+        .map(|(name, scope)| {
+            let scope = FixtureScope::from_str(&scope).map_err(invalid_test_definition)?;
+            Ok((name, scope))
+        })
+        .collect()
+}
+
 /// Collect mark information attached to a test function.
 fn collect_marks(value: &Bound<'_, PyAny>) -> PyResult<Vec<Mark>> {
     let Ok(attr) = value.getattr("__rustest_marks__") else {
@@ -2277,35 +3050,38 @@ fn collect_marks(value: &Bound<'_, PyAny>) -> PyResult<Vec<Mark>> {
     let mut marks = Vec::new();
     for element in sequence.try_iter()? {
         let element = element?;
-        let mark_dict: Bound<'_, PyDict> = element.cast_into()?;
-
-        // Extract name
-        let name = mark_dict
-            .get_item("name")?
-            .ok_or_else(|| invalid_test_definition("Missing name in mark metadata"))?;
-        let name: String = name.extract()?;
+        marks.push(mark_from_dict(value.py(), &element.cast_into()?)?);
+    }
+    Ok(marks)
+}
 
-        // Extract args (default to empty list if not present)
-        // Convert tuple to list if necessary, since Python decorators store args as tuples
-        let args_raw = mark_dict
-            .get_item("args")?
-            .unwrap_or_else(|| PyList::empty(value.py()).into_any());
-        let args: Py<PyList> = if args_raw.is_instance_of::<pyo3::types::PyTuple>() {
-            let tuple: Bound<'_, pyo3::types::PyTuple> = args_raw.cast_into()?;
-            PyList::new(value.py(), tuple.iter())?.unbind()
-        } else {
-            args_raw.extract()?
-        };
+/// Build a [`Mark`] from its `{"name", "args", "kwargs"}` metadata dict.
+fn mark_from_dict(py: Python<'_>, mark_dict: &Bound<'_, PyDict>) -> PyResult<Mark> {
+    // Extract name
+    let name = mark_dict
+        .get_item("name")?
+        .ok_or_else(|| invalid_test_definition("Missing name in mark metadata"))?;
+    let name: String = name.extract()?;
+
+    // Extract args (default to empty list if not present)
+    // Convert tuple to list if necessary, since Python decorators store args as tuples
+    let args_raw = mark_dict
+        .get_item("args")?
+        .unwrap_or_else(|| PyList::empty(py).into_any());
+    let args: Py<PyList> = if args_raw.is_instance_of::<pyo3::types::PyTuple>() {
+        let tuple: Bound<'_, pyo3::types::PyTuple> = args_raw.cast_into()?;
+        PyList::new(py, tuple.iter())?.unbind()
+    } else {
+        args_raw.extract()?
+    };
 
-        // Extract kwargs (default to empty dict if not present)
-        let kwargs = mark_dict
-            .get_item("kwargs")?
-            .unwrap_or_else(|| PyDict::new(value.py()).into_any());
-        let kwargs: Py<PyDict> = kwargs.extract()?;
+    // Extract kwargs (default to empty dict if not present)
+    let kwargs = mark_dict
+        .get_item("kwargs")?
+        .unwrap_or_else(|| PyDict::new(py).into_any());
+    let kwargs: Py<PyDict> = kwargs.extract()?;
 
-        marks.push(Mark::new(name, args, kwargs));
-    }
-    Ok(marks)
+    Ok(Mark::new(name, args, kwargs))
 }
 
 /// Load parent __init__.py files to ensure package structure is initialized.
@@ -2392,6 +3168,7 @@ fn load_python_module<'py>(
     path: &Path,
     module_name: &str,
     package: Option<&str>,
+    timeout: Option<Duration>,
 ) -> PyResult<Bound<'py, PyAny>> {
     // Ensure parent packages are loaded for relative imports to work
     ensure_parent_packages_loaded(py, path)?;
@@ -2414,12 +3191,61 @@ fn load_python_module<'py>(
     let sys = py.import("sys")?;
     let modules: Bound<'_, PyDict> = sys.getattr("modules")?.cast_into()?;
     modules.set_item(module_name, &module)?;
-    loader.call_method1("exec_module", (&module,))?;
+    match timeout {
+        Some(timeout) => exec_module_with_timeout(py, path, &loader, &module, timeout)
+            .inspect_err(|_| {
+                modules.del_item(module_name).ok();
+            })?,
+        None => {
+            loader.call_method1("exec_module", (&module,))?;
+        }
+    }
     Ok(module)
 }
 
+/// Execute `module` on a helper thread, giving up after `timeout`.
+///
+/// The GIL is released while waiting so the helper thread can run. Python
+/// offers no way to stop a thread, so a module that overruns keeps importing
+/// in the background; its error is returned straight away and the collection
+/// moves on to the next file.
+fn exec_module_with_timeout(
+    py: Python<'_>,
+    path: &Path,
+    loader: &Bound<'_, PyAny>,
+    module: &Bound<'_, PyAny>,
+    timeout: Duration,
+) -> PyResult<()> {
+    let loader = loader.clone().unbind();
+    let module = module.clone().unbind();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let result = Python::attach(|py| {
+            loader
+                .bind(py)
+                .call_method1("exec_module", (module.bind(py),))
+                .map(|_| ())
+        });
+        // The receiver is gone once the import has timed out
+        sender.send(result).ok();
+    });
+    match py.detach(move || receiver.recv_timeout(timeout)) {
+        Ok(result) => result,
+        Err(_) => Err(pyo3::exceptions::PyTimeoutError::new_err(format!(
+            "Importing {} took longer than the import timeout of {:.1}s",
+            path.display(),
+            timeout.as_secs_f64()
+        ))),
+    }
+}
+
 /// Compute a stable module and package name for the test file.
-fn infer_module_names(path: &Path, fallback_id: usize) -> (String, Option<String>) {
+///
+/// Files outside any package get a generated name that includes a hash of
+/// their canonical path, so files sharing a stem in different directories
+/// never replace each other in `sys.modules`, and a file keeps the same name
+/// from run to run.
+fn infer_module_names(path: &Path) -> (String, Option<String>) {
     let stem = path
         .file_stem()
         .and_then(|value| value.to_str())
@@ -2442,7 +3268,7 @@ fn infer_module_names(path: &Path, fallback_id: usize) -> (String, Option<String
 
     if components.len() == 1 {
         // Fall back to a generated name when no package structure exists.
-        return (format!("rustest_module_{}", fallback_id), None);
+        return (standalone_module_name(path, stem), None);
     }
 
     components.reverse();
@@ -2457,64 +3283,327 @@ fn infer_module_names(path: &Path, fallback_id: usize) -> (String, Option<String
     (module_name, package_name)
 }
 
+/// Name for a module outside any package: `rustest_module_<stem>_<path hash>`.
+fn standalone_module_name(path: &Path, stem: &str) -> String {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    // FNV-1a: unlike std's hashers, its output is fixed across Rust releases
+    let hash = canonical
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    let identifier: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("rustest_module_{}_{:016x}", identifier, hash)
+}
+
 /// Apply last-failed filtering to the collected test modules.
-/// This modifies the modules in place, filtering or reordering tests based on the last failed cache.
-fn apply_last_failed_filter(
+///
+/// `OnlyFailed` keeps the tests in `failed_ids`, or every test when none of
+/// the collected tests failed last time. `FailedFirst` runs the previously
+/// failed tests first: modules containing one move to the front, and within
+/// each module the failed tests come before the rest. Returns how many of the
+/// collected tests failed last time.
+pub(crate) fn apply_last_failed_filter(
     modules: &mut Vec<TestModule>,
-    config: &RunConfiguration,
-) -> PyResult<()> {
-    // Read the last failed test IDs from cache
-    let failed_ids = cache::read_last_failed()?;
+    mode: LastFailedMode,
+    failed_ids: &HashSet<String>,
+) -> usize {
+    let previously_failed = |test: &TestCase| failed_ids.contains(&test.unique_id());
+    let rerun = modules
+        .iter()
+        .flat_map(|module| &module.tests)
+        .filter(|test| previously_failed(test))
+        .count();
+    if rerun == 0 {
+        return 0;
+    }
 
-    // If the cache is empty and we're in OnlyFailed mode, return empty modules
-    if failed_ids.is_empty() && config.last_failed_mode == LastFailedMode::OnlyFailed {
-        modules.clear();
-        return Ok(());
+    for module in modules.iter_mut() {
+        let (failed_tests, other_tests): (Vec<TestCase>, Vec<TestCase>) = module
+            .tests
+            .drain(..)
+            .partition(|test| previously_failed(test));
+        module.tests = failed_tests;
+        if mode == LastFailedMode::FailedFirst {
+            module.tests.extend(other_tests);
+        }
     }
 
-    // Process each module
+    match mode {
+        LastFailedMode::OnlyFailed => modules.retain(|m| !m.tests.is_empty()),
+        // The sort is stable, so modules keep their order within each group
+        _ => modules.sort_by_key(|module| !module.tests.first().is_some_and(previously_failed)),
+    }
+    rerun
+}
+
+/// Keep only the tests named by `node_ids` (`path::name`), in that order.
+///
+/// Paths are compared after resolving symlinks and relative paths, so a node
+/// id matches however the file was spelled on the command line.
+pub(crate) fn select_node_ids(modules: &mut Vec<TestModule>, node_ids: &[String]) {
+    let resolve = |path: &Path| std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let positions: HashMap<(PathBuf, &str), usize> = node_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(position, node_id)| {
+            let (path, name) = node_id.split_once("::")?;
+            Some(((resolve(Path::new(path)), name), position))
+        })
+        .collect();
+
     for module in modules.iter_mut() {
-        let mut failed_tests = Vec::new();
-        let mut other_tests = Vec::new();
-
-        // Separate tests into failed and non-failed
-        for test in module.tests.drain(..) {
-            let test_id = test.unique_id();
-            if failed_ids.contains(&test_id) {
-                failed_tests.push(test);
-            } else {
-                other_tests.push(test);
-            }
+        let path = resolve(&module.path);
+        let position_of = |test: &TestCase| {
+            positions
+                .get(&(path.clone(), test.display_name.as_str()))
+                .copied()
+        };
+        module.tests.retain(|test| position_of(test).is_some());
+        module.tests.sort_by_key(|test| position_of(test));
+    }
+    modules.retain(|m| !m.tests.is_empty());
+    modules.sort_by_key(|module| {
+        let path = resolve(&module.path);
+        positions
+            .get(&(path, module.tests[0].display_name.as_str()))
+            .copied()
+    });
+}
+
+/// Keep tests whose source lines overlap a changed hunk, or whose module
+/// imports changed code.
+///
+/// Changes in a test file outside every test (imports, helpers, fixtures)
+/// may affect any of its tests, so they keep the whole file. Tests whose
+/// source can't be located are kept whenever their file changed.
+pub(crate) fn select_changed_tests(
+    py: Python<'_>,
+    modules: &mut Vec<TestModule>,
+    changed: &ChangedLines,
+) {
+    let Ok(inspect) = py.import("inspect") else {
+        return;
+    };
+
+    for module in modules.iter_mut() {
+        let path = module
+            .path
+            .canonicalize()
+            .unwrap_or_else(|_| module.path.clone());
+        let spans: Vec<Option<(usize, usize)>> = module
+            .tests
+            .iter()
+            .map(|test| {
+                test_function(py, test)
+                    .and_then(|function| source_span(&inspect, &function))
+                    .filter(|(file, _, _)| *file == path)
+                    .map(|(_, start, end)| (start, end))
+            })
+            .collect();
+
+        let module_level_change = match changed.ranges(&path) {
+            None => changed.touches_file(&path),
+            Some(ranges) => ranges
+                .iter()
+                .any(|&(start, end)| !spans.iter().flatten().any(|&(s, e)| s <= start && end <= e)),
+        };
+        let imports_changed = module
+            .tests
+            .iter()
+            .find_map(|test| test_function(py, test))
+            .is_some_and(|function| imports_changed_code(&inspect, &function, &path, changed));
+
+        if module_level_change || imports_changed {
+            continue;
         }
+        let mut spans = spans.into_iter();
+        module.tests.retain(|_| match spans.next().flatten() {
+            Some((start, end)) => changed.touches(&path, start, end),
+            None => changed.touches_file(&path),
+        });
+    }
+    modules.retain(|m| !m.tests.is_empty());
+}
 
-        // Apply the filtering/ordering based on mode
-        match config.last_failed_mode {
-            LastFailedMode::None => {
-                // This should not happen as we check this before calling this function
-                module.tests = failed_tests;
-                module.tests.extend(other_tests);
-            }
-            LastFailedMode::OnlyFailed => {
-                // Only include failed tests
-                module.tests = failed_tests;
-            }
-            LastFailedMode::FailedFirst => {
-                // Include failed tests first, then other tests
-                module.tests = failed_tests;
-                module.tests.extend(other_tests);
-            }
+/// The user's function behind a test's callable.
+fn test_function<'py>(py: Python<'py>, test: &TestCase) -> Option<Bound<'py, PyAny>> {
+    let callable = test.callable.bind(py);
+    if test.class_name.is_some() {
+        // Class tests run through a generated wrapper holding the class
+        callable
+            .getattr("__globals__")
+            .and_then(|globals| globals.get_item("test_class"))
+            .and_then(|class| class.getattr(test.name.as_str()))
+            .ok()
+    } else {
+        Some(callable.clone())
+    }
+}
+
+/// File and line span of a function or class, decorators included.
+fn source_span(
+    inspect: &Bound<'_, PyModule>,
+    object: &Bound<'_, PyAny>,
+) -> Option<(PathBuf, usize, usize)> {
+    let object = inspect.call_method1("unwrap", (object,)).ok()?;
+    let file: String = inspect
+        .call_method1("getsourcefile", (&object,))
+        .ok()?
+        .extract()
+        .ok()?;
+    let (lines, start): (Vec<String>, usize) = inspect
+        .call_method1("getsourcelines", (&object,))
+        .ok()?
+        .extract()
+        .ok()?;
+    let file = Path::new(&file).canonicalize().ok()?;
+    Some((file, start, start + lines.len().saturating_sub(1)))
+}
+
+/// Whether the test module imports changed code: a changed module, or a
+/// function or class whose own lines changed.
+fn imports_changed_code(
+    inspect: &Bound<'_, PyModule>,
+    function: &Bound<'_, PyAny>,
+    module_path: &Path,
+    changed: &ChangedLines,
+) -> bool {
+    let Ok(function) = inspect.call_method1("unwrap", (function,)) else {
+        return false;
+    };
+    let Ok(globals) = function
+        .getattr("__globals__")
+        .and_then(|globals| globals.cast_into::<PyDict>().map_err(PyErr::from))
+    else {
+        return false;
+    };
+
+    globals.values().iter().any(|value| {
+        let is_module = inspect
+            .call_method1("ismodule", (&value,))
+            .and_then(|result| result.is_truthy())
+            .unwrap_or(false);
+        if is_module {
+            return value
+                .getattr("__file__")
+                .and_then(|file| file.extract::<String>())
+                .ok()
+                .and_then(|file| Path::new(&file).canonicalize().ok())
+                .is_some_and(|file| file != module_path && changed.touches_file(&file));
         }
+        let is_definition = ["isfunction", "isclass"].iter().any(|check| {
+            inspect
+                .call_method1(*check, (&value,))
+                .and_then(|result| result.is_truthy())
+                .unwrap_or(false)
+        });
+        is_definition
+            && source_span(inspect, &value).is_some_and(|(file, start, end)| {
+                file != module_path && changed.touches(&file, start, end)
+            })
+    })
+}
+
+/// Drop tests marked `slow`, returning how many were deselected.
+fn deselect_slow_tests(modules: &mut Vec<TestModule>) -> usize {
+    let mut deselected = 0;
+    for module in modules.iter_mut() {
+        let before = module.tests.len();
+        module
+            .tests
+            .retain(|test| !test.marks.iter().any(|mark| mark.is_named("slow")));
+        deselected += before - module.tests.len();
     }
+    modules.retain(|m| !m.tests.is_empty());
+    deselected
+}
 
-    // Remove modules that have no tests (only relevant in OnlyFailed mode)
+/// Keep a random sample of up to `sample` tests across all modules.
+///
+/// Tests keep their collection order, so per-module and per-class setup still
+/// happens in one place, but a class can lose some of its tests. Fixtures of
+/// every scope work as usual for whichever tests are kept.
+///
+/// Returns `(sampled, total)` test counts.
+fn apply_sample(modules: &mut Vec<TestModule>, sample: usize, seed: u64) -> (usize, usize) {
+    let total: usize = modules.iter().map(|m| m.tests.len()).sum();
+    let keep: HashSet<usize> = sample_indices(total, sample, seed).into_iter().collect();
+
+    let mut index = 0;
+    for module in modules.iter_mut() {
+        module.tests.retain(|_| {
+            let kept = keep.contains(&index);
+            index += 1;
+            kept
+        });
+    }
     modules.retain(|m| !m.tests.is_empty());
 
-    Ok(())
+    (keep.len(), total)
+}
+
+/// Pick up to `sample` distinct indices from `0..total`, reproducibly for a
+/// given seed, returned in ascending order.
+fn sample_indices(total: usize, sample: usize, seed: u64) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..total).collect();
+    let count = sample.min(total);
+    let mut state = seed;
+    // Partial Fisher-Yates: only the first `count` slots need shuffling
+    for i in 0..count {
+        let j = i + (splitmix64(&mut state) % (total - i) as u64) as usize;
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices.sort_unstable();
+    indices
+}
+
+/// Shuffle the order of `modules`, then the order of the tests within each.
+///
+/// Modules are shuffled as whole units so their tests never interleave, and
+/// module-scoped fixtures are still set up once per module.
+fn apply_shuffle(modules: &mut [TestModule], seed: u64) {
+    let mut state = seed;
+    shuffle(modules, &mut state);
+    for module in modules.iter_mut() {
+        shuffle(&mut module.tests, &mut state);
+    }
+}
+
+/// Fisher-Yates shuffle driven by [`splitmix64`].
+fn shuffle<T>(items: &mut [T], state: &mut u64) {
+    for i in (1..items.len()).rev() {
+        let j = (splitmix64(state) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// SplitMix64 step; small and good enough for picking tests.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Seed used when sampling or shuffling without an explicit `shuffle_seed`.
+fn time_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::file_contains_pytest_import;
+    use super::{file_contains_pytest_import, infer_module_names, SourcePrefetch};
+    use std::path::PathBuf;
 
     #[test]
     fn detects_import_pytest() {
@@ -2671,4 +3760,111 @@ def test_something():
 "#;
         assert!(!file_contains_pytest_import(content));
     }
+
+    #[test]
+    fn traces_matching_and_non_matching_files() {
+        use super::{build_file_glob, trace_candidate_files};
+
+        let dir = std::env::temp_dir().join("rustest_trace_config_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let matching = dir.join("test_found.py");
+        let non_matching = dir.join("helpers.py");
+        std::fs::write(&matching, "def test_ok():\n    pass\n").unwrap();
+        std::fs::write(&non_matching, "VALUE = 1\n").unwrap();
+
+        let py_glob = build_file_glob().unwrap();
+        let lines = trace_candidate_files(std::slice::from_ref(&dir), &py_glob, None, None);
+
+        assert!(lines.contains(&format!("matched test file glob: {}", matching.display())));
+        assert!(lines.contains(&format!(
+            "ignored (no glob match): {}",
+            non_matching.display()
+        )));
+        assert_eq!(lines.len(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn warns_about_files_over_max_size() {
+        use super::oversized_file_warning;
+
+        let dir = std::env::temp_dir().join("rustest_max_file_size_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let large = dir.join("test_large.py");
+        let small = dir.join("test_small.py");
+        std::fs::write(&large, "# padding\n".repeat(1000)).unwrap();
+        std::fs::write(&small, "def test_ok():\n    pass\n").unwrap();
+
+        let warning = oversized_file_warning(&large, Some(1024)).expect("large file is skipped");
+        assert!(warning.contains("test_large.py"));
+        assert!(warning.contains("10000 bytes exceeds --max-file-size of 1024 bytes"));
+        assert!(oversized_file_warning(&small, Some(1024)).is_none());
+        assert!(oversized_file_warning(&large, None).is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn sample_indices_are_reproducible_for_a_seed() {
+        use super::sample_indices;
+
+        let first = sample_indices(100, 10, 42);
+        assert_eq!(first.len(), 10);
+        assert_eq!(first, sample_indices(100, 10, 42));
+        assert!(first.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(first.iter().all(|&index| index < 100));
+        assert_ne!(first, sample_indices(100, 10, 43));
+        assert_eq!(sample_indices(3, 10, 42), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn prefetch_returns_each_file_in_order() {
+        let dir = std::env::temp_dir().join("rustest_prefetch");
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PathBuf> = (0..25)
+            .map(|i| {
+                let path = dir.join(format!("file_{}.md", i));
+                std::fs::write(&path, format!("contents {}", i)).unwrap();
+                path
+            })
+            .collect();
+        let missing = dir.join("missing.md");
+
+        let mut all_paths = paths.clone();
+        all_paths.push(missing);
+        let mut prefetch = SourcePrefetch::start(all_paths, 3);
+        let contents: Vec<Option<String>> = (0..26).map(|i| prefetch.take(i)).collect();
+        std::fs::remove_dir_all(&dir).ok();
+
+        for (i, content) in contents[..25].iter().enumerate() {
+            assert_eq!(content.as_deref(), Some(format!("contents {}", i).as_str()));
+        }
+        assert_eq!(contents[25], None);
+    }
+
+    #[test]
+    fn standalone_modules_get_distinct_stable_names() {
+        let dir = std::env::temp_dir().join("rustest_module_names");
+        let first = dir.join("one").join("test_same.py");
+        let second = dir.join("two").join("test_same.py");
+        for file in [&first, &second] {
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, "def test_x():\n    pass\n").unwrap();
+        }
+
+        let (first_name, first_package) = infer_module_names(&first);
+        let (second_name, _) = infer_module_names(&second);
+        let (first_again, _) = infer_module_names(&first);
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(
+            first_name.starts_with("rustest_module_test_same_"),
+            "{}",
+            first_name
+        );
+        assert_eq!(first_package, None);
+        assert_ne!(first_name, second_name);
+        assert_eq!(first_name, first_again);
+    }
 }
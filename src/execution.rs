@@ -10,23 +10,33 @@
 //! - Sync tests always run sequentially
 //! - Fixture scopes are respected: shared fixtures resolve once, function fixtures per-test
 
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::c_void;
-use std::time::Instant;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use indexmap::IndexMap;
-use pyo3::exceptions::PyRuntimeError;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indexmap::{IndexMap, IndexSet};
+use pyo3::exceptions::{PyKeyboardInterrupt, PyRuntimeError};
 use pyo3::prelude::PyAnyMethods;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList, PyTuple};
 
 use crate::cache;
 use crate::model::{
-    invalid_test_definition, to_relative_path, CollectionError, Fixture, FixtureScope, Mark,
-    ParameterMap, PyRunReport, PyTestResult, RunConfiguration, TestCase, TestModule,
+    invalid_test_definition, to_relative_path, AutouseExclusion, CollectionError, Discovery,
+    Fixture, FixtureScope, Mark, ParameterMap, PyRunReport, PyTestResult, RunConfiguration,
+    TeardownError, TestCase, TestModule, XUNIT_FIXTURE_PREFIX,
 };
-use crate::output::{EventStreamRenderer, OutputConfig, OutputRenderer, SpinnerDisplay};
+use crate::output::{
+    format_slowest_files, format_slowest_tests, format_teardown_errors, format_warnings_summary,
+    record_span, start_trace, write_chrome_trace, write_json_report, write_tap_report,
+    CompactDisplay, EventStreamRenderer, JUnitXmlRenderer, OutputConfig, OutputMode,
+    OutputRenderer, SessionHeader, SpinnerDisplay, SqliteRecorder,
+};
+use crate::parallel::{shard_exclusive, WorkerCommand, WorkerPool, WorkerResult};
+use serde_json::{Map, Value};
 
 /// Represents a batch of async tests that can run in parallel.
 /// All tests in a batch share the same event loop scope (class, module, or session).
@@ -57,6 +67,14 @@ fn is_async_test(py: Python<'_>, test_case: &TestCase) -> bool {
         .unwrap_or(false)
 }
 
+/// Whether the test reads its own output through `capsys` or `capfd`, which
+/// needs the per-test capture of a sequential run.
+fn reads_captured_output(test: &TestCase) -> bool {
+    test.parameters
+        .iter()
+        .any(|name| name == "capsys" || name == "capfd")
+}
+
 /// Partition tests into execution units for optimal async parallelization.
 ///
 /// Tests are grouped based on their loop scope:
@@ -92,8 +110,17 @@ fn partition_tests_for_parallel<'a>(
         let is_async = is_async_test(py, test);
         let loop_scope = determine_test_loop_scope(py, test, fixtures, config);
 
-        // Only batch async tests with non-function loop scope
-        let can_batch = is_async && loop_scope > FixtureScope::Function;
+        // Only batch async tests with non-function loop scope. Line tracing
+        // and asyncio debug attribute to one test at a time, flaky tests
+        // rerun one at a time, capsys reads per-test capture, and exclusive
+        // tests must not overlap another test, so those run singly.
+        let can_batch = is_async
+            && loop_scope > FixtureScope::Function
+            && !config.line_trace
+            && !config.asyncio_debug
+            && flaky_reruns(py, &test.marks).is_none()
+            && !reads_captured_output(test)
+            && test.exclusive_resources(py).is_empty();
 
         if can_batch {
             match &mut current_batch {
@@ -172,6 +199,61 @@ thread_local! {
     static ACTIVE_RESOLVER: RefCell<Vec<*mut c_void>> = const { RefCell::new(Vec::new()) };
 }
 
+// Fixture setups and teardowns performed in the current run, reported on
+// `PyRunReport` to spot fixtures rebuilt more often than their scope implies.
+thread_local! {
+    static FIXTURE_SETUPS: Cell<usize> = const { Cell::new(0) };
+    static FIXTURE_TEARDOWNS: Cell<usize> = const { Cell::new(0) };
+}
+
+// Warnings recorded per test during the run, as (node id, message): asyncio
+// warnings under `asyncio_debug`, a loop scope widened with
+// `loop_scope_strict` off, and tests that return a value.
+thread_local! {
+    static TEST_WARNINGS: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Fixture teardowns that raised during the run, as (fixture name, scope,
+// error). They are formatted once the run finishes, where the configuration
+// that controls traceback filtering is at hand.
+thread_local! {
+    static TEARDOWN_ERRORS: RefCell<Vec<(String, FixtureScope, PyErr)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn reset_fixture_counts() {
+    FIXTURE_SETUPS.with(|count| count.set(0));
+    FIXTURE_TEARDOWNS.with(|count| count.set(0));
+}
+
+/// Record the fixture counts of the run on its report.
+fn attach_fixture_counts(report: &mut PyRunReport) {
+    report.fixture_setups = FIXTURE_SETUPS.with(Cell::get);
+    report.fixture_teardowns = FIXTURE_TEARDOWNS.with(Cell::get);
+}
+
+/// Move the warnings recorded during the run onto its report.
+fn attach_test_warnings(report: &mut PyRunReport) {
+    report.warnings = TEST_WARNINGS.with(|warnings| warnings.take());
+}
+
+/// Move the teardown errors recorded during the run onto its report.
+fn attach_teardown_errors(py: Python<'_>, report: &mut PyRunReport, config: &RunConfiguration) {
+    report.teardown_errors = TEARDOWN_ERRORS
+        .with(|errors| errors.take())
+        .into_iter()
+        .map(|(fixture, scope, err)| TeardownError {
+            fixture,
+            scope: scope.as_str().to_string(),
+            message: format_pyerr(py, &err, config).unwrap_or_else(|_| err.to_string()),
+        })
+        .collect();
+}
+
+/// Record a warning attributed to the test `node_id`.
+fn record_test_warning(node_id: &str, message: String) {
+    TEST_WARNINGS.with(|warnings| warnings.borrow_mut().push((node_id.to_string(), message)));
+}
+
 struct ResolverActivationGuard {
     // Store the pointer to verify we pop the correct one
     ptr: *mut c_void,
@@ -228,12 +310,42 @@ pub(crate) fn resolve_fixture_for_request(name: &str) -> PyResult<Py<PyAny>> {
     })
 }
 
+/// Why a fixture is set up for a test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FixtureOrigin {
+    Autouse,
+    Usefixtures,
+    Signature,
+}
+
+/// A fixture that failed while setting up a test.
+struct FixtureSetupError {
+    origin: FixtureOrigin,
+    name: String,
+    error: PyErr,
+}
+
+impl FixtureSetupError {
+    /// First line of the setup error reported for the test.
+    fn heading(&self) -> String {
+        match self.origin {
+            FixtureOrigin::Autouse => "Autouse fixture setup error:".to_string(),
+            FixtureOrigin::Usefixtures => "Usefixtures mark error:".to_string(),
+            FixtureOrigin::Signature => format!("Fixture '{}' resolution error:", self.name),
+        }
+    }
+}
+
 /// Manages teardown for generator fixtures across different scopes.
 struct TeardownCollector {
     session: Vec<Py<PyAny>>,
     package: Vec<Py<PyAny>>,
     module: Vec<Py<PyAny>>,
     class: Vec<Py<PyAny>>,
+    /// Generators of parametrized session fixtures keyed by cache key
+    /// (`name[idx]`), so a value can be finalized as soon as no remaining
+    /// test needs it instead of at session end.
+    session_params: HashMap<String, Py<PyAny>>,
 }
 
 impl TeardownCollector {
@@ -243,6 +355,7 @@ impl TeardownCollector {
             package: Vec::new(),
             module: Vec::new(),
             class: Vec::new(),
+            session_params: HashMap::new(),
         }
     }
 }
@@ -288,7 +401,7 @@ impl FixtureContext {
             FixtureScope::Session => (&mut self.teardowns.session, &mut self.session_event_loop),
             FixtureScope::Function => return,
         };
-        finalize_generators(py, teardowns, event_loop.as_ref());
+        finalize_generators(py, teardowns, scope, event_loop.as_ref());
         close_event_loop(py, event_loop);
     }
 
@@ -317,11 +430,28 @@ impl FixtureContext {
             ),
             FixtureScope::Function => return,
         };
-        finalize_generators(py, teardowns, event_loop.as_ref());
+        finalize_generators(py, teardowns, scope, event_loop.as_ref());
         cache.clear();
         close_event_loop(py, event_loop);
     }
 
+    /// Tear down one value of a parametrized session fixture and drop it
+    /// from the cache, leaving the rest of the session untouched.
+    fn release_session_param(&mut self, py: Python<'_>, cache_key: &str) {
+        self.session_cache.shift_remove(cache_key);
+        if let Some(generator) = self.teardowns.session_params.remove(cache_key) {
+            self.teardowns
+                .session
+                .retain(|pending| !pending.bind(py).is(generator.bind(py)));
+            finalize_generators(
+                py,
+                &mut vec![generator],
+                FixtureScope::Session,
+                self.session_event_loop.as_ref(),
+            );
+        }
+    }
+
     /// Clean up all scopes from narrowest to widest.
     fn cleanup_all(&mut self, py: Python<'_>) {
         for scope in [
@@ -339,49 +469,228 @@ impl FixtureContext {
 /// high-level summary information.
 pub fn run_collected_tests(
     py: Python<'_>,
-    modules: &[TestModule],
-    collection_errors: &[CollectionError],
+    discovery: &Discovery,
     config: &RunConfiguration,
 ) -> PyResult<PyRunReport> {
+    let modules = discovery.modules.as_slice();
+    let collection_errors = discovery.collection_errors.as_slice();
     let start = Instant::now();
     let mut results = Vec::new();
-    let mut passed = 0;
-    let mut failed = 0;
-    let mut skipped = 0;
+    let mut counts = OutcomeCounts::default();
+
+    // Custom policy applies to every loop created below; the guard restores the old one
+    let _policy_guard = EventLoopPolicyGuard::install(py, config.event_loop_policy.as_ref())?;
+    configure_log_capture(py, config.log_level.as_deref())?;
 
     // Create output renderer based on configuration
     let output_config = OutputConfig::from_run_config(config);
     let mut renderer: Box<dyn OutputRenderer> = if let Some(ref callback) = config.event_callback {
         // Use event stream renderer when callback is provided
         let callback_clone = callback.clone_ref(py);
-        Box::new(EventStreamRenderer::new(Some(callback_clone)))
+        Box::new(EventStreamRenderer::new(
+            Some(callback_clone),
+            output_config.github_annotations,
+            output_config.name_width,
+        ))
+    } else if matches!(output_config.mode, OutputMode::Compact) {
+        Box::new(CompactDisplay::new(
+            output_config.use_colors,
+            output_config.name_width,
+            output_config.symbols.clone(),
+            output_config.github_annotations,
+            output_config.wrap_width,
+            output_config.group_failures,
+            output_config.collapse_param_failures,
+        ))
     } else {
         // Fall back to default spinner display
         Box::new(SpinnerDisplay::new(
             output_config.use_colors,
             output_config.ascii_mode,
+            output_config.name_width,
+            output_config.symbols.clone(),
+            output_config.github_annotations,
+            output_config.wrap_width,
+            output_config.verbose,
+            output_config.group_failures,
+            output_config.show_test_fixtures,
+            output_config.collapse_param_failures,
         ))
     };
+    if let Some(ref junit_xml_path) = config.junit_xml_path {
+        renderer = Box::new(JUnitXmlRenderer::new(renderer, junit_xml_path.clone()));
+    }
 
     // Display collection errors before running tests (like pytest does)
     for error in collection_errors {
         renderer.collection_error(error);
     }
 
+    // With --no-continue-on-collection-errors, any collection error aborts the run
+    if !config.continue_on_collection_errors && !collection_errors.is_empty() {
+        let count = collection_errors.len();
+        renderer.println(&format!(
+            "Interrupted: {} {} during collection",
+            count,
+            if count == 1 { "error" } else { "errors" }
+        ));
+        let duration = start.elapsed();
+        renderer.finish_suite(0, 0, 0, 0, count, 0, 0, duration);
+        let mut report = PyRunReport::new(
+            0,
+            0,
+            0,
+            0,
+            0,
+            duration.as_secs_f64(),
+            Vec::new(),
+            collection_errors.to_vec(),
+        );
+        report.artifacts = renderer.written_files();
+        return Ok(report);
+    }
+
+    // With --check, validate the fixture graph and stop before executing anything
+    if config.check {
+        let problems = check_fixture_graph(py, modules, config.loop_scope_strict);
+        for problem in &problems {
+            renderer.collection_error(problem);
+        }
+        let total_tests = discovery.test_count();
+        renderer.println(&if problems.is_empty() {
+            format!("Fixture check passed for {} tests", total_tests)
+        } else {
+            format!(
+                "Fixture check found {} {}",
+                problems.len(),
+                if problems.len() == 1 {
+                    "problem"
+                } else {
+                    "problems"
+                }
+            )
+        });
+
+        let mut all_errors = collection_errors.to_vec();
+        all_errors.extend(problems);
+        let duration = start.elapsed();
+        renderer.finish_suite(0, 0, 0, 0, all_errors.len(), 0, 0, duration);
+        let mut report = PyRunReport::new(
+            0,
+            0,
+            0,
+            0,
+            0,
+            duration.as_secs_f64(),
+            Vec::new(),
+            all_errors,
+        );
+        report.artifacts = renderer.written_files();
+        return Ok(report);
+    }
+
+    // With several worker processes, each runs a shard of whole modules
+    if config.worker_processes > 1 && modules.len() > 1 {
+        match config.in_process_option() {
+            Some(option) => renderer.println(&format!(
+                "{} needs every test in one process; running in-process instead of in {} workers",
+                option, config.worker_processes
+            )),
+            None => {
+                return run_in_worker_processes(
+                    py,
+                    modules,
+                    collection_errors,
+                    config,
+                    renderer.as_mut(),
+                    start,
+                )
+            }
+        }
+    }
+
+    // Execution groups tests by class and batches async tests, so remember
+    // where each result belongs to report them in discovery order
+    let discovery_positions: HashMap<String, usize> = modules
+        .iter()
+        .flat_map(|module| module.tests.iter())
+        .enumerate()
+        .map(|(position, test)| (test.unique_id(), position))
+        .collect();
+    let position_of = |test: &TestCase| {
+        discovery_positions
+            .get(&test.unique_id())
+            .copied()
+            .unwrap_or(usize::MAX)
+    };
+    let mut result_positions: Vec<usize> = Vec::new();
+
+    // With --resume, tests an interrupted run already recorded aren't run again
+    let mut resumed = if config.resume {
+        cache::read_journal(&config.journal_path)?
+    } else {
+        IndexMap::new()
+    };
+    resumed.retain(|node_id, _| discovery_positions.contains_key(node_id));
+    // A worker session leaves the journal to the run that started it
+    let mut journal =
+        (!config.worker).then(|| cache::Journal::start(&config.journal_path, config.resume));
+    let mut sqlite = config
+        .sqlite_path
+        .as_deref()
+        .map(SqliteRecorder::open)
+        .transpose()?;
+    reset_fixture_counts();
+    if config.trace_json.is_some() {
+        start_trace();
+    }
+    TEST_WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+    TEARDOWN_ERRORS.with(|errors| errors.borrow_mut().clear());
+    if config.line_trace {
+        py.import("rustest.line_trace")?.call_method0("reset")?;
+    }
+
     // Calculate totals for progress tracking
     let total_files = modules.len();
-    let total_tests: usize = modules.iter().map(|m| m.tests.len()).sum();
-    renderer.start_suite(total_files, total_tests);
+    let total_tests = discovery.test_count() - resumed.len();
+    let header = SessionHeader::detect(py, modules.first().map(|module| module.path.as_path()));
+    renderer.start_suite(total_files, total_tests, &header);
 
     // Fixture context lives for the entire test run
     let mut context = FixtureContext::new();
+    let session_param_releases = session_param_last_uses(modules);
+
+    // Merge the outcomes recorded by the interrupted run into this report
+    let resumed_ids: HashSet<String> = resumed.keys().cloned().collect();
+    if !resumed.is_empty() {
+        renderer.println(&format!(
+            "Resuming: {} {} already recorded in the journal",
+            resumed.len(),
+            if resumed.len() == 1 { "test" } else { "tests" }
+        ));
+    }
+    for (node_id, entry) in resumed {
+        let result = entry.into_result();
+        counts.record(&result.status);
+        results.push(result);
+        result_positions.push(discovery_positions[&node_id]);
+    }
+
+    let mut file_durations = Vec::new();
+    let mut interrupted = false;
+    'run: for module in modules.iter() {
+        let module_tests: Vec<&TestCase> = module
+            .tests
+            .iter()
+            .filter(|test| !resumed_ids.contains(&test.unique_id()))
+            .collect();
+        if module_tests.is_empty() {
+            continue;
+        }
 
-    for module in modules.iter() {
         // Track per-file statistics
         let file_start = Instant::now();
-        let mut file_passed = 0;
-        let mut file_failed = 0;
-        let mut file_skipped = 0;
+        let mut file_counts = OutcomeCounts::default();
 
         // Notify renderer that this file is starting
         renderer.start_file(module);
@@ -403,15 +712,7 @@ pub fn run_collected_tests(
         close_event_loop(py, &mut context.module_event_loop);
 
         // Group tests by class for class-scoped fixtures
-        let mut tests_by_class: IndexMap<Option<String>, Vec<&TestCase>> = IndexMap::new();
-        for test in module.tests.iter() {
-            tests_by_class
-                .entry(test.class_name.clone())
-                .or_default()
-                .push(test);
-        }
-
-        for (_class_name, tests) in tests_by_class {
+        for (_class_name, tests) in group_tests_by_class(module_tests) {
             // Reset class-scoped cache for this class
             context.class_cache.clear();
 
@@ -420,57 +721,83 @@ pub fn run_collected_tests(
                 partition_tests_for_parallel(py, &tests, &module.fixtures, config);
 
             for unit in execution_units {
-                let (unit_results, is_plain_function_test): (Vec<PyTestResult>, bool) = match unit {
-                    TestExecutionUnit::Single(test) => {
-                        let result = run_single_test(py, module, test, config, &mut context)?;
-                        let is_plain = test.class_name.is_none();
-                        (vec![result], is_plain)
-                    }
+                let unit_test_ids: Vec<String> = match &unit {
+                    TestExecutionUnit::Single(test) => vec![test.unique_id()],
                     TestExecutionUnit::Batch(batch) => {
-                        let batch_results =
-                            run_async_batch(py, module, &batch, config, &mut context)?;
-                        // For batches, check if any test is a plain function test
-                        let any_plain = batch.tests.iter().any(|t| t.class_name.is_none());
-                        (
-                            batch_results.into_iter().map(|(_, r)| r).collect(),
-                            any_plain,
-                        )
+                        batch.tests.iter().map(|test| test.unique_id()).collect()
                     }
                 };
-
-                let mut should_fail_fast = false;
-
-                for result in unit_results {
-                    let is_failed = result.status == "failed";
-
-                    // Update global and per-file counters
-                    match result.status.as_str() {
-                        "passed" => {
-                            passed += 1;
-                            file_passed += 1;
-                        }
-                        "failed" => {
-                            failed += 1;
-                            file_failed += 1;
+                let (unit_results, is_plain_function_test): (Vec<(usize, PyTestResult)>, bool) =
+                    match unit {
+                        TestExecutionUnit::Single(test) => {
+                            let Some(result) = unless_interrupted(
+                                py,
+                                run_single_test(py, module, test, config, &mut context),
+                            )?
+                            else {
+                                interrupted = true;
+                                break 'run;
+                            };
+                            let is_plain = test.class_name.is_none();
+                            (vec![(position_of(test), result)], is_plain)
                         }
-                        "skipped" => {
-                            skipped += 1;
-                            file_skipped += 1;
+                        TestExecutionUnit::Batch(batch) => {
+                            let Some(batch_results) = unless_interrupted(
+                                py,
+                                run_async_batch(
+                                    py,
+                                    module,
+                                    &batch,
+                                    config,
+                                    &mut context,
+                                    config
+                                        .failure_limit()
+                                        .map(|limit| limit.saturating_sub(counts.failures())),
+                                ),
+                            )?
+                            else {
+                                interrupted = true;
+                                break 'run;
+                            };
+                            // For batches, check if any test is a plain function test
+                            let any_plain = batch.tests.iter().any(|t| t.class_name.is_none());
+                            (
+                                batch_results
+                                    .into_iter()
+                                    .map(|(test, r)| (position_of(test), r))
+                                    .collect(),
+                                any_plain,
+                            )
                         }
-                        _ => {
-                            failed += 1;
-                            file_failed += 1;
-                        }
-                    }
+                    };
+
+                for (position, result) in unit_results {
+                    // Update global and per-file counters
+                    counts.record(&result.status);
+                    file_counts.record(&result.status);
 
                     // Notify renderer of test completion
                     renderer.test_completed(&result);
+                    if let Some(journal) = journal.as_mut() {
+                        journal.record(&result);
+                    }
+                    if let Some(sqlite) = sqlite.as_mut() {
+                        sqlite.record(&result);
+                    }
 
                     results.push(result);
-
-                    // Check for fail-fast mode
-                    if config.fail_fast && is_failed {
-                        should_fail_fast = true;
+                    result_positions.push(position);
+                }
+                let should_fail_fast = config
+                    .failure_limit()
+                    .is_some_and(|limit| counts.failures() >= limit);
+
+                // Finalize parametrized session fixture values no later test needs
+                for test_id in &unit_test_ids {
+                    if let Some(cache_keys) = session_param_releases.get(test_id) {
+                        for cache_key in cache_keys {
+                            context.release_session_param(py, cache_key);
+                        }
                     }
                 }
 
@@ -481,6 +808,7 @@ pub fn run_collected_tests(
                     finalize_generators(
                         py,
                         &mut context.teardowns.class,
+                        FixtureScope::Class,
                         context.class_event_loop.as_ref(),
                     );
                 }
@@ -489,39 +817,31 @@ pub fn run_collected_tests(
                 if should_fail_fast {
                     // Clean up fixtures before returning early
                     context.cleanup_all(py);
-
-                    let duration = start.elapsed();
-                    let total = passed + failed + skipped;
-
-                    // Notify renderer of early exit
-                    renderer.finish_suite(
-                        total,
-                        passed,
-                        failed,
-                        skipped,
-                        collection_errors.len(),
-                        duration,
-                    );
-
-                    let report = PyRunReport::new(
-                        total,
-                        passed,
-                        failed,
-                        skipped,
-                        duration.as_secs_f64(),
-                        results,
-                        collection_errors.to_vec(),
+                    let outcome = RunOutcome {
+                        counts,
+                        results: in_discovery_order(results, result_positions),
+                        file_durations,
+                        duration: start.elapsed(),
+                        interrupted: false,
+                        teardown_errors: Vec::new(),
+                    };
+                    return finish_run(
+                        py,
+                        config,
+                        renderer.as_mut(),
+                        collection_errors,
+                        outcome,
+                        journal,
+                        sqlite,
                     );
-
-                    // Write cache before returning
-                    write_failed_tests_cache(&report)?;
-
-                    return Ok(report);
                 }
 
                 // Check for signals (like Ctrl+C) after each execution unit
                 // This allows users to interrupt test runs with KeyboardInterrupt
-                py.check_signals()?;
+                if unless_interrupted(py, py.check_signals())?.is_none() {
+                    interrupted = true;
+                    break 'run;
+                }
             }
 
             // Class-scoped fixtures are dropped here - run teardowns
@@ -532,59 +852,493 @@ pub fn run_collected_tests(
         finalize_generators(
             py,
             &mut context.teardowns.module,
+            FixtureScope::Module,
             context.module_event_loop.as_ref(),
         );
 
+        // Write this file's results in one batch
+        if let Some(sqlite) = sqlite.as_mut() {
+            sqlite.flush();
+        }
+
         // Notify renderer that this file is complete
-        let file_duration = file_start.elapsed();
-        renderer.file_completed(
-            &to_relative_path(&module.path),
-            file_duration,
-            file_passed,
-            file_failed,
-            file_skipped,
+        finish_file(
+            renderer.as_mut(),
+            &mut file_durations,
+            module,
+            file_start.elapsed(),
+            &file_counts,
         );
 
         // Check for signals (like Ctrl+C) after each file/module
         // This allows users to interrupt test runs with KeyboardInterrupt
-        py.check_signals()?;
+        if unless_interrupted(py, py.check_signals())?.is_none() {
+            interrupted = true;
+            break;
+        }
+    }
+
+    if interrupted {
+        // Stopped mid-file: tear down every scope that is still open
+        context.cleanup_all(py);
+    } else {
+        // Package-scoped fixtures are dropped here - run teardowns for last package
+        context.teardown_scope(py, FixtureScope::Package);
+
+        // Session-scoped fixtures are dropped here - run teardowns
+        context.teardown_scope(py, FixtureScope::Session);
+    }
+
+    if interrupted {
+        let expected = total_tests + resumed_ids.len();
+        renderer.println(&format!(
+            "Interrupted: {} of {} {} ran before KeyboardInterrupt",
+            counts.total(),
+            expected,
+            if expected == 1 { "test" } else { "tests" }
+        ));
+    }
+
+    let outcome = RunOutcome {
+        counts,
+        results: in_discovery_order(results, result_positions),
+        file_durations,
+        duration: start.elapsed(),
+        interrupted,
+        teardown_errors: Vec::new(),
+    };
+    finish_run(
+        py,
+        config,
+        renderer.as_mut(),
+        collection_errors,
+        outcome,
+        journal,
+        sqlite,
+    )
+}
+
+/// Number of tests that ended with each status.
+#[derive(Clone, Copy, Default)]
+struct OutcomeCounts {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    errors: usize,
+    xfailed: usize,
+    xpassed: usize,
+}
+
+impl OutcomeCounts {
+    fn record(&mut self, status: &str) {
+        match status {
+            "passed" => self.passed += 1,
+            "skipped" => self.skipped += 1,
+            "xfailed" => self.xfailed += 1,
+            "xpassed" => self.xpassed += 1,
+            "error" => self.errors += 1,
+            _ => self.failed += 1,
+        }
     }
 
-    // Package-scoped fixtures are dropped here - run teardowns for last package
-    context.teardown_scope(py, FixtureScope::Package);
+    fn total(&self) -> usize {
+        self.passed + self.failed + self.skipped + self.errors + self.xfailed + self.xpassed
+    }
 
-    // Session-scoped fixtures are dropped here - run teardowns
-    context.teardown_scope(py, FixtureScope::Session);
+    /// Failed and erroring tests, the ones `--maxfail` counts.
+    fn failures(&self) -> usize {
+        self.failed + self.errors
+    }
+}
 
-    let duration = start.elapsed();
-    let total = passed + failed + skipped;
+/// Report a finished file to the renderer and record its duration. Per file,
+/// xpassed tests count as passed and xfailed tests as skipped.
+fn finish_file(
+    renderer: &mut dyn OutputRenderer,
+    file_durations: &mut Vec<(String, f64)>,
+    module: &TestModule,
+    duration: Duration,
+    counts: &OutcomeCounts,
+) {
+    let path = to_relative_path(&module.path);
+    renderer.file_completed(
+        &path,
+        duration,
+        counts.passed + counts.xpassed,
+        counts.failures(),
+        counts.skipped + counts.xfailed,
+    );
+    file_durations.push((path, duration.as_secs_f64()));
+}
+
+/// What a run produced, before it is reported.
+struct RunOutcome {
+    counts: OutcomeCounts,
+    /// Results in discovery order
+    results: Vec<PyTestResult>,
+    file_durations: Vec<(String, f64)>,
+    duration: Duration,
+    interrupted: bool,
+    /// Teardown errors reported by worker processes; this process's own are
+    /// still in `TEARDOWN_ERRORS`
+    teardown_errors: Vec<TeardownError>,
+}
 
-    // Notify renderer that the entire suite is complete
+/// Finish the suite on the renderer, build the run report, and write the
+/// caches and report files the configuration asks for. In-process and
+/// worker-process runs both end here.
+fn finish_run(
+    py: Python<'_>,
+    config: &RunConfiguration,
+    renderer: &mut dyn OutputRenderer,
+    collection_errors: &[CollectionError],
+    outcome: RunOutcome,
+    journal: Option<cache::Journal>,
+    sqlite: Option<SqliteRecorder>,
+) -> PyResult<PyRunReport> {
+    let RunOutcome {
+        counts,
+        results,
+        file_durations,
+        duration,
+        interrupted,
+        teardown_errors,
+    } = outcome;
     renderer.finish_suite(
-        total,
-        passed,
-        failed,
-        skipped,
-        collection_errors.len(),
+        counts.total(),
+        counts.passed,
+        counts.failed,
+        counts.skipped,
+        counts.errors + collection_errors.len(),
+        counts.xfailed,
+        counts.xpassed,
         duration,
     );
 
-    let report = PyRunReport::new(
-        total,
-        passed,
-        failed,
-        skipped,
+    let mut report = PyRunReport::new(
+        counts.total(),
+        counts.passed,
+        counts.failed,
+        counts.skipped,
+        counts.errors,
         duration.as_secs_f64(),
         results,
         collection_errors.to_vec(),
     );
+    report.artifacts = renderer.written_files();
+    attach_fixture_counts(&mut report);
+    attach_test_warnings(&mut report);
+    report.file_durations = file_durations;
+    report.interrupted = interrupted;
+    attach_teardown_errors(py, &mut report, config);
+    report.teardown_errors.extend(teardown_errors);
+    if !report.warnings.is_empty() {
+        renderer.println(&format_warnings_summary(&report.warnings));
+    }
+    if !report.teardown_errors.is_empty() {
+        renderer.println(&format_teardown_errors(&report.teardown_errors));
+    }
+    if let Some(count) = config.slowest_files {
+        renderer.println(&format_slowest_files(&report.file_durations, count));
+    }
+    if let Some(count) = config.durations {
+        renderer.println(&format_slowest_tests(&report.results, count));
+    }
 
-    // Write cache after all tests complete
-    write_failed_tests_cache(&report)?;
+    if !config.worker {
+        write_failed_tests_cache(&report)?;
+    }
+    // Keep the journal of an interrupted run so --resume can pick it up
+    if let Some(journal) = journal.filter(|_| !interrupted) {
+        journal.finish();
+    }
+    if let Some(sqlite) = sqlite {
+        sqlite.finish();
+        report.artifacts.extend(config.sqlite_path.clone());
+    }
+    if let Some(ref tap_output) = config.tap_output {
+        write_tap_report(&report.results, tap_output)?;
+        report.artifacts.push(tap_output.clone());
+    }
+    if let Some(ref json_report_path) = config.json_report_path {
+        write_json_report(&report, json_report_path)?;
+        report.artifacts.push(json_report_path.clone());
+    }
+    if let Some(ref trace_json) = config.trace_json {
+        write_chrome_trace(trace_json)?;
+        report.artifacts.push(trace_json.clone());
+    }
+    if config.line_trace {
+        write_line_trace(py, &config.line_trace_path)?;
+    }
 
     Ok(report)
 }
 
+/// Run `modules` in `config.worker_processes` subprocesses and merge what
+/// they report into one run report.
+///
+/// Modules are split into one shard per worker, balanced by test count, and
+/// modules sharing an `@mark.exclusive` resource are kept in one shard. Each
+/// worker runs its shard as a single session: session-scoped fixtures
+/// are set up once per worker, not once per run. Results are rendered per
+/// file after the workers finish, in discovery order.
+fn run_in_worker_processes(
+    py: Python<'_>,
+    modules: &[TestModule],
+    collection_errors: &[CollectionError],
+    config: &RunConfiguration,
+    renderer: &mut dyn OutputRenderer,
+    start: Instant,
+) -> PyResult<PyRunReport> {
+    let sizes: Vec<usize> = modules.iter().map(|module| module.tests.len()).collect();
+    let resources: Vec<Vec<String>> = modules
+        .iter()
+        .map(|module| {
+            module
+                .tests
+                .iter()
+                .flat_map(|test| test.exclusive_resources(py))
+                .collect()
+        })
+        .collect();
+    let shards = shard_exclusive(&sizes, &resources, config.worker_processes);
+    let batches: Vec<Vec<String>> = shards
+        .iter()
+        .map(|shard| {
+            shard
+                .iter()
+                .flat_map(|&index| modules[index].tests.iter().map(TestCase::unique_id))
+                .collect()
+        })
+        .collect();
+
+    let header = SessionHeader::detect(py, modules.first().map(|module| module.path.as_path()));
+    renderer.start_suite(modules.len(), sizes.iter().sum(), &header);
+
+    let pool =
+        WorkerPool::new(worker_command(py)?, shards.len()).with_options(worker_options(config));
+    let responses = py.detach(|| pool.run(batches));
+
+    // Record what the workers' sessions reported as if they ran here
+    reset_fixture_counts();
+    TEST_WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+    TEARDOWN_ERRORS.with(|errors| errors.borrow_mut().clear());
+    let mut reported: HashMap<String, WorkerResult> = HashMap::new();
+    let mut teardown_errors = Vec::new();
+    for response in responses {
+        FIXTURE_SETUPS.with(|count| count.set(count.get() + response.fixture_setups));
+        FIXTURE_TEARDOWNS.with(|count| count.set(count.get() + response.fixture_teardowns));
+        TEST_WARNINGS.with(|warnings| warnings.borrow_mut().extend(response.warnings));
+        teardown_errors.extend(response.teardown_errors.into_iter().map(
+            |(fixture, scope, message)| TeardownError {
+                fixture,
+                scope,
+                message,
+            },
+        ));
+        reported.extend(
+            response
+                .results
+                .into_iter()
+                .map(|result| (result.node_id.clone(), result)),
+        );
+    }
+
+    let mut journal = Some(cache::Journal::start(&config.journal_path, false));
+    let mut sqlite = config
+        .sqlite_path
+        .as_deref()
+        .map(SqliteRecorder::open)
+        .transpose()?;
+    let mut results = Vec::new();
+    let mut file_durations = Vec::new();
+    let mut counts = OutcomeCounts::default();
+
+    for module in modules {
+        renderer.start_file(module);
+        let mut file_counts = OutcomeCounts::default();
+        let mut file_duration = 0.0;
+
+        for test in &module.tests {
+            let result = worker_test_result(test, reported.remove(&test.unique_id()));
+            counts.record(&result.status);
+            file_counts.record(&result.status);
+            file_duration += result.duration;
+            renderer.test_completed(&result);
+            if let Some(journal) = journal.as_mut() {
+                journal.record(&result);
+            }
+            if let Some(sqlite) = sqlite.as_mut() {
+                sqlite.record(&result);
+            }
+            results.push(result);
+        }
+
+        if let Some(sqlite) = sqlite.as_mut() {
+            sqlite.flush();
+        }
+        finish_file(
+            renderer,
+            &mut file_durations,
+            module,
+            Duration::from_secs_f64(file_duration),
+            &file_counts,
+        );
+    }
+
+    let outcome = RunOutcome {
+        counts,
+        results,
+        file_durations,
+        duration: start.elapsed(),
+        interrupted: false,
+        teardown_errors,
+    };
+    finish_run(
+        py,
+        config,
+        renderer,
+        collection_errors,
+        outcome,
+        journal,
+        sqlite,
+    )
+}
+
+/// `python -m rustest.worker` with this interpreter and its import path.
+fn worker_command(py: Python<'_>) -> PyResult<WorkerCommand> {
+    let sys = py.import("sys")?;
+    let executable: String = sys.getattr("executable")?.extract()?;
+    let sys_path: Vec<String> = sys
+        .getattr("path")?
+        .try_iter()?
+        .filter_map(|entry| entry.ok()?.extract::<String>().ok())
+        .filter(|entry| !entry.is_empty())
+        .collect();
+
+    let mut command = WorkerCommand::python(executable);
+    if let Ok(python_path) = std::env::join_paths(sys_path) {
+        command.env.push((
+            "PYTHONPATH".to_string(),
+            python_path.to_string_lossy().into_owned(),
+        ));
+    }
+    Ok(command)
+}
+
+/// Settings each worker's `run` call needs to execute tests like this process.
+///
+/// Selection options are left out: selection already happened here, and
+/// workers run exactly the node ids they are sent.
+fn worker_options(config: &RunConfiguration) -> Map<String, Value> {
+    Map::from_iter([
+        (
+            "capture_output".to_string(),
+            Value::Bool(config.capture_output),
+        ),
+        (
+            "enable_codeblocks".to_string(),
+            Value::Bool(config.enable_codeblocks),
+        ),
+        (
+            "pytest_compat".to_string(),
+            Value::Bool(config.pytest_compat),
+        ),
+        ("notebooks".to_string(), Value::Bool(config.notebooks)),
+        (
+            "loop_scope_strict".to_string(),
+            Value::Bool(config.loop_scope_strict),
+        ),
+        (
+            "filter_traceback".to_string(),
+            Value::Bool(config.filter_traceback),
+        ),
+        (
+            "asyncio_debug".to_string(),
+            Value::Bool(config.asyncio_debug),
+        ),
+        (
+            "xfail_list".to_string(),
+            config
+                .xfail_list
+                .as_ref()
+                .map_or(Value::Null, |path| Value::from(path.to_string_lossy())),
+        ),
+        ("run_skipped".to_string(), Value::Bool(config.run_skipped)),
+        (
+            "failure_exceptions".to_string(),
+            config
+                .failure_exceptions
+                .as_ref()
+                .map_or(Value::Null, |names| Value::from(names.clone())),
+        ),
+        (
+            "log_level".to_string(),
+            config.log_level.as_deref().map_or(Value::Null, Value::from),
+        ),
+        (
+            "default_test_loop_scope".to_string(),
+            Value::from(config.default_test_loop_scope.as_str()),
+        ),
+        (
+            "default_fixture_loop_scope".to_string(),
+            Value::from(config.default_fixture_loop_scope.as_str()),
+        ),
+        (
+            "import_timeout".to_string(),
+            config
+                .import_timeout
+                .map_or(Value::Null, |timeout| Value::from(timeout.as_secs_f64())),
+        ),
+    ])
+}
+
+/// Turn what a worker reported for `test` into a result. A test the worker
+/// never reported becomes an error rather than silently disappearing.
+fn worker_test_result(test: &TestCase, reported: Option<WorkerResult>) -> PyTestResult {
+    let name = test.display_name.clone();
+    let path = to_relative_path(&test.path);
+    let Some(reported) = reported else {
+        return PyTestResult::error(
+            name,
+            path,
+            0.0,
+            "Worker process did not report a result for this test".to_string(),
+            None,
+            None,
+            test.mark_names(),
+        );
+    };
+
+    let mut result = PyTestResult::passed(
+        name,
+        path,
+        reported.duration,
+        reported.stdout,
+        reported.stderr,
+        test.mark_names(),
+    );
+    result.status = reported.status;
+    result.message = reported.message;
+    result.fixtures = reported.fixtures;
+    if result.status != "skipped" {
+        // The worker only runs a test with a skip reason under `run_skipped`
+        result.was_skipped = test.skip_reason.clone();
+    }
+    result
+}
+
+/// Separate a `KeyboardInterrupt` from other errors; `Ok(None)` means the
+/// run was interrupted and should stop scheduling tests.
+fn unless_interrupted<T>(py: Python<'_>, result: PyResult<T>) -> PyResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.is_instance_of::<PyKeyboardInterrupt>(py) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
 /// Execute a single test case and convert the outcome into a [`PyTestResult`].
 fn run_single_test(
     py: Python<'_>,
@@ -593,7 +1347,11 @@ fn run_single_test(
     config: &RunConfiguration,
     context: &mut FixtureContext,
 ) -> PyResult<PyTestResult> {
-    if let Some(reason) = &test_case.skip_reason {
+    if let Some(reason) = test_case
+        .skip_reason
+        .as_ref()
+        .filter(|_| !config.run_skipped)
+    {
         return Ok(PyTestResult::skipped(
             test_case.display_name.clone(),
             to_relative_path(&test_case.path),
@@ -603,8 +1361,27 @@ fn run_single_test(
         ));
     }
 
+    // Each attempt resolves function-scoped fixtures afresh and tears them down
+    let flaky = flaky_reruns(py, &test_case.marks);
     let start = Instant::now();
-    let outcome = execute_test_case(py, module, test_case, config, context);
+    let mut reruns = 0;
+    let mut fixtures = Vec::new();
+    let outcome = loop {
+        let outcome = execute_test_case(py, module, test_case, config, context, &mut fixtures);
+        match (&outcome, flaky) {
+            (Err(failure), Some((max_reruns, delay)))
+                if reruns < max_reruns && is_rerunnable_failure(&failure.message) =>
+            {
+                reruns += 1;
+                if delay > 0.0 {
+                    py.detach(|| std::thread::sleep(Duration::from_secs_f64(delay)));
+                }
+            }
+            _ => break outcome,
+        }
+    };
+
+    let unexpected_exception = matches!(&outcome, Err(failure) if failure.unexpected_exception);
     let duration = start.elapsed().as_secs_f64();
     let name = test_case.display_name.clone();
     let path = to_relative_path(&test_case.path);
@@ -619,6 +1396,10 @@ fn run_single_test(
             test_case.mark_names(),
         ),
         Err(failure) => {
+            // Ctrl+C while the test body ran stops the run instead of failing the test
+            if is_keyboard_interrupt(&failure.message) {
+                return Err(PyKeyboardInterrupt::new_err(failure.message));
+            }
             // Check if this is a skip exception
             if is_skip_exception(&failure.message) {
                 let reason = extract_skip_reason(&failure.message);
@@ -626,12 +1407,17 @@ fn run_single_test(
             } else if is_xfail_exception(&failure.message) {
                 // Runtime xfail() call – treat as expected failure
                 let reason = extract_xfail_reason(&failure.message);
-                let xfail_reason = if reason.is_empty() {
-                    "[XFAIL]".to_string()
-                } else {
-                    format!("[XFAIL] {}", reason)
-                };
-                PyTestResult::skipped(name, path, duration, xfail_reason, test_case.mark_names())
+                PyTestResult::xfailed(name, path, duration, reason, test_case.mark_names())
+            } else if failure.setup_error {
+                PyTestResult::error(
+                    name,
+                    path,
+                    duration,
+                    failure.message,
+                    failure.stdout,
+                    failure.stderr,
+                    test_case.mark_names(),
+                )
             } else {
                 PyTestResult::failed(
                     name,
@@ -647,7 +1433,65 @@ fn run_single_test(
     };
 
     // Apply xfail mark semantics: convert expected failures to skips, etc.
-    Ok(apply_xfail(py, &test_case.marks, result))
+    let result = apply_xfail(py, &test_case.marks, result);
+    let mut result = apply_xfail_list(config, result);
+    if unexpected_exception && result.status == "failed" {
+        result.status = "error".to_string();
+    }
+    result.reruns = reruns;
+    result.was_skipped = test_case.skip_reason.clone();
+    result.fixtures = fixtures;
+    record_span(
+        &test_case.unique_id(),
+        "test",
+        start,
+        serde_json::json!({ "status": result.status }),
+    );
+    Ok(result)
+}
+
+/// Derive the `rng_seed` fixture value from a test's node id.
+///
+/// Uses 32-bit FNV-1a so the seed is stable across runs, platforms and Rust
+/// versions, and fits APIs such as `numpy.random.seed` that reject larger
+/// integers.
+fn rng_seed_for_node(node_id: &str) -> u32 {
+    node_id.bytes().fold(0x811c_9dc5, |hash: u32, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Rerun settings from a `@mark.flaky(reruns=N, reruns_delay=S)` mark.
+///
+/// Returns the maximum number of reruns and the delay in seconds between
+/// attempts. A bare `@mark.flaky` without `reruns` only labels the test.
+fn flaky_reruns(py: Python<'_>, marks: &[Mark]) -> Option<(usize, f64)> {
+    let mark = marks.iter().find(|mark| mark.is_named("flaky"))?;
+    let reruns = mark
+        .get_kwarg(py, "reruns")
+        .and_then(|v| v.bind(py).extract::<usize>().ok())
+        .filter(|&reruns| reruns > 0)?;
+    let delay = mark
+        .get_kwarg(py, "reruns_delay")
+        .and_then(|v| v.bind(py).extract::<f64>().ok())
+        .unwrap_or(0.0)
+        .max(0.0);
+    Some((reruns, delay))
+}
+
+/// Whether a failed attempt of a flaky test should be retried.
+///
+/// Skips, runtime xfails and Ctrl+C are deliberate outcomes, not flakes.
+fn is_rerunnable_failure(message: &str) -> bool {
+    !is_keyboard_interrupt(message) && !is_skip_exception(message) && !is_xfail_exception(message)
+}
+
+/// Check if an error message comes from a `KeyboardInterrupt`.
+fn is_keyboard_interrupt(message: &str) -> bool {
+    message.lines().any(|line| {
+        let trimmed = line.trim();
+        trimmed == "KeyboardInterrupt" || trimmed.starts_with("KeyboardInterrupt:")
+    })
 }
 
 /// Check if an error message indicates a skipped test.
@@ -741,34 +1585,28 @@ fn get_xfail_info(py: Python<'_>, marks: &[Mark]) -> Option<(bool, String, bool)
 /// Apply xfail semantics to a [`PyTestResult`].
 ///
 /// If the test has an active xfail mark:
-/// - A failure is converted into a skip (expected failure).
+/// - A failure is reported as `xfailed` (expected failure).
 /// - A pass with `strict=True` is converted into a failure (unexpected pass).
-/// - A non-strict pass is left as-is.
+/// - A non-strict pass is reported as `xpassed`.
 fn apply_xfail(py: Python<'_>, marks: &[Mark], result: PyTestResult) -> PyTestResult {
     if let Some((condition_met, reason, strict)) = get_xfail_info(py, marks) {
         if condition_met {
             match result.status.as_str() {
                 "failed" => {
-                    // Expected failure – report as skipped/xfail
-                    let xfail_reason = if reason.is_empty() {
-                        "[XFAIL]".to_string()
-                    } else {
-                        format!("[XFAIL] {}", reason)
-                    };
-                    return PyTestResult::skipped(
+                    return PyTestResult::xfailed(
                         result.name,
                         result.path,
                         result.duration,
-                        xfail_reason,
+                        reason,
                         result.marks,
                     );
                 }
                 "passed" if strict => {
                     // Unexpected pass with strict – this is a failure
                     let msg = if reason.is_empty() {
-                        "[XFAIL] Unexpected pass (strict xfail)".to_string()
+                        "[XPASS(strict)] Unexpected pass".to_string()
                     } else {
-                        format!("[XFAIL] Unexpected pass: {}", reason)
+                        format!("[XPASS(strict)] {}", reason)
                     };
                     return PyTestResult::failed(
                         result.name,
@@ -780,13 +1618,60 @@ fn apply_xfail(py: Python<'_>, marks: &[Mark], result: PyTestResult) -> PyTestRe
                         result.marks,
                     );
                 }
-                _ => { /* non-strict pass – leave as-is */ }
+                "passed" => {
+                    return PyTestResult::xpassed(
+                        result.name,
+                        result.path,
+                        result.duration,
+                        reason,
+                        result.stdout,
+                        result.stderr,
+                        result.marks,
+                    );
+                }
+                _ => {}
             }
         }
     }
     result
 }
 
+/// Apply the external expected-failures list (`--xfail-list`).
+///
+/// A listed test that fails is reported as `xfailed`. One that passes is
+/// reported as `xpassed` so the stale entry can be spotted and removed.
+fn apply_xfail_list(config: &RunConfiguration, result: PyTestResult) -> PyTestResult {
+    let Some(reason) = config.xfail_entries.get(&result.unique_id()) else {
+        return result;
+    };
+    let reason = reason.clone().unwrap_or_else(|| {
+        let list = config
+            .xfail_list
+            .as_deref()
+            .unwrap_or(Path::new("xfail list"));
+        format!("listed in {}", list.display())
+    });
+    match result.status.as_str() {
+        "failed" => PyTestResult::xfailed(
+            result.name,
+            result.path,
+            result.duration,
+            reason,
+            result.marks,
+        ),
+        "passed" => PyTestResult::xpassed(
+            result.name,
+            result.path,
+            result.duration,
+            reason,
+            result.stdout,
+            result.stderr,
+            result.marks,
+        ),
+        _ => result,
+    }
+}
+
 /// Run a batch of async tests in parallel using asyncio.gather().
 ///
 /// This function:
@@ -797,24 +1682,29 @@ fn apply_xfail(py: Python<'_>, marks: &[Mark], result: PyTestResult) -> PyTestRe
 /// 5. Returns results for each test
 ///
 /// Returns a vector of (test_case, result) tuples in the same order as input.
+/// `failure_budget` is how many more failures the run's failure limit allows.
 fn run_async_batch<'a>(
     py: Python<'_>,
     module: &TestModule,
     batch: &AsyncBatch<'a>,
     config: &RunConfiguration,
     context: &mut FixtureContext,
+    failure_budget: Option<usize>,
 ) -> PyResult<Vec<(&'a TestCase, PyTestResult)>> {
     let mut results: Vec<(&TestCase, PyTestResult)> = Vec::with_capacity(batch.tests.len());
 
-    // When fail-fast is enabled, fall back to sequential execution
-    // (fail-fast semantics require stopping on first failure)
+    // With a failure limit, fall back to sequential execution so the run
+    // stops at exactly the failure that reaches it
     // Note: Batches are guaranteed to have at least 2 tests by partition_tests_for_parallel
-    if config.fail_fast {
+    if let Some(budget) = failure_budget {
+        let mut failures = 0;
         for test in &batch.tests {
             let result = run_single_test(py, module, test, config, context)?;
-            let is_failed = result.status == "failed";
+            if result.status == "failed" || result.status == "error" {
+                failures += 1;
+            }
             results.push((test, result));
-            if is_failed {
+            if failures >= budget {
                 break;
             }
         }
@@ -838,7 +1728,13 @@ fn run_async_batch<'a>(
         let test_id = test.unique_id();
 
         // Validate loop scope compatibility
-        if let Some(error_message) = validate_loop_scope_compatibility(py, test, &module.fixtures) {
+        if !config.loop_scope_strict {
+            if let Some((explicit, required)) = loop_scope_mismatch(py, test, &module.fixtures) {
+                warn_loop_scope_widened(&test_id, explicit, required);
+            }
+        } else if let Some(error_message) =
+            validate_loop_scope_compatibility(py, test, &module.fixtures)
+        {
             preparation_errors.push((
                 test_id.clone(),
                 format!("Loop scope validation error:\n{}", error_message),
@@ -862,6 +1758,8 @@ fn run_async_batch<'a>(
             &mut context.teardowns,
             &test.fixture_param_indices,
             &test.indirect_params,
+            &test.param_scopes,
+            &test.excluded_autouse,
             &mut context.session_event_loop,
             &mut context.package_event_loop,
             &mut context.module_event_loop,
@@ -876,7 +1774,7 @@ fn run_async_batch<'a>(
 
         // Populate fixture registry
         if let Err(err) = populate_fixture_registry(py, &module.fixtures) {
-            let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
+            let message = format_pyerr(py, &err, config).unwrap_or_else(|_| err.to_string());
             preparation_errors.push((
                 test_id.clone(),
                 format!("Fixture registry error:\n{}", message),
@@ -888,53 +1786,28 @@ fn run_async_batch<'a>(
         {
             let _resolver_guard = ResolverActivationGuard::new(&mut resolver);
 
-            // Resolve test arguments FIRST - this triggers higher-scoped fixture
-            // resolution (e.g. session) which must happen before lower-scoped
-            // autouse fixtures that may depend on them
-            let mut call_args = Vec::new();
-            let mut resolution_failed = false;
-            for param in &test.parameters {
-                match resolver.resolve_argument(param) {
-                    Ok(value) => call_args.push(value),
-                    Err(err) => {
-                        let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-                        preparation_errors.push((
-                            test_id.clone(),
-                            format!("Fixture '{}' resolution error:\n{}", param, message),
-                        ));
-                        resolution_failed = true;
-                        break;
-                    }
+            let call_args = match resolver.setup_test_fixtures(&test.parameters) {
+                Ok(call_args) => call_args,
+                Err(failure) => {
+                    let message = format_pyerr(py, &failure.error, config)
+                        .unwrap_or_else(|_| failure.error.to_string());
+                    preparation_errors.push((
+                        test_id.clone(),
+                        format!("{}\n{}", failure.heading(), message),
+                    ));
+                    // Clean up function teardowns for this test
+                    let event_loop = resolver
+                        .get_test_scope_event_loop()
+                        .map(|l| l.clone_ref(py));
+                    finalize_generators(
+                        py,
+                        &mut resolver.function_teardowns,
+                        FixtureScope::Function,
+                        event_loop.as_ref(),
+                    );
+                    continue;
                 }
-            }
-
-            if resolution_failed {
-                // Clean up function teardowns for this test
-                let event_loop = resolver
-                    .get_test_scope_event_loop()
-                    .map(|l| l.clone_ref(py));
-                finalize_generators(py, &mut resolver.function_teardowns, event_loop.as_ref());
-                continue;
-            }
-
-            // THEN resolve autouse fixtures - higher-scoped ones are now cached
-            if let Err(err) = resolver.resolve_autouse_fixtures() {
-                let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-                preparation_errors.push((
-                    test_id.clone(),
-                    format!("Autouse fixture setup error:\n{}", message),
-                ));
-                continue;
-            }
-
-            if let Err(err) = resolver.apply_usefixtures_marks() {
-                let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-                preparation_errors.push((
-                    test_id.clone(),
-                    format!("Usefixtures mark error:\n{}", message),
-                ));
-                continue;
-            }
+            };
 
             // Extract timeout from asyncio mark(s) if present
             // A test may have multiple asyncio marks (one with timeout, one from class decoration)
@@ -964,12 +1837,12 @@ fn run_async_batch<'a>(
         }
     }
 
-    // Add preparation errors as failed results
+    // Add preparation errors as setup errors
     for (test_id, error_message) in preparation_errors {
         if let Some(test) = batch.tests.iter().find(|t| t.unique_id() == test_id) {
             results.push((
                 *test,
-                PyTestResult::failed(
+                PyTestResult::error(
                     test.display_name.clone(),
                     to_relative_path(&test.path),
                     0.0,
@@ -986,7 +1859,12 @@ fn run_async_batch<'a>(
     if test_coroutines.is_empty() {
         // Run any pending teardowns from preparation phase
         for (_, mut teardowns) in test_function_teardowns {
-            finalize_generators(py, &mut teardowns, Some(&event_loop));
+            finalize_generators(
+                py,
+                &mut teardowns,
+                FixtureScope::Function,
+                Some(&event_loop),
+            );
         }
         return Ok(results);
     }
@@ -999,12 +1877,26 @@ fn run_async_batch<'a>(
             Err(e) => {
                 // Ensure teardowns run even on error
                 for (_, mut teardowns) in test_function_teardowns {
-                    finalize_generators(py, &mut teardowns, Some(&event_loop));
+                    finalize_generators(
+                        py,
+                        &mut teardowns,
+                        FixtureScope::Function,
+                        Some(&event_loop),
+                    );
                 }
                 return Err(e);
             }
         };
 
+    // Generator fixtures requested via getfixturevalue() are torn down first,
+    // since they were set up last
+    finalize_generators(
+        py,
+        &mut take_registry_teardowns(py),
+        FixtureScope::Function,
+        Some(&event_loop),
+    );
+
     // Process results and run teardowns
     for ((test_id, _, _, _), result_dict) in test_coroutines.iter().zip(parallel_results.iter()) {
         // Find the corresponding test
@@ -1019,7 +1911,7 @@ fn run_async_batch<'a>(
             .iter_mut()
             .find(|(id, _)| id == test_id)
         {
-            finalize_generators(py, teardowns, Some(&event_loop));
+            finalize_generators(py, teardowns, FixtureScope::Function, Some(&event_loop));
         }
 
         // Extract result from dictionary
@@ -1064,16 +1956,11 @@ fn run_async_batch<'a>(
                 }
                 Some(ref msg) if is_xfail_exception(msg) => {
                     let reason = extract_xfail_reason(msg);
-                    let xfail_reason = if reason.is_empty() {
-                        "[XFAIL]".to_string()
-                    } else {
-                        format!("[XFAIL] {}", reason)
-                    };
-                    PyTestResult::skipped(
+                    PyTestResult::xfailed(
                         test.display_name.clone(),
                         to_relative_path(&test.path),
                         duration,
-                        xfail_reason,
+                        reason,
                         test.mark_names(),
                     )
                 }
@@ -1100,6 +1987,7 @@ fn run_async_batch<'a>(
 
         // Apply xfail semantics: convert expected failures to skips, etc.
         let result = apply_xfail(py, &test.marks, result);
+        let result = apply_xfail_list(config, result);
 
         results.push((test, result));
     }
@@ -1227,6 +2115,43 @@ struct TestCallFailure {
     message: String,
     stdout: Option<String>,
     stderr: Option<String>,
+    /// Whether the failure happened while setting up fixtures, before the
+    /// test body ran. Such failures are reported as errors, not failures.
+    setup_error: bool,
+    /// Whether the test body raised an exception outside `failure_exceptions`.
+    /// Reported as an error unless an xfail turns it into an expected failure.
+    unexpected_exception: bool,
+}
+
+/// Failed assertions and rustest's `fail()` exceptions, which always count
+/// as failures
+const FAIL_EXCEPTIONS: &[&str] = &[
+    "builtins.AssertionError",
+    "rustest.decorators.Failed",
+    "rustest._pytest_stub.outcomes.Failed",
+];
+
+/// Whether `err` is outside the configured `failure_exceptions`, making the
+/// test an error rather than a failure. Exception classes match by name or
+/// `module.name`, including base classes; `AssertionError` and `fail()`
+/// always count as failures.
+fn is_unexpected_exception(py: Python<'_>, err: &PyErr, config: &RunConfiguration) -> bool {
+    let Some(names) = config.failure_exceptions.as_ref() else {
+        return false;
+    };
+    let class_name = |class: &Bound<'_, PyAny>, attr: &str| -> String {
+        class
+            .getattr(attr)
+            .and_then(|value| value.extract::<String>())
+            .unwrap_or_default()
+    };
+    let counts_as_failure = err.get_type(py).mro().iter().any(|class| {
+        let name = class_name(&class, "__name__");
+        let qualified = format!("{}.{}", class_name(&class, "__module__"), name);
+        FAIL_EXCEPTIONS.contains(&qualified.as_str())
+            || names.iter().any(|n| *n == name || *n == qualified)
+    });
+    !counts_as_failure
 }
 
 /// Populate the Python fixture registry for getfixturevalue() support.
@@ -1250,6 +2175,16 @@ fn populate_fixture_registry(py: Python<'_>, fixtures: &IndexMap<String, Fixture
     Ok(())
 }
 
+/// Take the generator fixtures the Python registry entered while serving
+/// `request.getfixturevalue()`, so they can be finalized with the test's own
+/// function-scoped fixtures.
+fn take_registry_teardowns(py: Python<'_>) -> Vec<Py<PyAny>> {
+    py.import("rustest.fixture_registry")
+        .and_then(|registry| registry.call_method0("take_pending_teardowns"))
+        .and_then(|generators| generators.extract::<Vec<Py<PyAny>>>())
+        .unwrap_or_default()
+}
+
 /// Extract the loop_scope from a test's asyncio mark(s), if present.
 /// Returns Some(scope) if explicitly specified in any asyncio mark, None otherwise.
 /// Note: A test may have multiple asyncio marks (e.g., one for timeout, one from class decoration).
@@ -1349,23 +2284,45 @@ fn scope_to_string(scope: &FixtureScope) -> &'static str {
     }
 }
 
-/// Validate that an explicit loop_scope is compatible with the test's fixture requirements.
+/// Find an explicit loop_scope narrower than the test's async fixtures require.
 ///
-/// Returns an error message if the explicit scope is too narrow for the fixtures used.
-/// This helps users understand why they're getting "attached to a different loop" errors.
-fn validate_loop_scope_compatibility(
+/// Returns the explicit and the required scope when they are incompatible.
+fn loop_scope_mismatch(
     py: Python<'_>,
     test_case: &TestCase,
     fixtures: &IndexMap<String, Fixture>,
-) -> Option<String> {
+) -> Option<(FixtureScope, FixtureScope)> {
     // Only validate if there's an explicit loop_scope
     let explicit_scope = get_explicit_loop_scope_from_marks(py, test_case)?;
 
     // Detect what scope is required by fixtures
     let required_scope = detect_required_loop_scope_from_fixtures(fixtures, &test_case.parameters);
 
+    is_scope_wider(&required_scope, &explicit_scope).then_some((explicit_scope, required_scope))
+}
+
+/// Record the warning for a loop scope widened with `loop_scope_strict` off.
+fn warn_loop_scope_widened(test_nodeid: &str, explicit: FixtureScope, required: FixtureScope) {
+    let message = format!(
+        "loop_scope=\"{}\" widened to \"{}\" to match the {}-scoped async fixtures the test uses",
+        scope_to_string(&explicit),
+        scope_to_string(&required),
+        scope_to_string(&required),
+    );
+    record_test_warning(test_nodeid, message);
+}
+
+/// Validate that an explicit loop_scope is compatible with the test's fixture requirements.
+///
+/// Returns an error message if the explicit scope is too narrow for the fixtures used.
+/// This helps users understand why they're getting "attached to a different loop" errors.
+fn validate_loop_scope_compatibility(
+    py: Python<'_>,
+    test_case: &TestCase,
+    fixtures: &IndexMap<String, Fixture>,
+) -> Option<String> {
     // Check if explicit scope is narrower than required
-    if is_scope_wider(&required_scope, &explicit_scope) {
+    if let Some((explicit_scope, required_scope)) = loop_scope_mismatch(py, test_case, fixtures) {
         // Find the async fixture(s) that require the wider scope
         let mut problematic_fixtures = Vec::new();
         let mut visited = HashSet::new();
@@ -1444,7 +2401,8 @@ fn find_async_fixtures_with_scope(
 /// Determine the appropriate loop scope for a test.
 ///
 /// Strategy (matching pytest-asyncio with smart defaults):
-/// 1. If @mark.asyncio(loop_scope="...") is explicit, use that
+/// 1. If @mark.asyncio(loop_scope="...") is explicit, use that, widened to
+///    what its async fixtures require when `loop_scope_strict` is off
 /// 2. Otherwise, analyze fixture dependencies to find widest async fixture scope
 /// 3. Default to function scope if no async fixtures are used
 ///
@@ -1458,51 +2416,223 @@ fn determine_test_loop_scope(
 ) -> FixtureScope {
     // Check for explicit loop_scope mark first
     if let Some(explicit_scope) = get_explicit_loop_scope_from_marks(py, test_case) {
+        if !config.loop_scope_strict {
+            if let Some((_, required_scope)) = loop_scope_mismatch(py, test_case, fixtures) {
+                return required_scope;
+            }
+        }
         return explicit_scope;
     }
 
     // Collect both explicit parameters AND autouse fixtures for analysis
     let mut all_fixture_names: Vec<String> = test_case.parameters.clone();
 
-    // Include autouse fixtures - they run for every test and affect loop scope
-    for (name, fixture) in fixtures {
-        if fixture.autouse && !all_fixture_names.contains(name) {
-            // For class-scoped autouse fixtures, only include if the test is in that class
-            match (&fixture.class_name, &test_case.class_name) {
-                (Some(fixture_class), Some(test_class)) if fixture_class == test_class => {
-                    all_fixture_names.push(name.clone());
-                }
-                (None, _) => {
-                    all_fixture_names.push(name.clone());
-                }
-                _ => {} // Skip class fixtures for non-matching tests
+    // Include autouse fixtures - they run for every test and affect loop scope
+    for (name, fixture) in fixtures {
+        if fixture.autouse
+            && !all_fixture_names.contains(name)
+            && !test_case.excluded_autouse.excludes(name)
+        {
+            // For class-scoped autouse fixtures, only include if the test is in that class
+            match (&fixture.class_name, &test_case.class_name) {
+                (Some(fixture_class), Some(test_class)) if fixture_class == test_class => {
+                    all_fixture_names.push(name.clone());
+                }
+                (None, _) => {
+                    all_fixture_names.push(name.clone());
+                }
+                _ => {} // Skip class fixtures for non-matching tests
+            }
+        }
+    }
+
+    // Analyze fixture dependencies to find required scope
+    let detected = detect_required_loop_scope_from_fixtures(fixtures, &all_fixture_names);
+
+    // Use the wider of: detected scope vs config default
+    std::cmp::max(detected, config.default_test_loop_scope)
+}
+
+/// Message for a fixture name that isn't defined for the module.
+fn unknown_fixture_message(
+    fixtures: &IndexMap<String, Fixture>,
+    name: &str,
+    has_pytest_fixtures: bool,
+) -> String {
+    let mut available: Vec<&str> = fixtures.keys().map(String::as_str).collect();
+    available.sort();
+    let available_list = available.join(", ");
+    let hint = if has_pytest_fixtures {
+        "\n\nHint: This project uses @pytest.fixture definitions that rustest cannot load natively.\n      Run with --pytest-compat to use existing pytest fixtures."
+    } else {
+        ""
+    };
+    format!(
+        "Unknown fixture '{}'.\nAvailable fixtures: {}{}",
+        name, available_list, hint
+    )
+}
+
+/// Validate every test's fixture graph without executing anything.
+///
+/// Applies the same checks `FixtureResolver` performs while resolving (unknown
+/// fixtures, recursive dependencies, scope ordering, loop scope compatibility
+/// unless `loop_scope_strict` is off) but keeps going after the first problem,
+/// returning one entry per distinct problem in each module.
+pub fn check_fixture_graph(
+    py: Python<'_>,
+    modules: &[TestModule],
+    loop_scope_strict: bool,
+) -> Vec<CollectionError> {
+    let mut problems = Vec::new();
+
+    for module in modules {
+        let mut module_problems: IndexSet<String> = IndexSet::new();
+        let mut checked: HashSet<String> = HashSet::new();
+
+        for test in &module.tests {
+            if loop_scope_strict {
+                if let Some(message) = validate_loop_scope_compatibility(py, test, &module.fixtures)
+                {
+                    module_problems.insert(message);
+                }
+            }
+
+            let mut requested: Vec<String> = test
+                .parameters
+                .iter()
+                .filter(|param| {
+                    !test.parameter_values.contains_key(param.as_str())
+                        || test.indirect_params.contains(param)
+                })
+                .cloned()
+                .collect();
+            for mark in test
+                .marks
+                .iter()
+                .filter(|mark| mark.is_named("usefixtures"))
+            {
+                for item in mark.args.bind(py).iter() {
+                    if let Ok(name) = item.extract::<String>() {
+                        requested.push(name);
+                    }
+                }
+            }
+            requested.extend(
+                module
+                    .fixtures
+                    .values()
+                    .filter(|fixture| {
+                        fixture.autouse
+                            && !test.excluded_autouse.excludes(&fixture.name)
+                            && (fixture.class_name.is_none()
+                                || fixture.class_name == test.class_name)
+                    })
+                    .map(|fixture| fixture.name.clone()),
+            );
+
+            for name in requested {
+                check_fixture_node(
+                    py,
+                    module,
+                    &test.name,
+                    &name,
+                    &mut Vec::new(),
+                    &mut checked,
+                    &mut module_problems,
+                );
+            }
+        }
+
+        let path = to_relative_path(&module.path);
+        problems.extend(
+            module_problems
+                .into_iter()
+                .map(|message| CollectionError::new(path.clone(), message)),
+        );
+    }
+
+    problems
+}
+
+/// Depth-first walk of one fixture and its dependencies for `check_fixture_graph`.
+fn check_fixture_node(
+    py: Python<'_>,
+    module: &TestModule,
+    requested_by: &str,
+    name: &str,
+    stack: &mut Vec<String>,
+    checked: &mut HashSet<String>,
+    problems: &mut IndexSet<String>,
+) {
+    // Built-ins the resolver provides itself
+    if name == "request"
+        || (matches!(name, "rng_seed" | "event_loop") && !module.fixtures.contains_key(name))
+    {
+        return;
+    }
+
+    let Some(fixture) = module.fixtures.get(name) else {
+        problems.insert(format!(
+            "{}: {}",
+            requested_by,
+            unknown_fixture_message(&module.fixtures, name, module.has_pytest_fixtures)
+        ));
+        return;
+    };
+
+    if stack.iter().any(|entry| entry == name) {
+        problems.insert(format!(
+            "Detected recursive fixture dependency involving '{}'.",
+            name
+        ));
+        return;
+    }
+    if !checked.insert(name.to_string()) {
+        return;
+    }
+
+    stack.push(name.to_string());
+    for dependency in &fixture.parameters {
+        if dependency == "request" {
+            continue;
+        }
+        if let Some(dep_fixture) = module.fixtures.get(dependency) {
+            if let Err(err) = FixtureResolver::validate_scope_dependency(fixture, dep_fixture) {
+                problems.insert(err.value(py).to_string());
             }
         }
+        check_fixture_node(py, module, name, dependency, stack, checked, problems);
     }
-
-    // Analyze fixture dependencies to find required scope
-    let detected = detect_required_loop_scope_from_fixtures(fixtures, &all_fixture_names);
-
-    // Use the wider of: detected scope vs config default
-    std::cmp::max(detected, config.default_test_loop_scope)
+    stack.pop();
 }
 
 /// Execute a test case and return either success metadata or failure details.
+/// Run one attempt of a test. `used_fixtures` receives the fixtures it
+/// requested, including those set up before a fixture failed.
 fn execute_test_case(
     py: Python<'_>,
     module: &TestModule,
     test_case: &TestCase,
     config: &RunConfiguration,
     context: &mut FixtureContext,
+    used_fixtures: &mut Vec<String>,
 ) -> Result<TestCallSuccess, TestCallFailure> {
     // Validate loop scope compatibility before running the test
     // This catches cases where explicit loop_scope is too narrow for the fixtures used
-    if let Some(error_message) = validate_loop_scope_compatibility(py, test_case, &module.fixtures)
+    if !config.loop_scope_strict {
+        if let Some((explicit, required)) = loop_scope_mismatch(py, test_case, &module.fixtures) {
+            warn_loop_scope_widened(&test_case.unique_id(), explicit, required);
+        }
+    } else if let Some(error_message) =
+        validate_loop_scope_compatibility(py, test_case, &module.fixtures)
     {
         return Err(TestCallFailure {
             message: error_message,
             stdout: None,
             stderr: None,
+            setup_error: true,
+            unexpected_exception: false,
         });
     }
 
@@ -1524,6 +2654,8 @@ fn execute_test_case(
         &mut context.teardowns,
         &test_case.fixture_param_indices,
         &test_case.indirect_params,
+        &test_case.param_scopes,
+        &test_case.excluded_autouse,
         &mut context.session_event_loop,
         &mut context.package_event_loop,
         &mut context.module_event_loop,
@@ -1540,52 +2672,66 @@ fn execute_test_case(
 
     // Populate Python fixture registry for getfixturevalue() support
     if let Err(err) = populate_fixture_registry(py, &module.fixtures) {
-        let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
+        let message = format_pyerr(py, &err, config).unwrap_or_else(|_| err.to_string());
         return Err(TestCallFailure {
             message,
             stdout: None,
             stderr: None,
+            setup_error: true,
+            unexpected_exception: false,
         });
     }
 
-    // Resolve test arguments FIRST - this triggers higher-scoped fixture
-    // resolution (e.g. session) which must happen before lower-scoped
-    // autouse fixtures that may depend on them
-    let mut call_args: Vec<(String, Py<PyAny>)> = Vec::new();
-    for param in &test_case.parameters {
-        match resolver.resolve_argument(param) {
-            Ok(value) => call_args.push((param.clone(), value)),
-            Err(err) => {
-                let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-                return Err(TestCallFailure {
-                    message,
-                    stdout: None,
-                    stderr: None,
-                });
-            }
+    // Created before fixtures resolve so `capsys` reads the buffers the
+    // test's output is redirected to
+    let capture = match begin_test_capture(py, config.capture_output) {
+        Ok(capture) => capture,
+        Err(err) => {
+            return Err(TestCallFailure {
+                message: err.to_string(),
+                stdout: None,
+                stderr: None,
+                setup_error: true,
+                unexpected_exception: false,
+            });
         }
-    }
-
-    // THEN resolve autouse fixtures - higher-scoped ones are now cached
-    if let Err(err) = resolver.resolve_autouse_fixtures() {
-        let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-        return Err(TestCallFailure {
-            message,
-            stdout: None,
-            stderr: None,
-        });
-    }
+    };
 
-    if let Err(err) = resolver.apply_usefixtures_marks() {
-        let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
-        return Err(TestCallFailure {
-            message,
-            stdout: None,
-            stderr: None,
-        });
-    }
+    let setup = resolver.setup_test_fixtures(&test_case.parameters);
+    used_fixtures.clone_from(&resolver.used_fixtures);
+    let call_args: Vec<(String, Py<PyAny>)> = match setup {
+        Ok(values) => test_case.parameters.iter().cloned().zip(values).collect(),
+        Err(failure) => {
+            let message = format_pyerr(py, &failure.error, config)
+                .unwrap_or_else(|_| failure.error.to_string());
+            // Tear down the function fixtures set up before the failure, so
+            // undo steps such as monkeypatch's still run
+            let event_loop = resolver
+                .get_test_scope_event_loop()
+                .map(|l| l.clone_ref(py));
+            finalize_generators(
+                py,
+                &mut resolver.function_teardowns,
+                FixtureScope::Function,
+                event_loop.as_ref(),
+            );
+            close_event_loop(py, &mut resolver.function_event_loop);
+            return Err(TestCallFailure {
+                message,
+                stdout: None,
+                stderr: None,
+                setup_error: true,
+                unexpected_exception: false,
+            });
+        }
+    };
 
-    let call_result = call_with_capture(py, config.capture_output, || {
+    let call_result = call_with_capture(py, capture.as_ref(), || {
+        let _line_trace = if config.line_trace {
+            Some(LineTraceGuard::start(py, &test_case.unique_id())?)
+        } else {
+            None
+        };
         let callable = test_case.callable.bind(py);
 
         // For @patch-decorated tests, pass fixture args as keyword arguments
@@ -1635,15 +2781,34 @@ fn execute_test_case(
                 result
             };
 
-            Ok(event_loop
+            let recorder = if config.asyncio_debug {
+                Some(
+                    py.import("rustest.asyncio_debug")?
+                        .getattr("TaskWarningRecorder")?
+                        .call1((event_loop.bind(py),))?,
+                )
+            } else {
+                None
+            };
+            let outcome = event_loop
                 .bind(py)
-                .call_method1("run_until_complete", (&coro_to_run,))?
-                .unbind())
+                .call_method1("run_until_complete", (&coro_to_run,));
+            if let Some(recorder) = recorder {
+                let messages: Vec<String> = recorder.call_method0("finish")?.extract()?;
+                let node_id = test_case.unique_id();
+                for message in messages {
+                    record_test_warning(&node_id, message);
+                }
+            }
+            Ok(outcome?.unbind())
         } else {
             Ok(result.unbind())
         }
     });
 
+    // Fixtures requested through getfixturevalue() during the test body
+    used_fixtures.clone_from(&resolver.used_fixtures);
+
     let (result, stdout, stderr) = match call_result {
         Ok(value) => value,
         Err(err) => {
@@ -1651,34 +2816,73 @@ fn execute_test_case(
             let event_loop = resolver
                 .get_test_scope_event_loop()
                 .map(|l| l.clone_ref(py));
-            finalize_generators(py, &mut resolver.function_teardowns, event_loop.as_ref());
+            resolver
+                .function_teardowns
+                .extend(take_registry_teardowns(py));
+            finalize_generators(
+                py,
+                &mut resolver.function_teardowns,
+                FixtureScope::Function,
+                event_loop.as_ref(),
+            );
             close_event_loop(py, &mut resolver.function_event_loop);
             return Err(TestCallFailure {
                 message: err.to_string(),
                 stdout: None,
                 stderr: None,
+                setup_error: false,
+                unexpected_exception: false,
             });
         }
     };
 
-    // Clean up function-scoped fixtures after test completes
+    // Clean up function-scoped fixtures after test completes, including
+    // generator fixtures the test requested through getfixturevalue()
     let event_loop = resolver
         .get_test_scope_event_loop()
         .map(|l| l.clone_ref(py));
-    finalize_generators(py, &mut resolver.function_teardowns, event_loop.as_ref());
+    resolver
+        .function_teardowns
+        .extend(take_registry_teardowns(py));
+    finalize_generators(
+        py,
+        &mut resolver.function_teardowns,
+        FixtureScope::Function,
+        event_loop.as_ref(),
+    );
 
     // Close the function-scoped event loop to release async resources (DB connections,
     // sockets, etc.) immediately rather than leaking them until GC runs.
     close_event_loop(py, &mut resolver.function_event_loop);
 
     match result {
-        Ok(_) => Ok(TestCallSuccess { stdout, stderr }),
+        Ok(value) => {
+            // Returning a value instead of asserting it is almost always a mistake
+            let value = value.bind(py);
+            if !value.is_none() {
+                let type_name = value
+                    .get_type()
+                    .name()
+                    .map(|name| name.to_string())
+                    .unwrap_or_default();
+                record_test_warning(
+                    &test_case.unique_id(),
+                    format!(
+                        "test returned a value ({}); did you mean to assert?",
+                        type_name
+                    ),
+                );
+            }
+            Ok(TestCallSuccess { stdout, stderr })
+        }
         Err(err) => {
-            let message = format_pyerr(py, &err).unwrap_or_else(|_| err.to_string());
+            let message = format_pyerr(py, &err, config).unwrap_or_else(|_| err.to_string());
             Err(TestCallFailure {
                 message,
                 stdout,
                 stderr,
+                setup_error: false,
+                unexpected_exception: is_unexpected_exception(py, &err, config),
             })
         }
     }
@@ -1714,6 +2918,10 @@ struct FixtureResolver<'py> {
     indirect_param_override: Option<Py<PyAny>>,
     /// Parameter names that should be resolved as fixture references (indirect parametrization).
     indirect_params: &'py [String],
+    /// Scopes that fixtures built from indirect parameters are cached at.
+    param_scopes: &'py HashMap<String, FixtureScope>,
+    /// Autouse fixtures the current test opted out of
+    excluded_autouse: &'py AutouseExclusion,
     /// Event loops for different scopes (for async fixtures)
     session_event_loop: &'py mut Option<Py<PyAny>>,
     package_event_loop: &'py mut Option<Py<PyAny>>,
@@ -1733,6 +2941,8 @@ struct FixtureResolver<'py> {
     /// True when the module or any conftest file in its ancestor chain contains
     /// @pytest.fixture definitions. Used to enrich "Unknown fixture" error messages.
     has_pytest_fixtures: bool,
+    /// Fixtures requested while setting up and running the test, in order
+    used_fixtures: Vec<String>,
 }
 
 impl<'py> FixtureResolver<'py> {
@@ -1748,6 +2958,8 @@ impl<'py> FixtureResolver<'py> {
         teardowns: &'py mut TeardownCollector,
         fixture_param_indices: &'py IndexMap<String, usize>,
         indirect_params: &'py [String],
+        param_scopes: &'py HashMap<String, FixtureScope>,
+        excluded_autouse: &'py AutouseExclusion,
         session_event_loop: &'py mut Option<Py<PyAny>>,
         package_event_loop: &'py mut Option<Py<PyAny>>,
         module_event_loop: &'py mut Option<Py<PyAny>>,
@@ -1775,6 +2987,8 @@ impl<'py> FixtureResolver<'py> {
             current_fixture_param: None,
             indirect_param_override: None,
             indirect_params,
+            param_scopes,
+            excluded_autouse,
             session_event_loop,
             package_event_loop,
             module_event_loop,
@@ -1786,6 +3000,7 @@ impl<'py> FixtureResolver<'py> {
             test_nodeid,
             test_marks,
             has_pytest_fixtures,
+            used_fixtures: Vec::new(),
         }
     }
 
@@ -1808,12 +3023,39 @@ impl<'py> FixtureResolver<'py> {
     }
 
     fn resolve_fixture_value(&mut self, name: &str) -> PyResult<Py<PyAny>> {
+        // The xunit hooks are an implementation detail of class tests
+        if !name.starts_with(XUNIT_FIXTURE_PREFIX) && !self.used_fixtures.iter().any(|n| n == name)
+        {
+            self.used_fixtures.push(name.to_string());
+        }
+
         // Special handling for "request" fixture - create with current param value
         if name == "request" {
-            return self.create_request_fixture();
+            let request = self.create_request_fixture()?;
+            // Finalizers added through a test's own request run with its
+            // function teardowns; fixtures register theirs by scope
+            if self.stack.is_empty() {
+                let finalizers = request.bind(self.py).getattr("_run_finalizers")?.unbind();
+                self.function_teardowns.push(finalizers);
+            }
+            return Ok(request);
+        }
+
+        // Built-in "rng_seed" fixture, unless the project defines its own
+        if name == "rng_seed" && !self.fixtures.contains_key(name) {
+            let seed = rng_seed_for_node(&self.test_nodeid);
+            return Ok(seed.into_pyobject(self.py)?.into_any().unbind());
+        }
+
+        // Built-in "event_loop" fixture: the loop an async test with the same
+        // loop scope would run on, so sync tests can drive coroutines on it
+        if name == "event_loop" && !self.fixtures.contains_key(name) {
+            return self.get_or_create_test_event_loop();
         }
 
-        // Check if this is a parametrized fixture and get the cache key
+        // Check if this is a parametrized fixture and get the cache key.
+        // Only the param selected for this test is materialized; values for
+        // params no running test selects never invoke the fixture.
         let (cache_key, param_value) = if let Some(&param_idx) =
             self.fixture_param_indices.get(name)
         {
@@ -1841,6 +3083,20 @@ impl<'py> FixtureResolver<'py> {
             (name.to_string(), None)
         };
 
+        // A fixture built from an indirect parameter is cached per value, at the
+        // scope given to @parametrize when there is one. That scope is part of
+        // the key so tests parametrized without it never share the instance.
+        let (cache_key, param_scope) = match &self.indirect_param_override {
+            Some(value) => {
+                let value = value.bind(self.py).repr()?;
+                match self.param_scopes.get(name) {
+                    Some(&scope) => (format!("{}[{}]@{:?}", name, value, scope), Some(scope)),
+                    None => (format!("{}[{}]", name, value), None),
+                }
+            }
+            None => (cache_key, None),
+        };
+
         // Check all caches in order: function -> class -> module -> package -> session
         if let Some(value) = self.function_cache.get(&cache_key) {
             return Ok(value.clone_ref(self.py));
@@ -1860,20 +3116,15 @@ impl<'py> FixtureResolver<'py> {
 
         // Fixture not in any cache, need to execute it
         let fixture = self.fixtures.get(name).ok_or_else(|| {
-            let mut available: Vec<&str> = self.fixtures.keys().map(String::as_str).collect();
-            available.sort();
-            let available_list = available.join(", ");
-            let hint = if self.has_pytest_fixtures {
-                "\n\nHint: This project uses @pytest.fixture definitions that rustest cannot load natively.\n      Run with --pytest-compat to use existing pytest fixtures."
-            } else {
-                ""
-            };
-            invalid_test_definition(format!(
-                "Unknown fixture '{}'.\nAvailable fixtures: {}{}",
-                name, available_list, hint
+            invalid_test_definition(unknown_fixture_message(
+                self.fixtures,
+                name,
+                self.has_pytest_fixtures,
             ))
         })?;
 
+        let scope = param_scope.unwrap_or(fixture.scope);
+
         // Set current fixture param for request.param access
         let previous_param = self.current_fixture_param.take();
         // Indirect parametrize override takes precedence over fixture's own params
@@ -1899,7 +3150,7 @@ impl<'py> FixtureResolver<'py> {
                 continue; // Skip scope validation for request fixture
             }
             if let Some(dep_fixture) = self.fixtures.get(param) {
-                self.validate_scope_dependency(fixture, dep_fixture)?;
+                Self::validate_scope_dependency(fixture, dep_fixture)?;
             }
         }
 
@@ -1907,10 +3158,17 @@ impl<'py> FixtureResolver<'py> {
         let mut args = Vec::new();
         for param in fixture.parameters.iter() {
             let value = self.resolve_argument(param)?;
+            if param == "request" {
+                // Pushed before the fixture's own generator, so finalizers
+                // added through `request` run after its teardown
+                let finalizers = value.bind(self.py).getattr("_run_finalizers")?.unbind();
+                self.push_teardown(scope, finalizers);
+            }
             args.push(value);
         }
 
         // Execute the fixture
+        let setup_start = Instant::now();
         let args_tuple = PyTuple::new(self.py, &args)?;
         let result = if fixture.is_async_generator {
             // For async generator fixtures: call to get async generator, then call anext() to get yielded value
@@ -1923,7 +3181,7 @@ impl<'py> FixtureResolver<'py> {
             // Use the wider of the fixture's scope and the test's loop scope.
             // Session-scoped async fixtures must use the session event loop to avoid
             // "attached to a different loop" errors when reused across tests.
-            let effective_scope = std::cmp::max(fixture.scope, self.test_loop_scope);
+            let effective_scope = std::cmp::max(scope, self.test_loop_scope);
             let event_loop = self.get_or_create_event_loop(effective_scope)?;
 
             // Call anext() on the async generator to get the yielded value
@@ -1937,23 +3195,12 @@ impl<'py> FixtureResolver<'py> {
                 .unbind();
 
             // Store the async generator in the appropriate teardown list
-            match fixture.scope {
-                FixtureScope::Session => {
-                    self.teardowns.session.push(async_generator);
-                }
-                FixtureScope::Package => {
-                    self.teardowns.package.push(async_generator);
-                }
-                FixtureScope::Module => {
-                    self.teardowns.module.push(async_generator);
-                }
-                FixtureScope::Class => {
-                    self.teardowns.class.push(async_generator);
-                }
-                FixtureScope::Function => {
-                    self.function_teardowns.push(async_generator);
-                }
+            if scope == FixtureScope::Session && cache_key != fixture.name {
+                self.teardowns
+                    .session_params
+                    .insert(cache_key.clone(), async_generator.clone_ref(self.py));
             }
+            self.push_teardown(scope, async_generator);
 
             yielded_value
         } else if fixture.is_generator {
@@ -1968,23 +3215,12 @@ impl<'py> FixtureResolver<'py> {
             let yielded_value = generator.bind(self.py).call_method0("__next__")?.unbind();
 
             // Store the generator in the appropriate teardown list
-            match fixture.scope {
-                FixtureScope::Session => {
-                    self.teardowns.session.push(generator);
-                }
-                FixtureScope::Package => {
-                    self.teardowns.package.push(generator);
-                }
-                FixtureScope::Module => {
-                    self.teardowns.module.push(generator);
-                }
-                FixtureScope::Class => {
-                    self.teardowns.class.push(generator);
-                }
-                FixtureScope::Function => {
-                    self.function_teardowns.push(generator);
-                }
+            if scope == FixtureScope::Session && cache_key != fixture.name {
+                self.teardowns
+                    .session_params
+                    .insert(cache_key.clone(), generator.clone_ref(self.py));
             }
+            self.push_teardown(scope, generator);
 
             yielded_value
         } else if fixture.is_async {
@@ -1998,7 +3234,7 @@ impl<'py> FixtureResolver<'py> {
             // Use the wider of the fixture's scope and the test's loop scope.
             // Session-scoped async fixtures must use the session event loop to avoid
             // "attached to a different loop" errors when reused across tests.
-            let effective_scope = std::cmp::max(fixture.scope, self.test_loop_scope);
+            let effective_scope = std::cmp::max(scope, self.test_loop_scope);
             let event_loop = self.get_or_create_event_loop(effective_scope)?;
 
             // Run the coroutine in the scoped event loop
@@ -2016,13 +3252,20 @@ impl<'py> FixtureResolver<'py> {
         };
 
         self.stack.remove(&fixture.name);
+        FIXTURE_SETUPS.with(|count| count.set(count.get() + 1));
+        record_span(
+            &format!("setup {}", fixture.name),
+            "fixture",
+            setup_start,
+            serde_json::json!({ "scope": scope.as_str() }),
+        );
 
         // Restore previous fixture param
         self.current_fixture_param = previous_param;
 
         // Store in the appropriate cache based on scope
         // Use cache_key which includes param index for parametrized fixtures
-        match fixture.scope {
+        match scope {
             FixtureScope::Session => {
                 self.session_cache
                     .insert(cache_key, result.clone_ref(self.py));
@@ -2059,7 +3302,7 @@ impl<'py> FixtureResolver<'py> {
     /// - Module fixtures can depend on: session, module
     /// - Class fixtures can depend on: session, module, class
     /// - Function fixtures can depend on: session, module, class, function
-    fn validate_scope_dependency(&self, fixture: &Fixture, dependency: &Fixture) -> PyResult<()> {
+    fn validate_scope_dependency(fixture: &Fixture, dependency: &Fixture) -> PyResult<()> {
         // Check if dependency scope is narrower than fixture scope
         if fixture.scope > dependency.scope {
             return Err(invalid_test_definition(format!(
@@ -2071,15 +3314,95 @@ impl<'py> FixtureResolver<'py> {
         Ok(())
     }
 
-    /// Apply @mark.usefixtures by eagerly resolving the referenced fixtures.
+    /// Set up every fixture the current test needs and return its call arguments.
+    ///
+    /// Fixtures are instantiated widest scope first. Within one scope,
+    /// autouse fixtures come first, then the fixtures named by
+    /// `@mark.usefixtures`, then the test's own parameters in signature
+    /// order. Dependencies are set up on demand, just before the fixture
+    /// that needs them, and anything already cached is reused.
+    fn setup_test_fixtures(
+        &mut self,
+        parameters: &[String],
+    ) -> Result<Vec<Py<PyAny>>, FixtureSetupError> {
+        let mut plan: Vec<(FixtureScope, FixtureOrigin, String)> = Vec::new();
+        for name in self.autouse_fixture_names() {
+            plan.push((self.planned_scope(&name), FixtureOrigin::Autouse, name));
+        }
+        let usefixtures = self
+            .usefixtures_names()
+            .map_err(|error| FixtureSetupError {
+                origin: FixtureOrigin::Usefixtures,
+                name: String::new(),
+                error,
+            })?;
+        for name in usefixtures {
+            plan.push((self.planned_scope(&name), FixtureOrigin::Usefixtures, name));
+        }
+        for name in parameters {
+            // Plain parametrized values and the per-call `request` object are
+            // only produced below, when the arguments are collected
+            let direct_value =
+                self.parameters.contains_key(name) && !self.indirect_params.contains(name);
+            if direct_value || name == "request" {
+                continue;
+            }
+            plan.push((
+                self.planned_scope(name),
+                FixtureOrigin::Signature,
+                name.clone(),
+            ));
+        }
+        // Stable, so equal scopes keep the autouse, usefixtures, signature order
+        plan.sort_by_key(|entry| std::cmp::Reverse(entry.0));
+
+        if std::env::var_os("RUSTEST_DEBUG_AUTOUSE").is_some() {
+            eprintln!("[rustest-debug] fixture setup order: {:?}", plan);
+        }
+
+        for (_, origin, name) in plan {
+            self.resolve_argument(&name)
+                .map_err(|error| FixtureSetupError {
+                    origin,
+                    name,
+                    error,
+                })?;
+        }
+
+        parameters
+            .iter()
+            .map(|name| {
+                self.resolve_argument(name)
+                    .map_err(|error| FixtureSetupError {
+                        origin: FixtureOrigin::Signature,
+                        name: name.clone(),
+                        error,
+                    })
+            })
+            .collect()
+    }
+
+    /// The scope a requested fixture is cached at, used to order test setup.
+    /// Names that are not fixtures sort with function scope.
+    fn planned_scope(&self, name: &str) -> FixtureScope {
+        if self.indirect_params.iter().any(|param| param == name) {
+            if let Some(&scope) = self.param_scopes.get(name) {
+                return scope;
+            }
+        }
+        self.fixtures
+            .get(name)
+            .map(|fixture| fixture.scope)
+            .unwrap_or(FixtureScope::Function)
+    }
+
+    /// Fixture names from the current test's `@mark.usefixtures` marks.
     ///
     /// Pytest treats `@mark.usefixtures("foo")` as if "foo" were listed in the test signature.
-    /// Rather than mutating the signature, we simply resolve the fixtures up front so all
-    /// registered setup/teardown behaviour still runs.
-    fn apply_usefixtures_marks(&mut self) -> PyResult<()> {
-        // Safely collect fixture names first so we can drop the immutable borrow on
-        // `self.test_marks` before calling `resolve_fixture_value`.
-        let mut names_to_resolve: Vec<String> = Vec::new();
+    /// Rather than mutating the signature, the fixtures are set up alongside the
+    /// test's own, so all registered setup/teardown behaviour still runs.
+    fn usefixtures_names(&self) -> PyResult<Vec<String>> {
+        let mut names: Vec<String> = Vec::new();
         for mark in &self.test_marks {
             if !mark.is_named("usefixtures") {
                 continue;
@@ -2088,30 +3411,23 @@ impl<'py> FixtureResolver<'py> {
             let args = mark.args.bind(self.py);
             for item in args.iter() {
                 let fixture_name: String = item.extract()?;
-                names_to_resolve.push(fixture_name);
-            }
-        }
-
-        let mut resolved = HashSet::new();
-        for fixture_name in names_to_resolve {
-            if resolved.insert(fixture_name.clone()) {
-                self.resolve_fixture_value(&fixture_name)?;
+                if !names.contains(&fixture_name) {
+                    names.push(fixture_name);
+                }
             }
         }
-
-        Ok(())
+        Ok(names)
     }
 
-    /// Resolve all autouse fixtures appropriate for the current test.
+    /// Autouse fixtures that apply to the current test, widest scope first.
     /// Autouse fixtures are automatically executed without needing to be explicitly requested.
-    /// Fixtures are sorted by scope (session first, function last) to match pytest behavior.
-    fn resolve_autouse_fixtures(&mut self) -> PyResult<()> {
+    fn autouse_fixture_names(&self) -> Vec<String> {
         // Collect all autouse fixtures that match the current test's class, with their scope
         let mut autouse_fixtures: Vec<(String, FixtureScope)> = self
             .fixtures
             .iter()
-            .filter(|(_, fixture)| {
-                if !fixture.autouse {
+            .filter(|(name, fixture)| {
+                if !fixture.autouse || self.excluded_autouse.excludes(name) {
                     return false;
                 }
                 // If fixture has a class_name, it should only run for tests in that class
@@ -2125,32 +3441,8 @@ impl<'py> FixtureResolver<'py> {
             .collect();
 
         // Sort by scope: session (widest) first, function (narrowest) last
-        autouse_fixtures.sort_by(|a, b| b.1.cmp(&a.1));
-
-        if std::env::var_os("RUSTEST_DEBUG_AUTOUSE").is_some() {
-            eprintln!(
-                "[rustest-debug] autouse fixtures (sorted by scope): {:?}",
-                autouse_fixtures
-            );
-        }
-
-        // Resolve each autouse fixture in scope order
-        for (name, _) in autouse_fixtures {
-            // Skip if already in cache (for higher-scoped autouse fixtures)
-            if self.function_cache.contains_key(&name)
-                || self.class_cache.contains_key(&name)
-                || self.module_cache.contains_key(&name)
-                || self.package_cache.contains_key(&name)
-                || self.session_cache.contains_key(&name)
-            {
-                continue;
-            }
-
-            // Resolve the autouse fixture
-            self.resolve_argument(&name)?;
-        }
-
-        Ok(())
+        autouse_fixtures.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+        autouse_fixtures.into_iter().map(|(name, _)| name).collect()
     }
 
     /// Get or create an event loop for the given scope.
@@ -2219,6 +3511,17 @@ impl<'py> FixtureResolver<'py> {
     }
 
     /// Create a request fixture with the current param value.
+    /// Queue a generator or finalizer for teardown at the end of `scope`.
+    fn push_teardown(&mut self, scope: FixtureScope, teardown: Py<PyAny>) {
+        match scope {
+            FixtureScope::Session => self.teardowns.session.push(teardown),
+            FixtureScope::Package => self.teardowns.package.push(teardown),
+            FixtureScope::Module => self.teardowns.module.push(teardown),
+            FixtureScope::Class => self.teardowns.class.push(teardown),
+            FixtureScope::Function => self.function_teardowns.push(teardown),
+        }
+    }
+
     fn create_request_fixture(&self) -> PyResult<Py<PyAny>> {
         // Import the FixtureRequest class from rustest.compat.pytest
         let compat = self.py.import("rustest.compat.pytest")?;
@@ -2263,28 +3566,88 @@ impl<'py> FixtureResolver<'py> {
     }
 }
 
+/// `StringIO` buffers a test's stdout and stderr are redirected to.
+struct CaptureBuffers {
+    stdout: Py<PyAny>,
+    stderr: Py<PyAny>,
+}
+
+/// Create the capture buffers for the next test, or `None` without capture.
+///
+/// The buffers are also published to `rustest.builtin_fixtures`, where the
+/// `capsys` and `capfd` fixtures read and reset them.
+fn begin_test_capture(py: Python<'_>, capture_output: bool) -> PyResult<Option<CaptureBuffers>> {
+    let buffers = py
+        .import("rustest.builtin_fixtures")?
+        .call_method1("_begin_test_capture", (capture_output,))?;
+    if buffers.is_none() {
+        return Ok(None);
+    }
+    let (stdout, stderr): (Py<PyAny>, Py<PyAny>) = buffers.extract()?;
+    Ok(Some(CaptureBuffers { stdout, stderr }))
+}
+
+/// Set the level the `caplog` fixture captures at for the rest of the run.
+fn configure_log_capture(py: Python<'_>, level: Option<&str>) -> PyResult<()> {
+    py.import("rustest.builtin_fixtures")?
+        .call_method1("_configure_log_capture", (level,))?;
+    Ok(())
+}
+
 /// Result type for test execution with optional stdout/stderr capture.
 type CallResult = (PyResult<Py<PyAny>>, Option<String>, Option<String>);
 
+/// Keeps the `--line-trace` tracer installed for one test call.
+///
+/// The tracer is removed on drop, so an early return or a failing test can
+/// never leave `sys.settrace` active for the next test.
+struct LineTraceGuard<'py> {
+    module: Bound<'py, PyModule>,
+}
+
+impl<'py> LineTraceGuard<'py> {
+    fn start(py: Python<'py>, node_id: &str) -> PyResult<Self> {
+        let module = py.import("rustest.line_trace")?;
+        module.call_method1("start", (node_id,))?;
+        Ok(Self { module })
+    }
+}
+
+impl Drop for LineTraceGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.module.call_method0("stop");
+    }
+}
+
+/// Write the lines recorded by `--line-trace` as JSON.
+fn write_line_trace(py: Python<'_>, path: &Path) -> PyResult<()> {
+    py.import("rustest.line_trace")?
+        .call_method1("write", (path,))
+        .map(|_| ())
+}
+
 /// Execute a callable while optionally capturing stdout/stderr.
-fn call_with_capture<F>(py: Python<'_>, capture_output: bool, f: F) -> PyResult<CallResult>
+fn call_with_capture<F>(
+    py: Python<'_>,
+    capture: Option<&CaptureBuffers>,
+    f: F,
+) -> PyResult<CallResult>
 where
     F: FnOnce() -> PyResult<Py<PyAny>>,
 {
-    if !capture_output {
+    let Some(capture) = capture else {
         return Ok((f(), None, None));
-    }
+    };
 
     let contextlib = py.import("contextlib")?;
-    let io = py.import("io")?;
-    let stdout_buffer = io.getattr("StringIO")?.call0()?;
-    let stderr_buffer = io.getattr("StringIO")?.call0()?;
+    let stdout_buffer = capture.stdout.bind(py);
+    let stderr_buffer = capture.stderr.bind(py);
     let redirect_stdout = contextlib
         .getattr("redirect_stdout")?
-        .call1((&stdout_buffer,))?;
+        .call1((stdout_buffer,))?;
     let redirect_stderr = contextlib
         .getattr("redirect_stderr")?
-        .call1((&stderr_buffer,))?;
+        .call1((stderr_buffer,))?;
     let stack = contextlib.getattr("ExitStack")?.call0()?;
     stack.call_method1("enter_context", (&redirect_stdout,))?;
     stack.call_method1("enter_context", (&redirect_stderr,))?;
@@ -2311,14 +3674,17 @@ where
 /// Format a Python exception using `traceback.format_exception`.
 /// For AssertionErrors, also attempts to extract the actual vs expected values
 /// from the local scope.
-fn format_pyerr(py: Python<'_>, err: &PyErr) -> PyResult<String> {
+///
+/// Enrichment is skipped for frames in files matching `config.enrich_exclude`.
+fn format_pyerr(py: Python<'_>, err: &PyErr, config: &RunConfiguration) -> PyResult<String> {
     let traceback = py.import("traceback")?;
     let exc_type: Py<PyAny> = err.get_type(py).unbind().into();
     let exc_value: Py<PyAny> = err.value(py).clone().unbind().into();
-    let exc_tb: Py<PyAny> = err
-        .traceback(py)
-        .map(|tb| tb.clone().unbind().into())
-        .unwrap_or_else(|| py.None());
+    let exc_tb: Py<PyAny> = match err.traceback(py) {
+        Some(tb) if config.filter_traceback => filter_library_frames(py, tb.into_any())?.unbind(),
+        Some(tb) => tb.into_any().unbind(),
+        None => py.None(),
+    };
     let formatted: Vec<String> = traceback
         .call_method1("format_exception", (exc_type, exc_value, exc_tb))?
         .extract()?;
@@ -2328,8 +3694,10 @@ fn format_pyerr(py: Python<'_>, err: &PyErr) -> PyResult<String> {
     // For AssertionError, try to extract comparison values from the frame
     if err.is_instance_of::<pyo3::exceptions::PyAssertionError>(py) {
         if let Some(tb) = err.traceback(py) {
-            if let Ok(enriched) = enrich_assertion_error(py, &tb, &result) {
-                result = enriched;
+            if !enrichment_excluded(&tb, &config.enrich_exclude)? {
+                if let Ok(enriched) = enrich_assertion_error(py, &tb, &result) {
+                    result = enriched;
+                }
             }
         }
     }
@@ -2337,6 +3705,111 @@ fn format_pyerr(py: Python<'_>, err: &PyErr) -> PyResult<String> {
     Ok(result)
 }
 
+/// Compile `enrich_exclude` patterns into a glob set.
+pub fn build_enrich_exclude(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| format!("Invalid enrich_exclude pattern '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Whether the frame the assertion enricher evaluates in belongs to a file
+/// excluded from enrichment. Patterns match either the path relative to the
+/// working directory or the absolute path.
+fn enrichment_excluded(
+    tb: &pyo3::Bound<'_, pyo3::types::PyTraceback>,
+    patterns: &[String],
+) -> PyResult<bool> {
+    if patterns.is_empty() {
+        return Ok(false);
+    }
+    // Patterns were validated when the run was configured
+    let Ok(globs) = build_enrich_exclude(patterns) else {
+        return Ok(false);
+    };
+    let filename: String = tb
+        .getattr("tb_frame")?
+        .getattr("f_code")?
+        .getattr("co_filename")?
+        .extract()?;
+    let path = Path::new(&filename);
+    Ok(globs.is_match(to_relative_path(path)) || globs.is_match(path))
+}
+
+/// Drop traceback entries that live in the stdlib or site-packages so the
+/// user's own frames are not buried under library internals.
+///
+/// Returns the traceback unchanged when every frame belongs to a library.
+fn filter_library_frames<'py>(
+    py: Python<'py>,
+    tb: Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let library_roots = library_roots(py)?;
+
+    let mut kept = Vec::new();
+    let mut current = tb.clone();
+    while !current.is_none() {
+        let filename: String = current
+            .getattr("tb_frame")?
+            .getattr("f_code")?
+            .getattr("co_filename")?
+            .extract()?;
+        if !is_library_file(&filename, &library_roots) {
+            kept.push(current.clone());
+        }
+        current = current.getattr("tb_next")?;
+    }
+
+    if kept.is_empty() {
+        return Ok(tb);
+    }
+
+    // Rebuild the chain from the kept entries, innermost first
+    let traceback_type = py.import("types")?.getattr("TracebackType")?;
+    let mut filtered = py.None().into_bound(py);
+    for entry in kept.iter().rev() {
+        filtered = traceback_type.call1((
+            filtered,
+            entry.getattr("tb_frame")?,
+            entry.getattr("tb_lasti")?,
+            entry.getattr("tb_lineno")?,
+        ))?;
+    }
+    Ok(filtered)
+}
+
+/// Directories holding the standard library and installed packages.
+fn library_roots(py: Python<'_>) -> PyResult<Vec<PathBuf>> {
+    let paths = py.import("sysconfig")?.call_method0("get_paths")?;
+    let mut roots = Vec::new();
+    for key in ["stdlib", "platstdlib", "purelib", "platlib"] {
+        if let Ok(path) = paths
+            .get_item(key)
+            .and_then(|path| path.extract::<String>())
+        {
+            roots.push(PathBuf::from(path));
+        }
+    }
+    Ok(roots)
+}
+
+/// Whether a traceback filename belongs to the stdlib or an installed package.
+fn is_library_file(filename: &str, library_roots: &[PathBuf]) -> bool {
+    if filename.starts_with("<frozen ") {
+        return true;
+    }
+    let path = Path::new(filename);
+    path.components().any(|component| {
+        matches!(
+            component.as_os_str().to_str(),
+            Some("site-packages" | "dist-packages")
+        )
+    }) || library_roots.iter().any(|root| path.starts_with(root))
+}
+
 /// Attempt to enrich an AssertionError with actual vs expected values
 /// by inspecting the local variables in the frame where the assertion failed.
 fn enrich_assertion_error(
@@ -2356,12 +3829,18 @@ fn enrich_assertion_error(
             let assertion = line.trim();
 
             // Try to extract comparison values
-            if let Some(values) = extract_comparison_values(py, assertion, &locals)? {
+            if let Some((expected, received, diff)) =
+                extract_comparison_values(py, assertion, &locals)?
+            {
                 // Append the extracted values to the formatted traceback
-                return Ok(format!(
+                let mut enriched = format!(
                     "{}\n__RUSTEST_ASSERTION_VALUES__\nExpected: {}\nReceived: {}",
-                    formatted, values.0, values.1
-                ));
+                    formatted, expected, received
+                );
+                for line in diff {
+                    enriched.push_str(&format!("\nDiff: {}", line));
+                }
+                return Ok(enriched);
             }
             break;
         }
@@ -2370,12 +3849,13 @@ fn enrich_assertion_error(
     Ok(formatted.to_string())
 }
 
-/// Extract the actual comparison values from local variables
+/// Extract the actual comparison values from local variables, plus a
+/// structural diff when both sides of an `==` are containers
 fn extract_comparison_values(
     py: Python<'_>,
     assertion: &str,
     locals: &pyo3::Bound<'_, pyo3::PyAny>,
-) -> PyResult<Option<(String, String)>> {
+) -> PyResult<Option<(String, String, Vec<String>)>> {
     use regex::Regex;
 
     // Match patterns like: assert x == y, assert a != b, assert response.status_code == 404, etc.
@@ -2388,39 +3868,56 @@ fn extract_comparison_values(
         let operator = &caps[2];
 
         // Try to evaluate both expressions in the locals context
-        let eval_expr = |expr: &str| -> Option<String> {
+        let eval_expr = |expr: &str| -> Option<pyo3::Bound<'_, pyo3::PyAny>> {
             // First try direct variable lookup for simple cases
             if let Ok(true) = locals.contains(expr) {
                 if let Ok(val) = locals.get_item(expr) {
-                    return val.repr().ok().map(|r| r.to_string());
+                    return Some(val);
                 }
             }
 
             // For complex expressions (e.g., response.status_code), try eval
             #[allow(deprecated)]
             let locals_dict: Option<&pyo3::Bound<'_, pyo3::types::PyDict>> = locals.downcast().ok();
-            match locals_dict.and_then(|d| {
+            locals_dict.and_then(|d| {
                 py.eval(&std::ffi::CString::new(expr).ok()?, Some(d), None)
                     .ok()
-            }) {
-                Some(val) => val.repr().ok().map(|r| r.to_string()),
-                None => None,
-            }
+            })
         };
 
         // Try to evaluate both sides
         let left_val = eval_expr(left_expr);
         let right_val = eval_expr(right_expr);
 
-        if let (Some(left_repr), Some(right_repr)) = (left_val, right_val) {
+        if let (Some(left), Some(right)) = (left_val, right_val) {
+            let repr = |val: &pyo3::Bound<'_, pyo3::PyAny>| {
+                val.repr()
+                    .map(|r| r.to_string())
+                    .unwrap_or_else(|_| "<unrepresentable>".to_string())
+            };
+            let (left_repr, right_repr) = (repr(&left), repr(&right));
+
+            // Floats that only differ by rounding error get the size of the
+            // difference, so users can tell precision noise from a real bug
+            let left_repr = match operator {
+                "==" => match float_precision_delta(&left, &right) {
+                    Some(delta) => format!("{} ({})", left_repr, delta),
+                    None => left_repr,
+                },
+                _ => left_repr,
+            };
+
             // For == comparisons, left is actual, right is expected (by convention)
             // For comparison operators (>, <, >=, <=), left is the value being tested,
             // right is the threshold/expected value
             return Ok(match operator {
-                "==" => Some((right_repr, left_repr)), // (expected, actual)
-                "!=" => Some((left_repr, right_repr)), // Show both sides
-                ">=" | "<=" | ">" | "<" => Some((right_repr, left_repr)), // (threshold, actual)
-                _ => Some((left_repr, right_repr)),
+                "==" => {
+                    let diff = structural_diff(py, &right, &left).unwrap_or_default();
+                    Some((right_repr, left_repr, diff)) // (expected, actual)
+                }
+                "!=" => Some((left_repr, right_repr, Vec::new())), // Show both sides
+                ">=" | "<=" | ">" | "<" => Some((right_repr, left_repr, Vec::new())), // (threshold, actual)
+                _ => Some((left_repr, right_repr, Vec::new())),
             });
         }
     }
@@ -2428,6 +3925,71 @@ fn extract_comparison_values(
     Ok(None)
 }
 
+/// Path-by-path differences between two dicts or lists, computed by
+/// `rustest.assertion_diff`. Empty when either side is not a container.
+fn structural_diff(
+    py: Python<'_>,
+    expected: &pyo3::Bound<'_, pyo3::PyAny>,
+    actual: &pyo3::Bound<'_, pyo3::PyAny>,
+) -> PyResult<Vec<String>> {
+    let module = py.import("rustest.assertion_diff")?;
+    for value in [expected, actual] {
+        if !module.call_method1("is_container", (value,))?.is_truthy()? {
+            return Ok(Vec::new());
+        }
+    }
+    module
+        .call_method1("structural_diff", (expected, actual))?
+        .extract()
+}
+
+/// Describe the absolute and relative difference between two numbers when
+/// at least one is a float and they differ only beyond a tiny epsilon.
+fn float_precision_delta(
+    left: &pyo3::Bound<'_, pyo3::PyAny>,
+    right: &pyo3::Bound<'_, pyo3::PyAny>,
+) -> Option<String> {
+    use pyo3::types::{PyBool, PyFloat, PyInt};
+
+    const RELATIVE_EPSILON: f64 = 1e-9;
+
+    let is_number = |val: &pyo3::Bound<'_, pyo3::PyAny>| {
+        (val.is_instance_of::<PyFloat>() || val.is_instance_of::<PyInt>())
+            && !val.is_instance_of::<PyBool>()
+    };
+    if !is_number(left) || !is_number(right) {
+        return None;
+    }
+    if !left.is_instance_of::<PyFloat>() && !right.is_instance_of::<PyFloat>() {
+        return None;
+    }
+
+    let a: f64 = left.extract().ok()?;
+    let b: f64 = right.extract().ok()?;
+    let abs_diff = (a - b).abs();
+    if abs_diff == 0.0 || !abs_diff.is_finite() {
+        return None;
+    }
+    let rel_diff = abs_diff / a.abs().max(b.abs());
+    if rel_diff > RELATIVE_EPSILON {
+        return None;
+    }
+
+    Some(format!(
+        "differs by {:e}, relative {:e}; likely floating point precision",
+        abs_diff, rel_diff
+    ))
+}
+
+/// Reorder results to match discovery order, given each result's position.
+/// The sort is stable, so results without a known position keep their
+/// execution order at the end.
+fn in_discovery_order(results: Vec<PyTestResult>, positions: Vec<usize>) -> Vec<PyTestResult> {
+    let mut indexed: Vec<(usize, PyTestResult)> = positions.into_iter().zip(results).collect();
+    indexed.sort_by_key(|(position, _)| *position);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
 /// Extract the package name from a test file path.
 ///
 /// The package is determined by the parent directory of the test file.
@@ -2441,24 +4003,80 @@ fn extract_package_name(path: &std::path::Path) -> String {
         .unwrap_or_default()
 }
 
+/// Group a module's tests by class, in the order each class first appears.
+/// This is the order the tests of a module actually run in.
+fn group_tests_by_class<'a>(
+    tests: impl IntoIterator<Item = &'a TestCase>,
+) -> IndexMap<Option<String>, Vec<&'a TestCase>> {
+    let mut tests_by_class: IndexMap<Option<String>, Vec<&TestCase>> = IndexMap::new();
+    for test in tests {
+        tests_by_class
+            .entry(test.class_name.clone())
+            .or_default()
+            .push(test);
+    }
+    tests_by_class
+}
+
+/// Map each test to the parametrized session fixture values (by cache key)
+/// that it is the last test to use, so they can be finalized right after it.
+/// Tests are walked in execution order, not discovery order, because a class
+/// runs all of its tests together even when shuffling or --ff split them up.
+fn session_param_last_uses(modules: &[TestModule]) -> HashMap<String, Vec<String>> {
+    let mut last_use: IndexMap<String, String> = IndexMap::new();
+    for module in modules {
+        let tests_by_class = group_tests_by_class(&module.tests);
+        for test in tests_by_class.values().flatten() {
+            for (fixture_name, param_idx) in &test.fixture_param_indices {
+                let is_session = module
+                    .fixtures
+                    .get(fixture_name)
+                    .is_some_and(|fixture| fixture.scope == FixtureScope::Session);
+                if is_session {
+                    last_use.insert(format!("{}[{}]", fixture_name, param_idx), test.unique_id());
+                }
+            }
+        }
+    }
+
+    let mut releases: HashMap<String, Vec<String>> = HashMap::new();
+    for (cache_key, test_id) in last_use {
+        releases.entry(test_id).or_default().push(cache_key);
+    }
+    releases
+}
+
 /// Finalize generator fixtures by running their teardown code.
 /// This calls next() on each generator (or anext() for async generators),
 /// which will execute the code after yield.
 /// The generator will raise StopIteration (or StopAsyncIteration) when complete, which we catch and ignore.
+/// Any other exception is recorded as a teardown error of `scope` and the remaining teardowns still run.
 /// For async generators, use the provided event loop if available; otherwise get the running loop or create one.
 fn finalize_generators(
     py: Python<'_>,
     generators: &mut Vec<Py<PyAny>>,
+    scope: FixtureScope,
     event_loop: Option<&Py<PyAny>>,
 ) {
-    // Process generators in reverse order (LIFO) to match pytest behavior
+    // Process generators in reverse order (LIFO) to match pytest behavior.
+    // Dependencies are always set up (and pushed) before their dependents, so
+    // this also tears dependent fixtures down before the fixtures they use.
     for generator in generators.drain(..).rev() {
         let gen_bound = generator.bind(py);
+        let teardown_start = Instant::now();
 
         // Check if this is an async generator by checking if it has __anext__ method
         let is_async_gen = gen_bound.hasattr("__anext__").unwrap_or(false);
+        // Anything that is neither kind of generator is a finalizer runner
+        // registered through `request.addfinalizer`, called as-is
+        let is_finalizer = !is_async_gen && !gen_bound.hasattr("__next__").unwrap_or(false);
+        if !is_finalizer {
+            FIXTURE_TEARDOWNS.with(|count| count.set(count.get() + 1));
+        }
 
-        let result = if is_async_gen {
+        let result = if is_finalizer {
+            gen_bound.call0().map(|_| ())
+        } else if is_async_gen {
             // For async generators, use anext() with the scoped event loop
             match py.import("builtins").and_then(|builtins| {
                 let anext = builtins.getattr("anext")?;
@@ -2484,18 +4102,25 @@ fn finalize_generators(
             gen_bound.call_method0("__next__").map(|_| ())
         };
 
-        // Ignore StopIteration/StopAsyncIteration (expected) and log other errors
+        let name = gen_bound
+            .getattr("__name__")
+            .and_then(|name| name.extract::<String>())
+            .unwrap_or_else(|_| "fixture".to_string());
+        // Ignore StopIteration/StopAsyncIteration (expected) and record other errors.
+        // Like pytest, a failing teardown doesn't stop the remaining ones from running.
         if let Err(err) = result {
-            // Check if it's StopIteration or StopAsyncIteration - that's expected and OK
             if !err.is_instance_of::<pyo3::exceptions::PyStopIteration>(py)
                 && !err.is_instance_of::<pyo3::exceptions::PyStopAsyncIteration>(py)
             {
-                // For other exceptions, we could log them, but for now we'll ignore
-                // to avoid breaking the test run. In pytest, teardown errors are collected
-                // but don't stop other teardowns from running.
-                eprintln!("Warning: Error during fixture teardown: {}", err);
+                TEARDOWN_ERRORS.with(|errors| errors.borrow_mut().push((name.clone(), scope, err)));
             }
         }
+        record_span(
+            &format!("teardown {}", name),
+            "fixture",
+            teardown_start,
+            serde_json::json!({}),
+        );
     }
 }
 
@@ -2505,7 +4130,7 @@ fn write_failed_tests_cache(report: &PyRunReport) -> PyResult<()> {
 
     // Collect all failed test IDs
     for result in &report.results {
-        if result.status == "failed" {
+        if result.status == "failed" || result.status == "error" {
             failed_tests.insert(result.unique_id());
         }
     }
@@ -2516,6 +4141,41 @@ fn write_failed_tests_cache(report: &PyRunReport) -> PyResult<()> {
     Ok(())
 }
 
+/// Installs the configured event loop policy for the session and restores
+/// the previous one when dropped, so `new_event_loop` uses e.g. uvloop.
+struct EventLoopPolicyGuard {
+    previous: Option<Py<PyAny>>,
+}
+
+impl EventLoopPolicyGuard {
+    fn install(py: Python<'_>, policy: Option<&Py<PyAny>>) -> PyResult<Self> {
+        let Some(policy) = policy else {
+            return Ok(Self { previous: None });
+        };
+        let asyncio = py.import("asyncio")?;
+        let previous = asyncio.call_method0("get_event_loop_policy")?.unbind();
+        asyncio.call_method1("set_event_loop_policy", (policy.bind(py),))?;
+        Ok(Self {
+            previous: Some(previous),
+        })
+    }
+}
+
+impl Drop for EventLoopPolicyGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            Python::attach(|py| {
+                let restored = py.import("asyncio").and_then(|asyncio| {
+                    asyncio.call_method1("set_event_loop_policy", (previous.bind(py),))
+                });
+                if let Err(err) = restored {
+                    eprintln!("Warning: failed to restore event loop policy: {}", err);
+                }
+            });
+        }
+    }
+}
+
 /// Close an event loop if it exists, properly cleaning up pending tasks.
 ///
 /// This follows the proper asyncio shutdown pattern: cancel all tasks, then
@@ -2538,7 +4198,11 @@ fn close_event_loop(py: Python<'_>, event_loop: &mut Option<Py<PyAny>>) {
             // Cancel pending tasks and await their completion
             if let Ok(asyncio) = py.import("asyncio") {
                 if let Ok(tasks) = asyncio.call_method1("all_tasks", (loop_bound,)) {
-                    if let Ok(task_list) = tasks.extract::<Vec<Py<PyAny>>>() {
+                    // all_tasks() returns a set, which does not extract as a sequence
+                    let task_list: PyResult<Vec<Py<PyAny>>> = tasks
+                        .try_iter()
+                        .and_then(|iter| iter.map(|task| task.map(Bound::unbind)).collect());
+                    if let Ok(task_list) = task_list {
                         if !task_list.is_empty() {
                             // Cancel all pending tasks
                             for task in &task_list {
@@ -0,0 +1,262 @@
+//! Keyword expression parser and evaluator for `-k`.
+//!
+//! A keyword expression combines case-insensitive substring terms with the
+//! boolean operators `and`, `or` and `not`, plus parentheses for grouping.
+//! Each term is matched against the test's display name, its file path and
+//! the names of its marks.
+//!
+//! Examples:
+//! - "login" - any test whose name or path contains "login"
+//! - "login and not slow" - "login" tests that are not named or marked "slow"
+//! - "(api or cli) and test_parse[" - grouping with pytest-style ids
+
+use crate::model::TestCase;
+
+/// A keyword expression that can be evaluated against a test case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeywordExpr {
+    /// A substring term (e.g., "login")
+    Term(String),
+    /// Logical NOT (e.g., "not slow")
+    Not(Box<KeywordExpr>),
+    /// Logical AND (e.g., "login and api")
+    And(Box<KeywordExpr>, Box<KeywordExpr>),
+    /// Logical OR (e.g., "login or logout")
+    Or(Box<KeywordExpr>, Box<KeywordExpr>),
+}
+
+impl KeywordExpr {
+    /// Parse a keyword expression from a string.
+    ///
+    /// A pattern without operators parses to a single term, so plain
+    /// substrings keep their original meaning.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut parser = Parser::new(input);
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.current() {
+            return Err(format!("Unexpected token after expression: {:?}", token));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate this expression against a test case.
+    pub fn matches(&self, test_case: &TestCase) -> bool {
+        let mut keywords = vec![
+            test_case.display_name.to_lowercase(),
+            test_case.path.display().to_string().to_lowercase(),
+        ];
+        keywords.extend(test_case.marks.iter().map(|mark| mark.name.to_lowercase()));
+        self.matches_keywords(&keywords)
+    }
+
+    /// Evaluate this expression against pre-lowercased keywords.
+    fn matches_keywords(&self, keywords: &[String]) -> bool {
+        match self {
+            KeywordExpr::Term(term) => {
+                let term = term.to_lowercase();
+                keywords.iter().any(|keyword| keyword.contains(&term))
+            }
+            KeywordExpr::Not(expr) => !expr.matches_keywords(keywords),
+            KeywordExpr::And(left, right) => {
+                left.matches_keywords(keywords) && right.matches_keywords(keywords)
+            }
+            KeywordExpr::Or(left, right) => {
+                left.matches_keywords(keywords) || right.matches_keywords(keywords)
+            }
+        }
+    }
+}
+
+/// Tokens for the keyword expression parser.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Term(String),
+    Not,
+    And,
+    Or,
+    LParen,
+    RParen,
+}
+
+/// Split the input into tokens.
+///
+/// Terms run until whitespace or a parenthesis so that parametrize ids,
+/// module paths and `::` separators can be used verbatim.
+fn tokenize(input: &str) -> Vec<Token> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let ch = chars[pos];
+        if ch.is_whitespace() {
+            pos += 1;
+        } else if ch == '(' {
+            tokens.push(Token::LParen);
+            pos += 1;
+        } else if ch == ')' {
+            tokens.push(Token::RParen);
+            pos += 1;
+        } else {
+            let start = pos;
+            while pos < chars.len()
+                && !chars[pos].is_whitespace()
+                && chars[pos] != '('
+                && chars[pos] != ')'
+            {
+                pos += 1;
+            }
+            let word: String = chars[start..pos].iter().collect();
+            tokens.push(match word.as_str() {
+                "not" => Token::Not,
+                "and" => Token::And,
+                "or" => Token::Or,
+                _ => Token::Term(word),
+            });
+        }
+    }
+    tokens
+}
+
+/// A recursive descent parser for keyword expressions.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Self {
+        Self {
+            tokens: tokenize(input),
+            pos: 0,
+        }
+    }
+
+    fn current(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Parse an OR expression (lowest precedence).
+    fn parse_or(&mut self) -> Result<KeywordExpr, String> {
+        let mut left = self.parse_and()?;
+        while matches!(self.current(), Some(Token::Or)) {
+            self.advance();
+            let right = self.parse_and()?;
+            left = KeywordExpr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// Parse an AND expression (medium precedence).
+    fn parse_and(&mut self) -> Result<KeywordExpr, String> {
+        let mut left = self.parse_not()?;
+        while matches!(self.current(), Some(Token::And)) {
+            self.advance();
+            let right = self.parse_not()?;
+            left = KeywordExpr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// Parse a NOT expression (high precedence).
+    fn parse_not(&mut self) -> Result<KeywordExpr, String> {
+        if matches!(self.current(), Some(Token::Not)) {
+            self.advance();
+            let expr = self.parse_not()?;
+            Ok(KeywordExpr::Not(Box::new(expr)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    /// Parse a primary expression (term or parenthesized expression).
+    fn parse_primary(&mut self) -> Result<KeywordExpr, String> {
+        match self.advance() {
+            Some(Token::Term(term)) => Ok(KeywordExpr::Term(term)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Expected ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("Unexpected token: {:?}", token)),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keywords(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_lowercase()).collect()
+    }
+
+    #[test]
+    fn test_parse_plain_substring() {
+        let expr = KeywordExpr::parse("test_parse[1-2]").unwrap();
+        assert_eq!(expr, KeywordExpr::Term("test_parse[1-2]".to_string()));
+    }
+
+    #[test]
+    fn test_parse_and_not() {
+        let expr = KeywordExpr::parse("login and not slow").unwrap();
+        assert_eq!(
+            expr,
+            KeywordExpr::And(
+                Box::new(KeywordExpr::Term("login".to_string())),
+                Box::new(KeywordExpr::Not(Box::new(KeywordExpr::Term(
+                    "slow".to_string()
+                ))))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_grouping() {
+        let expr = KeywordExpr::parse("(api or cli) and parse").unwrap();
+        assert_eq!(
+            expr,
+            KeywordExpr::And(
+                Box::new(KeywordExpr::Or(
+                    Box::new(KeywordExpr::Term("api".to_string())),
+                    Box::new(KeywordExpr::Term("cli".to_string()))
+                )),
+                Box::new(KeywordExpr::Term("parse".to_string()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(KeywordExpr::parse("login and").is_err());
+        assert!(KeywordExpr::parse("(login").is_err());
+        assert!(KeywordExpr::parse("login slow").is_err());
+        assert!(KeywordExpr::parse("").is_err());
+    }
+
+    #[test]
+    fn test_matches_names_and_marks() {
+        let expr = KeywordExpr::parse("login and not slow").unwrap();
+        assert!(expr.matches_keywords(&keywords(&["test_login", "tests/test_auth.py"])));
+        assert!(!expr.matches_keywords(&keywords(&["test_login", "tests/test_auth.py", "slow"])));
+        assert!(!expr.matches_keywords(&keywords(&["test_logout", "tests/test_auth.py"])));
+    }
+
+    #[test]
+    fn test_matches_case_insensitively() {
+        let expr = KeywordExpr::parse("LOGIN or Api").unwrap();
+        assert!(expr.matches_keywords(&keywords(&["test_login"])));
+        assert!(expr.matches_keywords(&keywords(&["test_API_client"])));
+        assert!(!expr.matches_keywords(&keywords(&["test_other"])));
+    }
+}
@@ -8,11 +8,16 @@
 #![allow(clippy::useless_conversion)]
 
 mod cache;
+mod changed_lines;
 mod discovery;
 mod execution;
+mod keyword_expr;
 mod mark_expr;
+mod markers;
 mod model;
+mod notebook;
 mod output;
+mod parallel;
 mod python_support;
 
 #[cfg(test)]
@@ -22,12 +27,21 @@ mod python_support_tests;
 
 use discovery::discover_tests;
 use execution::{resolve_fixture_for_request, run_collected_tests};
-use model::{CollectionError, FixtureScope, LastFailedMode, PyRunReport, RunConfiguration};
+use indexmap::IndexMap;
+use markers::MarkerInfo;
+use model::{
+    CollectionError, CollectionFrame, FixtureScope, LastFailedMode, PyRunReport, RunConfiguration,
+    TeardownError,
+};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use pyo3::wrap_pyfunction;
 use python_support::PyPaths;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[pyfunction(signature = (paths, pattern = None, mark_expr = None, workers = None, capture_output = true, enable_codeblocks = true, last_failed_mode = "none", fail_fast = false, pytest_compat = false, verbose = false, ascii = false, no_color = false, event_callback = None, default_test_loop_scope = "function", default_fixture_loop_scope = "function"))]
+#[pyfunction(signature = (paths, pattern = None, mark_expr = None, workers = None, capture_output = true, enable_codeblocks = true, last_failed_mode = "none", fail_fast = false, pytest_compat = false, verbose = false, ascii = false, no_color = false, event_callback = None, default_test_loop_scope = "function", default_fixture_loop_scope = "function", compact = false, force_color = false, max_name_width = None, max_processes = None, trace_config = false, max_file_size = None, event_loop_policy = None, filter_traceback = true, continue_on_collection_errors = true, check = false, status_symbols = None, sample = None, shuffle_seed = None, github_annotations = false, tap_output = None, wrap_failures = false, passed_only = None, discovery_threads = None, run_slow = false, enrich_exclude = None, resume = false, xfail_list = None, line_trace = false, sqlite_path = None, changed_lines = None, slowest_files = None, asyncio_debug = false, trace_json = None, group_failures = false, loop_scope_strict = true, notebooks = false, junit_xml_path = None, json_report_path = None, durations = None, on_complete = None, failure_exceptions = None, log_level = None, shuffle = false, run_skipped = false, import_timeout = None, show_test_fixtures = false, collapse_param_failures = false, max_failures = None, node_ids = None, worker = false))]
 #[allow(clippy::too_many_arguments)]
 fn run(
     py: Python<'_>,
@@ -46,7 +60,52 @@ fn run(
     event_callback: Option<Py<PyAny>>,
     default_test_loop_scope: &str,
     default_fixture_loop_scope: &str,
-) -> PyResult<PyRunReport> {
+    compact: bool,
+    force_color: bool,
+    max_name_width: Option<usize>,
+    max_processes: Option<usize>,
+    trace_config: bool,
+    max_file_size: Option<u64>,
+    event_loop_policy: Option<Py<PyAny>>,
+    filter_traceback: bool,
+    continue_on_collection_errors: bool,
+    check: bool,
+    status_symbols: Option<HashMap<String, String>>,
+    sample: Option<usize>,
+    shuffle_seed: Option<u64>,
+    github_annotations: bool,
+    tap_output: Option<PathBuf>,
+    wrap_failures: bool,
+    passed_only: Option<PathBuf>,
+    discovery_threads: Option<usize>,
+    run_slow: bool,
+    enrich_exclude: Option<Vec<String>>,
+    resume: bool,
+    xfail_list: Option<PathBuf>,
+    line_trace: bool,
+    sqlite_path: Option<PathBuf>,
+    changed_lines: Option<String>,
+    slowest_files: Option<usize>,
+    asyncio_debug: bool,
+    trace_json: Option<PathBuf>,
+    group_failures: bool,
+    loop_scope_strict: bool,
+    notebooks: bool,
+    junit_xml_path: Option<PathBuf>,
+    json_report_path: Option<PathBuf>,
+    durations: Option<usize>,
+    on_complete: Option<Py<PyAny>>,
+    failure_exceptions: Option<Vec<String>>,
+    log_level: Option<String>,
+    shuffle: bool,
+    run_skipped: bool,
+    import_timeout: Option<f64>,
+    show_test_fixtures: bool,
+    collapse_param_failures: bool,
+    max_failures: Option<usize>,
+    node_ids: Option<Vec<String>>,
+    worker: bool,
+) -> PyResult<Py<PyRunReport>> {
     let last_failed_mode = LastFailedMode::from_str(last_failed_mode)
         .map_err(pyo3::exceptions::PyValueError::new_err)?;
     let default_test_loop_scope = FixtureScope::from_str(default_test_loop_scope)
@@ -54,7 +113,7 @@ fn run(
     let default_fixture_loop_scope = FixtureScope::from_str(default_fixture_loop_scope)
         .map_err(pyo3::exceptions::PyValueError::new_err)?;
 
-    let config = RunConfiguration::new(
+    let mut config = RunConfiguration::new(
         pattern,
         mark_expr,
         workers,
@@ -70,12 +129,139 @@ fn run(
         default_test_loop_scope,
         default_fixture_loop_scope,
     );
+    config.compact = compact;
+    config.force_color = force_color;
+    config.max_name_width = max_name_width;
+    config.set_max_processes(max_processes);
+    config.trace_config = trace_config;
+    config.max_file_size = max_file_size;
+    config.event_loop_policy = event_loop_policy;
+    config.filter_traceback = filter_traceback;
+    config.continue_on_collection_errors = continue_on_collection_errors;
+    config.check = check;
+    if let Some(ref symbols) = status_symbols {
+        output::validate_status_symbols(symbols)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    }
+    config.status_symbols = status_symbols.unwrap_or_default();
+    config.sample = sample;
+    config.shuffle_seed = shuffle_seed;
+    config.github_annotations = github_annotations;
+    config.tap_output = tap_output;
+    config.wrap_failures = wrap_failures;
+    config.passed_only = passed_only;
+    config.discovery_threads = discovery_threads;
+    config.run_slow = run_slow;
+    if let Some(ref patterns) = enrich_exclude {
+        execution::build_enrich_exclude(patterns)
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    }
+    config.enrich_exclude = enrich_exclude.unwrap_or_default();
+    config.resume = resume;
+    if let Some(path) = xfail_list {
+        config.load_xfail_list(path)?;
+    }
+    config.line_trace = line_trace;
+    config.sqlite_path = sqlite_path;
+    config.changed_lines = changed_lines;
+    config.slowest_files = slowest_files;
+    config.asyncio_debug = asyncio_debug;
+    config.trace_json = trace_json;
+    config.group_failures = group_failures;
+    config.loop_scope_strict = loop_scope_strict;
+    config.notebooks = notebooks;
+    config.junit_xml_path = junit_xml_path;
+    config.json_report_path = json_report_path;
+    config.durations = durations;
+    config.on_complete = on_complete;
+    config.failure_exceptions = failure_exceptions;
+    config.log_level = log_level;
+    config.shuffle = shuffle;
+    config.run_skipped = run_skipped;
+    config.import_timeout = import_timeout
+        .map(Duration::try_from_secs_f64)
+        .transpose()
+        .map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid import_timeout: {}", e))
+        })?;
+    config.show_test_fixtures = show_test_fixtures;
+    config.collapse_param_failures = collapse_param_failures;
+    config.max_failures = max_failures;
+    config.node_ids = node_ids;
+    config.worker = worker;
+    // Addopts only fill in what the arguments left unset, so apply them last.
+    // A worker session already got the parent's options with them applied.
+    if !config.worker {
+        config
+            .apply_env_addopts()
+            .map_err(pyo3::exceptions::PyValueError::new_err)?;
+    }
     let input_paths = PyPaths::from_vec(paths);
-    let (collected, collection_errors) = discover_tests(py, &input_paths, &config)?;
-    let report = run_collected_tests(py, &collected, &collection_errors, &config)?;
+    let discovery = discover_tests(py, &input_paths, &config)?;
+    let report = run_collected_tests(py, &discovery, &config)?;
+    let report = Py::new(py, report)?;
+    notify_on_complete(py, &config, &report);
     Ok(report)
 }
 
+/// Call the `on_complete` hook with the finished report and the report files
+/// the run wrote. An exception from the hook is printed, never propagated, so
+/// the caller still gets the report.
+fn notify_on_complete(py: Python<'_>, config: &RunConfiguration, report: &Py<PyRunReport>) {
+    let Some(hook) = config.on_complete.as_ref() else {
+        return;
+    };
+    let artifacts: Vec<String> = report
+        .borrow(py)
+        .artifacts
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    if let Err(err) = hook.bind(py).call1((report.clone_ref(py), artifacts)) {
+        eprintln!("Warning: on_complete hook failed: {}", err);
+    }
+}
+
+/// Marks known to the registry or used by the tests collected from `paths`.
+///
+/// `markers` registers project marks with a description, like pytest's
+/// `markers` ini option. Marks that tests use but nothing registers are
+/// reported with `registered=False`.
+#[pyfunction(signature = (paths, markers = None))]
+fn list_markers(
+    py: Python<'_>,
+    paths: Vec<String>,
+    markers: Option<Bound<'_, PyDict>>,
+) -> PyResult<Vec<MarkerInfo>> {
+    let mut registered = IndexMap::new();
+    if let Some(markers) = markers {
+        for (name, description) in markers.iter() {
+            registered.insert(name.extract::<String>()?, description.extract::<String>()?);
+        }
+    }
+
+    let mut config = RunConfiguration::new(
+        None,
+        None,
+        None,
+        true,
+        true,
+        LastFailedMode::None,
+        false,
+        false,
+        false,
+        false,
+        false,
+        None,
+        FixtureScope::Function,
+        FixtureScope::Function,
+    );
+    // Slow tests are deselected by default; their marks still count
+    config.run_slow = true;
+    let modules = discover_tests(py, &PyPaths::from_vec(paths), &config)?.modules;
+    Ok(markers::list_markers(&modules, &registered))
+}
+
 #[pyfunction]
 fn getfixturevalue(name: &str) -> PyResult<Py<PyAny>> {
     resolve_fixture_for_request(name)
@@ -92,7 +278,11 @@ fn rust(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 
     m.add_class::<PyRunReport>()?;
     m.add_class::<CollectionError>()?;
+    m.add_class::<TeardownError>()?;
+    m.add_class::<CollectionFrame>()?;
+    m.add_class::<MarkerInfo>()?;
     m.add_function(wrap_pyfunction!(run, m)?)?;
+    m.add_function(wrap_pyfunction!(list_markers, m)?)?;
     m.add_function(wrap_pyfunction!(getfixturevalue, m)?)?;
 
     // Event types for event stream consumers
@@ -113,6 +303,7 @@ fn rust(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::path::{Path, PathBuf};
 
     use crate::discovery::discover_tests;
@@ -120,10 +311,31 @@ mod tests {
     use crate::model::{FixtureScope, LastFailedMode, RunConfiguration};
     use crate::python_support::PyPaths;
     use pyo3::prelude::PyAnyMethods;
-    use pyo3::types::PyList;
+    use pyo3::types::{PyDict, PyDictMethods, PyList, PyModule};
     use pyo3::Bound;
     use pyo3::Python;
 
+    /// The configuration a plain `rustest` run uses; tests override only the
+    /// fields they exercise.
+    fn test_config() -> RunConfiguration {
+        RunConfiguration::new(
+            None,
+            None,
+            None,
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        )
+    }
+
     fn ensure_python_package_on_path(py: Python<'_>) {
         let sys = py.import("sys").expect("failed to import sys");
         let path = sys.getattr("path").expect("missing sys.path");
@@ -142,13 +354,7 @@ mod tests {
             .join(name)
     }
 
-    fn run_discovery(
-        py: Python<'_>,
-        path: &Path,
-    ) -> (
-        Vec<crate::model::TestModule>,
-        Vec<crate::model::CollectionError>,
-    ) {
+    fn run_discovery(py: Python<'_>, path: &Path) -> crate::model::Discovery {
         let config = RunConfiguration::new(
             None,
             None,
@@ -175,7 +381,7 @@ mod tests {
             ensure_python_package_on_path(py);
             let file_path = sample_test_module("test_basic.py");
 
-            let (modules, _collection_errors) = run_discovery(py, &file_path);
+            let modules = run_discovery(py, &file_path).modules;
             assert_eq!(modules.len(), 1);
             let module = &modules[0];
             assert_eq!(module.tests.len(), 1);
@@ -206,11 +412,10 @@ mod tests {
                 FixtureScope::Function,
             );
             let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
-            let (modules, collection_errors) =
-                discover_tests(py, &paths, &config).expect("discovery should succeed");
-            assert_eq!(modules.len(), 1);
-            let report = run_collected_tests(py, &modules, &collection_errors, &config)
-                .expect("execution should succeed");
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            assert_eq!(discovery.modules.len(), 1);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
             assert_eq!(report.total, 1);
             assert_eq!(report.passed, 1);
             assert_eq!(report.failed, 0);
@@ -243,14 +448,14 @@ mod tests {
                 FixtureScope::Function,
             );
             let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
-            let (modules, collection_errors) =
-                discover_tests(py, &paths, &config).expect("discovery should succeed");
-            let report = run_collected_tests(py, &modules, &collection_errors, &config)
-                .expect("execution should succeed");
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
 
             assert_eq!(report.total, 3);
             assert_eq!(report.passed, 3);
-            let discovered_names: Vec<_> = modules
+            let discovered_names: Vec<_> = discovery
+                .modules
                 .into_iter()
                 .flat_map(|module| module.tests.into_iter().map(|case| case.display_name))
                 .collect();
@@ -301,8 +506,9 @@ mod tests {
                 FixtureScope::Function,
             );
             let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
-            let (modules, _collection_errors) =
-                discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let modules = discover_tests(py, &paths, &config)
+                .expect("discovery should succeed")
+                .modules;
 
             // No modules should match the pattern
             assert_eq!(modules.len(), 0);
@@ -315,7 +521,7 @@ mod tests {
             ensure_python_package_on_path(py);
             let dir_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
 
-            let (modules, _collection_errors) = run_discovery(py, &dir_path);
+            let modules = run_discovery(py, &dir_path).modules;
             // Should discover all test files in the directory
             assert!(modules.len() >= 3);
         });
@@ -344,10 +550,9 @@ mod tests {
                 FixtureScope::Function,
             );
             let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
-            let (modules, collection_errors) =
-                discover_tests(py, &paths, &config).expect("discovery should succeed");
-            let report = run_collected_tests(py, &modules, &collection_errors, &config)
-                .expect("execution should succeed");
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
 
             // Output should not be captured
             assert_eq!(report.results[0].stdout, None);
@@ -364,7 +569,7 @@ mod tests {
             let temp_dir = std::env::temp_dir().join("rustest_empty");
             std::fs::create_dir_all(&temp_dir).unwrap();
 
-            let (modules, _collection_errors) = run_discovery(py, &temp_dir);
+            let modules = run_discovery(py, &temp_dir).modules;
             assert_eq!(modules.len(), 0);
 
             // Cleanup
@@ -422,10 +627,9 @@ mod tests {
                 FixtureScope::Function,
             );
             let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
-            let (modules, collection_errors) =
-                discover_tests(py, &paths, &config).expect("discovery should succeed");
-            let report = run_collected_tests(py, &modules, &collection_errors, &config)
-                .expect("execution should succeed");
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
 
             // Verify statistics are consistent
             assert_eq!(report.total, report.passed + report.failed + report.skipped);
@@ -435,59 +639,3321 @@ mod tests {
     }
 
     #[test]
-    fn test_worker_count_configuration() {
-        let config1 = RunConfiguration::new(
-            None,
-            None,
-            Some(1),
-            true,
-            true,
-            LastFailedMode::None,
-            false,
-            false,
-            false,
-            false,
-            false,
-            None,
-            FixtureScope::Function,
-            FixtureScope::Function,
-        );
-        assert_eq!(config1.worker_count, 1);
+    fn discovers_dynamically_added_class_test_methods() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let file_path = sample_test_module("test_dynamic_class_methods.py");
 
-        let config2 = RunConfiguration::new(
-            None,
-            None,
-            Some(8),
-            true,
-            true,
-            LastFailedMode::None,
-            false,
-            false,
-            false,
-            false,
-            false,
-            None,
-            FixtureScope::Function,
-            FixtureScope::Function,
-        );
-        assert_eq!(config2.worker_count, 8);
+            let discovery = run_discovery(py, &file_path);
+            assert!(discovery.collection_errors.is_empty());
+            let names: Vec<_> = discovery.modules[0]
+                .tests
+                .iter()
+                .map(|case| case.display_name.as_str())
+                .collect();
+            assert_eq!(
+                names,
+                vec![
+                    "TestDynamicMethods::test_added_by_decorator",
+                    "TestDynamicMethods::test_added_by_init_subclass",
+                    "TestDynamicMethods::test_added_on_instance",
+                    "TestDynamicMethods::test_defined_in_body",
+                    "TestConstructorSideEffects::test_defined_in_body",
+                ]
+            );
+        });
+    }
 
-        let config3 = RunConfiguration::new(
-            None,
-            None,
-            None,
-            true,
-            true,
-            LastFailedMode::None,
-            false,
-            false,
-            false,
-            false,
-            false,
-            None,
-            FixtureScope::Function,
-            FixtureScope::Function,
-        );
-        assert!(config3.worker_count >= 1);
+    #[test]
+    fn classes_without_instance_tests_are_not_constructed_during_discovery() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let sys = py.import("sys").unwrap();
+            sys.setattr("rustest_constructor_calls", 0).unwrap();
+            let file_path = sample_test_module("test_dynamic_class_methods.py");
+
+            let discovery = run_discovery(py, &file_path);
+            assert!(discovery.collection_errors.is_empty());
+            let calls: usize = sys
+                .getattr("rustest_constructor_calls")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(calls, 0);
+        });
+    }
+
+    #[test]
+    fn parametrized_session_fixture_releases_previous_value() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let file_path = sample_test_module("test_session_param_teardown.py");
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.failed, 0);
+            assert_eq!(report.passed, 3);
+
+            let events = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_events"))
+                .and_then(|events| events.extract::<Vec<String>>())
+                .expect("fixture events should be recorded");
+            assert_eq!(
+                events,
+                vec!["setup A", "teardown A", "setup B", "teardown B"]
+            );
+        });
+    }
+
+    #[test]
+    fn session_param_last_use_follows_execution_order() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_session_param_order");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_order.py");
+            std::fs::write(
+                &file_path,
+                r#"
+from rustest import fixture
+
+_events = []
+
+
+@fixture(scope="session", params=["A", "B"])
+def resource(request):
+    _events.append(f"setup {request.param}")
+    yield request.param
+    _events.append(f"teardown {request.param}")
+
+
+def test_plain(resource):
+    pass
+
+
+class TestGrouped:
+    def test_method(self, resource):
+        pass
+"#,
+            )
+            .unwrap();
+
+            let mut discovery = run_discovery(py, &file_path);
+            // Reorder as --ff might: the class runs its tests together, so
+            // "A" is last used by the method even though the plain test is
+            // listed after it
+            let order = [
+                "test_plain[B]",
+                "TestGrouped::test_method[A]",
+                "test_plain[A]",
+                "TestGrouped::test_method[B]",
+            ];
+            discovery.modules[0].tests.sort_by_key(|test| {
+                order
+                    .iter()
+                    .position(|name| *name == test.display_name)
+                    .unwrap_or(usize::MAX)
+            });
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            let events = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_events"))
+                .and_then(|events| events.extract::<Vec<String>>())
+                .expect("fixture events should be recorded");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 4);
+            assert_eq!(
+                events.iter().filter(|event| *event == "setup A").count(),
+                1,
+                "{:?}",
+                events
+            );
+        });
+    }
+
+    #[test]
+    fn module_fixtures_tear_down_in_reverse_dependency_order() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let file_path = sample_test_module("test_module_teardown_order.py");
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.passed, 3);
+
+            let order = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_teardown_order"))
+                .and_then(|order| order.extract::<Vec<String>>())
+                .expect("teardown order should be recorded");
+            assert_eq!(order, vec!["b".to_string(), "a".to_string()]);
+        });
+    }
+
+    #[test]
+    fn rng_seed_is_stable_per_node_id() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // rng_seed is rustest-only, so keep the sample out of the pytest-run tree
+            let temp_dir = std::env::temp_dir().join("rustest_rng_seed");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_rng_seed.py");
+            std::fs::write(
+                &file_path,
+                "_seeds = {}\n\n\
+                 def test_first(rng_seed):\n    _seeds['first'] = rng_seed\n\n\
+                 def test_second(rng_seed):\n    _seeds['second'] = rng_seed\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let run_seeds = || {
+                let discovery = run_discovery(py, &file_path);
+                let report =
+                    run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+                assert_eq!(report.passed, 2);
+                discovery.modules[0].tests[0]
+                    .callable
+                    .bind(py)
+                    .getattr("__globals__")
+                    .and_then(|globals| globals.get_item("_seeds"))
+                    .and_then(|seeds| seeds.extract::<HashMap<String, u64>>())
+                    .expect("seeds should be recorded")
+            };
+            let first_run = run_seeds();
+            let second_run = run_seeds();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(first_run, second_run);
+            assert_ne!(first_run["first"], first_run["second"]);
+        });
+    }
+
+    #[test]
+    fn skips_files_larger_than_max_file_size() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_max_file_size");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_small.py"),
+                "def test_small():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(
+                temp_dir.join("test_generated.py"),
+                format!(
+                    "DATA = {:?}\n\ndef test_generated():\n    pass\n",
+                    "x".repeat(4096)
+                ),
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.max_file_size = Some(1024);
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(discovery.collection_errors.is_empty());
+            assert_eq!(discovery.modules.len(), 1);
+            assert_eq!(discovery.modules[0].tests[0].display_name, "test_small");
+        });
+    }
+
+    #[test]
+    fn run_applies_env_addopts_over_argument_defaults() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_run_addopts");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_addopts.py"),
+                "def test_fast():\n    pass\n\n\ndef test_other():\n    pass\n",
+            )
+            .unwrap();
+
+            let failing: String = (0..3)
+                .map(|i| format!("def test_broken_{}():\n    assert False\n\n", i))
+                .collect();
+            std::fs::write(temp_dir.join("test_broken.py"), failing).unwrap();
+
+            // Both cases share one test so no other test sees the variable
+            let run_fn = pyo3::wrap_pyfunction!(super::run, py).unwrap();
+            let run_with = |addopts: &str, file: &str| {
+                std::env::set_var("RUSTEST_ADDOPTS", addopts);
+                let path = temp_dir.join(file).to_string_lossy().into_owned();
+                let report = run_fn.call1((vec![path],));
+                std::env::remove_var("RUSTEST_ADDOPTS");
+                let report = report.expect("run should succeed");
+                let count =
+                    |field: &str| -> usize { report.getattr(field).unwrap().extract().unwrap() };
+                (count("passed"), count("failed"))
+            };
+            let selected = run_with("-k fast", "test_addopts.py");
+            let maxfail = run_with("--maxfail=2", "test_broken.py");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(
+                selected,
+                (1, 0),
+                "-k from RUSTEST_ADDOPTS should select the test"
+            );
+            assert_eq!(
+                maxfail,
+                (0, 2),
+                "--maxfail from RUSTEST_ADDOPTS should stop the run"
+            );
+        });
+    }
+
+    #[test]
+    fn passed_only_selects_previously_passing_tests() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_passed_only");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_mixed.py"),
+                "def test_green():\n    pass\n\n\
+                 def test_red():\n    pass\n\n\
+                 def test_skipped():\n    pass\n\n\
+                 def test_new():\n    pass\n\n\
+                 def test_also_green():\n    pass\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let collected_ids = |config: &RunConfiguration| -> Vec<String> {
+                let modules = discover_tests(py, &paths, config)
+                    .expect("discovery should succeed")
+                    .modules;
+                modules
+                    .iter()
+                    .flat_map(|module| module.tests.iter().map(|test| test.unique_id()))
+                    .collect()
+            };
+
+            // Build a prior report from the real node ids; test_new wasn't in it
+            let all_ids = collected_ids(&config);
+            let node_id = |name: &str| {
+                all_ids
+                    .iter()
+                    .find(|id| id.ends_with(&format!("::{}", name)))
+                    .unwrap()
+                    .clone()
+            };
+            let report = serde_json::json!({
+                "results": [
+                    {"node_id": node_id("test_green"), "status": "passed"},
+                    {"node_id": node_id("test_red"), "status": "failed"},
+                    {"node_id": node_id("test_skipped"), "status": "skipped"},
+                    {"node_id": node_id("test_also_green"), "status": "passed"},
+                ]
+            });
+            let report_path = temp_dir.join("report.json");
+            std::fs::write(&report_path, report.to_string()).unwrap();
+
+            config.passed_only = Some(report_path);
+            let selected = collected_ids(&config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(
+                selected,
+                [node_id("test_green"), node_id("test_also_green")]
+            );
+        });
+    }
+
+    #[test]
+    fn discovery_order_does_not_depend_on_thread_count() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_discovery_threads");
+            for package in ["alpha", "beta", "gamma"] {
+                let dir = temp_dir.join(package);
+                std::fs::create_dir_all(&dir).unwrap();
+                for i in 0..6 {
+                    std::fs::write(
+                        dir.join(format!("test_{}_{}.py", package, i)),
+                        "def test_one():\n    pass\n\ndef test_two():\n    pass\n",
+                    )
+                    .unwrap();
+                }
+            }
+            std::fs::write(
+                temp_dir.join("test_notes.md"),
+                "```python\nassert True\n```\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let mut collected_ids = |threads: Option<usize>| -> Vec<String> {
+                config.discovery_threads = threads;
+                let discovery =
+                    discover_tests(py, &paths, &config).expect("discovery should succeed");
+                assert!(discovery.collection_errors.is_empty());
+                discovery
+                    .modules
+                    .iter()
+                    .flat_map(|module| module.tests.iter().map(|test| test.unique_id()))
+                    .collect()
+            };
+
+            let sequential = collected_ids(None);
+            let two_threads = collected_ids(Some(2));
+            let eight_threads = collected_ids(Some(8));
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(sequential.len(), 37);
+            assert_eq!(two_threads, sequential);
+            assert_eq!(eight_threads, sequential);
+        });
+    }
+
+    #[test]
+    fn slow_tests_only_run_when_requested() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_run_slow");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_speed.py"),
+                "from rustest import mark\n\n\
+                 def test_quick():\n    pass\n\n\
+                 @mark.slow\n\
+                 def test_sluggish():\n    pass\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let collected_names = |config: &RunConfiguration| -> Vec<String> {
+                let modules = discover_tests(py, &paths, config)
+                    .expect("discovery should succeed")
+                    .modules;
+                modules
+                    .iter()
+                    .flat_map(|module| module.tests.iter().map(|test| test.name.clone()))
+                    .collect()
+            };
+
+            let by_default = collected_names(&config);
+            config.run_slow = true;
+            let with_run_slow = collected_names(&config);
+            config.run_slow = false;
+            config.mark_expr = Some("slow".to_string());
+            let with_mark_expr = collected_names(&config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(by_default, ["test_quick"]);
+            assert_eq!(with_run_slow, ["test_quick", "test_sluggish"]);
+            assert_eq!(with_mark_expr, ["test_sluggish"]);
+        });
+    }
+
+    #[test]
+    fn same_named_standalone_files_do_not_shadow_each_other() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_same_stem");
+            for name in ["first", "second"] {
+                let dir = temp_dir.join(name);
+                std::fs::create_dir_all(&dir).unwrap();
+                std::fs::write(
+                    dir.join("test_same.py"),
+                    format!(
+                        "import sys\n\n\
+                         OWNER = '{name}'\n\n\
+                         def test_owner():\n    \
+                         assert sys.modules[__name__].OWNER == '{name}'\n"
+                    ),
+                )
+                .unwrap();
+            }
+
+            let config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.total, 2);
+            assert_eq!(report.passed, 2, "{:?}", report.results[0].message);
+        });
+    }
+
+    #[test]
+    fn xfail_list_marks_listed_failures_as_expected() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_xfail_list");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_known.py");
+            std::fs::write(
+                &test_file,
+                "def test_known_bug():\n    assert False\n\n\
+                 def test_fixed_bug():\n    assert True\n\n\
+                 def test_unlisted():\n    assert False\n",
+            )
+            .unwrap();
+            let file = test_file.to_string_lossy();
+            let list = temp_dir.join("known-failures.txt");
+            std::fs::write(
+                &list,
+                format!(
+                    "# Known failures\n\n{file}::test_known_bug  # tracked upstream\n{file}::test_fixed_bug\n"
+                ),
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.load_xfail_list(list).unwrap();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcome = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (result.status.as_str(), result.message.clone())
+            };
+            assert_eq!(
+                outcome("test_known_bug"),
+                ("xfailed", Some("tracked upstream".to_string()))
+            );
+            let (status, message) = outcome("test_fixed_bug");
+            assert_eq!(status, "xpassed");
+            assert!(message.unwrap().starts_with("listed in "));
+            assert_eq!(outcome("test_unlisted").0, "failed");
+        });
+    }
+
+    #[test]
+    fn line_trace_records_lines_of_each_test_body() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_line_trace");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_traced.py");
+            std::fs::write(
+                &test_file,
+                "def helper(x):\n    return x * 2\n\n\
+                 def test_traced():\n    value = helper(2)\n    assert value == 4\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.line_trace = true;
+            config.line_trace_path = temp_dir.join("line_trace.json");
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            let content = std::fs::read_to_string(&config.line_trace_path).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 1);
+            let trace: serde_json::Value = serde_json::from_str(&content).unwrap();
+            let node_id = report.results[0].unique_id();
+            let lines: Vec<u64> = trace[&node_id][test_file.to_string_lossy().as_ref()]
+                .as_array()
+                .unwrap_or_else(|| panic!("no lines for {}: {}", node_id, content))
+                .iter()
+                .map(|line| line.as_u64().unwrap())
+                .collect();
+            // The test body and the helper it called
+            assert_eq!(lines, [2, 5, 6]);
+
+            // The tracer is removed once the test finishes
+            let sys = py.import("sys").unwrap();
+            assert!(sys.call_method0("gettrace").unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn syntax_error_collection_error_points_at_the_line() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_structured_collection_error");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_broken.py");
+            std::fs::write(
+                &test_file,
+                "def test_ok():\n    pass\n\ndef test_broken(:\n    pass\n",
+            )
+            .unwrap();
+
+            let collection_errors = run_discovery(py, &test_file).collection_errors;
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(collection_errors.len(), 1);
+            let error = &collection_errors[0];
+            assert_eq!(error.error_type.as_deref(), Some("SyntaxError"));
+            assert_eq!(
+                error.filename.as_deref(),
+                Some(test_file.to_string_lossy().as_ref())
+            );
+            assert_eq!(error.lineno, Some(4));
+            assert!(error.message.contains("SyntaxError"));
+            assert!(error
+                .frames
+                .iter()
+                .all(|frame| !frame.filename.starts_with("<frozen ")));
+        });
+    }
+
+    #[test]
+    fn async_tests_share_a_module_loop_when_that_is_the_default() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let loop_ids = |scope: FixtureScope| -> Vec<String> {
+                let temp_dir =
+                    std::env::temp_dir().join(format!("rustest_default_loop_{:?}", scope));
+                std::fs::create_dir_all(&temp_dir).unwrap();
+                let log = temp_dir.join("loops.txt");
+                let _ = std::fs::remove_file(&log);
+                let test_file = temp_dir.join("test_loops.py");
+                std::fs::write(
+                    &test_file,
+                    format!(
+                        "import asyncio\n\n\
+                         LOOPS = []  # keep loops alive so their ids stay unique\n\n\
+                         def record():\n    \
+                         loop = asyncio.get_running_loop()\n    \
+                         LOOPS.append(loop)\n    \
+                         with open({log:?}, 'a') as f:\n        \
+                         f.write(f'{{id(loop)}}\\n')\n\n\
+                         async def test_first():\n    record()\n\n\
+                         async def test_second():\n    record()\n"
+                    ),
+                )
+                .unwrap();
+
+                let mut config = test_config();
+                config.default_test_loop_scope = scope;
+                let discovery = run_discovery(py, &test_file);
+                let report =
+                    run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+                assert_eq!(report.passed, 2, "{:?}", report.results[0].message);
+                let ids = std::fs::read_to_string(&log).unwrap();
+                std::fs::remove_dir_all(&temp_dir).ok();
+                ids.lines().map(str::to_string).collect()
+            };
+
+            let module_ids = loop_ids(FixtureScope::Module);
+            assert_eq!(module_ids.len(), 2);
+            assert_eq!(module_ids[0], module_ids[1]);
+
+            let function_ids = loop_ids(FixtureScope::Function);
+            assert_eq!(function_ids.len(), 2);
+            assert_ne!(function_ids[0], function_ids[1]);
+        });
+    }
+
+    #[test]
+    fn session_fixture_is_set_up_and_torn_down_once() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_fixture_counts");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_counts.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture\n\n\
+                 @fixture(scope='session')\n\
+                 def database():\n    yield {'connected': True}\n\n\
+                 def test_one(database):\n    assert database['connected']\n\n\
+                 def test_two(database):\n    assert database['connected']\n\n\
+                 def test_three(database):\n    assert database['connected']\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 3);
+            assert_eq!(report.fixture_setups, 1);
+            assert_eq!(report.fixture_teardowns, 1);
+        });
+    }
+
+    #[test]
+    fn freeze_time_fixture_freezes_the_clock_for_one_test() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_freeze_time");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_clock.py");
+            std::fs::write(
+                &test_file,
+                "import datetime\nimport time\n\n\
+                 from rustest import mark\n\n\
+                 @mark.freeze_time('2001-09-09T01:46:40+00:00')\n\
+                 def test_frozen(freeze_time):\n    \
+                 assert time.time() == 1_000_000_000\n    \
+                 assert datetime.datetime.now(datetime.timezone.utc) == freeze_time\n    \
+                 assert datetime.datetime.utcnow().year == 2001\n\n\
+                 def test_real_clock_restored():\n    \
+                 assert time.time() > 1_600_000_000\n    \
+                 assert datetime.datetime.now().year > 2001\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter(|r| r.status != "passed")
+                .map(|r| (&r.name, &r.message))
+                .collect();
+            assert_eq!(report.passed, 2, "{:?}", failures);
+            let now: f64 = py
+                .import("time")
+                .unwrap()
+                .call_method0("time")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(now > 1_600_000_000.0);
+        });
+    }
+
+    #[test]
+    fn no_autouse_mark_skips_autouse_fixtures_for_that_test() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_no_autouse");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_opt_out.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture, mark\n\n\
+                 CALLS = []\n\n\
+                 @fixture(autouse=True)\n\
+                 def transaction():\n    CALLS.append('transaction')\n\n\
+                 @mark.no_autouse('transaction')\n\
+                 def test_named_opt_out():\n    assert CALLS == []\n\n\
+                 @mark.no_autouse\n\
+                 def test_bare_opt_out():\n    assert CALLS == []\n\n\
+                 def test_uses_autouse():\n    assert CALLS == ['transaction']\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter(|r| r.status != "passed")
+                .map(|r| (&r.name, &r.message))
+                .collect();
+            assert_eq!(report.passed, 3, "{:?}", failures);
+        });
+    }
+
+    #[test]
+    fn sqlite_path_records_each_result_with_a_run_id() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_sqlite_run");
+            let _ = std::fs::remove_dir_all(&temp_dir);
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            for (file, body) in [
+                (
+                    "test_one.py",
+                    "def test_pass():\n    pass\n\ndef test_fail():\n    assert False\n",
+                ),
+                ("test_two.py", "def test_other():\n    pass\n"),
+            ] {
+                std::fs::write(temp_dir.join(file), body).unwrap();
+            }
+            let db = temp_dir.join("history.db");
+
+            let mut config = test_config();
+            config.sqlite_path = Some(db.clone());
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.total, 3);
+
+            let connection = rusqlite::Connection::open(&db).unwrap();
+            let mut query = connection
+                .prepare(
+                    "SELECT node_id, status, run_id, timestamp FROM test_results ORDER BY node_id",
+                )
+                .unwrap();
+            let rows: Vec<(String, String, String, f64)> = query
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            drop(query);
+            drop(connection);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcomes: Vec<(String, &str)> = rows
+                .iter()
+                .map(|(node_id, status, _, _)| {
+                    let name = node_id.rsplit('/').next().unwrap().to_string();
+                    (name, status.as_str())
+                })
+                .collect();
+            assert_eq!(
+                outcomes,
+                [
+                    ("test_one.py::test_fail".to_string(), "failed"),
+                    ("test_one.py::test_pass".to_string(), "passed"),
+                    ("test_two.py::test_other".to_string(), "passed"),
+                ]
+            );
+            assert!(rows.iter().all(|row| row.2 == rows[0].2));
+            assert!(rows.iter().all(|row| row.3 > 0.0));
+        });
+    }
+
+    #[test]
+    fn changed_lines_selects_tests_overlapping_the_diff() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_changed_lines");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let root = temp_dir.canonicalize().unwrap();
+            std::fs::write(
+                root.join("changed_helper.py"),
+                "def double(x):\n    return x * 2\n\n\
+                 def triple(x):\n    return x * 3\n",
+            )
+            .unwrap();
+            // The test module imports the helper from its own directory
+            py.import("sys")
+                .and_then(|sys| sys.getattr("path"))
+                .and_then(|path| path.call_method1("insert", (0, root.to_string_lossy())))
+                .unwrap();
+            let test_file = root.join("test_changed.py");
+            std::fs::write(
+                &test_file,
+                "from changed_helper import double\n\n\
+                 def test_one():\n    assert double(1) == 2\n\n\
+                 def test_two():\n    assert double(2) == 4\n\n\
+                 def test_three():\n    assert double(3) == 6\n",
+            )
+            .unwrap();
+
+            let selected = |diff: &str| {
+                let mut modules = run_discovery(py, &test_file).modules;
+                let changed = crate::changed_lines::ChangedLines::parse(diff, &root);
+                crate::discovery::select_changed_tests(py, &mut modules, &changed);
+                modules
+                    .iter()
+                    .flat_map(|m| m.tests.iter().map(|t| t.name.clone()))
+                    .collect::<Vec<_>>()
+            };
+            let hunk = |file: &str, header: &str| {
+                format!(
+                    "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n{1}\n",
+                    file, header
+                )
+            };
+
+            let inside_test_two = selected(&hunk("test_changed.py", "@@ -7 +7 @@"));
+            let unused_helper = selected(&hunk("changed_helper.py", "@@ -5 +5 @@"));
+            let used_helper = selected(&hunk("changed_helper.py", "@@ -2 +2 @@"));
+            let module_level = selected(&hunk("test_changed.py", "@@ -1 +1 @@"));
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(inside_test_two, ["test_two"]);
+            assert!(unused_helper.is_empty());
+            assert_eq!(used_helper, ["test_one", "test_two", "test_three"]);
+            assert_eq!(module_level, ["test_one", "test_two", "test_three"]);
+        });
+    }
+
+    #[test]
+    fn dunder_test_false_excludes_functions_and_classes() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_dunder_test");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_helpers.py");
+            std::fs::write(
+                &test_file,
+                "def test_helper(value):\n    return value * 2\n\n\
+                 test_helper.__test__ = False\n\n\
+                 def test_real():\n    assert test_helper(2) == 4\n\n\
+                 class TestBase:\n    __test__ = False\n\n    def test_inherited(self):\n        pass\n\n\
+                 class TestPartly:\n    def test_kept(self):\n        pass\n\n\
+                 \x20   def test_dropped(self):\n        pass\n\n\
+                 \x20   test_dropped.__test__ = False\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &test_file);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(discovery.collection_errors.is_empty());
+            let names: Vec<&str> = discovery
+                .modules
+                .iter()
+                .flat_map(|m| m.tests.iter().map(|t| t.display_name.as_str()))
+                .collect();
+            assert_eq!(names, ["test_real", "TestPartly::test_kept"]);
+        });
+    }
+
+    #[test]
+    fn keyword_expression_filters_by_name_and_mark() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_keyword_expr");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_auth.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import mark\n\n\
+                 def test_login():\n    pass\n\n\
+                 @mark.slow\ndef test_login_remote():\n    pass\n\n\
+                 def test_login_slow_path():\n    pass\n\n\
+                 def test_logout():\n    pass\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.pattern = Some("login and not slow".to_string());
+            let paths = PyPaths::from_vec(vec![test_file.to_string_lossy().into_owned()]);
+            let modules = discover_tests(py, &paths, &config).unwrap().modules;
+            let names: Vec<&str> = modules
+                .iter()
+                .flat_map(|m| m.tests.iter().map(|t| t.display_name.as_str()))
+                .collect();
+            assert_eq!(names, ["test_login"]);
+
+            config.pattern = Some("login and".to_string());
+            let result = discover_tests(py, &paths, &config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+            assert!(result.is_err());
+        });
+    }
+
+    #[test]
+    fn nested_conftest_fixtures_merge_with_closest_winning() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_nested_conftest");
+            let inner = temp_dir.join("inner");
+            std::fs::create_dir_all(&inner).unwrap();
+            std::fs::write(
+                temp_dir.join("conftest.py"),
+                "from rustest import fixture\n\n\
+                 @fixture\ndef base():\n    return 'outer'\n\n\
+                 @fixture\ndef label():\n    return 'outer'\n\n\
+                 @fixture\ndef local():\n    return 'conftest'\n",
+            )
+            .unwrap();
+            std::fs::write(
+                inner.join("conftest.py"),
+                "from rustest import fixture\n\n\
+                 @fixture\ndef label():\n    return 'inner'\n\n\
+                 @fixture\ndef combined(base, label):\n    return f'{base}+{label}'\n",
+            )
+            .unwrap();
+            let test_file = inner.join("test_nested.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture\n\n\
+                 @fixture\ndef local():\n    return 'module'\n\n\
+                 def test_closest_conftest_wins(label):\n    assert label == 'inner'\n\n\
+                 def test_depends_on_outer_conftest(combined):\n    assert combined == 'outer+inner'\n\n\
+                 def test_module_fixture_wins(local):\n    assert local == 'module'\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter(|r| r.status != "passed")
+                .map(|r| (r.name.clone(), r.message.clone()))
+                .collect();
+            assert!(failures.is_empty(), "{:?}", failures);
+            assert_eq!(report.passed, 3);
+        });
+    }
+
+    #[test]
+    fn module_scoped_parametrize_builds_indirect_fixture_once_per_value() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_parametrize_scope");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_param_scope.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture, parametrize\n\n\
+                 BUILT = []\n\n\
+                 @fixture\ndef backend(request):\n    BUILT.append(request.param)\n    return request.param\n\n\
+                 @parametrize('backend', ['sqlite', 'postgres'], indirect=True, scope='module')\n\
+                 def test_first(backend):\n    assert backend in ('sqlite', 'postgres')\n\n\
+                 @parametrize('backend', ['sqlite', 'postgres'], indirect=True, scope='module')\n\
+                 def test_second(backend):\n    assert backend in ('sqlite', 'postgres')\n\n\
+                 @parametrize('backend', ['sqlite'], indirect=True)\n\
+                 def test_function_scoped(backend):\n    assert backend == 'sqlite'\n\n\
+                 def test_zz_built_once_per_value():\n    assert sorted(BUILT) == ['postgres', 'sqlite', 'sqlite'], BUILT\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter(|r| r.status != "passed")
+                .map(|r| (r.name.clone(), r.message.clone()))
+                .collect();
+            assert!(failures.is_empty(), "{:?}", failures);
+            assert_eq!(report.passed, 6);
+        });
+    }
+
+    #[test]
+    fn workers_shard_modules_across_processes_and_merge_results() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_worker_processes");
+            let fake_package = temp_dir.join("fake").join("rustest");
+            std::fs::create_dir_all(&fake_package).unwrap();
+            // Workers import `rustest.worker` from the parent's sys.path, so a
+            // stand-in placed first answers for them: tests named `*_fails`
+            // fail, everything else passes, and stdout carries the worker pid.
+            std::fs::write(fake_package.join("__init__.py"), "").unwrap();
+            std::fs::write(
+                fake_package.join("worker.py"),
+                "import json, os, struct, sys\n\
+                 stdin, stdout = sys.stdin.buffer, sys.stdout.buffer\n\
+                 while True:\n\
+                 \x20   header = stdin.read(4)\n\
+                 \x20   if len(header) < 4:\n\
+                 \x20       break\n\
+                 \x20   request = json.loads(stdin.read(struct.unpack('>I', header)[0]))\n\
+                 \x20   assert 'run_slow' not in request['options']\n\
+                 \x20   results = [{'node_id': i, 'duration': 0.25, 'stdout': str(os.getpid()),\n\
+                 \x20               'status': 'failed' if i.endswith('_fails') else 'passed',\n\
+                 \x20               'message': 'boom' if i.endswith('_fails') else None}\n\
+                 \x20              for i in request['node_ids']]\n\
+                 \x20   payload = json.dumps({'batch_id': request['batch_id'], 'results': results,\n\
+                 \x20                         'warnings': [[request['node_ids'][0], 'UserWarning: hi']],\n\
+                 \x20                         'teardown_errors': [['db', 'session', 'boom']],\n\
+                 \x20                         'fixture_setups': 2, 'fixture_teardowns': 1}).encode()\n\
+                 \x20   stdout.write(struct.pack('>I', len(payload)) + payload)\n\
+                 \x20   stdout.flush()\n",
+            )
+            .unwrap();
+            for (file, body) in [
+                (
+                    "test_one.py",
+                    "def test_a():\n    pass\n\ndef test_b():\n    pass\n",
+                ),
+                (
+                    "test_two.py",
+                    "def test_c():\n    pass\n\ndef test_d_fails():\n    pass\n",
+                ),
+                ("test_three.py", "def test_e():\n    pass\n"),
+            ] {
+                std::fs::write(temp_dir.join(file), body).unwrap();
+            }
+
+            let mut config = test_config();
+            config.worker_processes = 2;
+            let paths = PyPaths::from_vec(
+                ["test_one.py", "test_two.py", "test_three.py"]
+                    .iter()
+                    .map(|file| temp_dir.join(file).to_string_lossy().into_owned())
+                    .collect(),
+            );
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            assert!(discovery.collection_errors.is_empty());
+            let sys_path = py.import("sys").unwrap().getattr("path").unwrap();
+            let fake_root = temp_dir.join("fake").to_string_lossy().into_owned();
+            sys_path.call_method1("insert", (0, &fake_root)).unwrap();
+            let report = run_collected_tests(py, &discovery, &config);
+            sys_path.call_method1("remove", (&fake_root,)).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+            let report = report.expect("execution should succeed");
+
+            assert_eq!((report.total, report.passed, report.failed), (5, 4, 1));
+            let names: Vec<&str> = report.results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(
+                names,
+                ["test_a", "test_b", "test_c", "test_d_fails", "test_e"]
+            );
+            assert_eq!(report.results[3].message.as_deref(), Some("boom"));
+            assert_eq!(report.file_durations.len(), 3);
+            assert!((report.file_durations[0].1 - 0.5).abs() < 1e-9);
+
+            // Whole modules run on one worker, and both workers were used
+            let pid = |i: usize| report.results[i].stdout.clone().unwrap();
+            assert_eq!(pid(0), pid(1));
+            assert_eq!(pid(2), pid(3));
+            let pids: std::collections::HashSet<String> = (0..5).map(pid).collect();
+            assert_eq!(pids.len(), 2);
+
+            // What each worker's session reported besides results is merged too
+            assert_eq!(report.warnings.len(), 2);
+            assert_eq!(report.teardown_errors.len(), 2);
+            assert_eq!(report.teardown_errors[0].fixture, "db");
+            assert_eq!((report.fixture_setups, report.fixture_teardowns), (4, 2));
+        });
+    }
+
+    #[test]
+    fn workers_fall_back_to_in_process_for_exitfirst() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_worker_fallback");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            for file in ["test_one.py", "test_two.py"] {
+                std::fs::write(
+                    temp_dir.join(file),
+                    "def test_a():\n    assert False\n\ndef test_b():\n    assert False\n",
+                )
+                .unwrap();
+            }
+
+            let mut config = test_config();
+            config.worker_processes = 2;
+            config.fail_fast = true;
+            assert_eq!(config.in_process_option(), Some("--exitfirst"));
+            let discovery = run_discovery(py, &temp_dir);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            // Only an in-process run can stop right after the first failure
+            assert_eq!(report.failed, 1);
+        });
+    }
+
+    #[test]
+    fn node_ids_select_exactly_those_tests_in_order() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_node_ids");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_one.py"),
+                "from rustest import mark\n\ndef test_a():\n    pass\n\n@mark.slow\ndef test_b():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(temp_dir.join("test_two.py"), "def test_c():\n    pass\n").unwrap();
+
+            let node_id =
+                |file: &str, name: &str| format!("{}::{}", temp_dir.join(file).display(), name);
+            let mut config = test_config();
+            // A slow test named explicitly runs without --run-slow
+            config.node_ids = Some(vec![
+                node_id("test_two.py", "test_c"),
+                node_id("test_one.py", "test_b"),
+            ]);
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let names: Vec<&str> = discovery
+                .modules
+                .iter()
+                .flat_map(|module| &module.tests)
+                .map(|test| test.name.as_str())
+                .collect();
+            assert_eq!(names, ["test_c", "test_b"]);
+        });
+    }
+
+    #[test]
+    fn worker_sessions_leave_the_cache_and_journal_to_the_parent_run() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_worker_session");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_worker_session.py");
+            std::fs::write(&file_path, "def test_fails():\n    assert False\n").unwrap();
+            // The parent's journal of an interrupted run must survive its workers
+            let journal_path = temp_dir.join("journal");
+            std::fs::write(&journal_path, "{}\n").unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let mut config = test_config();
+            config.worker = true;
+            config.journal_path = journal_path.clone();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            let journal = std::fs::read_to_string(&journal_path).ok();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.failed, 1);
+            assert_eq!(journal.as_deref(), Some("{}\n"));
+            let last_failed = crate::cache::read_last_failed().unwrap_or_default();
+            assert!(!last_failed.contains(&report.results[0].unique_id()));
+        });
+    }
+
+    #[test]
+    fn asyncio_debug_reports_tasks_left_pending_by_a_test() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_asyncio_debug");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_leaky.py");
+            std::fs::write(
+                &test_file,
+                "import asyncio\n\n\
+                 async def test_leaks_task():\n    asyncio.create_task(asyncio.sleep(10))\n\n\
+                 async def test_clean():\n    await asyncio.sleep(0)\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &test_file);
+            let mut config = test_config();
+            config.asyncio_debug = true;
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 2);
+            assert_eq!(report.warnings.len(), 1);
+            let (node_id, message) = &report.warnings[0];
+            assert!(node_id.ends_with("test_leaks_task"));
+            assert!(message.starts_with("Task was still pending when the test finished"));
+        });
+    }
+
+    #[test]
+    fn mark_expression_selects_tests_and_drops_empty_modules() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_mark_expr_selection");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_api.py"),
+                "from rustest import mark\n\n\
+                 @mark.integration\ndef test_remote():\n    pass\n\n\
+                 @mark.integration\n@mark.flaky\ndef test_remote_flaky():\n    pass\n\n\
+                 def test_local():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(
+                temp_dir.join("test_units.py"),
+                "def test_unit():\n    pass\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.mark_expr = Some("(slow or integration) and not flaky".to_string());
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).unwrap();
+
+            assert!(discovery.collection_errors.is_empty());
+            assert_eq!(discovery.modules.len(), 1);
+            let names: Vec<&str> = discovery.modules[0]
+                .tests
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect();
+            assert_eq!(names, ["test_remote"]);
+
+            config.mark_expr = Some("slow or".to_string());
+            let result = discover_tests(py, &paths, &config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+            assert!(result.is_err());
+        });
+    }
+
+    #[test]
+    fn keyboard_interrupt_returns_partial_report_after_teardown() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_keyboard_interrupt");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let marker = temp_dir.join("torn_down");
+            let test_file = temp_dir.join("test_interrupt.py");
+            std::fs::write(
+                &test_file,
+                format!(
+                    "from rustest import fixture\n\n\
+                     @fixture(scope='module')\ndef resource():\n    yield 1\n    open({:?}, 'w').close()\n\n\
+                     def test_first(resource):\n    pass\n\n\
+                     def test_interrupts(resource):\n    raise KeyboardInterrupt\n\n\
+                     def test_never_runs():\n    pass\n",
+                    marker.to_string_lossy()
+                ),
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &test_file);
+            let config = test_config();
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            let torn_down = marker.exists();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(report.interrupted);
+            assert_eq!(report.total, 1);
+            assert_eq!(report.passed, 1);
+            let names: Vec<&str> = report.results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, ["test_first"]);
+            assert!(torn_down, "module fixture teardown should run on interrupt");
+        });
+    }
+
+    #[test]
+    fn event_loop_fixture_lets_sync_tests_drive_coroutines() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_event_loop_fixture");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_event_loop.py");
+            std::fs::write(
+                &test_file,
+                "import asyncio\n\
+                 from rustest import fixture\n\n\
+                 _loops = {}\n\n\
+                 @fixture\nasync def greeting():\n    return asyncio.get_running_loop()\n\n\
+                 def test_sync_drives_loop(event_loop, greeting):\n\
+                 \x20   async def running():\n        return asyncio.get_running_loop()\n\
+                 \x20   assert event_loop.run_until_complete(running()) is event_loop\n\
+                 \x20   assert greeting is event_loop\n\
+                 \x20   _loops['sync'] = event_loop\n\n\
+                 async def test_async_gets_fresh_loop():\n\
+                 \x20   assert _loops['sync'].is_closed()\n\
+                 \x20   assert asyncio.get_running_loop() is not _loops['sync']\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &test_file);
+            let config = test_config();
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter_map(|r| r.message.clone())
+                .collect();
+            assert_eq!(report.passed, 2, "{:?}", failures);
+        });
+    }
+
+    #[test]
+    fn trace_json_writes_chrome_trace_spans() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_trace_json");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_traced.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture\n\n\
+                 @fixture\ndef resource():\n    yield 1\n\n\
+                 def test_traced(resource):\n    assert resource == 1\n",
+            )
+            .unwrap();
+            let trace_path = temp_dir.join("trace.json");
+
+            let mut config = test_config();
+            config.trace_json = Some(trace_path.clone());
+            let paths = PyPaths::from_vec(vec![test_file.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).unwrap();
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            assert_eq!(report.passed, 1);
+
+            let trace: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&trace_path).unwrap()).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let events = trace["traceEvents"].as_array().unwrap();
+            let span = |name: &str| {
+                events
+                    .iter()
+                    .find(|event| event["name"].as_str().is_some_and(|n| n.ends_with(name)))
+                    .unwrap_or_else(|| panic!("no {} span in {:?}", name, events))
+            };
+            for event in events {
+                assert_eq!(event["ph"], "X");
+                assert!(event["ts"].is_number() && event["dur"].is_number());
+            }
+            span("collection");
+            let test = span("test_traced.py::test_traced");
+            assert_eq!(test["args"]["status"], "passed");
+            let setup = span("setup resource");
+            span("teardown resource");
+
+            // The fixture span sits inside the test span on the same thread
+            let start = |event: &serde_json::Value| event["ts"].as_f64().unwrap();
+            let end = |event: &serde_json::Value| start(event) + event["dur"].as_f64().unwrap();
+            assert_eq!(setup["tid"], test["tid"]);
+            assert!(start(setup) >= start(test) && end(setup) <= end(test));
+        });
+    }
+
+    #[test]
+    fn xfail_mark_reports_xfailed_and_xpassed_outcomes() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_xfail_outcomes");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_xfail.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import mark\n\n\
+                 @mark.xfail(reason='known bug')\n\
+                 def test_expected():\n    assert False\n\n\
+                 @mark.xfail(reason='fixed now')\n\
+                 def test_unexpected():\n    assert True\n\n\
+                 @mark.xfail(reason='must fail', strict=True)\n\
+                 def test_strict():\n    assert True\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcome = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (result.status.clone(), result.message.clone())
+            };
+            assert_eq!(
+                outcome("test_expected"),
+                ("xfailed".to_string(), Some("known bug".to_string()))
+            );
+            assert_eq!(
+                outcome("test_unexpected"),
+                ("xpassed".to_string(), Some("fixed now".to_string()))
+            );
+            assert_eq!(
+                outcome("test_strict"),
+                (
+                    "failed".to_string(),
+                    Some("[XPASS(strict)] must fail".to_string())
+                )
+            );
+            assert_eq!(report.total, 3);
+            assert_eq!((report.passed, report.failed, report.skipped), (0, 1, 0));
+            assert_eq!((report.xfailed, report.xpassed), (1, 1));
+        });
+    }
+
+    #[test]
+    fn flaky_mark_reruns_failures_with_fresh_fixtures() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_flaky_reruns");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_flaky.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture, mark\n\n\
+                 attempts = []\n\
+                 events = []\n\n\
+                 @fixture\n\
+                 def connection():\n    events.append('setup')\n    yield len(events)\n    events.append('teardown')\n\n\
+                 @mark.flaky(reruns=3)\n\
+                 def test_recovers(connection):\n    attempts.append(connection)\n    assert len(attempts) == 3\n\n\
+                 @mark.flaky(reruns=1, reruns_delay=0)\n\
+                 def test_always_fails():\n    assert False\n\n\
+                 def test_fixture_lifecycle():\n    assert attempts == [1, 3, 5]\n    assert events == ['setup', 'teardown'] * 3\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcome = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (result.status.as_str(), result.reruns)
+            };
+            assert_eq!(outcome("test_recovers"), ("passed", 2));
+            assert_eq!(outcome("test_always_fails"), ("failed", 1));
+            assert_eq!(outcome("test_fixture_lifecycle"), ("passed", 0));
+            assert_eq!((report.passed, report.failed), (2, 1));
+        });
+    }
+
+    #[test]
+    fn non_strict_loop_scope_widens_with_a_warning() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_loop_scope_strict");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_loop_scope.py");
+            std::fs::write(
+                &test_file,
+                "import asyncio\n\
+                 from rustest import fixture, mark\n\n\
+                 @fixture(scope='module')\n\
+                 async def shared_loop():\n    return asyncio.get_running_loop()\n\n\
+                 @mark.asyncio(loop_scope='function')\n\
+                 async def test_first(shared_loop):\n    assert asyncio.get_running_loop() is shared_loop\n\n\
+                 @mark.asyncio(loop_scope='function')\n\
+                 async def test_second(shared_loop):\n    assert asyncio.get_running_loop() is shared_loop\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let strict =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            config.loop_scope_strict = false;
+            let discovery = run_discovery(py, &test_file);
+            let widened =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(strict.errors, 2);
+            assert!(strict.results[0]
+                .message
+                .as_deref()
+                .unwrap()
+                .contains("Loop scope mismatch"));
+
+            assert_eq!(widened.passed, 2);
+            assert_eq!(widened.warnings.len(), 2);
+            assert!(widened.warnings[0].0.ends_with("::test_first"));
+            assert_eq!(
+                widened.warnings[0].1,
+                "loop_scope=\"function\" widened to \"module\" to match the module-scoped async fixtures the test uses"
+            );
+        });
+    }
+
+    #[test]
+    fn notebooks_are_collected_when_enabled() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_notebooks");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let notebook = temp_dir.join("test_analysis.ipynb");
+            std::fs::write(
+                &notebook,
+                r##"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Revenue checks\n"]},
+  {"cell_type": "code", "metadata": {}, "execution_count": 1, "outputs": [],
+   "source": ["%matplotlib inline\n", "totals = [3, 4]\n"]},
+  {"cell_type": "code", "metadata": {}, "execution_count": 2, "outputs": [],
+   "source": ["def test_totals_add_up():\n", "    assert sum(totals) == 7\n"]}
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##,
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let skipped = discover_tests(py, &paths, &config)
+                .expect("discovery")
+                .modules;
+            config.notebooks = true;
+            let discovery = discover_tests(py, &paths, &config).expect("discovery");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(skipped.is_empty());
+            assert!(discovery.collection_errors.is_empty());
+            assert_eq!(report.passed, 1);
+            assert!(report.results[0]
+                .unique_id()
+                .ends_with("test_analysis.ipynb::test_totals_add_up"));
+        });
+    }
+
+    #[test]
+    fn junit_xml_reports_each_test_case() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_junit_xml");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_report.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import skip\n\n\
+                 def test_passes():\n    pass\n\n\
+                 def test_fails():\n    print('about to fail')\n    assert 1 == 2\n\n\
+                 def test_skipped():\n    skip('not today')\n",
+            )
+            .unwrap();
+            let report_path = temp_dir.join("junit.xml");
+
+            let mut config = test_config();
+            config.junit_xml_path = Some(report_path.clone());
+            let discovery = run_discovery(py, &test_file);
+            run_collected_tests(py, &discovery, &config).unwrap();
+            let xml = std::fs::read_to_string(&report_path).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(xml.contains("tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""));
+            assert_eq!(xml.matches("<testcase classname=").count(), 3);
+            assert!(xml.contains("name=\"test_passes\""));
+            assert!(xml.contains("<failure message=\"AssertionError"));
+            assert!(xml.contains("<system-out>about to fail\n</system-out>"));
+            assert!(xml.contains("<skipped message=\"not today\"/>"));
+        });
+    }
+
+    #[test]
+    fn json_report_serializes_the_run() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_json_report");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_report.py");
+            std::fs::write(
+                &test_file,
+                "def test_passes():\n    pass\n\n\
+                 def test_fails():\n    print('about to fail')\n    assert False\n",
+            )
+            .unwrap();
+            let report_path = temp_dir.join("report.json");
+
+            let mut config = test_config();
+            config.json_report_path = Some(report_path.clone());
+            let discovery = run_discovery(py, &test_file);
+            run_collected_tests(py, &discovery, &config).unwrap();
+            let document: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(document["schema_version"], 1);
+            assert_eq!(document["totals"]["total"], 2);
+            assert_eq!(document["totals"]["passed"], 1);
+            assert_eq!(document["totals"]["failed"], 1);
+            let results = document["results"].as_array().unwrap();
+            assert_eq!(results[0]["name"], "test_passes");
+            assert_eq!(results[1]["status"], "failed");
+            assert_eq!(results[1]["stdout"], "about to fail\n");
+            assert!(results[1]["message"]
+                .as_str()
+                .unwrap()
+                .contains("AssertionError"));
+        });
+    }
+
+    #[test]
+    fn list_markers_flags_used_but_unregistered_marks() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_list_markers");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_marked.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import mark\n\n\
+                 @mark.integration\ndef test_api():\n    pass\n\n\
+                 @mark.integration\n@mark.slow\ndef test_db():\n    pass\n\n\
+                 @mark.intgration\ndef test_typo():\n    pass\n",
+            )
+            .unwrap();
+
+            let registered = PyDict::new(py);
+            registered
+                .set_item("integration", "talks to real services")
+                .unwrap();
+            let markers = crate::list_markers(
+                py,
+                vec![test_file.to_string_lossy().into_owned()],
+                Some(registered),
+            )
+            .unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let find = |name: &str| markers.iter().find(|marker| marker.name == name).unwrap();
+            assert!(find("integration").registered);
+            assert_eq!(find("integration").tests, 2);
+            assert!(find("slow").registered);
+            assert_eq!(find("slow").tests, 1);
+            let typo = find("intgration");
+            assert!(!typo.registered);
+            assert_eq!(typo.description, None);
+            assert_eq!(typo.tests, 1);
+            assert_eq!(markers.last().unwrap().name, "intgration");
+        });
+    }
+
+    #[test]
+    fn last_failed_modes_select_and_reorder_previous_failures() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_last_failed_modes");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_a.py"),
+                "def test_one():\n    pass\n\ndef test_two():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(
+                temp_dir.join("test_b.py"),
+                "def test_fine():\n    pass\n\ndef test_broken():\n    pass\n",
+            )
+            .unwrap();
+
+            let collected = run_discovery(py, &temp_dir).modules;
+            let broken = collected
+                .iter()
+                .flat_map(|m| &m.tests)
+                .find(|t| t.name == "test_broken")
+                .unwrap()
+                .unique_id();
+            let select = |mode: LastFailedMode, failed_ids: &[String]| {
+                let mut modules = run_discovery(py, &temp_dir).modules;
+                let failed_ids = failed_ids.iter().cloned().collect();
+                let rerun =
+                    crate::discovery::apply_last_failed_filter(&mut modules, mode, &failed_ids);
+                let names: Vec<String> = modules
+                    .iter()
+                    .flat_map(|m| m.tests.iter().map(|t| t.name.clone()))
+                    .collect();
+                (rerun, names)
+            };
+
+            assert_eq!(
+                select(LastFailedMode::OnlyFailed, std::slice::from_ref(&broken)),
+                (1, vec!["test_broken".to_string()])
+            );
+            assert_eq!(
+                select(LastFailedMode::FailedFirst, std::slice::from_ref(&broken)),
+                (
+                    1,
+                    vec![
+                        "test_broken".to_string(),
+                        "test_fine".to_string(),
+                        "test_one".to_string(),
+                        "test_two".to_string(),
+                    ]
+                )
+            );
+            // An empty cache runs everything, in discovery order
+            let (rerun, names) = select(LastFailedMode::OnlyFailed, &[]);
+            std::fs::remove_dir_all(&temp_dir).ok();
+            assert_eq!(rerun, 0);
+            let discovered: Vec<String> = collected
+                .iter()
+                .flat_map(|m| m.tests.iter().map(|t| t.name.clone()))
+                .collect();
+            assert_eq!(names, discovered);
+        });
+    }
+
+    #[test]
+    fn request_finalizers_run_at_the_fixture_scope_teardown() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_addfinalizer");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let marker = temp_dir.join("module_finalized.txt");
+            let test_file = temp_dir.join("test_finalizers.py");
+            std::fs::write(
+                &test_file,
+                format!(
+                    "from rustest import fixture\n\n\
+                     EVENTS = []\n\n\
+                     @fixture(scope='module')\n\
+                     def resource(request):\n    \
+                         request.addfinalizer(lambda: open({:?}, 'w').write(','.join(EVENTS)))\n    \
+                         return 'resource'\n\n\
+                     @fixture\n\
+                     def item(request, resource):\n    \
+                         request.addfinalizer(lambda: EVENTS.append('first'))\n    \
+                         request.addfinalizer(lambda: EVENTS.append('second'))\n    \
+                         yield 'item'\n    \
+                         EVENTS.append('yield teardown')\n\n\
+                     def test_uses_fixtures(item):\n    \
+                         EVENTS.append('test')\n\n\
+                     def test_function_finalizers_already_ran(resource):\n    \
+                         assert EVENTS == ['test', 'yield teardown', 'second', 'first']\n",
+                    marker.to_string_lossy()
+                ),
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            let module_events = std::fs::read_to_string(&marker).ok();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 2);
+            assert_eq!(
+                module_events.as_deref(),
+                Some("test,yield teardown,second,first")
+            );
+        });
+    }
+
+    #[test]
+    fn fixtures_are_instantiated_by_scope_then_autouse_usefixtures_signature() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_fixture_order");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_order.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture, mark\n\n\
+                 ORDER = []\n\n\
+                 @fixture(scope='session')\n\
+                 def shared():\n    ORDER.append('shared')\n\n\
+                 @fixture(autouse=True)\n\
+                 def auto_function():\n    ORDER.append('auto_function')\n\n\
+                 @fixture(scope='module', autouse=True)\n\
+                 def auto_module():\n    ORDER.append('auto_module')\n\n\
+                 @fixture\n\
+                 def used():\n    ORDER.append('used')\n\n\
+                 @fixture\n\
+                 def dependency():\n    ORDER.append('dependency')\n\n\
+                 @fixture\n\
+                 def second(dependency):\n    ORDER.append('second')\n\n\
+                 @fixture\n\
+                 def first():\n    ORDER.append('first')\n\n\
+                 @mark.usefixtures('used')\n\
+                 def test_order(first, second, shared):\n    \
+                     assert ORDER == ['shared', 'auto_module', 'auto_function', 'used', \
+                     'first', 'dependency', 'second']\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 1, "{:?}", report.results[0].message);
+        });
+    }
+
+    #[test]
+    fn on_complete_hook_receives_the_report_and_artifacts() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_on_complete");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_hooked.py");
+            std::fs::write(
+                &test_file,
+                "def test_passes():\n    pass\n\n\
+                 def test_fails():\n    assert False\n",
+            )
+            .unwrap();
+            let report_path = temp_dir.join("report.json");
+            let hooks = PyModule::from_code(
+                py,
+                c"calls = []\n\
+                  def record(report, artifacts):\n    \
+                  calls.append((report.total, report.failed, artifacts))\n\
+                  def explode(report, artifacts):\n    \
+                  raise RuntimeError('upload failed')\n",
+                c"on_complete_hooks.py",
+                c"on_complete_hooks",
+            )
+            .expect("hook module should compile");
+
+            let mut config = test_config();
+            config.json_report_path = Some(report_path.clone());
+            config.on_complete = Some(hooks.getattr("record").unwrap().unbind());
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            let report = pyo3::Py::new(py, report).unwrap();
+            crate::notify_on_complete(py, &config, &report);
+            let calls: Vec<(usize, usize, Vec<String>)> =
+                hooks.getattr("calls").unwrap().extract().unwrap();
+
+            // A failing hook is reported, and the report is still usable
+            config.on_complete = Some(hooks.getattr("explode").unwrap().unbind());
+            crate::notify_on_complete(py, &config, &report);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(
+                calls,
+                vec![(2, 1, vec![report_path.to_string_lossy().into_owned()])]
+            );
+            assert_eq!(report.borrow(py).total, 2);
+        });
+    }
+
+    #[test]
+    fn failure_exceptions_separate_failures_from_errors() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_failure_exceptions");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_outcomes.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fail, mark\n\n\
+                 def test_assertion():\n    assert 1 == 2\n\n\
+                 def test_value_error():\n    raise ValueError('bad input')\n\n\
+                 def test_explicit_fail():\n    fail('not yet')\n\n\
+                 class Failed(Exception):\n    pass\n\n\
+                 def test_own_failed():\n    raise Failed('unrelated')\n\n\
+                 @mark.xfail(reason='known bug')\n\
+                 def test_expected():\n    raise ValueError('known')\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let default_report = run_collected_tests(py, &discovery, &config).unwrap();
+            config.failure_exceptions = Some(vec!["AssertionError".to_string()]);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            // Failed assertions stay failures even when other classes are named
+            config.failure_exceptions = Some(vec!["TimeoutError".to_string()]);
+            let timeout_report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!((default_report.failed, default_report.errors), (4, 0));
+            let status = |name: &str| {
+                report
+                    .results
+                    .iter()
+                    .find(|result| result.name == name)
+                    .map(|result| result.status.clone())
+                    .unwrap()
+            };
+            assert_eq!(status("test_assertion"), "failed");
+            assert_eq!(status("test_value_error"), "error");
+            assert_eq!(status("test_explicit_fail"), "failed");
+            assert_eq!(status("test_own_failed"), "error");
+            assert_eq!(status("test_expected"), "xfailed");
+            assert_eq!((report.failed, report.errors), (2, 2));
+            assert_eq!((timeout_report.failed, timeout_report.errors), (2, 2));
+        });
+    }
+
+    #[test]
+    fn artifacts_list_only_the_reports_this_run_wrote() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_artifacts");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_reported.py");
+            std::fs::write(&test_file, "def test_passes():\n    pass\n").unwrap();
+            // Exists, but the JUnit XML report can't be written over a directory
+            let junit_path = temp_dir.join("junit.xml");
+            std::fs::create_dir_all(&junit_path).unwrap();
+            let report_path = temp_dir.join("report.json");
+
+            let mut config = test_config();
+            config.junit_xml_path = Some(junit_path);
+            config.json_report_path = Some(report_path.clone());
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.artifacts, vec![report_path]);
+        });
+    }
+
+    #[test]
+    fn capsys_reads_the_captured_test_output() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_capsys");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_capsys.py");
+            std::fs::write(
+                &test_file,
+                "import sys\n\n\
+                 def test_reads_output(capsys):\n    \
+                     print('hello')\n    \
+                     sys.stderr.write('oops\\n')\n    \
+                     captured = capsys.readouterr()\n    \
+                     assert (captured.out, captured.err) == ('hello\\n', 'oops\\n')\n    \
+                     print('again')\n    \
+                     assert capsys.readouterr().out == 'again\\n'\n    \
+                     print('kept')\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let captured = run_collected_tests(py, &discovery, &config).unwrap();
+            config.capture_output = false;
+            let uncaptured = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let result = &captured.results[0];
+            assert_eq!(result.status, "passed", "{:?}", result.message);
+            assert_eq!(result.stdout.as_deref(), Some("kept\n"));
+            let result = &uncaptured.results[0];
+            assert_eq!(result.status, "error");
+            assert!(result
+                .message
+                .as_deref()
+                .unwrap()
+                .contains("requires output capture"));
+        });
+    }
+
+    #[test]
+    fn discovery_carries_tests_and_collection_errors() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_discovery_result");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_good.py"),
+                "def test_one():\n    pass\n\ndef test_two():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(temp_dir.join("test_broken.py"), "def test_(:\n    pass\n").unwrap();
+
+            let discovery = run_discovery(py, &temp_dir);
+            let config = test_config();
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(discovery.test_count(), 2);
+            assert_eq!(discovery.collection_errors.len(), 1);
+            assert!(discovery.collection_errors[0]
+                .path
+                .ends_with("test_broken.py"));
+            assert_eq!(report.passed, 2);
+            assert_eq!(report.collection_errors.len(), 1);
+        });
+    }
+
+    #[test]
+    fn monkeypatch_is_undone_when_the_test_or_its_setup_fails() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_monkeypatch_undo");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_patching.py");
+            std::fs::write(
+                &test_file,
+                "import os\nimport sys\nfrom rustest import fixture\n\n\
+                 class Settings:\n    debug = False\n    level = 1\n\n\
+                 CONFIG = {'mode': 'prod'}\n\
+                 TORN_DOWN = []\n\n\
+                 @fixture\n\
+                 def patched(monkeypatch):\n    \
+                     monkeypatch.setattr(Settings, 'debug', True)\n    \
+                     monkeypatch.delattr(Settings, 'level')\n    \
+                     monkeypatch.setitem(CONFIG, 'mode', 'test')\n    \
+                     monkeypatch.delitem(CONFIG, 'mode')\n    \
+                     monkeypatch.setenv('RUSTEST_PATCHED', 'yes')\n    \
+                     monkeypatch.delenv('HOME', raising=False)\n    \
+                     monkeypatch.syspath_prepend('/rustest/patched')\n\n\
+                 @fixture\n\
+                 def tracked():\n    yield\n    TORN_DOWN.append('tracked')\n\n\
+                 @fixture\n\
+                 def broken(patched, tracked):\n    raise RuntimeError('setup failed')\n\n\
+                 def test_raises(patched):\n    raise ValueError('boom')\n\n\
+                 def test_setup_fails(broken):\n    pass\n\n\
+                 def test_everything_restored():\n    \
+                     assert Settings.debug is False and Settings.level == 1\n    \
+                     assert CONFIG == {'mode': 'prod'}\n    \
+                     assert 'RUSTEST_PATCHED' not in os.environ\n    \
+                     assert '/rustest/patched' not in sys.path\n    \
+                     assert TORN_DOWN == ['tracked']\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let home = std::env::var_os("HOME");
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let status = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (result.status.clone(), result.message.clone())
+            };
+            assert_eq!(status("test_raises").0, "failed");
+            assert_eq!(status("test_setup_fails").0, "error");
+            assert_eq!(
+                status("test_everything_restored").0,
+                "passed",
+                "{:?}",
+                status("test_everything_restored").1
+            );
+            assert_eq!(std::env::var_os("HOME"), home);
+        });
+    }
+
+    #[test]
+    fn package_conftest_autouse_applies_only_under_its_directory() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_autouse_directory_scope");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            let pkg_a = temp_dir.join("pkg_a");
+            let pkg_b = temp_dir.join("pkg_b");
+            std::fs::create_dir_all(pkg_a.join("nested")).unwrap();
+            std::fs::create_dir_all(&pkg_b).unwrap();
+            std::fs::write(
+                pkg_a.join("conftest.py"),
+                "import os\nfrom rustest import fixture\n\n\
+                 @fixture(autouse=True)\n\
+                 def in_pkg_a():\n    os.environ['RUSTEST_IN_PKG_A'] = '1'\n    yield\n    \
+                 del os.environ['RUSTEST_IN_PKG_A']\n",
+            )
+            .unwrap();
+            std::fs::write(
+                pkg_a.join("test_a.py"),
+                "import os\n\ndef test_in_package():\n    \
+                 assert os.environ.get('RUSTEST_IN_PKG_A') == '1'\n",
+            )
+            .unwrap();
+            std::fs::write(
+                pkg_a.join("nested").join("test_nested.py"),
+                "import os\n\ndef test_in_subpackage():\n    \
+                 assert os.environ.get('RUSTEST_IN_PKG_A') == '1'\n",
+            )
+            .unwrap();
+            std::fs::write(
+                pkg_b.join("test_b.py"),
+                "import os\n\ndef test_in_sibling():\n    \
+                 assert 'RUSTEST_IN_PKG_A' not in os.environ\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &temp_dir);
+            let config = test_config();
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(discovery.test_count(), 3);
+            let failures: Vec<_> = report
+                .results
+                .iter()
+                .filter(|result| result.status != "passed")
+                .map(|result| (result.name.clone(), result.message.clone()))
+                .collect();
+            assert!(failures.is_empty(), "{:?}", failures);
+        });
+    }
+
+    #[test]
+    fn caplog_captures_from_the_configured_level_and_always_detaches() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_caplog_level");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_caplog_level.py");
+            std::fs::write(
+                &test_file,
+                "import logging\n\n\
+                 def test_default_level(caplog):\n    \
+                     logging.debug('hidden')\n    \
+                     logging.info('shown')\n    \
+                     assert caplog.messages == ['shown']\n\n\
+                 def test_lowered_level(caplog):\n    \
+                     with caplog.at_level(logging.DEBUG):\n        \
+                         logging.debug('verbose')\n    \
+                     assert caplog.messages == ['verbose']\n\n\
+                 def test_failing(caplog):\n    \
+                     logging.warning('before failure')\n    \
+                     assert caplog.records[0].levelname == 'WARNING'\n    \
+                     raise RuntimeError('boom')\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.log_level = Some("INFO".to_string());
+            let root = py
+                .import("logging")
+                .unwrap()
+                .call_method0("getLogger")
+                .unwrap();
+            let handlers_before = root.getattr("handlers").unwrap().len().unwrap();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            let handlers_after = root.getattr("handlers").unwrap().len().unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let status = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (result.status.clone(), result.message.clone())
+            };
+            assert_eq!(
+                status("test_default_level").0,
+                "passed",
+                "{:?}",
+                status("test_default_level")
+            );
+            assert_eq!(
+                status("test_lowered_level").0,
+                "passed",
+                "{:?}",
+                status("test_lowered_level")
+            );
+            let (failing, message) = status("test_failing");
+            assert_eq!(failing, "failed");
+            assert!(message.unwrap().contains("boom"));
+            assert_eq!(handlers_after, handlers_before);
+        });
+    }
+
+    #[test]
+    fn shuffle_reorders_without_interleaving_modules() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_shuffle");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            for module in ["alpha", "beta", "gamma"] {
+                let source: String = (0..6)
+                    .map(|i| format!("def test_{}_{}():\n    pass\n\n", module, i))
+                    .collect();
+                std::fs::write(temp_dir.join(format!("test_{}.py", module)), source).unwrap();
+            }
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &temp_dir);
+            let in_order = run_collected_tests(py, &discovery, &config).unwrap();
+            config.shuffle = true;
+            config.shuffle_seed = Some(3);
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let shuffled_run = || {
+                let discovery = discover_tests(py, &paths, &config).unwrap();
+                run_collected_tests(py, &discovery, &config).unwrap()
+            };
+            let first = shuffled_run();
+            let second = shuffled_run();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let names = |report: &crate::model::PyRunReport| -> Vec<String> {
+                report.results.iter().map(|r| r.name.clone()).collect()
+            };
+            assert_eq!(first.passed, 18);
+            assert_ne!(names(&first), names(&in_order));
+            assert_eq!(names(&first), names(&second));
+            // Each module's tests form one contiguous block
+            let mut seen_paths: Vec<&str> = Vec::new();
+            for result in &first.results {
+                if seen_paths.last() != Some(&result.path.as_str()) {
+                    assert!(
+                        !seen_paths.contains(&result.path.as_str()),
+                        "{} interleaves with another module",
+                        result.path
+                    );
+                    seen_paths.push(&result.path);
+                }
+            }
+            assert_eq!(seen_paths.len(), 3);
+        });
+    }
+
+    #[test]
+    fn parameter_set_marks_apply_to_their_case_only() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_param_marks");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_param_marks.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import mark, parametrize\n\
+                 from rustest.compat.pytest import param\n\n\
+                 @parametrize('value', [\n    \
+                     1,\n    \
+                     param(2, marks=mark.xfail(reason='known bug')),\n    \
+                     param(3, id='three', marks=[mark.skip(reason='not yet'), mark.integration]),\n\
+                 ])\n\
+                 def test_small(value):\n    \
+                     assert value < 2\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            config.mark_expr = Some("integration".to_string());
+            let paths = PyPaths::from_vec(vec![test_file.to_string_lossy().into_owned()]);
+            let selected = discover_tests(py, &paths, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let statuses: Vec<(String, String)> = report
+                .results
+                .iter()
+                .map(|result| (result.name.clone(), result.status.clone()))
+                .collect();
+            assert_eq!(
+                statuses,
+                vec![
+                    ("test_small[1]".to_string(), "passed".to_string()),
+                    ("test_small[2]".to_string(), "xfailed".to_string()),
+                    ("test_small[three]".to_string(), "skipped".to_string()),
+                ]
+            );
+            assert_eq!(report.results[2].message.as_deref(), Some("not yet"));
+            assert_eq!(selected.test_count(), 1);
+            assert_eq!(
+                selected.modules[0].tests[0].display_name,
+                "test_small[three]"
+            );
+        });
+    }
+
+    #[test]
+    fn skipif_marks_skip_when_any_condition_is_true() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_skipif");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_skipif.py");
+            std::fs::write(
+                &test_file,
+                "import sys\n\
+                 from rustest import mark\n\n\
+                 FLAG = True\n\n\
+                 @mark.skipif(True, reason='always')\n\
+                 def test_true():\n    assert False\n\n\
+                 @mark.skipif(False, reason='never')\n\
+                 def test_false():\n    pass\n\n\
+                 @mark.skipif(False, reason='first')\n\
+                 @mark.skipif(True, reason='second')\n\
+                 def test_either():\n    assert False\n\n\
+                 @mark.skipif('sys.platform == sys.platform')\n\
+                 def test_expression():\n    assert False\n\n\
+                 def test_unevaluated():\n    assert False\n\n\
+                 test_unevaluated.__rustest_marks__ = [\n    \
+                     {'name': 'skipif', 'args': ('FLAG',), 'kwargs': {}}\n\
+                 ]\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcome = |name: &str| {
+                let result = report.results.iter().find(|r| r.name == name).unwrap();
+                (
+                    result.status.clone(),
+                    result.message.clone().unwrap_or_default(),
+                )
+            };
+            assert_eq!(outcome("test_true"), ("skipped".into(), "always".into()));
+            assert_eq!(outcome("test_false").0, "passed");
+            assert_eq!(outcome("test_either"), ("skipped".into(), "second".into()));
+            assert_eq!(
+                outcome("test_expression"),
+                (
+                    "skipped".into(),
+                    "condition: sys.platform == sys.platform".into()
+                )
+            );
+            assert_eq!(
+                outcome("test_unevaluated"),
+                ("skipped".into(), "condition: FLAG".into())
+            );
+        });
+    }
+
+    #[test]
+    fn tests_returning_a_value_pass_with_a_warning() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_return_value");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_return_value.py");
+            std::fs::write(
+                &test_file,
+                "def test_returns_true():\n    return True\n\n\
+                 def test_returns_none():\n    return None\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 2);
+            assert_eq!(report.warnings.len(), 1);
+            let (node_id, message) = &report.warnings[0];
+            assert!(node_id.ends_with("test_return_value.py::test_returns_true"));
+            assert_eq!(
+                message,
+                "test returned a value (bool); did you mean to assert?"
+            );
+        });
+    }
+
+    #[test]
+    fn run_skipped_runs_skipped_tests_and_tags_them() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_run_skipped");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_run_skipped.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import mark\n\n\
+                 @mark.skip(reason=\"flaky on CI\")\n\
+                 def test_was_flaky():\n    assert True\n\n\
+                 def test_plain():\n    assert True\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let skipped = run_collected_tests(py, &discovery, &config).unwrap();
+            config.run_skipped = true;
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!((skipped.passed, skipped.skipped), (1, 1));
+            assert_eq!((report.passed, report.skipped), (2, 0));
+            let was_skipped = |name: &str| {
+                report
+                    .results
+                    .iter()
+                    .find(|result| result.name == name)
+                    .unwrap()
+                    .was_skipped
+                    .clone()
+            };
+            assert_eq!(
+                was_skipped("test_was_flaky").as_deref(),
+                Some("flaky on CI")
+            );
+            assert_eq!(was_skipped("test_plain"), None);
+        });
+    }
+
+    #[test]
+    fn teardown_errors_are_reported_with_their_traceback() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_teardown_errors");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_teardown_errors.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture\n\n\
+                 closed = []\n\n\
+                 @fixture(scope=\"module\")\n\
+                 def database():\n    yield 1\n    raise RuntimeError(\"still open\")\n\n\
+                 @fixture\n\
+                 def connection(database):\n    yield database\n    closed.append(True)\n\n\
+                 def test_uses_database(connection):\n    assert connection == 1\n\n\
+                 def test_teardown_ran():\n    assert closed == [True]\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 2);
+            assert_eq!(report.teardown_errors.len(), 1);
+            let error = &report.teardown_errors[0];
+            assert_eq!(error.fixture, "database");
+            assert_eq!(error.scope, "module");
+            assert!(error.message.starts_with("Traceback"));
+            assert!(error.message.contains("RuntimeError: still open"));
+        });
+    }
+
+    #[test]
+    fn import_timeout_turns_a_hanging_import_into_a_collection_error() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_import_timeout");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_hangs.py"),
+                "import time\ntime.sleep(3)\n\ndef test_never_collected():\n    pass\n",
+            )
+            .unwrap();
+            std::fs::write(
+                temp_dir.join("test_quick.py"),
+                "def test_collected():\n    pass\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.import_timeout = Some(std::time::Duration::from_millis(200));
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let started = std::time::Instant::now();
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let elapsed = started.elapsed();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(elapsed < std::time::Duration::from_secs(2));
+            assert_eq!(discovery.collection_errors.len(), 1);
+            let error = &discovery.collection_errors[0];
+            assert!(error.path.ends_with("test_hangs.py"));
+            assert!(error.message.contains("import timeout of 0.2s"));
+            let names: Vec<&str> = discovery
+                .modules
+                .iter()
+                .flat_map(|module| &module.tests)
+                .map(|test| test.name.as_str())
+                .collect();
+            assert_eq!(names, vec!["test_collected"]);
+        });
+    }
+
+    #[test]
+    fn gathered_async_tests_capture_only_their_own_output() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_gathered_capture");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_gathered_capture.py");
+            std::fs::write(
+                &test_file,
+                "import asyncio\nimport sys\n\n\
+                 async def test_first():\n    print(\"first starts\")\n    await asyncio.sleep(0.01)\n    print(\"first ends\")\n    assert False\n\n\
+                 async def test_second():\n    print(\"second starts\")\n    await asyncio.sleep(0.05)\n    print(\"second ends\", file=sys.stderr)\n",
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.default_test_loop_scope = FixtureScope::Module;
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let result = |name: &str| {
+                report
+                    .results
+                    .iter()
+                    .find(|result| result.name == name)
+                    .unwrap()
+            };
+            assert_eq!(result("test_first").status, "failed");
+            assert_eq!(
+                result("test_first").stdout.as_deref(),
+                Some("first starts\nfirst ends\n")
+            );
+            assert_eq!(result("test_first").stderr.as_deref(), Some(""));
+            assert_eq!(
+                result("test_second").stdout.as_deref(),
+                Some("second starts\n")
+            );
+            assert_eq!(
+                result("test_second").stderr.as_deref(),
+                Some("second ends\n")
+            );
+        });
+    }
+
+    #[test]
+    fn xunit_class_and_method_hooks_bracket_their_tests() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_xunit_hooks");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_xunit_hooks.py");
+            std::fs::write(
+                &test_file,
+                "calls = []\n\n\
+                 class TestHooks:\n\
+                 \x20   @classmethod\n\
+                 \x20   def setup_class(cls):\n        calls.append(\"setup_class\")\n\n\
+                 \x20   def teardown_class(cls):\n        calls.append(\"teardown_class\")\n\n\
+                 \x20   def setup_method(self, method):\n        self.value = method.__name__\n        calls.append(\"setup \" + method.__name__)\n\n\
+                 \x20   def teardown_method(self):\n        calls.append(\"teardown \" + self.value)\n\n\
+                 \x20   def test_a(self):\n        assert self.value == \"test_a\"\n\n\
+                 \x20   def test_b(self):\n        assert self.value == \"test_b\"\n\n\
+                 class TestBrokenSetup:\n\
+                 \x20   def setup_method(self):\n        raise RuntimeError(\"no connection\")\n\n\
+                 \x20   def test_never_runs(self):\n        calls.append(\"ran\")\n\n\
+                 class TestBrokenTeardown:\n\
+                 \x20   def teardown_method(self):\n        raise RuntimeError(\"still open\")\n\n\
+                 \x20   def test_passes(self):\n        pass\n\n\
+                 def test_order():\n\
+                 \x20   assert calls == [\n\
+                 \x20       \"setup_class\", \"setup test_a\", \"teardown test_a\",\n\
+                 \x20       \"setup test_b\", \"teardown test_b\", \"teardown_class\",\n\
+                 \x20   ]\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let status = |name: &str| {
+                let result = report
+                    .results
+                    .iter()
+                    .find(|result| result.name == name)
+                    .unwrap();
+                (result.status.clone(), result.message.clone())
+            };
+            assert_eq!(status("TestHooks::test_a").0, "passed");
+            assert_eq!(status("TestHooks::test_b").0, "passed");
+            assert_eq!(
+                status("test_order").0,
+                "passed",
+                "{:?}",
+                status("test_order").1
+            );
+            let (broken, message) = status("TestBrokenSetup::test_never_runs");
+            assert_eq!(broken, "error");
+            assert!(message.unwrap().contains("RuntimeError: no connection"));
+            assert_eq!(status("TestBrokenTeardown::test_passes").0, "passed");
+            assert_eq!(report.teardown_errors.len(), 1);
+            assert!(report.teardown_errors[0]
+                .message
+                .contains("RuntimeError: still open"));
+        });
+    }
+
+    #[test]
+    fn nested_dict_mismatches_report_the_differing_path() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_structural_diff");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(
+                temp_dir.join("test_profile.py"),
+                "def test_profile():\n    \
+                 actual = {'user': {'name': 'Ada', 'address': {'city': 'Lyon', 'zip': '69001'}}}\n    \
+                 expected = {'user': {'name': 'Ada', 'address': {'city': 'Paris', 'zip': '69001'}}}\n    \
+                 assert actual == expected\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let message = report.results[0].message.clone().unwrap();
+            let diff: Vec<&str> = message
+                .lines()
+                .filter_map(|line| line.strip_prefix("Diff: "))
+                .collect();
+            assert_eq!(
+                diff,
+                vec!["['user']['address']['city']: expected 'Paris', got 'Lyon'"],
+                "{}",
+                message
+            );
+        });
+    }
+
+    #[test]
+    fn results_record_explicit_autouse_and_dependent_fixtures() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_used_fixtures");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let test_file = temp_dir.join("test_used_fixtures.py");
+            std::fs::write(
+                &test_file,
+                "from rustest import fixture\n\n\
+                 @fixture(autouse=True)\n\
+                 def clean_env():\n    yield\n\n\
+                 @fixture\n\
+                 def db():\n    return {}\n\n\
+                 @fixture\n\
+                 def user(db):\n    return 'ada'\n\n\
+                 def test_profile(user):\n    assert user == 'ada'\n",
+            )
+            .unwrap();
+
+            let config = test_config();
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.passed, 1);
+            assert_eq!(report.results[0].fixtures, vec!["clean_env", "user", "db"]);
+        });
+    }
+
+    #[test]
+    fn test_results_expose_their_captured_output_to_python() {
+        Python::attach(|py| {
+            let result = crate::model::PyTestResult::failed(
+                "test_warns".to_string(),
+                "tests/test_io.py".to_string(),
+                0.25,
+                "AssertionError".to_string(),
+                Some("DeprecationWarning: old api\n".to_string()),
+                None,
+                vec!["slow".to_string()],
+            );
+            let result = pyo3::Py::new(py, result).unwrap();
+            let result = result.bind(py);
+
+            let stdout: String = result.getattr("stdout").unwrap().extract().unwrap();
+            assert!(stdout.contains("DeprecationWarning"));
+            assert!(result.getattr("stderr").unwrap().is_none());
+            let marks: Vec<String> = result.getattr("marks").unwrap().extract().unwrap();
+            assert_eq!(marks, vec!["slow"]);
+            assert_eq!(
+                result.repr().unwrap().to_string(),
+                "PyTestResult(node_id=\"tests/test_io.py::test_warns\", status=\"failed\", duration=0.250)"
+            );
+        });
+    }
+
+    #[test]
+    fn max_failures_stops_the_run_and_still_tears_down_fixtures() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_max_failures");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let marker = temp_dir.join("session_closed");
+            let test_file = temp_dir.join("test_max_failures.py");
+            std::fs::write(
+                &test_file,
+                format!(
+                    "from rustest import fixture\n\n\
+                     @fixture(scope='session')\n\
+                     def service():\n    yield 'up'\n    open({:?}, 'w').close()\n\n\
+                     def test_one(service):\n    assert False\n\n\
+                     def test_two():\n    pass\n\n\
+                     def test_three(service):\n    assert False\n\n\
+                     def test_four():\n    assert False\n\n\
+                     def test_five():\n    assert False\n",
+                    marker.to_string_lossy()
+                ),
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.max_failures = Some(2);
+            let discovery = run_discovery(py, &test_file);
+            let report = run_collected_tests(py, &discovery, &config).unwrap();
+            let session_closed = marker.exists();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!((report.total, report.passed, report.failed), (3, 1, 2));
+            assert!(session_closed, "session fixture was not torn down");
+        });
+    }
+
+    #[test]
+    fn sample_selects_a_reproducible_subset() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_sample");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let source: String = (0..20)
+                .map(|i| format!("def test_{}():\n    pass\n\n", i))
+                .collect();
+            std::fs::write(temp_dir.join("test_many.py"), source).unwrap();
+
+            let mut config = test_config();
+            config.sample = Some(5);
+            config.shuffle_seed = Some(7);
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let sampled_names = |config: &RunConfiguration| -> Vec<String> {
+                let modules = discover_tests(py, &paths, config)
+                    .expect("discovery should succeed")
+                    .modules;
+                modules
+                    .iter()
+                    .flat_map(|module| module.tests.iter().map(|test| test.name.clone()))
+                    .collect()
+            };
+
+            let first = sampled_names(&config);
+            let second = sampled_names(&config);
+            config.sample = Some(50);
+            let everything = sampled_names(&config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(first.len(), 5);
+            assert_eq!(first, second);
+            assert_eq!(everything.len(), 20);
+        });
+    }
+
+    #[test]
+    fn custom_event_loop_policy_is_used_and_restored() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_event_loop_policy");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_loop_policy.py");
+            std::fs::write(
+                &file_path,
+                "import asyncio\n\n\
+                 _loop_types = []\n\n\
+                 async def test_records_loop_type():\n    \
+                 _loop_types.append(type(asyncio.get_running_loop()).__name__)\n",
+            )
+            .unwrap();
+
+            let policy_module = PyModule::from_code(
+                py,
+                c"import asyncio\n\
+                  class CustomLoop(asyncio.SelectorEventLoop):\n    pass\n\
+                  class CustomPolicy(asyncio.DefaultEventLoopPolicy):\n    \
+                  def new_event_loop(self):\n        return CustomLoop()\n",
+                c"custom_policy.py",
+                c"custom_policy",
+            )
+            .expect("policy module should compile");
+            let policy = policy_module
+                .getattr("CustomPolicy")
+                .and_then(|cls| cls.call0())
+                .expect("policy should be created");
+            let asyncio = py.import("asyncio").unwrap();
+            let previous_policy = asyncio.call_method0("get_event_loop_policy").unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let mut config = test_config();
+            config.event_loop_policy = Some(policy.unbind());
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+            assert_eq!(report.passed, 1);
+
+            let loop_types = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_loop_types"))
+                .and_then(|types| types.extract::<Vec<String>>())
+                .expect("loop types should be recorded");
+            assert_eq!(loop_types, vec!["CustomLoop".to_string()]);
+
+            let current_policy = asyncio.call_method0("get_event_loop_policy").unwrap();
+            assert!(current_policy.is(&previous_policy));
+        });
+    }
+
+    #[test]
+    fn getfixturevalue_tears_down_generator_fixtures_after_the_test() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let file_path = sample_test_module("test_getfixturevalue_teardown.py");
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.failed, 0);
+            assert_eq!(report.passed, 2);
+
+            let events = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_events"))
+                .and_then(|events| events.extract::<Vec<String>>())
+                .expect("fixture events should be recorded");
+            assert_eq!(events, vec!["enter", "test body", "exit", "enter", "exit"]);
+        });
+    }
+
+    #[test]
+    fn failure_tracebacks_hide_library_frames() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Intentionally failing, so kept out of the repository's own test tree
+            let temp_dir = std::env::temp_dir().join("rustest_filter_traceback");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_library_error.py");
+            std::fs::write(
+                &file_path,
+                "import json\n\n\
+                 def test_parses_invalid_json():\n    json.loads('{')\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let mut config = test_config();
+            let message_with = |config: &RunConfiguration| {
+                let report =
+                    run_collected_tests(py, &discovery, config).expect("execution should succeed");
+                report.results[0].message.clone().unwrap_or_default()
+            };
+
+            let filtered = message_with(&config);
+            config.filter_traceback = false;
+            let full = message_with(&config);
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert!(filtered.contains("test_library_error.py"));
+            assert!(filtered.contains("JSONDecodeError"));
+            assert!(!filtered.contains("decoder.py"));
+            assert!(full.contains("decoder.py"));
+        });
+    }
+
+    #[test]
+    fn float_equality_failures_report_precision_delta() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Intentionally failing, so kept out of the repository's own test tree
+            let temp_dir = std::env::temp_dir().join("rustest_float_delta");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_float_delta.py");
+            std::fs::write(
+                &file_path,
+                "def test_float_sum():\n    assert 0.1 + 0.2 == 0.3\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let message = report.results[0].message.clone().unwrap_or_default();
+            assert!(message.contains("Expected: 0.3"), "{}", message);
+            assert!(
+                message.contains("Received: 0.30000000000000004 (differs by 5.551115123125783e-17"),
+                "{}",
+                message
+            );
+            assert!(message.contains("likely floating point precision"));
+        });
+    }
+
+    #[test]
+    fn results_count_captured_output_bytes() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_output_bytes");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_output_bytes.py");
+            std::fs::write(
+                &file_path,
+                "import sys\n\n\
+                 def test_chatty():\n    print('x' * 99)\n    sys.stderr.write('caf\u{e9}')\n\n\
+                 def test_quiet():\n    pass\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let sizes: Vec<(&str, usize, usize)> = report
+                .results
+                .iter()
+                .map(|r| (r.name.as_str(), r.stdout_bytes, r.stderr_bytes))
+                .collect();
+            // 99 characters plus the newline; "café" is five bytes in UTF-8
+            assert_eq!(sizes, [("test_chatty", 100, 5), ("test_quiet", 0, 0)]);
+        });
+    }
+
+    #[test]
+    fn enrich_exclude_leaves_matching_files_with_plain_tracebacks() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Intentionally failing, so kept out of the repository's own test tree
+            let temp_dir = std::env::temp_dir().join("rustest_enrich_exclude");
+            let legacy_dir = temp_dir.join("legacy");
+            std::fs::create_dir_all(&legacy_dir).unwrap();
+            let failing = "def test_mismatch():\n    actual = 1\n    assert actual == 2\n";
+            std::fs::write(legacy_dir.join("test_legacy.py"), failing).unwrap();
+            std::fs::write(temp_dir.join("test_current.py"), failing).unwrap();
+
+            let mut config = test_config();
+            config.enrich_exclude = vec!["**/legacy/*.py".to_string()];
+            let paths = PyPaths::from_vec(vec![temp_dir.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let message_for = |file: &str| {
+                report
+                    .results
+                    .iter()
+                    .find(|r| r.path.ends_with(file))
+                    .and_then(|r| r.message.clone())
+                    .unwrap()
+            };
+            let legacy = message_for("test_legacy.py");
+            let current = message_for("test_current.py");
+            assert!(legacy.contains("AssertionError"), "{}", legacy);
+            assert!(
+                !legacy.contains("__RUSTEST_ASSERTION_VALUES__"),
+                "{}",
+                legacy
+            );
+            assert!(
+                current.contains("__RUSTEST_ASSERTION_VALUES__"),
+                "{}",
+                current
+            );
+            assert!(current.contains("Received: 1"), "{}", current);
+        });
+    }
+
+    #[test]
+    fn resume_skips_and_merges_tests_recorded_in_the_journal() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Would fail if run again, so kept out of the repository's own test tree
+            let temp_dir = std::env::temp_dir().join("rustest_resume");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_resume.py");
+            std::fs::write(
+                &file_path,
+                "def test_done():\n    raise RuntimeError('ran again')\n\n\
+                 def test_broke():\n    raise RuntimeError('ran again')\n\n\
+                 def test_pending():\n    pass\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let path = discovery.modules[0].tests[0].unique_id();
+            let path = path.trim_end_matches("::test_done");
+            // The interrupted run finished two tests and crashed mid-line on a third
+            let journal_path = temp_dir.join("journal");
+            std::fs::write(
+                &journal_path,
+                format!(
+                    "{}\n{}\n{{\"path\": \"{}\", \"na",
+                    serde_json::json!({"path": path, "name": "test_done", "status": "passed", "duration": 0.5, "message": null}),
+                    serde_json::json!({"path": path, "name": "test_broke", "status": "failed", "duration": 0.1, "message": "boom"}),
+                    path
+                ),
+            )
+            .unwrap();
+
+            let mut config = test_config();
+            config.resume = true;
+            config.journal_path = journal_path.clone();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            let journal_left = journal_path.exists();
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let outcomes: Vec<(&str, &str, Option<&str>)> = report
+                .results
+                .iter()
+                .map(|r| (r.name.as_str(), r.status.as_str(), r.message.as_deref()))
+                .collect();
+            assert_eq!(
+                outcomes,
+                [
+                    ("test_done", "passed", None),
+                    ("test_broke", "failed", Some("boom")),
+                    ("test_pending", "passed", None),
+                ]
+            );
+            assert_eq!((report.passed, report.failed), (2, 1));
+            // A clean finish leaves nothing to resume
+            assert!(!journal_left);
+        });
+    }
+
+    #[test]
+    fn check_reports_every_fixture_graph_problem_without_running() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Deliberately broken fixtures, so kept out of the repository's own test tree
+            let temp_dir = std::env::temp_dir().join("rustest_fixture_check");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_broken_graph.py");
+            std::fs::write(
+                &file_path,
+                "from rustest import fixture\n\n\
+                 _calls = []\n\n\
+                 @fixture\n\
+                 def per_test():\n    _calls.append('per_test')\n    return 1\n\n\
+                 @fixture(scope='session')\n\
+                 def shared(per_test):\n    _calls.append('shared')\n    return per_test\n\n\
+                 def test_missing(not_defined):\n    _calls.append('test_missing')\n\n\
+                 def test_scope(shared):\n    _calls.append('test_scope')\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let mut config = test_config();
+            config.check = true;
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("check should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.total, 0);
+            let messages: Vec<&str> = report
+                .collection_errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect();
+            assert_eq!(messages.len(), 2, "{:?}", messages);
+            assert!(messages[0].contains("test_missing: Unknown fixture 'not_defined'"));
+            assert!(messages[1].contains("ScopeMismatch: Fixture 'shared'"));
+
+            let calls = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .unwrap()
+                .get_item("_calls")
+                .unwrap();
+            assert_eq!(calls.len().unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn results_are_reported_in_definition_order() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_result_order");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_interleaved.py");
+            std::fs::write(
+                &file_path,
+                "import asyncio\n\
+                 from rustest import mark\n\n\
+                 @mark.asyncio(loop_scope='module')\n\
+                 async def test_async_1():\n    await asyncio.sleep(0.02)\n\n\
+                 def test_sync_1():\n    pass\n\n\
+                 class TestGroup:\n    def test_in_class(self):\n        pass\n\n\
+                 @mark.asyncio(loop_scope='module')\n\
+                 async def test_async_2():\n    await asyncio.sleep(0.01)\n\n\
+                 @mark.asyncio(loop_scope='module')\n\
+                 async def test_async_3():\n    await asyncio.sleep(0)\n\n\
+                 def test_sync_2():\n    pass\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            let defined: Vec<String> = discovery.modules[0]
+                .tests
+                .iter()
+                .map(|test| test.display_name.clone())
+                .collect();
+            let reported: Vec<String> = report
+                .results
+                .iter()
+                .map(|result| result.name.clone())
+                .collect();
+            assert_eq!(report.passed, 6);
+            assert_eq!(reported, defined);
+        });
+    }
+
+    #[test]
+    fn collection_errors_abort_only_when_not_continuing() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let temp_dir = std::env::temp_dir().join("rustest_collection_error_abort");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            std::fs::write(temp_dir.join("test_broken.py"), "def test_broken(:\n").unwrap();
+            std::fs::write(temp_dir.join("test_ok.py"), "def test_ok():\n    pass\n").unwrap();
+
+            let discovery = run_discovery(py, &temp_dir);
+            std::fs::remove_dir_all(&temp_dir).ok();
+            assert_eq!(discovery.collection_errors.len(), 1);
+
+            let mut config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.passed, 1);
+            assert_eq!(report.collection_errors.len(), 1);
+
+            config.continue_on_collection_errors = false;
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.total, 0);
+            assert!(report.results.is_empty());
+            assert_eq!(report.collection_errors.len(), 1);
+        });
+    }
+
+    #[test]
+    fn unused_fixture_params_are_never_executed() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            let file_path = sample_test_module("test_lazy_fixture_params.py");
+
+            let mut config = test_config();
+            config.pattern = Some("test_backend[sqlite]".to_string());
+            let paths = PyPaths::from_vec(vec![file_path.to_string_lossy().into_owned()]);
+            let discovery = discover_tests(py, &paths, &config).expect("discovery should succeed");
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            assert_eq!(report.passed, 1);
+
+            let calls = discovery.modules[0].tests[0]
+                .callable
+                .bind(py)
+                .getattr("__globals__")
+                .and_then(|globals| globals.get_item("_calls"))
+                .and_then(|calls| calls.extract::<Vec<String>>())
+                .expect("fixture calls should be recorded");
+            assert_eq!(calls, vec!["sqlite".to_string()]);
+        });
+    }
+
+    #[test]
+    fn fixture_setup_failures_are_reported_as_errors() {
+        Python::attach(|py| {
+            ensure_python_package_on_path(py);
+            // Written to a temp dir so the failing sample is not picked up
+            // when the repository's own tests are run.
+            let temp_dir = std::env::temp_dir().join("rustest_setup_errors");
+            std::fs::create_dir_all(&temp_dir).unwrap();
+            let file_path = temp_dir.join("test_setup_errors.py");
+            std::fs::write(
+                &file_path,
+                "from rustest import fixture\n\n\
+                 @fixture\n\
+                 def broken():\n    raise RuntimeError('setup failed')\n\n\
+                 def test_uses_broken_fixture(broken):\n    pass\n\n\
+                 def test_body_fails():\n    assert False\n",
+            )
+            .unwrap();
+
+            let discovery = run_discovery(py, &file_path);
+            let config = test_config();
+            let report =
+                run_collected_tests(py, &discovery, &config).expect("execution should succeed");
+            std::fs::remove_dir_all(&temp_dir).ok();
+
+            assert_eq!(report.total, 2);
+            assert_eq!(report.errors, 1);
+            assert_eq!(report.failed, 1);
+
+            let status_of = |name: &str| {
+                report
+                    .results
+                    .iter()
+                    .find(|result| result.name == name)
+                    .map(|result| result.status.clone())
+            };
+            assert_eq!(
+                status_of("test_uses_broken_fixture").as_deref(),
+                Some("error")
+            );
+            assert_eq!(status_of("test_body_fails").as_deref(), Some("failed"));
+        });
+    }
+
+    #[test]
+    fn test_worker_count_configuration() {
+        let config1 = RunConfiguration::new(
+            None,
+            None,
+            Some(1),
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        );
+        assert_eq!(config1.worker_count, 1);
+
+        let config2 = RunConfiguration::new(
+            None,
+            None,
+            Some(8),
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        );
+        assert_eq!(config2.worker_count, 8);
+
+        let config3 = RunConfiguration::new(
+            None,
+            None,
+            None,
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        );
+        assert!(config3.worker_count >= 1);
+
+        // Only an explicit worker count leaves the in-process executor
+        assert_eq!(config1.worker_processes, 1);
+        assert_eq!(config2.worker_processes, 8);
+        assert_eq!(config3.worker_processes, 1);
     }
 }
@@ -120,8 +120,9 @@ impl Lexer {
                 })
             }
             _ => {
+                // Skip unknown characters without ending the token stream
                 self.pos += 1;
-                None // Skip unknown characters
+                self.next_token()
             }
         }
     }
@@ -284,6 +285,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_skips_unknown_characters() {
+        let expr = MarkExpr::parse("slow, or fast").unwrap();
+        assert_eq!(
+            expr,
+            MarkExpr::Or(
+                Box::new(MarkExpr::Name("slow".to_string())),
+                Box::new(MarkExpr::Name("fast".to_string()))
+            )
+        );
+    }
+
     #[test]
     fn test_matches_simple() {
         let expr = MarkExpr::parse("slow").unwrap();
@@ -0,0 +1,156 @@
+//! Marker introspection for `list_markers`
+//!
+//! The registry is rustest's built-in marks plus any the project registers
+//! with a description. It is combined with the marks actually used by the
+//! collected tests, so typos and undocumented marks show up as unregistered.
+
+use crate::model::TestModule;
+use indexmap::IndexMap;
+use pyo3::prelude::*;
+
+/// Marks rustest itself gives meaning to.
+const BUILTIN_MARKERS: &[(&str, &str)] = &[
+    (
+        "asyncio",
+        "run the test on an asyncio event loop; accepts loop_scope and timeout",
+    ),
+    (
+        "exclusive",
+        "never run at the same time as other tests naming the same resource",
+    ),
+    (
+        "flaky",
+        "rerun a failing test up to `reruns` times, `reruns_delay` seconds apart",
+    ),
+    (
+        "no_autouse",
+        "opt out of all autouse fixtures, or of the named ones",
+    ),
+    (
+        "parametrize",
+        "run the test once per set of argument values",
+    ),
+    ("skip", "always skip the test, with an optional reason"),
+    (
+        "skipif",
+        "skip the test when the condition is true, with a reason",
+    ),
+    ("slow", "deselected unless --run-slow is given"),
+    (
+        "usefixtures",
+        "set up the named fixtures without requesting them as arguments",
+    ),
+    (
+        "xfail",
+        "expect the test to fail; supports condition, reason, raises, run and strict",
+    ),
+];
+
+/// A mark known to the registry, used by collected tests, or both.
+#[pyclass(module = "rustest.rust")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkerInfo {
+    #[pyo3(get)]
+    pub name: String,
+    /// Description from the registry; `None` for unregistered marks
+    #[pyo3(get)]
+    pub description: Option<String>,
+    /// Whether the mark is built in or registered by the project
+    #[pyo3(get)]
+    pub registered: bool,
+    /// Number of collected tests carrying the mark
+    #[pyo3(get)]
+    pub tests: usize,
+}
+
+#[pymethods]
+impl MarkerInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "MarkerInfo(name={:?}, registered={}, tests={})",
+            self.name,
+            if self.registered { "True" } else { "False" },
+            self.tests
+        )
+    }
+}
+
+/// Combine the built-in and `registered` marks with the marks used by the
+/// collected tests.
+///
+/// Registered marks come first, built-ins then the project's in registry
+/// order, followed by unregistered marks in alphabetical order. A project
+/// description replaces the built-in one for the same name.
+pub fn list_markers(
+    modules: &[TestModule],
+    registered: &IndexMap<String, String>,
+) -> Vec<MarkerInfo> {
+    let mut markers: IndexMap<String, MarkerInfo> = IndexMap::new();
+    let builtins = BUILTIN_MARKERS
+        .iter()
+        .map(|(name, description)| (name.to_string(), description.to_string()));
+    for (name, description) in builtins.chain(registered.clone()) {
+        markers.insert(
+            name.clone(),
+            MarkerInfo {
+                name,
+                description: Some(description),
+                registered: true,
+                tests: 0,
+            },
+        );
+    }
+
+    let mut unregistered: Vec<MarkerInfo> = Vec::new();
+    for test in modules.iter().flat_map(|module| &module.tests) {
+        let mut names = test.mark_names();
+        names.sort();
+        names.dedup();
+        for name in names {
+            match markers.get_mut(&name) {
+                Some(marker) => marker.tests += 1,
+                None => match unregistered.iter_mut().find(|marker| marker.name == name) {
+                    Some(marker) => marker.tests += 1,
+                    None => unregistered.push(MarkerInfo {
+                        name,
+                        description: None,
+                        registered: false,
+                        tests: 1,
+                    }),
+                },
+            }
+        }
+    }
+    unregistered.sort_by(|a, b| a.name.cmp(&b.name));
+
+    markers.into_values().chain(unregistered).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_descriptions_override_builtins_and_extend_the_registry() {
+        let registered: IndexMap<String, String> = [
+            ("slow", "takes more than a second"),
+            ("integration", "talks to real services"),
+        ]
+        .iter()
+        .map(|(name, description)| (name.to_string(), description.to_string()))
+        .collect();
+
+        let markers = list_markers(&[], &registered);
+
+        let slow = markers.iter().find(|marker| marker.name == "slow").unwrap();
+        assert_eq!(
+            slow.description.as_deref(),
+            Some("takes more than a second")
+        );
+        let last = markers.last().unwrap();
+        assert_eq!(last.name, "integration");
+        assert!(last.registered);
+        assert_eq!(last.tests, 0);
+        assert_eq!(markers.len(), BUILTIN_MARKERS.len() + 1);
+    }
+}
@@ -5,8 +5,9 @@
 //! we ensure that the control flow is easy to follow for developers who may not
 //! have much Rust experience yet.
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 use indexmap::IndexMap;
 use pyo3::exceptions::PyValueError;
@@ -70,6 +71,17 @@ impl FixtureScope {
             _ => Err(format!("Invalid fixture scope: {}", s)),
         }
     }
+
+    /// The scope's name as Python spells it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FixtureScope::Function => "function",
+            FixtureScope::Class => "class",
+            FixtureScope::Module => "module",
+            FixtureScope::Package => "package",
+            FixtureScope::Session => "session",
+        }
+    }
 }
 
 /// Metadata describing a mark applied to a test function.
@@ -115,6 +127,56 @@ impl Mark {
     }
 }
 
+/// Name prefix of the fixtures running a test class's xunit-style
+/// `setup_*`/`teardown_*` hooks.
+pub const XUNIT_FIXTURE_PREFIX: &str = "_xunit_";
+
+/// Autouse fixtures a test opted out of with `@mark.no_autouse`.
+///
+/// `@mark.no_autouse("db", "clock")` names the fixtures to skip; a bare
+/// `@mark.no_autouse` skips every autouse fixture.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AutouseExclusion {
+    #[default]
+    None,
+    All,
+    Named(Vec<String>),
+}
+
+impl AutouseExclusion {
+    /// Combine the `no_autouse` marks on a test.
+    pub fn from_marks(py: Python<'_>, marks: &[Mark]) -> PyResult<Self> {
+        let mut exclusion = Self::None;
+        for mark in marks.iter().filter(|mark| mark.is_named("no_autouse")) {
+            let names: Vec<String> = mark.args.bind(py).extract().map_err(|_| {
+                invalid_test_definition("@mark.no_autouse expects fixture names as strings")
+            })?;
+            exclusion = match (exclusion, names.is_empty()) {
+                (Self::All, _) | (_, true) => Self::All,
+                (Self::None, false) => Self::Named(names),
+                (Self::Named(mut existing), false) => {
+                    existing.extend(names);
+                    Self::Named(existing)
+                }
+            };
+        }
+        Ok(exclusion)
+    }
+
+    /// Whether the autouse fixture `name` should be skipped. The xunit hooks
+    /// of a test class are part of the class, not opt-out fixtures.
+    pub fn excludes(&self, name: &str) -> bool {
+        if name.starts_with(XUNIT_FIXTURE_PREFIX) {
+            return false;
+        }
+        match self {
+            Self::None => false,
+            Self::All => true,
+            Self::Named(names) => names.iter().any(|excluded| excluded == name),
+        }
+    }
+}
+
 /// Metadata describing a single fixture function.
 pub struct Fixture {
     pub name: String,
@@ -224,10 +286,15 @@ pub struct TestCase {
     /// Parameters that should be resolved as fixture references (indirect parametrization).
     /// Contains the parameter names that are marked as indirect.
     pub indirect_params: Vec<String>,
+    /// Scope of fixtures built from indirect parameters, from
+    /// `@parametrize(..., scope=...)`. Unlisted parameters use the fixture's scope.
+    pub param_scopes: HashMap<String, FixtureScope>,
     /// Whether this test function has @patch decorators.
     /// When true, fixture args are passed as keyword arguments so that
     /// unittest.mock.patch can prepend mock objects as positional args.
     pub has_patches: bool,
+    /// Autouse fixtures this test opted out of with `@mark.no_autouse`.
+    pub excluded_autouse: AutouseExclusion,
 }
 
 impl TestCase {
@@ -239,6 +306,24 @@ impl TestCase {
     pub fn mark_names(&self) -> Vec<String> {
         self.marks.iter().map(|m| m.name.clone()).collect()
     }
+
+    /// Resources named by `@mark.exclusive("name", ...)`. Tests sharing a
+    /// resource never run at the same time; a bare `@mark.exclusive` names
+    /// the unnamed resource `""`.
+    pub fn exclusive_resources(&self, py: Python<'_>) -> Vec<String> {
+        let mut resources = Vec::new();
+        for mark in self.marks.iter().filter(|mark| mark.is_named("exclusive")) {
+            let args = mark.args.bind(py);
+            if args.is_empty() {
+                resources.push(String::new());
+            }
+            resources.extend(
+                args.iter()
+                    .map(|arg| arg.str().map(|name| name.to_string()).unwrap_or_default()),
+            );
+        }
+        resources
+    }
 }
 
 /// Collection of fixtures and test cases for a Python module.
@@ -276,6 +361,20 @@ impl TestModule {
     }
 }
 
+/// Everything a discovery pass produced: the modules with their tests and
+/// fixtures, and the files that failed to collect.
+pub struct Discovery {
+    pub modules: Vec<TestModule>,
+    pub collection_errors: Vec<CollectionError>,
+}
+
+impl Discovery {
+    /// Total number of collected tests across all modules.
+    pub fn test_count(&self) -> usize {
+        self.modules.iter().map(|module| module.tests.len()).sum()
+    }
+}
+
 /// Mode for running last failed tests.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LastFailedMode {
@@ -305,6 +404,9 @@ pub struct RunConfiguration {
     pub pattern: Option<String>,
     pub mark_expr: Option<String>,
     pub worker_count: usize,
+    /// Worker subprocesses to shard modules across. Only an explicit
+    /// `workers` above one leaves the in-process executor.
+    pub worker_processes: usize,
     pub capture_output: bool,
     pub enable_codeblocks: bool,
     pub last_failed_mode: LastFailedMode,
@@ -318,6 +420,105 @@ pub struct RunConfiguration {
     pub default_test_loop_scope: FixtureScope,
     /// Default loop scope for async fixtures (from pyproject.toml asyncio_default_fixture_loop_scope).
     pub default_fixture_loop_scope: FixtureScope,
+    /// Print one summary line per file instead of per-test progress.
+    pub compact: bool,
+    /// Force colored output even when stderr is not a terminal.
+    pub force_color: bool,
+    /// Maximum display width for test names; defaults to the terminal width.
+    pub max_name_width: Option<usize>,
+    /// Upper bound on the worker count (`--maxprocesses`).
+    pub max_processes: Option<usize>,
+    /// Log discovery decisions and `sys.path` additions to stderr.
+    pub trace_config: bool,
+    /// Skip collecting test files larger than this many bytes (`--max-file-size`).
+    pub max_file_size: Option<u64>,
+    /// Event loop policy installed for the session (e.g. uvloop's), restored afterwards.
+    pub event_loop_policy: Option<pyo3::Py<pyo3::PyAny>>,
+    /// Hide stdlib and site-packages frames from failure tracebacks (disabled by `--full-trace`).
+    pub filter_traceback: bool,
+    /// Run the collected tests even when some files failed to collect; when false, any
+    /// collection error aborts the run before tests execute.
+    pub continue_on_collection_errors: bool,
+    /// Validate the fixture graph of every collected test without executing anything.
+    pub check: bool,
+    /// Overrides for the symbol shown per outcome (`passed`, `failed`, `skipped`, `xfailed`).
+    pub status_symbols: HashMap<String, String>,
+    /// Run only a random sample of up to this many collected tests.
+    pub sample: Option<usize>,
+    /// Seed for `sample` and `shuffle`; a time-based seed is used (and printed) when unset.
+    pub shuffle_seed: Option<u64>,
+    /// Print failures as GitHub Actions `::error` workflow commands.
+    pub github_annotations: bool,
+    /// Write a TAP version 13 report here after the run (`-` for stdout).
+    pub tap_output: Option<PathBuf>,
+    /// Soft-wrap failure message lines to the terminal width
+    pub wrap_failures: bool,
+    /// Run only the tests that passed in this prior JSON report, matched by node id.
+    pub passed_only: Option<PathBuf>,
+    /// Threads reading test files ahead of the (sequential) imports during discovery
+    pub discovery_threads: Option<usize>,
+    /// Run tests marked `slow`, which are otherwise deselected unless a mark expression is given
+    pub run_slow: bool,
+    /// Globs of files whose assertion failures are shown without Expected/Received enrichment
+    pub enrich_exclude: Vec<String>,
+    /// Skip tests recorded in the progress journal by an interrupted run, merging their outcomes
+    pub resume: bool,
+    /// Where completed tests are journaled for `resume`
+    pub journal_path: PathBuf,
+    /// File of node ids whose failures are expected, kept outside the test code
+    pub xfail_list: Option<PathBuf>,
+    /// Node ids read from `xfail_list`, with the reason given on their line
+    pub xfail_entries: HashMap<String, Option<String>>,
+    /// Record the lines each test executes with `sys.settrace`, written to `line_trace_path`
+    pub line_trace: bool,
+    /// Where `line_trace` writes its JSON map
+    pub line_trace_path: PathBuf,
+    /// SQLite database that each test result is appended to during the run
+    pub sqlite_path: Option<PathBuf>,
+    /// Git revision to diff against; only tests whose lines (or imported code) changed are run
+    pub changed_lines: Option<String>,
+    /// Print the N test files that took longest after the run
+    pub slowest_files: Option<usize>,
+    /// Run async tests with asyncio debug mode and report the task warnings they leave behind
+    pub asyncio_debug: bool,
+    /// Write a Chrome tracing JSON file with collection, test and fixture spans
+    pub trace_json: Option<PathBuf>,
+    /// Print failures that share the same error once, listing the other affected tests
+    pub group_failures: bool,
+    /// Fail tests whose explicit loop_scope is narrower than their async fixtures need; when false, widen the scope and warn
+    pub loop_scope_strict: bool,
+    /// Collect tests from `test_*.ipynb` and `*_test.ipynb` notebooks
+    pub notebooks: bool,
+    /// Write a JUnit XML report of the run to this path
+    pub junit_xml_path: Option<PathBuf>,
+    /// Write a JSON report of the run to this path
+    pub json_report_path: Option<PathBuf>,
+    /// Print this many slowest tests after the run (0 prints all)
+    pub durations: Option<usize>,
+    /// Called once after the run with the report and the paths of the report files written
+    pub on_complete: Option<pyo3::Py<pyo3::PyAny>>,
+    /// Exception class names counted as test failures; any other exception from the test body is reported as an error. `None` counts every exception as a failure
+    pub failure_exceptions: Option<Vec<String>>,
+    /// Level `caplog` captures at by default (a level name or number); `None` captures every record
+    pub log_level: Option<String>,
+    /// Run modules, and the tests within each module, in a random order seeded by `shuffle_seed`
+    pub shuffle: bool,
+    /// Run tests that would be skipped by a skip reason or skip mark, tagging their results with the reason
+    pub run_skipped: bool,
+    /// Longest a test file may take to import before it becomes a collection error
+    pub import_timeout: Option<Duration>,
+    /// List the fixtures each test used under its line in verbose output
+    pub show_test_fixtures: bool,
+    /// Show one full traceback per parametrized test and a line for each identical failure
+    pub collapse_param_failures: bool,
+    /// Stop the run once this many tests have failed or errored; `fail_fast` is `Some(1)`
+    pub max_failures: Option<usize>,
+    /// Run exactly these `path::name` node ids, in this order, instead of
+    /// selecting tests; worker processes get the parent's selection this way
+    pub node_ids: Option<Vec<String>>,
+    /// This run is a worker session of another run, which owns the
+    /// last-failed cache, the journal and the `RUSTEST_ADDOPTS` options
+    pub worker: bool,
 }
 
 impl Clone for RunConfiguration {
@@ -326,6 +527,7 @@ impl Clone for RunConfiguration {
             pattern: self.pattern.clone(),
             mark_expr: self.mark_expr.clone(),
             worker_count: self.worker_count,
+            worker_processes: self.worker_processes,
             capture_output: self.capture_output,
             enable_codeblocks: self.enable_codeblocks,
             last_failed_mode: self.last_failed_mode,
@@ -340,6 +542,60 @@ impl Clone for RunConfiguration {
                 .map(|cb| pyo3::Python::attach(|py| cb.clone_ref(py))),
             default_test_loop_scope: self.default_test_loop_scope,
             default_fixture_loop_scope: self.default_fixture_loop_scope,
+            compact: self.compact,
+            force_color: self.force_color,
+            max_name_width: self.max_name_width,
+            max_processes: self.max_processes,
+            trace_config: self.trace_config,
+            max_file_size: self.max_file_size,
+            event_loop_policy: self
+                .event_loop_policy
+                .as_ref()
+                .map(|policy| pyo3::Python::attach(|py| policy.clone_ref(py))),
+            filter_traceback: self.filter_traceback,
+            continue_on_collection_errors: self.continue_on_collection_errors,
+            check: self.check,
+            status_symbols: self.status_symbols.clone(),
+            sample: self.sample,
+            shuffle_seed: self.shuffle_seed,
+            github_annotations: self.github_annotations,
+            tap_output: self.tap_output.clone(),
+            wrap_failures: self.wrap_failures,
+            passed_only: self.passed_only.clone(),
+            discovery_threads: self.discovery_threads,
+            run_slow: self.run_slow,
+            enrich_exclude: self.enrich_exclude.clone(),
+            resume: self.resume,
+            journal_path: self.journal_path.clone(),
+            xfail_list: self.xfail_list.clone(),
+            xfail_entries: self.xfail_entries.clone(),
+            line_trace: self.line_trace,
+            line_trace_path: self.line_trace_path.clone(),
+            sqlite_path: self.sqlite_path.clone(),
+            changed_lines: self.changed_lines.clone(),
+            slowest_files: self.slowest_files,
+            asyncio_debug: self.asyncio_debug,
+            trace_json: self.trace_json.clone(),
+            group_failures: self.group_failures,
+            loop_scope_strict: self.loop_scope_strict,
+            notebooks: self.notebooks,
+            junit_xml_path: self.junit_xml_path.clone(),
+            json_report_path: self.json_report_path.clone(),
+            durations: self.durations,
+            on_complete: self
+                .on_complete
+                .as_ref()
+                .map(|hook| pyo3::Python::attach(|py| hook.clone_ref(py))),
+            failure_exceptions: self.failure_exceptions.clone(),
+            log_level: self.log_level.clone(),
+            shuffle: self.shuffle,
+            run_skipped: self.run_skipped,
+            import_timeout: self.import_timeout,
+            show_test_fixtures: self.show_test_fixtures,
+            collapse_param_failures: self.collapse_param_failures,
+            max_failures: self.max_failures,
+            node_ids: self.node_ids.clone(),
+            worker: self.worker,
         }
     }
 }
@@ -362,11 +618,12 @@ impl RunConfiguration {
         default_test_loop_scope: FixtureScope,
         default_fixture_loop_scope: FixtureScope,
     ) -> Self {
-        let worker_count = workers.unwrap_or_else(|| rayon::current_num_threads().max(1));
+        let worker_count = resolve_worker_count(workers, None, rayon::current_num_threads());
         Self {
             pattern,
             mark_expr,
             worker_count,
+            worker_processes: workers.unwrap_or(1).max(1),
             capture_output,
             enable_codeblocks,
             last_failed_mode,
@@ -378,8 +635,291 @@ impl RunConfiguration {
             event_callback,
             default_test_loop_scope,
             default_fixture_loop_scope,
+            compact: false,
+            force_color: false,
+            max_name_width: None,
+            max_processes: None,
+            trace_config: false,
+            max_file_size: None,
+            event_loop_policy: None,
+            filter_traceback: true,
+            continue_on_collection_errors: true,
+            check: false,
+            status_symbols: HashMap::new(),
+            sample: None,
+            shuffle_seed: None,
+            github_annotations: false,
+            tap_output: None,
+            wrap_failures: false,
+            passed_only: None,
+            discovery_threads: None,
+            run_slow: false,
+            enrich_exclude: Vec::new(),
+            resume: false,
+            journal_path: crate::cache::default_journal_path(),
+            xfail_list: None,
+            xfail_entries: HashMap::new(),
+            line_trace: false,
+            line_trace_path: crate::cache::default_line_trace_path(),
+            sqlite_path: None,
+            changed_lines: None,
+            slowest_files: None,
+            asyncio_debug: false,
+            trace_json: None,
+            group_failures: false,
+            loop_scope_strict: true,
+            notebooks: false,
+            junit_xml_path: None,
+            json_report_path: None,
+            durations: None,
+            on_complete: None,
+            failure_exceptions: None,
+            log_level: None,
+            shuffle: false,
+            run_skipped: false,
+            import_timeout: None,
+            show_test_fixtures: false,
+            collapse_param_failures: false,
+            max_failures: None,
+            node_ids: None,
+            worker: false,
+        }
+    }
+
+    /// Read the expected-failures list at `path`.
+    ///
+    /// Each line holds one node id; text after `#` is the reason, and blank
+    /// or comment-only lines are ignored.
+    pub fn load_xfail_list(&mut self, path: PathBuf) -> PyResult<()> {
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            pyo3::exceptions::PyIOError::new_err(format!(
+                "Failed to read xfail list {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        self.xfail_entries = parse_xfail_list(&content);
+        self.xfail_list = Some(path);
+        Ok(())
+    }
+
+    /// Number of failed or erroring tests that stops the run, if any.
+    ///
+    /// `fail_fast` is shorthand for a limit of one; a `max_failures` of zero
+    /// means no limit, as with pytest's `--maxfail=0`.
+    pub fn failure_limit(&self) -> Option<usize> {
+        if self.fail_fast {
+            return Some(1);
+        }
+        self.max_failures.filter(|&limit| limit > 0)
+    }
+
+    /// The first option set that only works when every test runs in this
+    /// process. Such runs ignore `workers` and run in-process instead.
+    pub fn in_process_option(&self) -> Option<&'static str> {
+        [
+            (self.fail_fast, "--exitfirst"),
+            (self.failure_limit().is_some(), "--maxfail"),
+            (self.resume, "--resume"),
+            (self.line_trace, "--line-trace"),
+            (self.trace_json.is_some(), "--trace-json"),
+            (self.event_loop_policy.is_some(), "event_loop_policy"),
+        ]
+        .into_iter()
+        .find_map(|(set, option)| set.then_some(option))
+    }
+
+    /// Apply options from the `RUSTEST_ADDOPTS` environment variable, if set.
+    pub fn apply_env_addopts(&mut self) -> Result<(), String> {
+        match std::env::var("RUSTEST_ADDOPTS") {
+            Ok(addopts) => self.apply_addopts(&addopts),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Apply command-line style options, e.g. from `RUSTEST_ADDOPTS`.
+    ///
+    /// Options taking a value (`-k`, `-m`, ...) sit between file
+    /// configuration and explicit arguments: they only take effect when the
+    /// run didn't already set them. Flags behave like options appended to the
+    /// command line, as with `PYTEST_ADDOPTS`: a flag is always applied, so
+    /// `--no-capture` or `--full-trace` here win over the argument they flip.
+    pub fn apply_addopts(&mut self, addopts: &str) -> Result<(), String> {
+        let tokens = split_shell_words(addopts)?;
+        let mut tokens = tokens.into_iter();
+
+        while let Some(token) = tokens.next() {
+            let (flag, inline_value) = match token.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (token.clone(), None),
+            };
+            let mut value = |name: &str| {
+                inline_value
+                    .clone()
+                    .or_else(|| tokens.next())
+                    .ok_or_else(|| format!("RUSTEST_ADDOPTS: {} expects a value", name))
+            };
+            let number = |name: &str, raw: String| {
+                raw.parse::<u64>().map_err(|_| {
+                    format!("RUSTEST_ADDOPTS: {} expects a number, got '{}'", name, raw)
+                })
+            };
+
+            match flag.as_str() {
+                "-k" | "--pattern" => {
+                    let pattern = value(&flag)?;
+                    self.pattern.get_or_insert(pattern);
+                }
+                "-m" | "--marks" => {
+                    let mark_expr = value(&flag)?;
+                    self.mark_expr.get_or_insert(mark_expr);
+                }
+                "--max-file-size" => {
+                    let size = number(&flag, value(&flag)?)?;
+                    self.max_file_size.get_or_insert(size);
+                }
+                "--sample" => {
+                    let sample = number(&flag, value(&flag)?)? as usize;
+                    self.sample.get_or_insert(sample);
+                }
+                "--durations" => {
+                    let durations = number(&flag, value(&flag)?)? as usize;
+                    self.durations.get_or_insert(durations);
+                }
+                "--shuffle-seed" => {
+                    let seed = number(&flag, value(&flag)?)?;
+                    self.shuffle_seed.get_or_insert(seed);
+                }
+                "--maxfail" => {
+                    let limit = number(&flag, value(&flag)?)? as usize;
+                    self.max_failures.get_or_insert(limit);
+                }
+                "-x" | "--exitfirst" => self.fail_fast = true,
+                "-v" | "--verbose" => self.verbose = true,
+                "--ascii" => self.ascii = true,
+                "--compact" => self.compact = true,
+                "--no-capture" => self.capture_output = false,
+                "--no-codeblocks" => self.enable_codeblocks = false,
+                "--pytest-compat" => self.pytest_compat = true,
+                "--full-trace" => self.filter_traceback = false,
+                "--github-annotations" => self.github_annotations = true,
+                "--wrap-failures" => self.wrap_failures = true,
+                "--notebooks" => self.notebooks = true,
+                "--no-loop-scope-strict" => self.loop_scope_strict = false,
+                "--group-failures" => self.group_failures = true,
+                "--run-slow" => self.run_slow = true,
+                "--asyncio-debug" => self.asyncio_debug = true,
+                "--resume" => self.resume = true,
+                "--line-trace" => self.line_trace = true,
+                "--check" => self.check = true,
+                "--shuffle" => self.shuffle = true,
+                "--lf" | "--last-failed" => {
+                    if self.last_failed_mode == LastFailedMode::None {
+                        self.last_failed_mode = LastFailedMode::OnlyFailed;
+                    }
+                }
+                "--ff" | "--failed-first" => {
+                    if self.last_failed_mode == LastFailedMode::None {
+                        self.last_failed_mode = LastFailedMode::FailedFirst;
+                    }
+                }
+                _ => return Err(format!("RUSTEST_ADDOPTS: unsupported option '{}'", token)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cap the worker count, whether it was set explicitly or auto-detected.
+    pub fn set_max_processes(&mut self, max_processes: Option<usize>) {
+        self.max_processes = max_processes;
+        if let Some(cap) = max_processes {
+            self.worker_count = self.worker_count.min(cap.max(1));
+            self.worker_processes = self.worker_processes.min(cap.max(1));
+        }
+    }
+}
+
+/// Split a string into words the way a POSIX shell would: whitespace
+/// separates words, single quotes are literal, and double quotes and
+/// backslashes escape.
+pub fn split_shell_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    current.push(c);
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
         }
     }
+    if in_word {
+        words.push(current);
+    }
+
+    Ok(words)
+}
+
+/// Resolve the number of workers to use.
+///
+/// An explicit `workers` value wins over the `detected` CPU count, but both
+/// are clamped by `max_processes` when it is set.
+pub fn resolve_worker_count(
+    workers: Option<usize>,
+    max_processes: Option<usize>,
+    detected: usize,
+) -> usize {
+    let count = workers.unwrap_or_else(|| detected.max(1));
+    match max_processes {
+        Some(cap) => count.min(cap.max(1)),
+        None => count,
+    }
 }
 
 /// Public representation of the run summary exposed to Python.
@@ -393,36 +933,115 @@ pub struct PyRunReport {
     pub failed: usize,
     #[pyo3(get)]
     pub skipped: usize,
+    /// Tests whose fixture setup raised before the test body ran.
+    #[pyo3(get)]
+    pub errors: usize,
+    /// Tests marked `xfail` that failed as expected.
+    #[pyo3(get)]
+    pub xfailed: usize,
+    /// Tests marked `xfail` (non-strict) that passed anyway.
+    #[pyo3(get)]
+    pub xpassed: usize,
     #[pyo3(get)]
     pub duration: f64,
     #[pyo3(get)]
     pub results: Vec<PyTestResult>,
     #[pyo3(get)]
     pub collection_errors: Vec<CollectionError>,
+    /// Outcome counts per mark name, e.g. `{"slow": {"passed": 10, "failed": 2, ...}}`.
+    #[pyo3(get)]
+    pub by_marker: BTreeMap<String, BTreeMap<String, usize>>,
+    /// Fixture functions executed during the run (cache misses only).
+    #[pyo3(get)]
+    pub fixture_setups: usize,
+    /// Generator fixtures finalized during the run.
+    #[pyo3(get)]
+    pub fixture_teardowns: usize,
+    /// Total time spent in each test file, as `(path, seconds)` in run order.
+    #[pyo3(get)]
+    pub file_durations: Vec<(String, f64)>,
+    /// Warnings recorded per test during the run, as `(node id, message)`.
+    #[pyo3(get)]
+    pub warnings: Vec<(String, String)>,
+    /// Fixture teardowns that raised, in the order they ran.
+    #[pyo3(get)]
+    pub teardown_errors: Vec<TeardownError>,
+    /// Whether a `KeyboardInterrupt` stopped the run before every test ran.
+    #[pyo3(get)]
+    pub interrupted: bool,
+    /// Report files the run wrote, in the order it wrote them: the JUnit XML,
+    /// SQLite, TAP, JSON and trace outputs that were requested.
+    pub artifacts: Vec<PathBuf>,
 }
 
 impl PyRunReport {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         total: usize,
         passed: usize,
         failed: usize,
         skipped: usize,
+        errors: usize,
         duration: f64,
         results: Vec<PyTestResult>,
         collection_errors: Vec<CollectionError>,
     ) -> Self {
+        let by_marker = tally_by_marker(&results);
+        let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+        let (xfailed, xpassed) = (count("xfailed"), count("xpassed"));
         Self {
             total,
             passed,
             failed,
             skipped,
+            errors,
+            xfailed,
+            xpassed,
             duration,
             results,
             collection_errors,
+            by_marker,
+            fixture_setups: 0,
+            fixture_teardowns: 0,
+            file_durations: Vec::new(),
+            warnings: Vec::new(),
+            teardown_errors: Vec::new(),
+            interrupted: false,
+            artifacts: Vec::new(),
         }
     }
 }
 
+#[pymethods]
+impl PyRunReport {
+    /// The `count` slowest results, slowest first; `0` returns them all.
+    /// Equal durations are ordered by node id.
+    #[pyo3(signature = (count = 0))]
+    fn slowest(&self, count: usize) -> Vec<PyTestResult> {
+        crate::output::slowest(&self.results, count)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Count outcomes per mark name; a test with several marks counts in each group.
+fn tally_by_marker(results: &[PyTestResult]) -> BTreeMap<String, BTreeMap<String, usize>> {
+    let mut by_marker: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    for result in results {
+        for mark in &result.marks {
+            let counts = by_marker.entry(mark.clone()).or_insert_with(|| {
+                ["passed", "failed", "skipped", "error", "xfailed", "xpassed"]
+                    .iter()
+                    .map(|status| (status.to_string(), 0))
+                    .collect()
+            });
+            *counts.entry(result.status.clone()).or_insert(0) += 1;
+        }
+    }
+    by_marker
+}
+
 /// Individual test result exposed to Python callers.
 #[pyclass(module = "rustest.rust")]
 #[derive(Clone)]
@@ -441,8 +1060,24 @@ pub struct PyTestResult {
     pub stdout: Option<String>,
     #[pyo3(get)]
     pub stderr: Option<String>,
+    /// Size of the captured stdout in bytes (UTF-8)
+    #[pyo3(get)]
+    pub stdout_bytes: usize,
+    /// Size of the captured stderr in bytes (UTF-8)
+    #[pyo3(get)]
+    pub stderr_bytes: usize,
     #[pyo3(get)]
     pub marks: Vec<String>,
+    /// Times the test was rerun under `@mark.flaky` before this outcome
+    #[pyo3(get)]
+    pub reruns: usize,
+    /// Skip reason of a test that ran anyway under `run_skipped`
+    #[pyo3(get)]
+    pub was_skipped: Option<String>,
+    /// Fixtures the test used, in setup order: requested, autouse and their
+    /// dependencies
+    #[pyo3(get)]
+    pub fixtures: Vec<String>,
 }
 
 impl PyTestResult {
@@ -465,9 +1100,14 @@ impl PyTestResult {
             status: "passed".to_string(),
             duration,
             message: None,
+            stdout_bytes: output_size(&stdout),
+            stderr_bytes: output_size(&stderr),
             stdout,
             stderr,
             marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
         }
     }
 
@@ -486,7 +1126,12 @@ impl PyTestResult {
             message: Some(reason),
             stdout: None,
             stderr: None,
+            stdout_bytes: 0,
+            stderr_bytes: 0,
             marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
         }
     }
 
@@ -505,11 +1150,113 @@ impl PyTestResult {
             status: "failed".to_string(),
             duration,
             message: Some(message),
+            stdout_bytes: output_size(&stdout),
+            stderr_bytes: output_size(&stderr),
             stdout,
             stderr,
             marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
+        }
+    }
+
+    /// An expected failure: the test carries an active `xfail` and failed.
+    pub fn xfailed(
+        name: String,
+        path: String,
+        duration: f64,
+        reason: String,
+        marks: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            status: "xfailed".to_string(),
+            duration,
+            message: (!reason.is_empty()).then_some(reason),
+            stdout: None,
+            stderr: None,
+            stdout_bytes: 0,
+            stderr_bytes: 0,
+            marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
         }
     }
+
+    /// An unexpected pass: the test carries a non-strict `xfail` but passed.
+    pub fn xpassed(
+        name: String,
+        path: String,
+        duration: f64,
+        reason: String,
+        stdout: Option<String>,
+        stderr: Option<String>,
+        marks: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            status: "xpassed".to_string(),
+            duration,
+            message: (!reason.is_empty()).then_some(reason),
+            stdout_bytes: output_size(&stdout),
+            stderr_bytes: output_size(&stderr),
+            stdout,
+            stderr,
+            marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
+        }
+    }
+
+    /// A test whose fixtures failed during setup, before the body ran, or
+    /// whose body raised an exception outside `failure_exceptions`.
+    pub fn error(
+        name: String,
+        path: String,
+        duration: f64,
+        message: String,
+        stdout: Option<String>,
+        stderr: Option<String>,
+        marks: Vec<String>,
+    ) -> Self {
+        Self {
+            name,
+            path,
+            status: "error".to_string(),
+            duration,
+            message: Some(message),
+            stdout_bytes: output_size(&stdout),
+            stderr_bytes: output_size(&stderr),
+            stdout,
+            stderr,
+            marks,
+            reruns: 0,
+            was_skipped: None,
+            fixtures: Vec::new(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyTestResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "PyTestResult(node_id={:?}, status={:?}, duration={:.3})",
+            self.unique_id(),
+            self.status,
+            self.duration
+        )
+    }
+}
+
+/// Byte count of a captured stream, which is `None` when nothing was printed
+fn output_size(output: &Option<String>) -> usize {
+    output.as_ref().map_or(0, String::len)
 }
 
 /// Represents an error that occurred during test collection.
@@ -517,32 +1264,93 @@ impl PyTestResult {
 /// This is used to report errors that prevented tests from being collected,
 /// such as syntax errors in Python files or markdown code blocks. Unlike test
 /// failures, collection errors prevent the test from even being defined.
+///
+/// Errors raised while importing a file also carry the exception type, the
+/// location of the offending line, and the traceback frames, so tools can
+/// point at the problem without parsing `message`.
 #[pyclass(module = "rustest.rust")]
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct CollectionError {
     #[pyo3(get)]
     pub path: String,
     #[pyo3(get)]
     pub message: String,
+    /// Exception class name, e.g. `SyntaxError`
+    #[pyo3(get)]
+    pub error_type: Option<String>,
+    /// File containing the offending line
+    #[pyo3(get)]
+    pub filename: Option<String>,
+    /// The offending line number (1-based)
+    #[pyo3(get)]
+    pub lineno: Option<usize>,
+    /// Traceback frames of the import, outermost first
+    #[pyo3(get)]
+    pub frames: Vec<CollectionFrame>,
 }
 
 impl CollectionError {
     pub fn new(path: String, message: String) -> Self {
-        Self { path, message }
+        Self {
+            path,
+            message,
+            error_type: None,
+            filename: None,
+            lineno: None,
+            frames: Vec::new(),
+        }
     }
 }
 
-/// Light-weight helper used to generate monotonically increasing identifiers
-/// for dynamically generated module names.
-#[derive(Default)]
-pub struct ModuleIdGenerator {
-    counter: AtomicUsize,
+/// A fixture teardown that raised, reported on `PyRunReport.teardown_errors`.
+///
+/// Teardown errors don't change the outcome of any test; the remaining
+/// teardowns still run and the error is reported once the run finishes.
+#[pyclass(module = "rustest.rust")]
+#[derive(Clone, Debug)]
+pub struct TeardownError {
+    /// Name of the fixture (or `addfinalizer` callback) whose teardown raised
+    #[pyo3(get)]
+    pub fixture: String,
+    /// Scope the fixture was torn down at, e.g. `"module"`
+    #[pyo3(get)]
+    pub scope: String,
+    /// Formatted traceback of the exception
+    #[pyo3(get)]
+    pub message: String,
 }
 
-impl ModuleIdGenerator {
-    pub fn next(&self) -> usize {
-        self.counter.fetch_add(1, Ordering::Relaxed)
-    }
+/// One traceback frame of a collection error, as `traceback.extract_tb` reports it.
+#[pyclass(module = "rustest.rust")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectionFrame {
+    #[pyo3(get)]
+    pub filename: String,
+    #[pyo3(get)]
+    pub lineno: usize,
+    /// Function (or `<module>`) the frame was executing
+    #[pyo3(get)]
+    pub name: String,
+}
+
+/// Parse an expected-failures list into node ids and optional reasons.
+pub fn parse_xfail_list(content: &str) -> HashMap<String, Option<String>> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (node_id, reason) = match line.split_once('#') {
+                Some((node_id, reason)) => (node_id, Some(reason.trim())),
+                None => (line, None),
+            };
+            let node_id = node_id.trim();
+            (!node_id.is_empty()).then(|| {
+                (
+                    node_id.to_string(),
+                    reason.filter(|r| !r.is_empty()).map(str::to_string),
+                )
+            })
+        })
+        .collect()
 }
 
 /// Convenience wrapper that converts a raw Python exception into a structured
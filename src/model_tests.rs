@@ -6,6 +6,7 @@ mod tests {
     use indexmap::IndexMap;
     use pyo3::ffi::c_str;
     use pyo3::prelude::*;
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
     #[test]
@@ -49,7 +50,9 @@ mod tests {
                 class_name: None,
                 fixture_param_indices: IndexMap::new(),
                 indirect_params: vec![],
+                param_scopes: HashMap::new(),
                 has_patches: false,
+                excluded_autouse: AutouseExclusion::default(),
             };
 
             let unique_id = test_case.unique_id();
@@ -74,7 +77,9 @@ mod tests {
                 class_name: None,
                 fixture_param_indices: IndexMap::new(),
                 indirect_params: vec![],
+                param_scopes: HashMap::new(),
                 has_patches: false,
+                excluded_autouse: AutouseExclusion::default(),
             };
 
             assert_eq!(
@@ -164,6 +169,126 @@ mod tests {
         assert!(!config.capture_output);
     }
 
+    #[test]
+    fn test_resolve_worker_count_respects_detection_and_cap() {
+        // Auto-detection uses the detected CPU count
+        assert_eq!(resolve_worker_count(None, None, 8), 8);
+        assert_eq!(resolve_worker_count(None, None, 0), 1);
+        // The cap clamps auto-detection
+        assert_eq!(resolve_worker_count(None, Some(4), 8), 4);
+        assert_eq!(resolve_worker_count(None, Some(16), 8), 8);
+        // Explicit workers win over detection but are still clamped
+        assert_eq!(resolve_worker_count(Some(12), None, 8), 12);
+        assert_eq!(resolve_worker_count(Some(12), Some(6), 8), 6);
+        assert_eq!(resolve_worker_count(Some(2), Some(6), 8), 2);
+    }
+
+    fn default_config(pattern: Option<&str>) -> RunConfiguration {
+        RunConfiguration::new(
+            pattern.map(str::to_string),
+            None,
+            None,
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        )
+    }
+
+    #[test]
+    fn test_rustest_addopts_env_sets_pattern() {
+        std::env::set_var("RUSTEST_ADDOPTS", "-k foo -x --max-file-size=2048");
+        let mut config = default_config(None);
+        let mut explicit = default_config(Some("bar"));
+        let applied = config.apply_env_addopts();
+        let applied_explicit = explicit.apply_env_addopts();
+        std::env::remove_var("RUSTEST_ADDOPTS");
+
+        applied.unwrap();
+        applied_explicit.unwrap();
+        assert_eq!(config.pattern, Some("foo".to_string()));
+        assert!(config.fail_fast);
+        assert_eq!(config.max_file_size, Some(2048));
+        // Explicit arguments take precedence over RUSTEST_ADDOPTS
+        assert_eq!(explicit.pattern, Some("bar".to_string()));
+    }
+
+    #[test]
+    fn test_failure_limit_treats_fail_fast_as_one() {
+        let mut config = default_config(None);
+        assert_eq!(config.failure_limit(), None);
+        config.apply_addopts("--maxfail=5").unwrap();
+        assert_eq!(config.failure_limit(), Some(5));
+        config.fail_fast = true;
+        assert_eq!(config.failure_limit(), Some(1));
+        config.fail_fast = false;
+        config.max_failures = Some(0);
+        assert_eq!(config.failure_limit(), None);
+    }
+
+    #[test]
+    fn test_addopts_flags_apply_like_appended_arguments() {
+        // capture_output defaults to true, as an explicit argument would set it
+        let mut config = default_config(None);
+        config.apply_addopts("--no-capture --full-trace").unwrap();
+        assert!(!config.capture_output);
+        assert!(!config.filter_traceback);
+    }
+
+    #[test]
+    fn test_addopts_rejects_unknown_options() {
+        let mut config = default_config(None);
+        let err = config.apply_addopts("--frobnicate").unwrap_err();
+        assert!(err.contains("--frobnicate"));
+        assert!(config.apply_addopts("-k").is_err());
+    }
+
+    #[test]
+    fn test_split_shell_words() {
+        assert_eq!(
+            split_shell_words(r#"-k "not slow" -m 'a and b' plain\ word"#).unwrap(),
+            vec!["-k", "not slow", "-m", "a and b", "plain word"]
+        );
+        assert_eq!(
+            split_shell_words(r#"say "a \"quoted\" word""#).unwrap(),
+            vec!["say", r#"a "quoted" word"#]
+        );
+        assert_eq!(split_shell_words("  ").unwrap(), Vec::<String>::new());
+        assert_eq!(split_shell_words("''").unwrap(), vec![""]);
+        assert!(split_shell_words("'open").is_err());
+    }
+
+    #[test]
+    fn test_run_configuration_set_max_processes() {
+        let mut config = RunConfiguration::new(
+            None,
+            None,
+            Some(8),
+            true,
+            true,
+            LastFailedMode::None,
+            false,
+            false,
+            false,
+            false,
+            false,
+            None,
+            FixtureScope::Function,
+            FixtureScope::Function,
+        );
+        config.set_max_processes(Some(3));
+
+        assert_eq!(config.max_processes, Some(3));
+        assert_eq!(config.worker_count, 3);
+    }
+
     #[test]
     fn test_run_configuration_clone() {
         let config = RunConfiguration::new(
@@ -194,12 +319,13 @@ mod tests {
         Python::with_gil(|_py| {
             let results = vec![];
             let collection_errors = vec![];
-            let report = PyRunReport::new(10, 8, 1, 1, 1.5, results, collection_errors);
+            let report = PyRunReport::new(10, 8, 1, 1, 0, 1.5, results, collection_errors);
 
             assert_eq!(report.total, 10);
             assert_eq!(report.passed, 8);
             assert_eq!(report.failed, 1);
             assert_eq!(report.skipped, 1);
+            assert_eq!(report.errors, 0);
             assert_eq!(report.duration, 1.5);
         });
     }
@@ -243,6 +369,46 @@ mod tests {
         assert_eq!(result.stderr, Some("stderr".to_string()));
     }
 
+    #[test]
+    fn test_py_run_report_by_marker() {
+        let marks = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let results = vec![
+            PyTestResult::passed(
+                "test_query".to_string(),
+                "tests/test_db.py".to_string(),
+                0.1,
+                None,
+                None,
+                marks(&["slow", "db"]),
+            ),
+            PyTestResult::failed(
+                "test_migration".to_string(),
+                "tests/test_db.py".to_string(),
+                0.2,
+                "AssertionError".to_string(),
+                None,
+                None,
+                marks(&["slow"]),
+            ),
+            PyTestResult::passed(
+                "test_unmarked".to_string(),
+                "tests/test_db.py".to_string(),
+                0.1,
+                None,
+                None,
+                vec![],
+            ),
+        ];
+
+        let report = PyRunReport::new(3, 2, 1, 0, 0, 0.4, results, vec![]);
+
+        assert_eq!(report.by_marker.len(), 2);
+        assert_eq!(report.by_marker["slow"]["passed"], 1);
+        assert_eq!(report.by_marker["slow"]["failed"], 1);
+        assert_eq!(report.by_marker["db"]["passed"], 1);
+        assert_eq!(report.by_marker["db"]["failed"], 0);
+    }
+
     #[test]
     fn test_py_test_result_skipped() {
         let result = PyTestResult::skipped(
@@ -260,30 +426,6 @@ mod tests {
         assert_eq!(result.stderr, None);
     }
 
-    #[test]
-    fn test_module_id_generator_sequential() {
-        let generator = ModuleIdGenerator::default();
-
-        let id1 = generator.next();
-        let id2 = generator.next();
-        let id3 = generator.next();
-
-        assert_eq!(id1, 0);
-        assert_eq!(id2, 1);
-        assert_eq!(id3, 2);
-    }
-
-    #[test]
-    fn test_module_id_generator_multiple_instances() {
-        let gen1 = ModuleIdGenerator::default();
-        let gen2 = ModuleIdGenerator::default();
-
-        assert_eq!(gen1.next(), 0);
-        assert_eq!(gen2.next(), 0);
-        assert_eq!(gen1.next(), 1);
-        assert_eq!(gen2.next(), 1);
-    }
-
     #[test]
     fn test_invalid_test_definition() {
         let err = invalid_test_definition("Test error message");
@@ -329,7 +471,9 @@ mod tests {
                 class_name: None,
                 fixture_param_indices: IndexMap::new(),
                 indirect_params: vec![],
+                param_scopes: HashMap::new(),
                 has_patches: false,
+                excluded_autouse: AutouseExclusion::default(),
             };
 
             assert_eq!(test_case.parameters.len(), 2);
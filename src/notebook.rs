@@ -0,0 +1,99 @@
+//! Jupyter notebook support for `--notebooks`.
+//!
+//! A test notebook (`test_*.ipynb` or `*_test.ipynb`) is collected like a
+//! Python module whose source is the concatenation of its code cells. Markdown
+//! and raw cells are dropped, and IPython magics are neutralised so the
+//! remaining source compiles as plain Python.
+
+use serde_json::Value;
+
+/// Build the module source for a notebook from its code cells.
+///
+/// Each cell is preceded by an `# In[n]` comment so tracebacks into the
+/// synthetic source can be traced back to the cell. Cell magics (`%%time`)
+/// skip the whole cell, while line magics and shell escapes (`%matplotlib`,
+/// `!pip`) become `pass` statements that keep the indentation valid.
+pub fn notebook_source(content: &str) -> Result<String, String> {
+    let notebook: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let cells = notebook
+        .get("cells")
+        .and_then(Value::as_array)
+        .ok_or("no \"cells\" array (only nbformat 4 notebooks are supported)")?;
+
+    let mut source = String::new();
+    let code_cells = cells
+        .iter()
+        .filter(|cell| cell.get("cell_type").and_then(Value::as_str) == Some("code"));
+    for (index, cell) in code_cells.enumerate() {
+        let cell_source = cell_source(cell);
+        if cell_source.trim_start().starts_with("%%") {
+            continue;
+        }
+        source.push_str(&format!("# In[{}]\n", index + 1));
+        for line in cell_source.lines() {
+            let code = line.trim_start();
+            if code.starts_with('%') || code.starts_with('!') {
+                let indent = &line[..line.len() - code.len()];
+                source.push_str(&format!("{}pass  # {}\n", indent, code));
+            } else {
+                source.push_str(line);
+                source.push('\n');
+            }
+        }
+        source.push('\n');
+    }
+    Ok(source)
+}
+
+/// The source of a cell, stored either as one string or as a list of lines.
+fn cell_source(cell: &Value) -> String {
+    match cell.get("source") {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_code_cells_in_order() {
+        let content = r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n"]},
+                {"cell_type": "code", "source": ["import math\n", "RADIUS = 2\n"]},
+                {"cell_type": "code", "source": "def test_area():\n    assert math.pi * RADIUS ** 2 > 12"}
+            ],
+            "nbformat": 4
+        }"##;
+        assert_eq!(
+            notebook_source(content).unwrap(),
+            "# In[1]\nimport math\nRADIUS = 2\n\n\
+             # In[2]\ndef test_area():\n    assert math.pi * RADIUS ** 2 > 12\n\n"
+        );
+    }
+
+    #[test]
+    fn neutralises_magics() {
+        let content = r##"{
+            "cells": [
+                {"cell_type": "code", "source": "%%time\nslow()"},
+                {"cell_type": "code", "source": "%matplotlib inline\nif True:\n    !ls\n"}
+            ]
+        }"##;
+        assert_eq!(
+            notebook_source(content).unwrap(),
+            "# In[2]\npass  # %matplotlib inline\nif True:\n    pass  # !ls\n\n"
+        );
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_notebooks() {
+        assert!(notebook_source("not json").is_err());
+        assert!(notebook_source("{\"worksheets\": []}")
+            .unwrap_err()
+            .contains("nbformat 4"));
+    }
+}
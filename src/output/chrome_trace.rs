@@ -0,0 +1,95 @@
+//! Chrome tracing export for `--trace-json`
+//!
+//! Spans for collection, each test and each fixture setup/teardown are
+//! recorded as complete (`"ph": "X"`) events on one process and thread, so
+//! `chrome://tracing` and Perfetto nest fixture spans under the test whose
+//! time they fall in. Recording is a no-op unless a trace has been started.
+
+use pyo3::PyResult;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Thread id used for every span; the runner executes tests on one thread.
+const TRACE_TID: u32 = 1;
+
+thread_local! {
+    static EVENTS: RefCell<Option<Vec<Value>>> = const { RefCell::new(None) };
+}
+
+/// Common origin for the `ts` field of every event.
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// Start recording spans, keeping any recorded since the last write.
+pub fn start_trace() {
+    epoch();
+    EVENTS.with(|events| {
+        events.borrow_mut().get_or_insert_with(Vec::new);
+    });
+}
+
+/// Record a span that began at `start` and ends now.
+pub fn record_span(name: &str, category: &str, start: Instant, args: Value) {
+    EVENTS.with(|events| {
+        if let Some(events) = events.borrow_mut().as_mut() {
+            let now = Instant::now();
+            events.push(json!({
+                "name": name,
+                "cat": category,
+                "ph": "X",
+                "ts": start.saturating_duration_since(epoch()).as_secs_f64() * 1e6,
+                "dur": now.saturating_duration_since(start).as_secs_f64() * 1e6,
+                "pid": std::process::id(),
+                "tid": TRACE_TID,
+                "args": args,
+            }));
+        }
+    });
+}
+
+/// Write the recorded spans as a Chrome trace and stop recording.
+pub fn write_chrome_trace(destination: &Path) -> PyResult<()> {
+    let events = EVENTS.with(|events| events.borrow_mut().take().unwrap_or_default());
+    let document = json!({ "traceEvents": events, "displayTimeUnit": "ms" });
+    std::fs::write(destination, document.to_string()).map_err(|e| {
+        pyo3::exceptions::PyIOError::new_err(format!(
+            "Failed to write trace to {}: {}",
+            destination.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_are_only_recorded_while_tracing() {
+        record_span("ignored", "test", Instant::now(), json!({}));
+        start_trace();
+        let start = Instant::now();
+        record_span("test_one", "test", start, json!({"status": "passed"}));
+
+        let path = std::env::temp_dir().join("rustest_chrome_trace_unit.json");
+        write_chrome_trace(&path).unwrap();
+        let document: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let events = document["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["name"], "test_one");
+        assert_eq!(events[0]["ph"], "X");
+        assert!(events[0]["dur"].as_f64().unwrap() >= 0.0);
+
+        // Writing stops the trace
+        record_span("after", "test", Instant::now(), json!({}));
+        assert!(EVENTS.with(|events| events.borrow().is_none()));
+    }
+}
@@ -0,0 +1,287 @@
+//! Compact one-line-per-file display
+//!
+//! Prints a single line for each test file once it completes, with one
+//! character per test (`.` passed, `F` failed, `E` error, `s` skipped, `x`
+//! xfailed, `X` xpassed by default). Intended for CI logs where spinners and per-test lines
+//! only add noise.
+
+use super::failure_groups::{
+    collapse_param_failures, format_group_others, format_repeated_failure, group_failures,
+};
+use super::formatter::{truncate_middle, ErrorFormatter};
+use super::header::SessionHeader;
+use super::renderer::OutputRenderer;
+use super::symbols::StatusSymbols;
+use crate::model::{to_relative_path, CollectionError, PyTestResult, TestCase, TestModule};
+use console::style;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Map a test result to its compact outcome symbol.
+fn outcome_symbol<'a>(symbols: &'a StatusSymbols, status: &str) -> &'a str {
+    match status {
+        "error" => "E",
+        _ => symbols.for_status(status),
+    }
+}
+
+/// Format the outcome counts, e.g. `3 passed, 1 failed`.
+fn format_counts(passed: usize, failed: usize, skipped: usize) -> String {
+    let mut parts = Vec::new();
+    if passed > 0 {
+        parts.push(format!("{} passed", passed));
+    }
+    if failed > 0 {
+        parts.push(format!("{} failed", failed));
+    }
+    if skipped > 0 {
+        parts.push(format!("{} skipped", skipped));
+    }
+    if parts.is_empty() {
+        "0 tests".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+/// Build the summary line for a completed file,
+/// e.g. `tests/test_api.py ..F. 3 passed, 1 failed (0.3s)`.
+pub(crate) fn format_compact_line(
+    path: &str,
+    outcomes: &str,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    duration: Duration,
+) -> String {
+    let counts = format_counts(passed, failed, skipped);
+    if outcomes.is_empty() {
+        format!("{} {} ({:.1}s)", path, counts, duration.as_secs_f64())
+    } else {
+        format!(
+            "{} {} {} ({:.1}s)",
+            path,
+            outcomes,
+            counts,
+            duration.as_secs_f64()
+        )
+    }
+}
+
+/// Compact display showing one summary line per file
+pub struct CompactDisplay {
+    formatter: ErrorFormatter,
+    use_colors: bool,
+    /// Maximum display width for file paths
+    name_width: Option<usize>,
+    symbols: StatusSymbols,
+    /// Print GitHub Actions annotations for failures
+    github_annotations: bool,
+    /// Outcome characters collected per file, keyed by relative path
+    outcomes: HashMap<String, String>,
+    /// Collect failures to display at the end
+    deferred_failures: Vec<(String, String, String)>, // (name, path, message)
+    /// Collect collection errors to display at the end
+    collection_errors: Vec<(String, String)>, // (path, message)
+    /// Print failures sharing the same error once
+    group_failures: bool,
+    /// Print repeated failures of a parametrized test as one line each
+    collapse_param_failures: bool,
+}
+
+impl CompactDisplay {
+    /// Create a new compact display
+    pub fn new(
+        use_colors: bool,
+        name_width: Option<usize>,
+        symbols: StatusSymbols,
+        github_annotations: bool,
+        wrap_width: Option<usize>,
+        group_failures: bool,
+        collapse_param_failures: bool,
+    ) -> Self {
+        Self {
+            formatter: ErrorFormatter::new(use_colors).with_wrap_width(wrap_width),
+            use_colors,
+            name_width,
+            symbols,
+            github_annotations,
+            outcomes: HashMap::new(),
+            deferred_failures: Vec::new(),
+            collection_errors: Vec::new(),
+            group_failures,
+            collapse_param_failures,
+        }
+    }
+}
+
+impl OutputRenderer for CompactDisplay {
+    fn collection_error(&mut self, error: &CollectionError) {
+        self.collection_errors
+            .push((error.path.clone(), error.message.clone()));
+    }
+
+    fn start_suite(&mut self, _total_files: usize, _total_tests: usize, header: &SessionHeader) {
+        for line in header.lines() {
+            eprintln!("{}", line);
+        }
+    }
+
+    fn start_file(&mut self, module: &TestModule) {
+        self.outcomes
+            .insert(to_relative_path(&module.path), String::new());
+    }
+
+    fn start_test(&mut self, _test: &TestCase) {
+        // Not shown in compact mode
+    }
+
+    fn test_completed(&mut self, result: &PyTestResult) {
+        self.outcomes
+            .entry(result.path.clone())
+            .or_default()
+            .push_str(outcome_symbol(&self.symbols, &result.status));
+
+        if matches!(result.status.as_str(), "failed" | "error") {
+            if let Some(ref message) = result.message {
+                if self.github_annotations {
+                    println!(
+                        "{}",
+                        self.formatter
+                            .github_annotation(&result.name, &result.path, message)
+                    );
+                }
+                self.deferred_failures.push((
+                    result.name.clone(),
+                    result.path.clone(),
+                    message.clone(),
+                ));
+            }
+        }
+    }
+
+    fn file_completed(
+        &mut self,
+        path: &str,
+        duration: Duration,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+    ) {
+        let outcomes = self.outcomes.remove(path).unwrap_or_default();
+        let display_path = match self.name_width {
+            Some(width) => truncate_middle(path, width),
+            None => path.to_string(),
+        };
+        let line = format_compact_line(&display_path, &outcomes, passed, failed, skipped, duration);
+        if self.use_colors && failed > 0 {
+            eprintln!("{}", style(line).red());
+        } else {
+            eprintln!("{}", line);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn finish_suite(
+        &mut self,
+        total: usize,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        errors: usize,
+        xfailed: usize,
+        xpassed: usize,
+        duration: Duration,
+    ) {
+        for (path, message) in &self.collection_errors {
+            eprintln!();
+            eprintln!("ERROR collecting {}", path);
+            for line in message.lines() {
+                eprintln!("{}", line);
+            }
+        }
+
+        if self.group_failures {
+            for group in group_failures(&self.deferred_failures) {
+                let (name, path, message) = group.first;
+                eprintln!();
+                eprintln!("{}", self.formatter.format_failure(name, path, message));
+                if !group.others.is_empty() {
+                    eprint!("{}", format_group_others(&group.others));
+                }
+            }
+        } else if self.collapse_param_failures {
+            for (failure, first) in collapse_param_failures(&self.deferred_failures) {
+                match first {
+                    Some(first) => eprintln!("{}", format_repeated_failure(failure, first)),
+                    None => {
+                        let (name, path, message) = failure;
+                        eprintln!();
+                        eprintln!("{}", self.formatter.format_failure(name, path, message));
+                    }
+                }
+            }
+        } else {
+            for (name, path, message) in &self.deferred_failures {
+                eprintln!();
+                eprintln!("{}", self.formatter.format_failure(name, path, message));
+            }
+        }
+
+        let mut counts = format_counts(passed, failed, skipped);
+        if errors > 0 {
+            counts.push_str(&format!(", {} error", errors));
+        }
+        if xfailed > 0 {
+            counts.push_str(&format!(", {} xfailed", xfailed));
+        }
+        if xpassed > 0 {
+            counts.push_str(&format!(", {} xpassed", xpassed));
+        }
+        eprintln!();
+        eprintln!(
+            "{} tests: {} ({:.1}s)",
+            total,
+            counts,
+            duration.as_secs_f64()
+        );
+    }
+
+    fn println(&self, message: &str) {
+        eprintln!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_file_line_with_mixed_outcomes() {
+        let symbols = StatusSymbols::compact();
+        let outcomes: String = ["passed", "failed", "skipped", "passed"]
+            .iter()
+            .map(|status| outcome_symbol(&symbols, status))
+            .collect();
+
+        let line = format_compact_line(
+            "tests/test_api.py",
+            &outcomes,
+            2,
+            1,
+            1,
+            Duration::from_millis(300),
+        );
+
+        assert_eq!(
+            line,
+            "tests/test_api.py .Fs. 2 passed, 1 failed, 1 skipped (0.3s)"
+        );
+    }
+
+    #[test]
+    fn formats_file_line_without_tests() {
+        let line = format_compact_line("tests/test_empty.py", "", 0, 0, 0, Duration::ZERO);
+        assert_eq!(line, "tests/test_empty.py 0 tests (0.0s)");
+    }
+}
@@ -0,0 +1,157 @@
+//! Ordering for duration-sorted output
+//!
+//! Any slowest-tests or slowest-files list should go through [`slowest`] or
+//! [`slowest_files`] so equal durations always come out in the same order.
+
+use crate::model::PyTestResult;
+use std::cmp::Ordering;
+
+/// Slowest first; equal durations fall back to the node id, alphabetically.
+pub fn by_duration_desc(a: &PyTestResult, b: &PyTestResult) -> Ordering {
+    b.duration
+        .total_cmp(&a.duration)
+        .then_with(|| a.unique_id().cmp(&b.unique_id()))
+}
+
+/// The `count` slowest results, in a reproducible order; `0` keeps them all.
+pub fn slowest(results: &[PyTestResult], count: usize) -> Vec<&PyTestResult> {
+    let mut sorted: Vec<&PyTestResult> = results.iter().collect();
+    sorted.sort_by(|a, b| by_duration_desc(a, b));
+    if count > 0 {
+        sorted.truncate(count);
+    }
+    sorted
+}
+
+/// Summary block for `--durations`, one `1.23s node_id` line per test.
+pub fn format_slowest_tests(results: &[PyTestResult], count: usize) -> String {
+    let tests = slowest(results, count);
+    let mut output = format!(
+        "slowest {} {}:\n",
+        tests.len(),
+        if tests.len() == 1 { "test" } else { "tests" }
+    );
+    for result in tests {
+        output.push_str(&format!(
+            "{:>8.2}s {}\n",
+            result.duration,
+            result.unique_id()
+        ));
+    }
+    output
+}
+
+/// The `count` files that took longest in total; equal durations fall back
+/// to the path, alphabetically.
+pub fn slowest_files(file_durations: &[(String, f64)], count: usize) -> Vec<&(String, f64)> {
+    let mut sorted: Vec<&(String, f64)> = file_durations.iter().collect();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sorted.truncate(count);
+    sorted
+}
+
+/// Summary block for `--slowest-files`, one `1.23s path` line per file.
+pub fn format_slowest_files(file_durations: &[(String, f64)], count: usize) -> String {
+    let files = slowest_files(file_durations, count);
+    let mut output = format!(
+        "slowest {} {}:\n",
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" }
+    );
+    for (path, duration) in files {
+        output.push_str(&format!("{:>8.2}s {}\n", duration, path));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, name: &str, duration: f64) -> PyTestResult {
+        PyTestResult::passed(
+            name.to_string(),
+            path.to_string(),
+            duration,
+            None,
+            None,
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn equal_durations_break_ties_by_node_id() {
+        let results = vec![
+            result("tests/test_b.py", "test_two", 0.25),
+            result("tests/test_a.py", "test_zeta", 0.25),
+            result("tests/test_a.py", "test_fast", 0.01),
+            result("tests/test_c.py", "test_slow", 1.5),
+            result("tests/test_a.py", "test_alpha", 0.25),
+        ];
+
+        let ids = |results: &[PyTestResult]| -> Vec<String> {
+            slowest(results, 4)
+                .iter()
+                .map(|result| result.unique_id())
+                .collect()
+        };
+        assert_eq!(
+            ids(&results),
+            [
+                "tests/test_c.py::test_slow",
+                "tests/test_a.py::test_alpha",
+                "tests/test_a.py::test_zeta",
+                "tests/test_b.py::test_two",
+            ]
+        );
+
+        // Input order does not matter
+        let mut reversed = results.clone();
+        reversed.reverse();
+        assert_eq!(ids(&reversed), ids(&results));
+    }
+
+    #[test]
+    fn slowest_tests_summary_lists_all_for_zero() {
+        let results = vec![
+            result("tests/test_a.py", "test_fast", 0.01),
+            result("tests/test_b.py", "test_slow", 1.5),
+        ];
+
+        assert_eq!(
+            format_slowest_tests(&results, 1),
+            "slowest 1 test:\n    1.50s tests/test_b.py::test_slow\n"
+        );
+        assert_eq!(
+            format_slowest_tests(&results, 0),
+            "slowest 2 tests:\n    1.50s tests/test_b.py::test_slow\n    0.01s tests/test_a.py::test_fast\n"
+        );
+    }
+
+    #[test]
+    fn slowest_files_come_first() {
+        let file_durations = vec![
+            ("tests/test_fast.py".to_string(), 0.05),
+            ("tests/test_imports.py".to_string(), 2.5),
+            ("tests/test_db.py".to_string(), 0.75),
+            ("tests/test_api.py".to_string(), 0.75),
+        ];
+
+        let paths: Vec<&str> = slowest_files(&file_durations, 3)
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "tests/test_imports.py",
+                "tests/test_api.py",
+                "tests/test_db.py"
+            ]
+        );
+        assert_eq!(
+            format_slowest_files(&file_durations, 1),
+            "slowest 1 file:\n    2.50s tests/test_imports.py\n"
+        );
+    }
+}
@@ -5,6 +5,9 @@
 //! consume events and render them using rich, export to VS Code, etc.
 
 use super::events::*;
+use super::failure_groups::failure_fingerprint;
+use super::formatter::{truncate_middle, ErrorFormatter};
+use super::header::SessionHeader;
 use super::renderer::OutputRenderer;
 use crate::model::{to_relative_path, CollectionError, PyTestResult, TestCase, TestModule};
 use pyo3::prelude::*;
@@ -16,14 +19,32 @@ pub struct EventStreamRenderer {
     callback: Option<Py<PyAny>>,
     /// Store collection errors to defer them
     collection_errors: Vec<CollectionError>,
+    /// Formats GitHub Actions annotations when enabled
+    annotations: Option<ErrorFormatter>,
+    /// Maximum display width for file and test names
+    name_width: Option<usize>,
 }
 
 impl EventStreamRenderer {
     /// Create a new event stream renderer
-    pub fn new(callback: Option<Py<PyAny>>) -> Self {
+    pub fn new(
+        callback: Option<Py<PyAny>>,
+        github_annotations: bool,
+        name_width: Option<usize>,
+    ) -> Self {
         Self {
             callback,
             collection_errors: Vec::new(),
+            annotations: github_annotations.then(|| ErrorFormatter::new(false)),
+            name_width,
+        }
+    }
+
+    /// Shorten a name to the configured display width
+    fn display_name(&self, name: &str) -> String {
+        match self.name_width {
+            Some(width) => truncate_middle(name, width),
+            None => name.to_string(),
         }
     }
 }
@@ -50,23 +71,37 @@ impl OutputRenderer for EventStreamRenderer {
         let event = CollectionErrorEvent {
             path: error.path.clone(),
             message: error.message.clone(),
+            error_type: error.error_type.clone(),
+            filename: error.filename.clone(),
+            lineno: error.lineno,
+            frames: error.frames.clone(),
             timestamp: current_timestamp(),
         };
         emit_event!(&self.callback, event);
     }
 
-    fn start_suite(&mut self, total_files: usize, total_tests: usize) {
+    fn start_suite(&mut self, total_files: usize, total_tests: usize, header: &SessionHeader) {
         let event = SuiteStartedEvent {
             total_files,
             total_tests,
+            rootdir: header.rootdir.display().to_string(),
+            configfile: header
+                .config_file
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            rustest_version: header.rustest_version.clone(),
+            python_version: header.python_version.clone(),
+            platform: header.platform.clone(),
             timestamp: current_timestamp(),
         };
         emit_event!(&self.callback, event);
     }
 
     fn start_file(&mut self, module: &TestModule) {
+        let file_path = to_relative_path(&module.path);
         let event = FileStartedEvent {
-            file_path: to_relative_path(&module.path),
+            display_name: self.display_name(&file_path),
+            file_path,
             total_tests: module.tests.len(),
             timestamp: current_timestamp(),
         };
@@ -79,13 +114,30 @@ impl OutputRenderer for EventStreamRenderer {
     }
 
     fn test_completed(&mut self, result: &PyTestResult) {
+        if let (Some(formatter), Some(message)) = (&self.annotations, &result.message) {
+            if matches!(result.status.as_str(), "failed" | "error") {
+                println!(
+                    "{}",
+                    formatter.github_annotation(&result.name, &result.path, message)
+                );
+            }
+        }
+
+        let test_id = format!("{}::{}", result.path, result.name);
         let event = TestCompletedEvent {
-            test_id: format!("{}::{}", result.path, result.name),
+            display_name: self.display_name(&test_id),
+            test_id,
             file_path: result.path.clone(),
             test_name: result.name.clone(),
             status: result.status.clone(),
             duration: result.duration,
             message: result.message.clone(),
+            failure_fingerprint: result
+                .message
+                .as_deref()
+                .filter(|_| result.status == "failed" || result.status == "error")
+                .map(failure_fingerprint),
+            fixtures: result.fixtures.clone(),
             timestamp: current_timestamp(),
         };
         emit_event!(&self.callback, event);
@@ -110,6 +162,7 @@ impl OutputRenderer for EventStreamRenderer {
         emit_event!(&self.callback, event);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn finish_suite(
         &mut self,
         total: usize,
@@ -117,6 +170,8 @@ impl OutputRenderer for EventStreamRenderer {
         failed: usize,
         skipped: usize,
         errors: usize,
+        xfailed: usize,
+        xpassed: usize,
         duration: Duration,
     ) {
         let event = SuiteCompletedEvent {
@@ -125,6 +180,8 @@ impl OutputRenderer for EventStreamRenderer {
             failed,
             skipped,
             errors,
+            xfailed,
+            xpassed,
             duration: duration.as_secs_f64(),
             timestamp: current_timestamp(),
         };
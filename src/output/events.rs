@@ -6,6 +6,8 @@
 use pyo3::prelude::*;
 use pyo3::Py;
 
+use crate::model::CollectionFrame;
+
 /// Event emitted when a test file starts execution
 #[pyclass]
 #[derive(Clone, Debug)]
@@ -14,6 +16,10 @@ pub struct FileStartedEvent {
     #[pyo3(get)]
     pub file_path: String,
 
+    /// File path shortened to the configured name width, for display
+    #[pyo3(get)]
+    pub display_name: String,
+
     /// Total number of tests in this file
     #[pyo3(get)]
     pub total_tests: usize,
@@ -49,7 +55,11 @@ pub struct TestCompletedEvent {
     #[pyo3(get)]
     pub test_name: String,
 
-    /// Test status: "passed", "failed", "skipped"
+    /// Test id shortened to the configured name width, for display
+    #[pyo3(get)]
+    pub display_name: String,
+
+    /// Test status: "passed", "failed", "skipped", "error", "xfailed", "xpassed"
     #[pyo3(get)]
     pub status: String,
 
@@ -61,6 +71,15 @@ pub struct TestCompletedEvent {
     #[pyo3(get)]
     pub message: Option<String>,
 
+    /// Fingerprint of a failure's final exception line, equal for failures
+    /// that look the same (used by `--group-failures`)
+    #[pyo3(get)]
+    pub failure_fingerprint: Option<u64>,
+
+    /// Fixtures the test used, including autouse fixtures and dependencies
+    #[pyo3(get)]
+    pub fixtures: Vec<String>,
+
     /// Unix timestamp when test completed
     #[pyo3(get)]
     pub timestamp: f64,
@@ -127,6 +146,26 @@ pub struct SuiteStartedEvent {
     #[pyo3(get)]
     pub total_tests: usize,
 
+    /// Detected root directory of the run
+    #[pyo3(get)]
+    pub rootdir: String,
+
+    /// Configuration file that was loaded, if any
+    #[pyo3(get)]
+    pub configfile: Option<String>,
+
+    /// Version of rustest running the suite
+    #[pyo3(get)]
+    pub rustest_version: String,
+
+    /// Version of the Python interpreter
+    #[pyo3(get)]
+    pub python_version: String,
+
+    /// Platform identifier (`sys.platform`)
+    #[pyo3(get)]
+    pub platform: String,
+
     /// Unix timestamp when suite started
     #[pyo3(get)]
     pub timestamp: f64,
@@ -166,6 +205,14 @@ pub struct SuiteCompletedEvent {
     #[pyo3(get)]
     pub errors: usize,
 
+    /// Number of tests that failed as expected under `xfail`
+    #[pyo3(get)]
+    pub xfailed: usize,
+
+    /// Number of tests that passed despite a non-strict `xfail`
+    #[pyo3(get)]
+    pub xpassed: usize,
+
     /// Total duration in seconds
     #[pyo3(get)]
     pub duration: f64,
@@ -197,6 +244,22 @@ pub struct CollectionErrorEvent {
     #[pyo3(get)]
     pub message: String,
 
+    /// Exception class name, if the error came from an exception
+    #[pyo3(get)]
+    pub error_type: Option<String>,
+
+    /// File containing the offending line
+    #[pyo3(get)]
+    pub filename: Option<String>,
+
+    /// The offending line number (1-based)
+    #[pyo3(get)]
+    pub lineno: Option<usize>,
+
+    /// Traceback frames of the import, outermost first
+    #[pyo3(get)]
+    pub frames: Vec<CollectionFrame>,
+
     /// Unix timestamp when error occurred
     #[pyo3(get)]
     pub timestamp: f64,
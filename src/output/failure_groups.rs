@@ -0,0 +1,254 @@
+//! Grouping of failures that share the same error for `--group-failures`
+//! and `--collapse-param-failures`
+//!
+//! A systemic bug tends to fail many tests with the same exception. Failures
+//! are grouped by a fingerprint of their final exception line with memory
+//! addresses, paths and line numbers normalized away, so the summary can
+//! print the error once followed by the other affected tests. Collapsing
+//! applies the same fingerprint within the cases of one parametrized test.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A deferred failure as the renderers collect it: (name, path, message).
+pub type Failure = (String, String, String);
+
+/// Failures sharing one fingerprint, in the order they were reported.
+pub struct FailureGroup<'a> {
+    /// The failure whose message is printed in full
+    pub first: &'a Failure,
+    /// Further failures with the same fingerprint
+    pub others: Vec<&'a Failure>,
+}
+
+/// Normalize the exception line of a failure message for grouping.
+fn normalize_failure(message: &str) -> String {
+    static PATTERNS: OnceLock<[(Regex, &'static str); 3]> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            (Regex::new(r"0x[0-9a-fA-F]+").unwrap(), "0x?"),
+            (
+                Regex::new(r#"[^\s'"(),]*[/\\][^\s'"(),]*"#).unwrap(),
+                "<path>",
+            ),
+            (Regex::new(r"\bline \d+").unwrap(), "line ?"),
+        ]
+    });
+    let exception_line = message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("")
+        .trim();
+    patterns.iter().fold(
+        exception_line.to_string(),
+        |line, (pattern, replacement)| pattern.replace_all(&line, *replacement).into_owned(),
+    )
+}
+
+/// Stable 64-bit FNV-1a hash of the normalized failure message.
+pub fn failure_fingerprint(message: &str) -> u64 {
+    normalize_failure(message)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// Group failures by fingerprint, keeping the order of first occurrence.
+pub fn group_failures(failures: &[Failure]) -> Vec<FailureGroup<'_>> {
+    let mut groups: Vec<FailureGroup<'_>> = Vec::new();
+    let mut index_by_fingerprint: HashMap<u64, usize> = HashMap::new();
+    for failure in failures {
+        let fingerprint = failure_fingerprint(&failure.2);
+        match index_by_fingerprint.get(&fingerprint) {
+            Some(&index) => groups[index].others.push(failure),
+            None => {
+                index_by_fingerprint.insert(fingerprint, groups.len());
+                groups.push(FailureGroup {
+                    first: failure,
+                    others: Vec::new(),
+                });
+            }
+        }
+    }
+    groups
+}
+
+/// Name of a parametrized test without its case id: `test_div[2-0]` is `test_div`.
+fn base_test_name(name: &str) -> Option<&str> {
+    let open = name.find('[')?;
+    name.ends_with(']').then(|| &name[..open])
+}
+
+/// Pair each failure with the first failing case of the same parametrized
+/// test that failed the same way, in the order they were reported.
+///
+/// The first case of each group is paired with `None` and keeps its full
+/// traceback. Tests that aren't parametrized never repeat another failure.
+pub fn collapse_param_failures(failures: &[Failure]) -> Vec<(&Failure, Option<&Failure>)> {
+    let mut first_cases: HashMap<(&str, &str, u64), &Failure> = HashMap::new();
+    failures
+        .iter()
+        .map(|failure| {
+            let (name, path, message) = failure;
+            let Some(base) = base_test_name(name) else {
+                return (failure, None);
+            };
+            let key = (path.as_str(), base, failure_fingerprint(message));
+            match first_cases.get(&key) {
+                Some(&first) => (failure, Some(first)),
+                None => {
+                    first_cases.insert(key, failure);
+                    (failure, None)
+                }
+            }
+        })
+        .collect()
+}
+
+/// One-line entry for a case that failed like an earlier case of its test.
+pub fn format_repeated_failure(failure: &Failure, first: &Failure) -> String {
+    let (name, path, message) = failure;
+    // The assertion enrichment block trails the traceback
+    let exception_line = message
+        .split("__RUSTEST_ASSERTION_VALUES__")
+        .next()
+        .unwrap_or("")
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("")
+        .trim();
+    format!(
+        "{}::{} - {} (same as {})",
+        path, name, exception_line, first.0
+    )
+}
+
+/// Lines listing the other tests of a group, printed after its first failure.
+pub fn format_group_others(others: &[&Failure]) -> String {
+    let mut output = format!(
+        "Same failure in {} more {}:\n",
+        others.len(),
+        if others.len() == 1 { "test" } else { "tests" }
+    );
+    for (name, path, _) in others {
+        output.push_str(&format!("  {}::{}\n", path, name));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(name: &str, message: &str) -> Failure {
+        (
+            name.to_string(),
+            "tests/test_db.py".to_string(),
+            message.to_string(),
+        )
+    }
+
+    #[test]
+    fn identical_exceptions_share_a_group() {
+        let failures = vec![
+            failure(
+                "test_a",
+                "Traceback:\n  File \"/src/a.py\", line 3, in test_a\nConnectionError: <Conn at 0x7f01> refused by /var/run/db.sock",
+            ),
+            failure("test_b", "AssertionError: assert 1 == 2"),
+            failure(
+                "test_c",
+                "Traceback:\n  File \"/src/c.py\", line 9, in test_c\nConnectionError: <Conn at 0x7f99> refused by /tmp/other/db.sock",
+            ),
+        ];
+        let groups = group_failures(&failures);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].first.0, "test_a");
+        assert_eq!(groups[0].others.len(), 1);
+        assert_eq!(groups[0].others[0].0, "test_c");
+        assert!(groups[1].others.is_empty());
+        assert_eq!(
+            format_group_others(&groups[0].others),
+            "Same failure in 1 more test:\n  tests/test_db.py::test_c\n"
+        );
+    }
+
+    #[test]
+    fn three_identical_failures_form_one_entry() {
+        let message = "Traceback:\n  File \"/src/db.py\", line 40, in connect\nRuntimeError: database is down";
+        let failures = vec![
+            failure("test_read", message),
+            failure("test_write", message),
+            failure("test_delete", message),
+        ];
+        let groups = group_failures(&failures);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].first.0, "test_read");
+        assert_eq!(
+            format_group_others(&groups[0].others),
+            "Same failure in 2 more tests:\n  tests/test_db.py::test_write\n  tests/test_db.py::test_delete\n"
+        );
+    }
+
+    #[test]
+    fn identical_parametrized_failures_collapse_after_the_first_case() {
+        let message = "Traceback:\n  File \"/src/calc.py\", line 4, in divide\nZeroDivisionError: division by zero";
+        let failures = vec![
+            failure("test_divide[1]", message),
+            failure("test_divide[2]", message),
+            failure("test_divide[3]", message),
+        ];
+
+        let entries = collapse_param_failures(&failures);
+
+        let full: Vec<_> = entries
+            .iter()
+            .filter(|(_, first)| first.is_none())
+            .collect();
+        let compact: Vec<_> = entries
+            .iter()
+            .filter_map(|(failure, first)| {
+                first.map(|first| format_repeated_failure(failure, first))
+            })
+            .collect();
+        assert_eq!(full.len(), 1);
+        assert_eq!(full[0].0 .0, "test_divide[1]");
+        assert_eq!(
+            compact,
+            vec![
+                "tests/test_db.py::test_divide[2] - ZeroDivisionError: division by zero (same as test_divide[1])",
+                "tests/test_db.py::test_divide[3] - ZeroDivisionError: division by zero (same as test_divide[1])",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_parametrized_cases_with_the_same_error_collapse() {
+        let failures = vec![
+            failure("test_read", "RuntimeError: down"),
+            failure("test_read", "RuntimeError: down"),
+            failure("test_parse[a]", "ValueError: bad a"),
+            failure("test_parse[b]", "ValueError: bad b"),
+        ];
+
+        let entries = collapse_param_failures(&failures);
+
+        assert!(entries.iter().all(|(_, first)| first.is_none()));
+    }
+
+    #[test]
+    fn normalization_keeps_distinct_messages_apart() {
+        assert_ne!(
+            failure_fingerprint("ValueError: bad input"),
+            failure_fingerprint("ValueError: bad output")
+        );
+        assert_eq!(
+            normalize_failure("OSError: line 12 of C:\\tmp\\x.txt at 0xdeadbeef"),
+            "OSError: line ? of <path> at 0x?"
+        );
+    }
+}
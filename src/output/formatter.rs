@@ -3,19 +3,35 @@
 //! Formats test errors in a user-friendly way with colors, code context,
 //! and extracted assertion values.
 
+use crate::model::to_relative_path;
 use console::style;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::path::Path;
 
 /// Formats test failures for display
 pub struct ErrorFormatter {
     use_colors: bool,
+    /// Soft-wrap long lines to this width (`None` leaves them as-is)
+    wrap_width: Option<usize>,
 }
 
+/// Extra indentation for the continuation of a wrapped line
+const HANGING_INDENT: usize = 4;
+
 impl ErrorFormatter {
     /// Create a new error formatter
     pub fn new(use_colors: bool) -> Self {
-        Self { use_colors }
+        Self {
+            use_colors,
+            wrap_width: None,
+        }
+    }
+
+    /// Soft-wrap failure output to `width` columns
+    pub fn with_wrap_width(mut self, width: Option<usize>) -> Self {
+        self.wrap_width = width;
+        self
     }
 
     /// Format a test failure message
@@ -82,13 +98,69 @@ impl ErrorFormatter {
         if let Some((expected, actual)) = &parsed.assertion_values {
             output.push_str(&self.format_assertion_values(expected, actual));
         }
+        if !parsed.assertion_diff.is_empty() {
+            output.push_str(&self.format_assertion_diff(&parsed.assertion_diff));
+        }
 
         // If we didn't get structured data, just show the raw message
         if parsed.error.is_none() {
             output.push_str(message);
         }
 
-        output
+        match self.wrap_width {
+            Some(width) => output
+                .split('\n')
+                .flat_map(|line| wrap_line(line, width))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => output,
+        }
+    }
+
+    /// Format a failure as a GitHub Actions `::error` workflow command, so
+    /// it shows up inline on the offending line in pull requests.
+    ///
+    /// Points at the deepest frame in the traceback, which is the user's own
+    /// code once library frames are filtered. Falls back to the test file when
+    /// no frame can be found.
+    pub fn github_annotation(&self, test_name: &str, test_path: &str, message: &str) -> String {
+        // The enrichment block trails the traceback; keep it out of the error line
+        let traceback = message
+            .split("__RUSTEST_ASSERTION_VALUES__")
+            .next()
+            .unwrap_or(message);
+        let parsed = self.parse_traceback(traceback);
+
+        let location = match &parsed.location {
+            Some((file_path, line_num, _)) => format!(
+                "file={},line={}",
+                escape_annotation_property(&to_relative_path(Path::new(file_path))),
+                line_num
+            ),
+            None => format!("file={}", escape_annotation_property(test_path)),
+        };
+
+        let mut text = match parsed.error {
+            Some((error_type, Some(error_msg))) if !error_msg.is_empty() => {
+                format!("{}: {}", error_type, error_msg)
+            }
+            Some((error_type, _)) => error_type,
+            None => message.trim().to_string(),
+        };
+        let enrichment = self.parse_traceback(message);
+        if let Some((expected, actual)) = enrichment.assertion_values {
+            text.push_str(&format!("\nExpected: {}\nReceived: {}", expected, actual));
+        }
+        for line in enrichment.assertion_diff {
+            text.push_str(&format!("\n{}", line));
+        }
+
+        format!(
+            "::error {},title={}::{}",
+            location,
+            escape_annotation_property(test_name),
+            escape_annotation_data(&text)
+        )
     }
 
     /// Parse a Python traceback to extract key information
@@ -97,6 +169,7 @@ impl ErrorFormatter {
         let mut error_msg = None;
         let mut location = None;
         let mut assertion_values = None;
+        let mut assertion_diff = Vec::new();
 
         // Look for the last line which typically has the error type and message
         let lines: Vec<&str> = message.lines().collect();
@@ -152,6 +225,8 @@ impl ErrorFormatter {
                     expected = Some(stripped.to_string());
                 } else if let Some(stripped) = line.strip_prefix("Received: ") {
                     received = Some(stripped.to_string());
+                } else if let Some(stripped) = line.strip_prefix("Diff: ") {
+                    assertion_diff.push(stripped.to_string());
                 }
             }
 
@@ -164,6 +239,7 @@ impl ErrorFormatter {
             error: error_type.map(|t| (t, error_msg)),
             location,
             assertion_values,
+            assertion_diff,
         }
     }
 
@@ -242,6 +318,35 @@ impl ErrorFormatter {
             format!("  Expected: {}\n  Received: {}", expected, actual)
         }
     }
+
+    /// Format the path-by-path differences between two containers
+    fn format_assertion_diff(&self, diff: &[String]) -> String {
+        let title = if self.use_colors {
+            style("Differences").cyan().to_string()
+        } else {
+            "Differences".to_string()
+        };
+        let mut output = format!("\n  {}:", title);
+        for line in diff {
+            output.push_str(&format!("\n    {}", line));
+        }
+        output
+    }
+}
+
+/// Escape a workflow command message per the GitHub Actions rules.
+fn escape_annotation_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value, which also can't contain `:` or `,`.
+fn escape_annotation_property(value: &str) -> String {
+    escape_annotation_data(value)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
 }
 
 /// Parsed error information
@@ -252,4 +357,223 @@ struct ParsedError {
     location: Option<(String, usize, String)>,
     /// Expected and actual values for assertions
     assertion_values: Option<(String, String)>,
+    /// One line per differing path when both values are containers
+    assertion_diff: Vec<String>,
+}
+
+/// Soft-wrap one line to `width` visible columns.
+///
+/// Continuation lines keep the line's own leading indentation plus a hanging
+/// indent, so wrapped code and tracebacks stay readable. Lines break at the
+/// last space that fits, or mid-word when there is none. ANSI escape codes
+/// take no width and are never split.
+pub fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    if console::measure_text_width(line) <= width {
+        return vec![line.to_string()];
+    }
+
+    // Split into (text, visible width) tokens: escapes, then single chars
+    let mut tokens: Vec<(String, usize)> = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            let mut escape = c.to_string();
+            for next in chars.by_ref() {
+                escape.push(next);
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            tokens.push((escape, 0));
+        } else {
+            tokens.push((c.to_string(), console::measure_text_width(&c.to_string())));
+        }
+    }
+    let is_space = |token: &(String, usize)| token.1 > 0 && token.0.trim().is_empty();
+
+    let indent: usize = tokens
+        .iter()
+        .take_while(|token| token.1 == 0 || is_space(token))
+        .map(|token| token.1)
+        .sum();
+    // Keep at least half the width for content on continuation lines
+    let hanging = (indent + HANGING_INDENT).min(width / 2);
+    let hanging_tokens: Vec<(String, usize)> = (0..hanging).map(|_| (" ".to_string(), 1)).collect();
+
+    let mut lines = Vec::new();
+    let mut current: Vec<(String, usize)> = Vec::new();
+    let mut content_start = tokens
+        .iter()
+        .take_while(|token| token.1 == 0 || is_space(token))
+        .count();
+    let mut prefix_width = indent;
+
+    let render = |tokens: &[(String, usize)]| -> String {
+        let end = tokens
+            .iter()
+            .rposition(|token| !is_space(token))
+            .map_or(0, |i| i + 1);
+        tokens[..end].iter().map(|token| token.0.as_str()).collect()
+    };
+    let visible = |tokens: &[(String, usize)]| -> usize { tokens.iter().map(|t| t.1).sum() };
+
+    for token in tokens {
+        while token.1 > 0 && visible(&current) + token.1 > width && visible(&current) > prefix_width
+        {
+            let split_at = current
+                .iter()
+                .rposition(is_space)
+                .filter(|&i| i >= content_start);
+            let rest = match split_at {
+                Some(i) => current.split_off(i + 1),
+                None => Vec::new(),
+            };
+            lines.push(render(&current));
+            current = hanging_tokens.clone();
+            current.extend(rest.into_iter().skip_while(is_space));
+            content_start = hanging_tokens.len();
+            prefix_width = hanging;
+        }
+        // Don't start a continuation line with the space we broke at
+        if is_space(&token) && current.len() == content_start && !lines.is_empty() {
+            continue;
+        }
+        current.push(token);
+    }
+    lines.push(render(&current));
+    lines
+}
+
+/// Shorten a node id to at most `max_width` characters by replacing its
+/// middle with `...`, so both the file path and the parameter suffix stay
+/// visible (e.g. `tests/test_a...::test_x[case-1]`).
+pub fn truncate_middle(node_id: &str, max_width: usize) -> String {
+    const ELLIPSIS: &str = "...";
+
+    let chars: Vec<char> = node_id.chars().collect();
+    if chars.len() <= max_width {
+        return node_id.to_string();
+    }
+    if max_width <= ELLIPSIS.len() {
+        return chars[..max_width].iter().collect();
+    }
+
+    let available = max_width - ELLIPSIS.len();
+    let head = available.div_ceil(2);
+    let tail = available - head;
+
+    let mut truncated: String = chars[..head].iter().collect();
+    truncated.push_str(ELLIPSIS);
+    truncated.extend(&chars[chars.len() - tail..]);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate_middle, wrap_line, ErrorFormatter};
+
+    #[test]
+    fn keeps_short_node_ids_intact() {
+        assert_eq!(
+            truncate_middle("tests/test_a.py::test_x", 40),
+            "tests/test_a.py::test_x"
+        );
+    }
+
+    #[test]
+    fn truncates_long_node_ids_in_the_middle() {
+        let node_id = "tests/integration/api/test_endpoints.py::test_request[GET-/users-200]";
+        let truncated = truncate_middle(node_id, 40);
+
+        assert_eq!(truncated.chars().count(), 40);
+        assert!(truncated.starts_with("tests/integration/"));
+        assert!(truncated.contains("..."));
+        assert!(truncated.ends_with("[GET-/users-200]"));
+    }
+
+    #[test]
+    fn github_annotation_points_at_the_failing_line() {
+        let message = "Traceback (most recent call last):\n  \
+            File \"tests/test_math.py\", line 12, in test_add\n    \
+            assert add(2, 2) == 5, \"100%, really\"\n\
+            AssertionError: 100%, really\n";
+
+        let annotation = ErrorFormatter::new(false).github_annotation(
+            "test_add[a,b]",
+            "tests/test_math.py",
+            message,
+        );
+
+        assert_eq!(
+            annotation,
+            "::error file=tests/test_math.py,line=12,title=test_add[a%2Cb]::AssertionError: 100%25, really"
+        );
+    }
+
+    #[test]
+    fn assertion_diff_is_listed_under_the_values() {
+        let message = "Traceback (most recent call last):\n\
+            AssertionError\n\
+            __RUSTEST_ASSERTION_VALUES__\nExpected: {'a': {'b': 2}}\nReceived: {'a': {'b': 1}}\n\
+            Diff: ['a']['b']: expected 2, got 1";
+
+        let output =
+            ErrorFormatter::new(false).format_failure("test_nested", "tests/test_x.py", message);
+
+        assert!(
+            output.contains(
+                "  Received: {'a': {'b': 1}}\n  Differences:\n    ['a']['b']: expected 2, got 1"
+            ),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn github_annotation_includes_assertion_values() {
+        let message = "Traceback (most recent call last):\n  \
+            File \"tests/test_math.py\", line 3, in test_sum\n    \
+            assert total == 5\n\
+            AssertionError\n\
+            __RUSTEST_ASSERTION_VALUES__\nExpected: 5\nReceived: 4";
+
+        let annotation =
+            ErrorFormatter::new(false).github_annotation("test_sum", "tests/test_math.py", message);
+
+        assert_eq!(
+            annotation,
+            "::error file=tests/test_math.py,line=3,title=test_sum::AssertionError%0AExpected: 5%0AReceived: 4"
+        );
+    }
+
+    #[test]
+    fn wraps_long_lines_with_a_hanging_indent() {
+        let line = "    assert result == {'alpha': 1, 'beta': 2, 'gamma': 3, 'delta': 4}";
+
+        assert_eq!(
+            wrap_line(line, 40),
+            [
+                "    assert result == {'alpha': 1,",
+                "        'beta': 2, 'gamma': 3, 'delta':",
+                "        4}",
+            ]
+        );
+        // Short lines and unbroken words are left alone or split mid-word
+        assert_eq!(wrap_line("short", 40), ["short"]);
+        assert_eq!(wrap_line("abcdefghij", 4), ["abcd", "  ef", "  gh", "  ij"]);
+    }
+
+    #[test]
+    fn wrapping_ignores_ansi_escape_width() {
+        let line = format!("\x1b[1m{}\x1b[0m", "word ".repeat(6).trim_end());
+
+        let wrapped = wrap_line(&line, 15);
+
+        assert_eq!(wrapped.len(), 3);
+        assert!(wrapped.iter().all(|l| console::measure_text_width(l) <= 15));
+        assert_eq!(
+            console::strip_ansi_codes(&wrapped.join("|")),
+            "word word word|    word word|    word"
+        );
+    }
 }
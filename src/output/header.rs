@@ -0,0 +1,153 @@
+//! Session header shown before tests run
+//!
+//! Mirrors pytest's header so a log records where the run was rooted,
+//! which configuration file was used and which interpreter ran it.
+
+use crate::python_support::find_project_root;
+use pyo3::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Environment details printed at the start of a run
+#[derive(Debug, Clone)]
+pub struct SessionHeader {
+    pub rootdir: PathBuf,
+    pub config_file: Option<PathBuf>,
+    pub rustest_version: String,
+    pub python_version: String,
+    pub platform: String,
+}
+
+impl SessionHeader {
+    /// Resolve the header for a run whose first test path is `start`.
+    ///
+    /// The rootdir is the directory containing `pyproject.toml` (the same
+    /// lookup `setup_python_path` uses); without one, the current directory.
+    /// The config file is only reported when settings were read from it.
+    pub fn detect(py: Python<'_>, start: Option<&Path>) -> Self {
+        let project_root = start.and_then(find_project_root);
+        let config_file = project_root
+            .as_ref()
+            .map(|root| root.join("pyproject.toml"))
+            .filter(|path| has_pytest_config(path));
+        let rootdir = project_root
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+
+        let (python_version, platform) = py
+            .import("sys")
+            .and_then(|sys| {
+                let info = sys.getattr("version_info")?;
+                let version = format!(
+                    "{}.{}.{}",
+                    info.getattr("major")?,
+                    info.getattr("minor")?,
+                    info.getattr("micro")?
+                );
+                let platform: String = sys.getattr("platform")?.extract()?;
+                Ok((version, platform))
+            })
+            .unwrap_or_else(|_: PyErr| ("unknown".to_string(), std::env::consts::OS.to_string()));
+
+        Self {
+            rootdir,
+            config_file,
+            rustest_version: env!("CARGO_PKG_VERSION").to_string(),
+            python_version,
+            platform,
+        }
+    }
+
+    /// Header lines, e.g. `rootdir: /repo` and `configfile: pyproject.toml`.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "rustest {} -- Python {} on {}",
+                self.rustest_version, self.python_version, self.platform
+            ),
+            format!("rootdir: {}", self.rootdir.display()),
+        ];
+        if let Some(config_file) = &self.config_file {
+            lines.push(format!("configfile: {}", config_file.display()));
+        }
+        lines
+    }
+}
+
+/// Whether `pyproject.toml` has a `[tool.pytest.ini_options]` table, where
+/// `read_pythonpath_from_pyproject` and the asyncio loop scope defaults are
+/// read from.
+fn has_pytest_config(pyproject: &Path) -> bool {
+    std::fs::read_to_string(pyproject)
+        .ok()
+        .and_then(|contents| contents.parse::<toml::Value>().ok())
+        .is_some_and(|config| {
+            config
+                .get("tool")
+                .and_then(|tool| tool.get("pytest"))
+                .and_then(|pytest| pytest.get("ini_options"))
+                .is_some_and(toml::Value::is_table)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionHeader;
+    use pyo3::Python;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A fresh project directory for one test, unique to this process.
+    fn project_dir(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("rustest_header_{}_{}", name, std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        root
+    }
+
+    #[test]
+    fn header_reports_rootdir_and_config_file() {
+        let root = project_dir("config");
+        let tests_dir = root.join("tests");
+        fs::write(
+            root.join("pyproject.toml"),
+            "[tool.pytest.ini_options]\npythonpath = [\"src\"]\n",
+        )
+        .unwrap();
+
+        let header = Python::attach(|py| SessionHeader::detect(py, Some(&tests_dir)));
+        let lines = header.lines();
+
+        assert!(lines[0].starts_with(&format!("rustest {}", env!("CARGO_PKG_VERSION"))));
+        assert!(lines.contains(&format!("rootdir: {}", root.display())));
+        assert!(lines.contains(&format!(
+            "configfile: {}",
+            root.join("pyproject.toml").display()
+        )));
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn header_omits_pyproject_without_pytest_settings() {
+        let root = project_dir("no_config");
+        fs::write(root.join("pyproject.toml"), "[project]\nname = \"demo\"\n").unwrap();
+
+        let header = Python::attach(|py| SessionHeader::detect(py, Some(&root.join("tests"))));
+
+        assert_eq!(header.rootdir, root);
+        assert!(header.config_file.is_none());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn header_omits_config_file_when_none_found() {
+        let header = Python::attach(|py| SessionHeader::detect(py, None));
+        assert!(header.config_file.is_none());
+        assert!(!header
+            .lines()
+            .iter()
+            .any(|line| line.starts_with("configfile")));
+    }
+}
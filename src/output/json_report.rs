@@ -0,0 +1,153 @@
+//! JSON report for `--json-report`
+//!
+//! The report is mapped by hand rather than derived from the model types, so
+//! the schema only changes deliberately. Any change that removes or renames a
+//! key, or changes its meaning, bumps [`JSON_REPORT_SCHEMA_VERSION`]; new keys
+//! may be added within a version.
+
+use crate::model::PyRunReport;
+use pyo3::PyResult;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Version of the JSON report schema, written as `"schema_version"`.
+pub const JSON_REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Serialize a run report: totals, duration, every result and collection error.
+pub fn format_json_report(report: &PyRunReport) -> Value {
+    let results: Vec<Value> = report
+        .results
+        .iter()
+        .map(|result| {
+            json!({
+                "node_id": result.unique_id(),
+                "name": result.name,
+                "path": result.path,
+                "status": result.status,
+                "duration": result.duration,
+                "message": result.message,
+                "stdout": result.stdout,
+                "stderr": result.stderr,
+                "marks": result.marks,
+                "reruns": result.reruns,
+                "was_skipped": result.was_skipped,
+                "fixtures": result.fixtures,
+            })
+        })
+        .collect();
+    let collection_errors: Vec<Value> = report
+        .collection_errors
+        .iter()
+        .map(|error| json!({ "path": error.path, "message": error.message }))
+        .collect();
+    let warnings: Vec<Value> = report
+        .warnings
+        .iter()
+        .map(|(node_id, message)| json!({ "node_id": node_id, "message": message }))
+        .collect();
+    let teardown_errors: Vec<Value> = report
+        .teardown_errors
+        .iter()
+        .map(|error| {
+            json!({
+                "fixture": error.fixture,
+                "scope": error.scope,
+                "message": error.message,
+            })
+        })
+        .collect();
+
+    json!({
+        "schema_version": JSON_REPORT_SCHEMA_VERSION,
+        "totals": {
+            "total": report.total,
+            "passed": report.passed,
+            "failed": report.failed,
+            "skipped": report.skipped,
+            "errors": report.errors,
+            "xfailed": report.xfailed,
+            "xpassed": report.xpassed,
+        },
+        "duration": report.duration,
+        "interrupted": report.interrupted,
+        "results": results,
+        "collection_errors": collection_errors,
+        "warnings": warnings,
+        "teardown_errors": teardown_errors,
+    })
+}
+
+/// Write the JSON report of a run to `destination`.
+pub fn write_json_report(report: &PyRunReport, destination: &Path) -> PyResult<()> {
+    let document = serde_json::to_string_pretty(&format_json_report(report))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    std::fs::write(destination, document).map_err(|e| {
+        pyo3::exceptions::PyIOError::new_err(format!(
+            "Failed to write JSON report to {}: {}",
+            destination.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{CollectionError, PyTestResult};
+
+    #[test]
+    fn report_has_versioned_schema_with_totals_and_results() {
+        let mut failed = PyTestResult::failed(
+            "test_broken".to_string(),
+            "tests/test_api.py".to_string(),
+            0.5,
+            "AssertionError".to_string(),
+            Some("checking\n".to_string()),
+            None,
+            vec!["slow".to_string()],
+        );
+        failed.reruns = 2;
+        let report = PyRunReport::new(
+            2,
+            1,
+            1,
+            0,
+            0,
+            1.25,
+            vec![
+                PyTestResult::passed(
+                    "test_ok".to_string(),
+                    "tests/test_api.py".to_string(),
+                    0.1,
+                    None,
+                    None,
+                    Vec::new(),
+                ),
+                failed,
+            ],
+            vec![CollectionError::new(
+                "tests/test_bad.py".to_string(),
+                "SyntaxError".to_string(),
+            )],
+        );
+
+        let document = format_json_report(&report);
+
+        assert_eq!(document["schema_version"], JSON_REPORT_SCHEMA_VERSION);
+        assert_eq!(document["totals"]["total"], 2);
+        assert_eq!(document["totals"]["failed"], 1);
+        assert_eq!(document["duration"], 1.25);
+        let results = document["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["node_id"], "tests/test_api.py::test_ok");
+        assert_eq!(results[0]["message"], Value::Null);
+        assert_eq!(results[1]["status"], "failed");
+        assert_eq!(results[1]["stdout"], "checking\n");
+        assert_eq!(results[1]["marks"], json!(["slow"]));
+        assert_eq!(results[1]["reruns"], 2);
+        assert_eq!(
+            document["collection_errors"][0]["path"],
+            "tests/test_bad.py"
+        );
+    }
+}
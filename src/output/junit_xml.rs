@@ -0,0 +1,319 @@
+//! JUnit XML report for `--junit-xml`
+//!
+//! [`JUnitXmlRenderer`] wraps the active renderer: every call is forwarded to
+//! it unchanged, while completed tests and collection errors are recorded and
+//! written out as one `<testsuite>` when the suite finishes.
+
+use super::header::SessionHeader;
+use super::renderer::OutputRenderer;
+use crate::model::{CollectionError, PyTestResult, TestCase, TestModule};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Renderer that records results for a JUnit XML report around another renderer.
+pub struct JUnitXmlRenderer {
+    inner: Box<dyn OutputRenderer>,
+    destination: PathBuf,
+    results: Vec<PyTestResult>,
+    collection_errors: Vec<CollectionError>,
+    /// Whether the report reached `destination`
+    written: bool,
+}
+
+impl JUnitXmlRenderer {
+    /// Wrap `inner`, writing the report to `destination` on `finish_suite`.
+    pub fn new(inner: Box<dyn OutputRenderer>, destination: PathBuf) -> Self {
+        Self {
+            inner,
+            destination,
+            results: Vec::new(),
+            collection_errors: Vec::new(),
+            written: false,
+        }
+    }
+}
+
+impl OutputRenderer for JUnitXmlRenderer {
+    fn collection_error(&mut self, error: &CollectionError) {
+        self.collection_errors.push(error.clone());
+        self.inner.collection_error(error);
+    }
+
+    fn start_suite(&mut self, total_files: usize, total_tests: usize, header: &SessionHeader) {
+        self.inner.start_suite(total_files, total_tests, header);
+    }
+
+    fn start_file(&mut self, module: &TestModule) {
+        self.inner.start_file(module);
+    }
+
+    fn start_test(&mut self, test: &TestCase) {
+        self.inner.start_test(test);
+    }
+
+    fn test_completed(&mut self, result: &PyTestResult) {
+        self.results.push(result.clone());
+        self.inner.test_completed(result);
+    }
+
+    fn file_completed(
+        &mut self,
+        path: &str,
+        duration: Duration,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+    ) {
+        self.inner
+            .file_completed(path, duration, passed, failed, skipped);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn finish_suite(
+        &mut self,
+        total: usize,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        errors: usize,
+        xfailed: usize,
+        xpassed: usize,
+        duration: Duration,
+    ) {
+        self.inner.finish_suite(
+            total, passed, failed, skipped, errors, xfailed, xpassed, duration,
+        );
+        let xml = format_junit_xml(&self.results, &self.collection_errors, duration);
+        match std::fs::write(&self.destination, xml) {
+            Ok(()) => self.written = true,
+            Err(e) => self.inner.println(&format!(
+                "Failed to write JUnit XML report to {}: {}",
+                self.destination.display(),
+                e
+            )),
+        }
+    }
+
+    fn println(&self, message: &str) {
+        self.inner.println(message);
+    }
+
+    fn written_files(&self) -> Vec<PathBuf> {
+        let mut files = self.inner.written_files();
+        if self.written {
+            files.push(self.destination.clone());
+        }
+        files
+    }
+}
+
+/// Render results and collection errors as a JUnit XML document.
+///
+/// Each test becomes a `<testcase>` with its file path as `classname`.
+/// Failures and errors carry the full message plus captured output in
+/// `<system-out>`/`<system-err>`; skips and expected failures are
+/// `<skipped>`. Collection errors are reported as erroring test cases.
+pub fn format_junit_xml(
+    results: &[PyTestResult],
+    collection_errors: &[CollectionError],
+    duration: Duration,
+) -> String {
+    let count = |statuses: &[&str]| {
+        results
+            .iter()
+            .filter(|result| statuses.contains(&result.status.as_str()))
+            .count()
+    };
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<testsuites>\n");
+    output.push_str(&format!(
+        "  <testsuite name=\"rustest\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        results.len() + collection_errors.len(),
+        count(&["failed"]),
+        count(&["error"]) + collection_errors.len(),
+        count(&["skipped", "xfailed"]),
+        duration.as_secs_f64()
+    ));
+
+    for error in collection_errors {
+        output.push_str(&format!(
+            "    <testcase classname=\"{}\" name=\"collection\" time=\"0.000\">\n",
+            escape(&error.path)
+        ));
+        output.push_str(&format!(
+            "      <error message=\"collection failure\">{}</error>\n",
+            escape(&error.message)
+        ));
+        output.push_str("    </testcase>\n");
+    }
+
+    for result in results {
+        let open = format!(
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape(&result.path),
+            escape(&result.name),
+            result.duration
+        );
+        // The assertion enrichment block trails the traceback; keep it out of the report
+        let message = result
+            .message
+            .as_deref()
+            .unwrap_or("")
+            .split("__RUSTEST_ASSERTION_VALUES__")
+            .next()
+            .unwrap_or("")
+            .trim_end();
+        let element = match result.status.as_str() {
+            "failed" => "failure",
+            "error" => "error",
+            "skipped" | "xfailed" => {
+                let kind = if result.status == "xfailed" {
+                    " type=\"xfail\""
+                } else {
+                    ""
+                };
+                output.push_str(&format!(
+                    "{}>\n      <skipped{} message=\"{}\"/>\n    </testcase>\n",
+                    open,
+                    kind,
+                    escape(summary_line(message))
+                ));
+                continue;
+            }
+            _ => {
+                output.push_str(&open);
+                output.push_str("/>\n");
+                continue;
+            }
+        };
+        output.push_str(&open);
+        output.push_str(">\n");
+        output.push_str(&format!(
+            "      <{} message=\"{}\">{}</{}>\n",
+            element,
+            escape(summary_line(message)),
+            escape(message),
+            element
+        ));
+        if let Some(stdout) = result.stdout.as_deref().filter(|out| !out.is_empty()) {
+            output.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                escape(stdout)
+            ));
+        }
+        if let Some(stderr) = result.stderr.as_deref().filter(|err| !err.is_empty()) {
+            output.push_str(&format!(
+                "      <system-err>{}</system-err>\n",
+                escape(stderr)
+            ));
+        }
+        output.push_str("    </testcase>\n");
+    }
+
+    output.push_str("  </testsuite>\n</testsuites>\n");
+    output
+}
+
+/// The exception line of a message: its last non-empty line.
+fn summary_line(message: &str) -> &str {
+    message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("")
+        .trim()
+}
+
+/// Escape text for XML content and attributes, dropping characters XML 1.0 forbids.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            ch if ch < ' ' => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, status: &str, message: Option<&str>) -> PyTestResult {
+        let mut result = PyTestResult::passed(
+            name.to_string(),
+            "tests/test_api.py".to_string(),
+            0.25,
+            None,
+            None,
+            Vec::new(),
+        );
+        result.status = status.to_string();
+        result.message = message.map(str::to_string);
+        result
+    }
+
+    #[test]
+    fn formats_suite_with_failures_and_skips() {
+        let mut failed = result(
+            "test_broken",
+            "failed",
+            Some("Traceback (most recent call last):\nAssertionError: 1 < 2"),
+        );
+        failed.stdout = Some("checking\n".to_string());
+        let results = vec![
+            result("test_ok", "passed", None),
+            failed,
+            result("test_later", "skipped", Some("not ready")),
+        ];
+
+        let xml = format_junit_xml(&results, &[], Duration::from_millis(1500));
+
+        assert!(xml.contains(
+            "<testsuite name=\"rustest\" tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"1.500\">"
+        ));
+        assert!(xml.contains(
+            "<testcase classname=\"tests/test_api.py\" name=\"test_ok\" time=\"0.250\"/>"
+        ));
+        assert!(xml.contains(
+            "<failure message=\"AssertionError: 1 &lt; 2\">Traceback (most recent call last):\nAssertionError: 1 &lt; 2</failure>"
+        ));
+        assert!(xml.contains("<system-out>checking\n</system-out>"));
+        assert!(xml.contains("<skipped message=\"not ready\"/>"));
+    }
+
+    #[test]
+    fn assertion_values_are_left_out_of_the_failure() {
+        let results = vec![result(
+            "test_math",
+            "failed",
+            Some("Traceback:\nAssertionError\n\n__RUSTEST_ASSERTION_VALUES__\nExpected: 2\nReceived: 1"),
+        )];
+        let xml = format_junit_xml(&results, &[], Duration::ZERO);
+        assert!(xml
+            .contains("<failure message=\"AssertionError\">Traceback:\nAssertionError</failure>"));
+    }
+
+    #[test]
+    fn collection_errors_are_reported_as_errors() {
+        let errors = vec![CollectionError::new(
+            "tests/test_bad.py".to_string(),
+            "SyntaxError: invalid syntax".to_string(),
+        )];
+        let xml = format_junit_xml(&[], &errors, Duration::ZERO);
+        assert!(xml.contains("tests=\"1\" failures=\"0\" errors=\"1\""));
+        assert!(xml.contains(
+            "<testcase classname=\"tests/test_bad.py\" name=\"collection\" time=\"0.000\">"
+        ));
+    }
+
+    #[test]
+    fn escape_drops_control_characters() {
+        assert_eq!(escape("a\u{1b}[31m\"b\"&"), "a[31m&quot;b&quot;&amp;");
+    }
+}
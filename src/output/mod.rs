@@ -3,12 +3,26 @@
 //! This module handles all terminal output for rustest, providing
 //! real-time feedback during test execution.
 
+mod chrome_trace;
+mod compact_display;
+mod durations;
 mod event_stream;
 mod events;
+mod failure_groups;
 mod formatter;
+mod header;
+mod json_report;
+mod junit_xml;
 mod renderer;
 mod spinner_display;
+mod sqlite;
+mod symbols;
+mod tap;
+mod warnings;
 
+pub use chrome_trace::{record_span, start_trace, write_chrome_trace};
+pub use compact_display::CompactDisplay;
+pub use durations::{format_slowest_files, format_slowest_tests, slowest};
 pub use event_stream::EventStreamRenderer;
 pub use events::{
     emit_collection_completed, emit_collection_progress, emit_collection_started,
@@ -16,11 +30,21 @@ pub use events::{
     CollectionStartedEvent, FileCompletedEvent, FileStartedEvent, SuiteCompletedEvent,
     SuiteStartedEvent, TestCompletedEvent,
 };
+pub use header::SessionHeader;
+pub use json_report::write_json_report;
+pub use junit_xml::JUnitXmlRenderer;
 pub use renderer::{OutputMode, OutputRenderer};
 pub use spinner_display::SpinnerDisplay;
+pub use sqlite::SqliteRecorder;
+pub use symbols::{validate_status_symbols, StatusSymbols};
+pub use tap::write_tap_report;
+pub use warnings::{format_teardown_errors, format_warnings_summary};
 
 use crate::model::RunConfiguration;
 
+/// Columns reserved next to names for spinners, symbols, counts and timing
+const NAME_WIDTH_MARGIN: usize = 40;
+
 /// Configuration for output display
 #[derive(Debug, Clone)]
 pub struct OutputConfig {
@@ -28,21 +52,126 @@ pub struct OutputConfig {
     pub verbose: bool,
     pub ascii_mode: bool,
     pub use_colors: bool,
-    #[allow(dead_code)]
     pub mode: OutputMode,
+    /// Maximum display width for test and file names (`None` = no limit)
+    pub name_width: Option<usize>,
+    /// Symbols shown for each outcome, after user overrides
+    pub symbols: StatusSymbols,
+    /// Print failures as GitHub Actions `::error` workflow commands
+    pub github_annotations: bool,
+    /// Soft-wrap failure message lines to this width (`None` = no wrapping)
+    pub wrap_width: Option<usize>,
+    /// Print failures sharing the same error once in the summary
+    pub group_failures: bool,
+    /// List each test's fixtures under its verbose line
+    pub show_test_fixtures: bool,
+    /// Print repeated failures of a parametrized test as one line each
+    pub collapse_param_failures: bool,
+}
+
+/// Decide whether to emit colors.
+///
+/// Precedence: explicit override (`--color always/never`) > `NO_COLOR`
+/// (any non-empty value disables colors) > `FORCE_COLOR` (any non-empty
+/// value other than `0` enables colors) > whether stderr is a terminal.
+fn resolve_use_colors(
+    explicit: Option<bool>,
+    no_color_env: Option<&str>,
+    force_color_env: Option<&str>,
+    is_terminal: bool,
+) -> bool {
+    if let Some(explicit) = explicit {
+        return explicit;
+    }
+    if no_color_env.is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if let Some(value) = force_color_env.filter(|value| !value.is_empty()) {
+        return value != "0";
+    }
+    is_terminal
 }
 
 impl OutputConfig {
     pub fn from_run_config(config: &RunConfiguration) -> Self {
-        // Check if we're in a terminal and colors aren't disabled
-        let is_terminal = console::Term::stderr().is_term();
-        let use_colors = !config.no_color && is_terminal;
+        let explicit = if config.no_color {
+            Some(false)
+        } else if config.force_color {
+            Some(true)
+        } else {
+            None
+        };
+        let use_colors = resolve_use_colors(
+            explicit,
+            std::env::var("NO_COLOR").ok().as_deref(),
+            std::env::var("FORCE_COLOR").ok().as_deref(),
+            console::Term::stderr().is_term(),
+        );
+
+        // Without an explicit width, fit names to the terminal. Output that
+        // isn't going to a terminal (e.g. CI logs) is never truncated.
+        let term = console::Term::stderr();
+        let name_width = config.max_name_width.or_else(|| {
+            term.size_checked()
+                .map(|(_rows, cols)| usize::from(cols).saturating_sub(NAME_WIDTH_MARGIN))
+        });
+
+        // Wrapping only applies when writing to a terminal of known width
+        let wrap_width = if config.wrap_failures {
+            term.size_checked().map(|(_rows, cols)| usize::from(cols))
+        } else {
+            None
+        };
+
+        let mode = OutputMode::detect(config);
+        let symbols = match mode {
+            OutputMode::Compact => StatusSymbols::compact(),
+            _ => StatusSymbols::defaults(config.ascii),
+        }
+        .with_overrides(&config.status_symbols);
 
         Self {
             verbose: config.verbose,
             ascii_mode: config.ascii,
             use_colors,
-            mode: OutputMode::detect(config),
+            mode,
+            name_width,
+            symbols,
+            github_annotations: config.github_annotations,
+            wrap_width,
+            group_failures: config.group_failures,
+            show_test_fixtures: config.show_test_fixtures,
+            collapse_param_failures: config.collapse_param_failures,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_use_colors;
+
+    #[test]
+    fn explicit_override_wins() {
+        assert!(resolve_use_colors(Some(true), Some("1"), None, false));
+        assert!(!resolve_use_colors(Some(false), None, Some("1"), true));
+    }
+
+    #[test]
+    fn no_color_beats_force_color_and_tty() {
+        assert!(!resolve_use_colors(None, Some("1"), Some("1"), true));
+        // An empty NO_COLOR is treated as unset
+        assert!(resolve_use_colors(None, Some(""), None, true));
+    }
+
+    #[test]
+    fn force_color_beats_tty_detection() {
+        assert!(resolve_use_colors(None, None, Some("1"), false));
+        assert!(!resolve_use_colors(None, None, Some("0"), true));
+    }
+
+    #[test]
+    fn falls_back_to_tty_detection() {
+        assert!(resolve_use_colors(None, None, None, true));
+        assert!(!resolve_use_colors(None, None, None, false));
+    }
+}
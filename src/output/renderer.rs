@@ -1,6 +1,8 @@
 //! Output renderer trait and mode selection
 
+use super::header::SessionHeader;
 use crate::model::{CollectionError, PyTestResult, RunConfiguration, TestCase, TestModule};
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Output display mode
@@ -16,6 +18,8 @@ pub enum OutputMode {
     /// Quiet mode - minimal output
     #[allow(dead_code)]
     Quiet,
+    /// One summary line per file with compressed per-test outcomes
+    Compact,
 }
 
 impl OutputMode {
@@ -23,7 +27,9 @@ impl OutputMode {
     pub fn detect(config: &RunConfiguration) -> Self {
         // For now, use file spinners for non-verbose, hierarchical for verbose
         // Future: could detect file count and use progress bar for very large suites
-        if config.verbose {
+        if config.compact {
+            Self::Compact
+        } else if config.verbose {
             Self::Hierarchical
         } else {
             Self::FileSpinners
@@ -36,8 +42,8 @@ pub trait OutputRenderer {
     /// Called when a collection error occurs (syntax error, import error, etc.)
     fn collection_error(&mut self, error: &CollectionError);
 
-    /// Called when discovery completes with total counts
-    fn start_suite(&mut self, total_files: usize, total_tests: usize);
+    /// Called when discovery completes with total counts and the session header
+    fn start_suite(&mut self, total_files: usize, total_tests: usize, header: &SessionHeader);
 
     /// Called when a file starts execution
     fn start_file(&mut self, module: &TestModule);
@@ -60,6 +66,7 @@ pub trait OutputRenderer {
     );
 
     /// Called when entire suite completes
+    #[allow(clippy::too_many_arguments)]
     fn finish_suite(
         &mut self,
         total: usize,
@@ -67,10 +74,17 @@ pub trait OutputRenderer {
         failed: usize,
         skipped: usize,
         errors: usize,
+        xfailed: usize,
+        xpassed: usize,
         duration: Duration,
     );
 
     /// Print a message without disrupting progress display
     #[allow(dead_code)]
     fn println(&self, message: &str);
+
+    /// Report files this renderer has written, such as the JUnit XML report
+    fn written_files(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
 }
@@ -3,13 +3,18 @@
 //! Shows a spinner next to each test file as it runs, updating to a
 //! status symbol when complete.
 
-use super::formatter::ErrorFormatter;
+use super::failure_groups::{
+    collapse_param_failures, format_group_others, format_repeated_failure, group_failures,
+};
+use super::formatter::{truncate_middle, ErrorFormatter};
+use super::header::SessionHeader;
 use super::renderer::OutputRenderer;
+use super::symbols::StatusSymbols;
 use crate::model::{to_relative_path, CollectionError, PyTestResult, TestCase, TestModule};
 use console::style;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Format duration with appropriate units (ms or s) and optional color
 fn format_duration(duration: Duration, use_colors: bool) -> String {
@@ -27,6 +32,30 @@ fn format_duration(duration: Duration, use_colors: bool) -> String {
     }
 }
 
+/// Completion percentage column, like pytest's `[ 42%]`.
+///
+/// Without a known total (nothing was counted at collection) the column
+/// keeps its width but shows `?`. Reruns can complete more tests than were
+/// collected, so the percentage is capped at 100.
+fn progress_prefix(completed: usize, total: usize) -> String {
+    if total == 0 {
+        return "[   ?]".to_string();
+    }
+    let percent = (completed * 100 / total).min(100);
+    format!("[{:>3}%]", percent)
+}
+
+/// Estimated time left in the run, from the average time per completed test.
+///
+/// Returns `None` until 5% of the tests have completed, since earlier
+/// estimates swing too much to be useful, and once every test is done.
+fn estimate_remaining(completed: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if total == 0 || completed >= total || completed * 20 < total {
+        return None;
+    }
+    Some(elapsed.mul_f64((total - completed) as f64 / completed as f64))
+}
+
 /// Spinner display showing file-level progress
 pub struct SpinnerDisplay {
     multi: MultiProgress,
@@ -34,6 +63,19 @@ pub struct SpinnerDisplay {
     formatter: ErrorFormatter,
     use_colors: bool,
     ascii_mode: bool,
+    /// Maximum display width for file and test names
+    name_width: Option<usize>,
+    symbols: StatusSymbols,
+    /// Print GitHub Actions annotations for failures
+    github_annotations: bool,
+    /// Print one line per test, prefixed with the run's completion percentage
+    verbose: bool,
+    /// Tests collected, from `start_suite`; 0 when unknown
+    total_tests: usize,
+    /// Tests completed so far, for the percentage prefix
+    completed: usize,
+    /// When the suite started, for the ETA shown next to the running file
+    started: Option<Instant>,
     passed: usize,
     failed: usize,
     skipped: usize,
@@ -41,22 +83,50 @@ pub struct SpinnerDisplay {
     deferred_failures: Vec<(String, String, String)>, // (name, path, message)
     /// Collect collection errors to display at the end
     collection_errors: Vec<(String, String)>, // (path, message)
+    /// Print failures sharing the same error once
+    group_failures: bool,
+    /// List the fixtures a test used under its verbose line
+    show_test_fixtures: bool,
+    /// Print repeated failures of a parametrized test as one line each
+    collapse_param_failures: bool,
 }
 
 impl SpinnerDisplay {
     /// Create a new spinner display
-    pub fn new(use_colors: bool, ascii_mode: bool) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        use_colors: bool,
+        ascii_mode: bool,
+        name_width: Option<usize>,
+        symbols: StatusSymbols,
+        github_annotations: bool,
+        wrap_width: Option<usize>,
+        verbose: bool,
+        group_failures: bool,
+        show_test_fixtures: bool,
+        collapse_param_failures: bool,
+    ) -> Self {
         Self {
             multi: MultiProgress::new(),
             spinners: HashMap::new(),
-            formatter: ErrorFormatter::new(use_colors),
+            formatter: ErrorFormatter::new(use_colors).with_wrap_width(wrap_width),
             use_colors,
             ascii_mode,
+            name_width,
+            symbols,
+            github_annotations,
+            verbose,
+            total_tests: 0,
+            completed: 0,
+            started: None,
             passed: 0,
             failed: 0,
             skipped: 0,
             deferred_failures: Vec::new(),
             collection_errors: Vec::new(),
+            group_failures,
+            show_test_fixtures,
+            collapse_param_failures,
         }
     }
 
@@ -73,6 +143,14 @@ impl SpinnerDisplay {
         }
     }
 
+    /// Shorten a name to the configured display width
+    fn display_name(&self, name: &str) -> String {
+        match self.name_width {
+            Some(width) => truncate_middle(name, width),
+            None => name.to_string(),
+        }
+    }
+
     /// Apply a style function to text only when colors are enabled.
     fn styled<F>(&self, text: &str, styler: F) -> String
     where
@@ -88,12 +166,68 @@ impl SpinnerDisplay {
     /// Format a symbol based on status
     fn format_symbol(&self, failed: usize) -> String {
         if failed > 0 {
-            let text = if self.ascii_mode { "FAIL" } else { "✗" };
-            self.styled(text, |s| s.red())
+            self.styled(&self.symbols.failed, |s| s.red())
         } else {
-            let text = if self.ascii_mode { "PASS" } else { "✓" };
-            self.styled(text, |s| s.green())
+            self.styled(&self.symbols.passed, |s| s.green())
+        }
+    }
+
+    /// Build the verbose line for a completed test, e.g. `[ 42%] ✓ tests/test_a.py::test_one`
+    fn test_line(&self, result: &PyTestResult) -> String {
+        let symbol = self.symbols.for_status(&result.status);
+        let symbol = match result.status.as_str() {
+            "passed" => self.styled(symbol, |s| s.green()),
+            "skipped" | "xfailed" | "xpassed" => self.styled(symbol, |s| s.yellow()),
+            _ => self.styled(symbol, |s| s.red()),
+        };
+        format!(
+            "{} {} {}",
+            self.styled(&progress_prefix(self.completed, self.total_tests), |s| s
+                .dim()),
+            symbol,
+            self.display_name(&result.unique_id())
+        )
+    }
+
+    /// Build the line listing a test's fixtures, shown under its verbose line
+    fn fixtures_line(&self, result: &PyTestResult) -> Option<String> {
+        if !self.show_test_fixtures || result.fixtures.is_empty() {
+            return None;
+        }
+        let line = format!("       fixtures: {}", result.fixtures.join(", "));
+        Some(self.styled(&line, |s| s.dim()))
+    }
+
+    /// Build the line a file's spinner finishes with
+    fn file_line(&self, path: &str, duration: Duration, passed: usize, failed: usize) -> String {
+        let symbol = self.format_symbol(failed);
+        let total = passed + failed;
+        let time_str = format_duration(duration, self.use_colors);
+
+        // Build the status parts conditionally
+        let mut status_parts = Vec::new();
+        if passed > 0 {
+            status_parts.push(self.styled(&format!("{} passing", passed), |s| s.green()));
+        }
+        if failed > 0 {
+            status_parts.push(self.styled(&format!("{} failed", failed), |s| s.red()));
         }
+
+        let status_str = if status_parts.is_empty() {
+            "0 tests".to_string()
+        } else {
+            status_parts.join(", ")
+        };
+
+        format!(
+            "{} {} - {}/{} {} {}",
+            symbol,
+            self.display_name(path),
+            total,
+            total,
+            status_str,
+            time_str
+        )
     }
 }
 
@@ -104,15 +238,21 @@ impl OutputRenderer for SpinnerDisplay {
             .push((error.path.clone(), error.message.clone()));
     }
 
-    fn start_suite(&mut self, _total_files: usize, _total_tests: usize) {
-        // No-op for spinner mode - we don't show overall progress
+    fn start_suite(&mut self, _total_files: usize, total_tests: usize, header: &SessionHeader) {
+        // Overall progress only shows as the verbose percentage prefix
+        self.total_tests = total_tests;
+        self.started = Some(Instant::now());
+        for line in header.lines() {
+            eprintln!("{}", self.styled(&line, |s| s.dim()));
+        }
+        eprintln!();
     }
 
     fn start_file(&mut self, module: &TestModule) {
         let pb = self.multi.add(ProgressBar::new(module.tests.len() as u64));
         pb.set_style(self.spinner_style());
         let path_str = to_relative_path(&module.path);
-        pb.set_message(path_str.clone());
+        pb.set_message(self.display_name(&path_str));
         pb.enable_steady_tick(Duration::from_millis(100));
         self.spinners.insert(path_str, pb);
     }
@@ -122,19 +262,46 @@ impl OutputRenderer for SpinnerDisplay {
     }
 
     fn test_completed(&mut self, result: &PyTestResult) {
-        // Increment the spinner for this file
+        self.completed += 1;
+        // Increment the spinner for this file and refresh the run's ETA on it
         if let Some(pb) = self.spinners.get(&result.path) {
             pb.inc(1);
+            let eta = self.started.and_then(|started| {
+                estimate_remaining(self.completed, self.total_tests, started.elapsed())
+            });
+            if let Some(eta) = eta {
+                pb.set_message(format!(
+                    "{} {}",
+                    self.display_name(&result.path),
+                    self.styled(&format!("ETA ~{}s", eta.as_secs_f64().ceil()), |s| s.dim())
+                ));
+            }
+        }
+        if self.verbose {
+            let line = self.test_line(result);
+            let fixtures = self.fixtures_line(result);
+            self.multi.suspend(|| {
+                eprintln!("{}", line);
+                if let Some(fixtures) = fixtures {
+                    eprintln!("{}", fixtures);
+                }
+            });
         }
 
         // Update overall counters
         match result.status.as_str() {
             "passed" => self.passed += 1,
-            "failed" => {
+            "failed" | "error" => {
                 self.failed += 1;
 
                 // Defer error output to the end
                 if let Some(ref message) = result.message {
+                    if self.github_annotations {
+                        let annotation =
+                            self.formatter
+                                .github_annotation(&result.name, &result.path, message);
+                        self.multi.suspend(|| println!("{}", annotation));
+                    }
                     self.deferred_failures.push((
                         result.name.clone(),
                         result.path.clone(),
@@ -156,32 +323,11 @@ impl OutputRenderer for SpinnerDisplay {
         _skipped: usize,
     ) {
         if let Some(pb) = self.spinners.remove(path) {
-            let symbol = self.format_symbol(failed);
-            let total = passed + failed;
-            let time_str = format_duration(duration, self.use_colors);
-
-            // Build the status parts conditionally
-            let mut status_parts = Vec::new();
-            if passed > 0 {
-                status_parts.push(self.styled(&format!("{} passing", passed), |s| s.green()));
-            }
-            if failed > 0 {
-                status_parts.push(self.styled(&format!("{} failed", failed), |s| s.red()));
-            }
-
-            let status_str = if status_parts.is_empty() {
-                "0 tests".to_string()
-            } else {
-                status_parts.join(", ")
-            };
-
-            pb.finish_with_message(format!(
-                "{} {} - {}/{} {} {}",
-                symbol, path, total, total, status_str, time_str
-            ));
+            pb.finish_with_message(self.file_line(path, duration, passed, failed));
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn finish_suite(
         &mut self,
         total: usize,
@@ -189,6 +335,8 @@ impl OutputRenderer for SpinnerDisplay {
         failed: usize,
         skipped: usize,
         errors: usize,
+        xfailed: usize,
+        xpassed: usize,
         duration: Duration,
     ) {
         // Print collection errors first (like pytest does with "ERRORS" section)
@@ -216,9 +364,42 @@ impl OutputRenderer for SpinnerDisplay {
             eprintln!();
             eprintln!("{}", self.styled("FAILURES", |s| s.red().bold()));
 
-            for (name, path, message) in &self.deferred_failures {
-                let formatted = self.formatter.format_failure(name, path, message);
-                eprintln!("{}", formatted);
+            if self.group_failures {
+                for group in group_failures(&self.deferred_failures) {
+                    let (name, path, message) = group.first;
+                    let formatted =
+                        self.formatter
+                            .format_failure(&self.display_name(name), path, message);
+                    eprintln!("{}", formatted);
+                    if !group.others.is_empty() {
+                        eprintln!("{}", format_group_others(&group.others));
+                    }
+                }
+            } else if self.collapse_param_failures {
+                for (failure, first) in collapse_param_failures(&self.deferred_failures) {
+                    match first {
+                        Some(first) => {
+                            let line = format_repeated_failure(failure, first);
+                            eprintln!("{}", self.styled(&line, |s| s.dim()));
+                        }
+                        None => {
+                            let (name, path, message) = failure;
+                            let formatted = self.formatter.format_failure(
+                                &self.display_name(name),
+                                path,
+                                message,
+                            );
+                            eprintln!("{}", formatted);
+                        }
+                    }
+                }
+            } else {
+                for (name, path, message) in &self.deferred_failures {
+                    let formatted =
+                        self.formatter
+                            .format_failure(&self.display_name(name), path, message);
+                    eprintln!("{}", formatted);
+                }
             }
         }
 
@@ -241,6 +422,12 @@ impl OutputRenderer for SpinnerDisplay {
         if errors > 0 {
             parts.push(self.styled(&format!("{} error", errors), |s| s.red()));
         }
+        if xfailed > 0 {
+            parts.push(self.styled(&format!("{} xfailed", xfailed), |s| s.yellow()));
+        }
+        if xpassed > 0 {
+            parts.push(self.styled(&format!("{} xpassed", xpassed), |s| s.yellow()));
+        }
 
         let status_str = if parts.is_empty() {
             "0 tests".to_string()
@@ -249,11 +436,7 @@ impl OutputRenderer for SpinnerDisplay {
         };
 
         // Symbol is red if there are failures OR errors
-        let symbol = if failed > 0 || errors > 0 {
-            self.styled("✗", |s| s.red())
-        } else {
-            self.styled("✓", |s| s.green())
-        };
+        let symbol = self.format_symbol(failed + errors);
 
         eprintln!("{} {}/{} {} {}", symbol, total, total, status_str, time_str);
     }
@@ -264,3 +447,123 @@ impl OutputRenderer for SpinnerDisplay {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_lines_use_configured_symbols() {
+        let overrides = HashMap::from([
+            ("passed".to_string(), "OK".to_string()),
+            ("failed".to_string(), "KO".to_string()),
+        ]);
+        let display = SpinnerDisplay::new(
+            false,
+            false,
+            None,
+            StatusSymbols::defaults(false).with_overrides(&overrides),
+            false,
+            None,
+            false,
+            false,
+            false,
+            false,
+        );
+
+        let green = display.file_line("tests/test_a.py", Duration::from_millis(5), 2, 0);
+        let red = display.file_line("tests/test_b.py", Duration::from_millis(5), 1, 1);
+
+        assert_eq!(green, "OK tests/test_a.py - 2/2 2 passing (5ms)");
+        assert_eq!(red, "KO tests/test_b.py - 2/2 1 passing, 1 failed (5ms)");
+    }
+
+    #[test]
+    fn verbose_lines_carry_a_completion_percentage() {
+        let mut display = SpinnerDisplay::new(
+            false,
+            true,
+            None,
+            StatusSymbols::defaults(true),
+            false,
+            None,
+            true,
+            false,
+            false,
+            false,
+        );
+        display.total_tests = 3;
+        let mut lines = Vec::new();
+        for name in ["test_a", "test_b", "test_c"] {
+            let result = PyTestResult::passed(
+                name.to_string(),
+                "tests/test_progress.py".to_string(),
+                0.01,
+                None,
+                None,
+                Vec::new(),
+            );
+            display.test_completed(&result);
+            lines.push(display.test_line(&result));
+        }
+
+        assert_eq!(lines[0], "[ 33%] PASS tests/test_progress.py::test_a");
+        assert_eq!(lines[1], "[ 66%] PASS tests/test_progress.py::test_b");
+        assert_eq!(lines[2], "[100%] PASS tests/test_progress.py::test_c");
+    }
+
+    #[test]
+    fn fixtures_are_listed_under_the_verbose_line_when_enabled() {
+        let mut result = PyTestResult::passed(
+            "test_profile".to_string(),
+            "tests/test_users.py".to_string(),
+            0.01,
+            None,
+            None,
+            Vec::new(),
+        );
+        result.fixtures = vec!["clean_env".to_string(), "user".to_string()];
+        let display = |show_test_fixtures| {
+            SpinnerDisplay::new(
+                false,
+                true,
+                None,
+                StatusSymbols::defaults(true),
+                false,
+                None,
+                true,
+                false,
+                show_test_fixtures,
+                false,
+            )
+        };
+
+        assert_eq!(display(false).fixtures_line(&result), None);
+        assert_eq!(
+            display(true).fixtures_line(&result).as_deref(),
+            Some("       fixtures: clean_env, user")
+        );
+    }
+
+    #[test]
+    fn eta_waits_for_five_percent_then_extrapolates_the_average() {
+        let elapsed = Duration::from_secs(10);
+        assert_eq!(estimate_remaining(4, 100, elapsed), None);
+        assert_eq!(
+            estimate_remaining(5, 100, elapsed),
+            Some(Duration::from_secs(190))
+        );
+        assert_eq!(
+            estimate_remaining(50, 100, elapsed),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(estimate_remaining(100, 100, elapsed), None);
+        assert_eq!(estimate_remaining(3, 0, elapsed), None);
+    }
+
+    #[test]
+    fn progress_prefix_handles_an_unknown_total() {
+        assert_eq!(progress_prefix(4, 0), "[   ?]");
+        assert_eq!(progress_prefix(5, 4), "[100%]");
+    }
+}
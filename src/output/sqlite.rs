@@ -0,0 +1,178 @@
+//! Historical results in a SQLite database
+//!
+//! Every run appends its results to the `test_results` table, tagged with a
+//! run id, so outcomes can be compared across runs (e.g. to spot flaky
+//! tests). Results are buffered and written once per file, in one
+//! transaction, to keep database writes off the per-test path.
+
+use crate::model::PyTestResult;
+use crate::output::events::current_timestamp;
+use pyo3::PyResult;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Distinguishes recorders opened by one process within the same instant
+static RECORDERS_OPENED: AtomicUsize = AtomicUsize::new(0);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS test_results (
+    run_id TEXT NOT NULL,
+    node_id TEXT NOT NULL,
+    status TEXT NOT NULL,
+    duration REAL NOT NULL,
+    timestamp REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS test_results_node_id ON test_results (node_id);
+";
+
+/// A result waiting to be written at the next file boundary
+struct PendingRow {
+    node_id: String,
+    status: String,
+    duration: f64,
+    timestamp: f64,
+}
+
+/// Appends this run's results to a SQLite database
+pub struct SqliteRecorder {
+    path: PathBuf,
+    connection: Option<Connection>,
+    run_id: String,
+    pending: Vec<PendingRow>,
+}
+
+impl SqliteRecorder {
+    /// Open (or create) the database at `path` and make sure the schema exists.
+    pub fn open(path: &Path) -> PyResult<Self> {
+        let connection = Connection::open(path)
+            .and_then(|connection| connection.execute_batch(SCHEMA).map(|()| connection))
+            .map_err(|e| {
+                pyo3::exceptions::PyIOError::new_err(format!(
+                    "Failed to open SQLite database {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            connection: Some(connection),
+            run_id: format!(
+                "{:.6}-{}-{}",
+                current_timestamp(),
+                std::process::id(),
+                RECORDERS_OPENED.fetch_add(1, Ordering::Relaxed)
+            ),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Queue a completed test; it is written at the next [`flush`](Self::flush).
+    pub fn record(&mut self, result: &PyTestResult) {
+        if self.connection.is_none() {
+            return;
+        }
+        self.pending.push(PendingRow {
+            node_id: result.unique_id(),
+            status: result.status.clone(),
+            duration: result.duration,
+            timestamp: current_timestamp(),
+        });
+    }
+
+    /// Write the queued results in a single transaction.
+    ///
+    /// A failed write only disables recording; it never fails the run.
+    pub fn flush(&mut self) {
+        let Some(connection) = self.connection.as_mut() else {
+            return;
+        };
+        if self.pending.is_empty() {
+            return;
+        }
+        let written = connection.transaction().and_then(|transaction| {
+            {
+                let mut insert = transaction.prepare_cached(
+                    "INSERT INTO test_results (run_id, node_id, status, duration, timestamp)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for row in &self.pending {
+                    insert.execute(params![
+                        self.run_id,
+                        row.node_id,
+                        row.status,
+                        row.duration,
+                        row.timestamp
+                    ])?;
+                }
+            }
+            transaction.commit()
+        });
+        self.pending.clear();
+        if let Err(e) = written {
+            eprintln!(
+                "Warning: Stopped recording results to {}: {}",
+                self.path.display(),
+                e
+            );
+            self.connection = None;
+        }
+    }
+
+    /// Write anything still queued and close the database.
+    pub fn finish(mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_schema_and_appends_runs() {
+        let dir = std::env::temp_dir().join("rustest_sqlite_recorder");
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = dir.join("results.db");
+        let _ = std::fs::remove_file(&db);
+        let result = |name: &str| {
+            PyTestResult::passed(
+                name.to_string(),
+                "tests/test_db.py".to_string(),
+                0.25,
+                None,
+                None,
+                Vec::new(),
+            )
+        };
+
+        let mut first = SqliteRecorder::open(&db).unwrap();
+        first.record(&result("test_a"));
+        first.record(&result("test_b"));
+        first.finish();
+        let mut second = SqliteRecorder::open(&db).unwrap();
+        second.record(&result("test_a"));
+        // Nothing is written until the next file boundary
+        let connection = Connection::open(&db).unwrap();
+        let count = |connection: &Connection| -> i64 {
+            connection
+                .query_row("SELECT COUNT(*) FROM test_results", [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(count(&connection), 2);
+        second.flush();
+        assert_eq!(count(&connection), 3);
+
+        let runs: i64 = connection
+            .query_row(
+                "SELECT COUNT(DISTINCT run_id) FROM test_results",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        drop(connection);
+        drop(second);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(runs, 2);
+    }
+}
@@ -0,0 +1,120 @@
+//! Status symbols shown next to test and file outcomes
+//!
+//! Renderers look symbols up here instead of hard-coding glyphs, so they
+//! can be restyled or localized through the `status_symbols` run option.
+
+use std::collections::HashMap;
+
+/// Statuses whose symbol can be overridden
+const STATUS_KEYS: [&str; 5] = ["passed", "failed", "skipped", "xfailed", "xpassed"];
+
+/// Glyph or word shown for each outcome
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSymbols {
+    pub passed: String,
+    pub failed: String,
+    pub skipped: String,
+    pub xfailed: String,
+    pub xpassed: String,
+}
+
+impl StatusSymbols {
+    /// Glyphs used by the spinner display, or words in ASCII mode.
+    pub fn defaults(ascii_mode: bool) -> Self {
+        if ascii_mode {
+            Self::from_strs("PASS", "FAIL", "SKIP", "XFAIL", "XPASS")
+        } else {
+            Self::from_strs("✓", "✗", "⊘", "x", "X")
+        }
+    }
+
+    /// Single characters for compact mode's per-test outcome strip.
+    pub fn compact() -> Self {
+        Self::from_strs(".", "F", "s", "x", "X")
+    }
+
+    fn from_strs(passed: &str, failed: &str, skipped: &str, xfailed: &str, xpassed: &str) -> Self {
+        Self {
+            passed: passed.to_string(),
+            failed: failed.to_string(),
+            skipped: skipped.to_string(),
+            xfailed: xfailed.to_string(),
+            xpassed: xpassed.to_string(),
+        }
+    }
+
+    /// Replace symbols by status name. Unknown keys are ignored here; reject
+    /// them up front with [`validate_status_symbols`].
+    pub fn with_overrides(mut self, overrides: &HashMap<String, String>) -> Self {
+        for (key, value) in overrides {
+            match key.as_str() {
+                "passed" => self.passed = value.clone(),
+                "failed" => self.failed = value.clone(),
+                "skipped" => self.skipped = value.clone(),
+                "xfailed" => self.xfailed = value.clone(),
+                "xpassed" => self.xpassed = value.clone(),
+                _ => {}
+            }
+        }
+        self
+    }
+
+    /// Symbol for a test result status.
+    pub fn for_status(&self, status: &str) -> &str {
+        match status {
+            "passed" => &self.passed,
+            "skipped" => &self.skipped,
+            "xfailed" => &self.xfailed,
+            "xpassed" => &self.xpassed,
+            _ => &self.failed,
+        }
+    }
+}
+
+/// Reject override keys that don't name a known status.
+pub fn validate_status_symbols(overrides: &HashMap<String, String>) -> Result<(), String> {
+    let mut unknown: Vec<&str> = overrides
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !STATUS_KEYS.contains(key))
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    unknown.sort();
+    Err(format!(
+        "Unknown status symbol key(s): {}. Expected any of: {}",
+        unknown.join(", "),
+        STATUS_KEYS.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_mode_uses_words() {
+        let symbols = StatusSymbols::defaults(true);
+        assert_eq!(symbols.passed, "PASS");
+        assert_eq!(symbols.failed, "FAIL");
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let overrides = HashMap::from([("skipped".to_string(), "SALTADO".to_string())]);
+        let symbols = StatusSymbols::defaults(false).with_overrides(&overrides);
+        assert_eq!(symbols.passed, "✓");
+        assert_eq!(symbols.for_status("skipped"), "SALTADO");
+        assert_eq!(symbols.for_status("xfailed"), "x");
+        assert_eq!(symbols.for_status("xpassed"), "X");
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let overrides = HashMap::from([("pased".to_string(), "ok".to_string())]);
+        let err = validate_status_symbols(&overrides).unwrap_err();
+        assert!(err.contains("pased"));
+        assert!(validate_status_symbols(&HashMap::new()).is_ok());
+    }
+}
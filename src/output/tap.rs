@@ -0,0 +1,173 @@
+//! TAP (Test Anything Protocol) version 13 report
+//!
+//! Written after the run from the report's results, so it works alongside
+//! whichever renderer is active.
+
+use crate::model::PyTestResult;
+use pyo3::PyResult;
+use std::io::Write;
+use std::path::Path;
+
+/// Render results as a TAP version 13 document.
+///
+/// Skips become `ok ... # SKIP`, expected failures `not ok ... # TODO`,
+/// unexpected passes `ok ... # TODO`, and
+/// failures carry a YAML diagnostic block with the failure message.
+pub fn format_tap(results: &[PyTestResult]) -> String {
+    let mut output = String::from("TAP version 13\n");
+    output.push_str(&format!("1..{}\n", results.len()));
+
+    for (index, result) in results.iter().enumerate() {
+        let number = index + 1;
+        let description = escape_description(&result.unique_id());
+        let message = result.message.as_deref().unwrap_or("");
+
+        match result.status.as_str() {
+            "passed" => output.push_str(&format!("ok {} - {}\n", number, description)),
+            "skipped" => output.push_str(&format!(
+                "ok {} - {} # SKIP {}\n",
+                number,
+                description,
+                directive_reason(message, "skipped")
+            )),
+            "xfailed" => output.push_str(&format!(
+                "not ok {} - {} # TODO {}\n",
+                number,
+                description,
+                directive_reason(message, "expected failure")
+            )),
+            "xpassed" => output.push_str(&format!(
+                "ok {} - {} # TODO {}\n",
+                number,
+                description,
+                directive_reason(message, "unexpected pass")
+            )),
+            status => {
+                output.push_str(&format!("not ok {} - {}\n", number, description));
+                output.push_str("  ---\n");
+                output.push_str(&format!(
+                    "  severity: {}\n",
+                    if status == "error" { "error" } else { "fail" }
+                ));
+                output.push_str(&format!("  duration_ms: {:.3}\n", result.duration * 1000.0));
+                if !message.is_empty() {
+                    output.push_str("  message: |\n");
+                    for line in message.lines() {
+                        output.push_str(&format!("    {}\n", line));
+                    }
+                }
+                output.push_str("  ...\n");
+            }
+        }
+    }
+
+    output
+}
+
+/// Write the TAP report to `destination`, or to stdout when it is `-`.
+pub fn write_tap_report(results: &[PyTestResult], destination: &Path) -> PyResult<()> {
+    let tap = format_tap(results);
+    let written = if destination == Path::new("-") {
+        std::io::stdout().lock().write_all(tap.as_bytes())
+    } else {
+        std::fs::write(destination, tap)
+    };
+    written.map_err(|e| {
+        pyo3::exceptions::PyIOError::new_err(format!(
+            "Failed to write TAP report to {}: {}",
+            destination.display(),
+            e
+        ))
+    })
+}
+
+/// `#` starts a directive in TAP, so it must be escaped in descriptions.
+fn escape_description(description: &str) -> String {
+    description.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+/// Single-line reason for a SKIP/TODO directive.
+fn directive_reason(reason: &str, fallback: &str) -> String {
+    let reason = reason.lines().next().unwrap_or("").trim();
+    if reason.is_empty() {
+        fallback.to_string()
+    } else {
+        reason.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, status: &str, message: Option<&str>) -> PyTestResult {
+        let mut result = PyTestResult::passed(
+            name.to_string(),
+            "tests/test_tap.py".to_string(),
+            0.5,
+            None,
+            None,
+            Vec::new(),
+        );
+        result.status = status.to_string();
+        result.message = message.map(str::to_string);
+        result
+    }
+
+    #[test]
+    fn formats_plan_and_statuses() {
+        let results = vec![
+            result("test_ok", "passed", None),
+            result(
+                "test_broken",
+                "failed",
+                Some("Traceback (most recent call last):\nAssertionError: boom"),
+            ),
+            result("test_later", "skipped", Some("not ready")),
+            result("test_flaky", "xfailed", Some("known issue")),
+            result("test_fixed", "xpassed", None),
+            result("test_case[#1]", "error", Some("fixture exploded")),
+        ];
+
+        let tap = format_tap(&results);
+        let lines: Vec<&str> = tap.lines().collect();
+
+        assert_eq!(lines[0], "TAP version 13");
+        assert_eq!(lines[1], "1..6");
+        let test_lines: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|line| line.starts_with("ok ") || line.starts_with("not ok "))
+            .collect();
+        assert_eq!(
+            test_lines,
+            [
+                "ok 1 - tests/test_tap.py::test_ok",
+                "not ok 2 - tests/test_tap.py::test_broken",
+                "ok 3 - tests/test_tap.py::test_later # SKIP not ready",
+                "not ok 4 - tests/test_tap.py::test_flaky # TODO known issue",
+                "ok 5 - tests/test_tap.py::test_fixed # TODO unexpected pass",
+                "not ok 6 - tests/test_tap.py::test_case[\\#1]",
+            ]
+        );
+
+        // Failures carry an indented YAML block with the message
+        let start = lines
+            .iter()
+            .position(|l| l.starts_with("not ok 2"))
+            .unwrap();
+        assert_eq!(
+            &lines[start + 1..start + 7],
+            [
+                "  ---",
+                "  severity: fail",
+                "  duration_ms: 500.000",
+                "  message: |",
+                "    Traceback (most recent call last):",
+                "    AssertionError: boom",
+            ]
+        );
+        assert_eq!(lines[start + 7], "  ...");
+        assert!(tap.contains("  severity: error\n"));
+    }
+}
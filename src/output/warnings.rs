@@ -0,0 +1,75 @@
+//! Warnings and teardown error summaries printed after the run.
+
+use crate::model::TeardownError;
+
+/// Summary block listing recorded warnings grouped under the test that raised them.
+pub fn format_warnings_summary(warnings: &[(String, String)]) -> String {
+    let mut output = format!(
+        "warnings summary ({} {}):\n",
+        warnings.len(),
+        if warnings.len() == 1 {
+            "warning"
+        } else {
+            "warnings"
+        }
+    );
+    let mut current: Option<&str> = None;
+    for (node_id, message) in warnings {
+        if current != Some(node_id.as_str()) {
+            output.push_str(&format!("  {}\n", node_id));
+            current = Some(node_id);
+        }
+        output.push_str(&format!("    {}\n", message));
+    }
+    output
+}
+
+/// Summary block with the traceback of every fixture teardown that raised.
+pub fn format_teardown_errors(errors: &[TeardownError]) -> String {
+    let mut output = format!(
+        "teardown errors ({} {}):\n",
+        errors.len(),
+        if errors.len() == 1 { "error" } else { "errors" }
+    );
+    for error in errors {
+        output.push_str(&format!(
+            "  {} ({}-scoped fixture)\n",
+            error.fixture, error.scope
+        ));
+        for line in error.message.trim_end().lines() {
+            output.push_str(&format!("    {}\n", line));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_consecutive_warnings_by_test() {
+        let warnings = vec![
+            ("a.py::test_one".to_string(), "first".to_string()),
+            ("a.py::test_one".to_string(), "second".to_string()),
+            ("a.py::test_two".to_string(), "third".to_string()),
+        ];
+        assert_eq!(
+            format_warnings_summary(&warnings),
+            "warnings summary (3 warnings):\n  a.py::test_one\n    first\n    second\n  a.py::test_two\n    third\n"
+        );
+    }
+
+    #[test]
+    fn teardown_errors_name_the_fixture_and_indent_the_traceback() {
+        let errors = vec![TeardownError {
+            fixture: "database".to_string(),
+            scope: "module".to_string(),
+            message: "Traceback (most recent call last):\nRuntimeError: still open\n".to_string(),
+        }];
+        assert_eq!(
+            format_teardown_errors(&errors),
+            "teardown errors (1 error):\n  database (module-scoped fixture)\n    Traceback (most recent call last):\n    RuntimeError: still open\n"
+        );
+    }
+}
@@ -0,0 +1,19 @@
+//! Persistent worker subprocesses
+//!
+//! A [`WorkerPool`] keeps a fixed number of Python worker processes alive for
+//! the whole run. Each worker imports the test package once and then receives
+//! batches of node ids over its stdin, answering on stdout with the results.
+//! Messages on both pipes use the length-prefixed JSON framing in
+//! [`protocol`]. The Python side lives in `rustest.worker`.
+//!
+//! With `workers` above one, the executor splits modules into one shard per
+//! worker with [`shard_exclusive`] and merges what the workers report. Modules
+//! whose tests share an `@mark.exclusive` resource go to the same worker.
+
+mod pool;
+mod protocol;
+mod shard;
+
+pub use pool::{WorkerCommand, WorkerPool};
+pub use protocol::WorkerResult;
+pub use shard::shard_exclusive;
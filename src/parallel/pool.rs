@@ -0,0 +1,357 @@
+//! Supervisor for a pool of persistent worker processes
+
+use super::protocol::{read_message, write_message, BatchRequest, BatchResponse, WorkerResult};
+use serde_json::{Map, Value};
+use std::collections::VecDeque;
+use std::io::{self, BufReader, BufWriter};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+
+/// How to start one worker process
+#[derive(Debug, Clone)]
+pub struct WorkerCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Extra environment variables, e.g. `PYTHONPATH`
+    pub env: Vec<(String, String)>,
+}
+
+impl WorkerCommand {
+    /// The bundled Python worker, `python -m rustest.worker`.
+    pub fn python(executable: impl Into<String>) -> Self {
+        Self {
+            program: executable.into(),
+            args: vec!["-m".to_string(), "rustest.worker".to_string()],
+            env: Vec::new(),
+        }
+    }
+}
+
+/// A fixed-size pool of worker processes that stay alive across batches
+pub struct WorkerPool {
+    command: WorkerCommand,
+    size: usize,
+    /// Sent with every batch
+    options: Map<String, Value>,
+}
+
+/// A batch waiting in the queue, with how many times it has been tried
+struct QueuedBatch {
+    request: BatchRequest,
+    attempts: usize,
+}
+
+/// One running worker process and its pipes
+struct Worker {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    fn spawn(command: &WorkerCommand) -> io::Result<Self> {
+        let mut child = Command::new(&command.program)
+            .args(&command.args)
+            .envs(command.env.iter().map(|(key, value)| (key, value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        Ok(Self {
+            child,
+            stdin: BufWriter::new(stdin),
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    /// Send one batch and wait for its results.
+    fn run_batch(&mut self, request: &BatchRequest) -> io::Result<BatchResponse> {
+        write_message(&mut self.stdin, request)?;
+        let response: BatchResponse = read_message(&mut self.stdout)?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "worker exited mid-batch")
+        })?;
+        if response.batch_id != request.batch_id {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "worker answered batch {} while running batch {}",
+                    response.batch_id, request.batch_id
+                ),
+            ));
+        }
+        Ok(response)
+    }
+
+    /// Close stdin so the worker exits on its own, then reap it.
+    fn shutdown(self) {
+        let Worker {
+            mut child, stdin, ..
+        } = self;
+        drop(stdin);
+        let _ = child.wait();
+    }
+
+    /// Kill a worker that is in an unknown state.
+    fn kill(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl WorkerPool {
+    pub fn new(command: WorkerCommand, size: usize) -> Self {
+        Self {
+            command,
+            size: size.max(1),
+            options: Map::new(),
+        }
+    }
+
+    /// Keyword arguments each worker passes to `rustest.rust.run`.
+    pub fn with_options(mut self, options: Map<String, Value>) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Run every batch across the pool and return the responses in batch
+    /// order, each with its results as the worker reported them.
+    ///
+    /// A batch whose worker crashes is re-queued once on a fresh worker. If
+    /// it crashes again, each of its tests is reported as an error.
+    pub fn run(&self, batches: Vec<Vec<String>>) -> Vec<BatchResponse> {
+        let queue: Mutex<VecDeque<QueuedBatch>> = Mutex::new(
+            batches
+                .into_iter()
+                .enumerate()
+                .map(|(batch_id, node_ids)| QueuedBatch {
+                    request: BatchRequest {
+                        batch_id,
+                        node_ids,
+                        options: self.options.clone(),
+                    },
+                    attempts: 0,
+                })
+                .collect(),
+        );
+        let finished: Mutex<Vec<BatchResponse>> = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.size {
+                scope.spawn(|| self.supervise(&queue, &finished));
+            }
+        });
+
+        let mut finished = finished.into_inner().unwrap_or_else(|e| e.into_inner());
+        finished.sort_by_key(|response| response.batch_id);
+        finished
+    }
+
+    /// Drive one worker slot until the queue is empty.
+    ///
+    /// A slot that re-queues a crashed batch keeps pulling from the queue, so
+    /// retried batches are never stranded after other slots have finished.
+    fn supervise(
+        &self,
+        queue: &Mutex<VecDeque<QueuedBatch>>,
+        finished: &Mutex<Vec<BatchResponse>>,
+    ) {
+        let mut worker: Option<Worker> = None;
+
+        loop {
+            let Some(mut batch) = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front()
+            else {
+                break;
+            };
+            batch.attempts += 1;
+
+            let outcome = match worker.take() {
+                Some(existing) => Ok(existing),
+                None => Worker::spawn(&self.command),
+            }
+            .and_then(|mut running| match running.run_batch(&batch.request) {
+                Ok(response) => Ok((running, response)),
+                Err(err) => {
+                    running.kill();
+                    Err(err)
+                }
+            });
+
+            match outcome {
+                Ok((running, response)) => {
+                    worker = Some(running);
+                    finished
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(response);
+                }
+                Err(err) if batch.attempts < 2 => {
+                    eprintln!(
+                        "Warning: worker failed on batch {} ({}); retrying on a fresh worker",
+                        batch.request.batch_id, err
+                    );
+                    queue
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push_back(batch);
+                }
+                Err(err) => {
+                    let message = format!("Worker crashed twice while running this batch: {}", err);
+                    let results = batch
+                        .request
+                        .node_ids
+                        .into_iter()
+                        .map(|node_id| WorkerResult {
+                            node_id,
+                            status: "error".to_string(),
+                            duration: 0.0,
+                            message: Some(message.clone()),
+                            stdout: None,
+                            stderr: None,
+                            fixtures: Vec::new(),
+                        })
+                        .collect();
+                    finished
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(BatchResponse {
+                            batch_id: batch.request.batch_id,
+                            results,
+                            ..BatchResponse::default()
+                        });
+                }
+            }
+        }
+
+        if let Some(running) = worker {
+            running.shutdown();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stand-in worker that speaks the protocol without needing rustest:
+    /// every test passes, except that the first process to see a node id
+    /// containing `crash` exits mid-batch, and `always_crash` never succeeds.
+    const FAKE_WORKER: &str = r#"
+import json, os, struct, sys
+
+marker = sys.argv[1]
+stdin, stdout = sys.stdin.buffer, sys.stdout.buffer
+while True:
+    header = stdin.read(4)
+    if len(header) < 4:
+        break
+    request = json.loads(stdin.read(struct.unpack(">I", header)[0]))
+    ids = request["node_ids"]
+    if any("always_crash" in i for i in ids):
+        os._exit(1)
+    if any("crash" in i for i in ids) and not os.path.exists(marker):
+        open(marker, "w").close()
+        os._exit(1)
+    results = [
+        {"node_id": i, "status": "passed", "duration": 0.0, "message": str(os.getpid())}
+        for i in ids
+    ]
+    payload = json.dumps({"batch_id": request["batch_id"], "results": results}).encode()
+    stdout.write(struct.pack(">I", len(payload)) + payload)
+    stdout.flush()
+"#;
+
+    fn fake_pool(name: &str) -> (WorkerPool, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("fake_worker.py");
+        std::fs::write(&script, FAKE_WORKER).unwrap();
+        let marker = dir.join("crashed");
+        let _ = std::fs::remove_file(&marker);
+        let command = WorkerCommand {
+            program: "python3".to_string(),
+            args: vec![
+                script.to_string_lossy().into_owned(),
+                marker.to_string_lossy().into_owned(),
+            ],
+            env: Vec::new(),
+        };
+        (WorkerPool::new(command, 2), dir)
+    }
+
+    fn batch(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn aggregates_results_from_persistent_workers() {
+        let (pool, dir) = fake_pool("rustest_pool_aggregate");
+        let batches = vec![
+            batch(&["a.py::test_1", "a.py::test_2"]),
+            batch(&["b.py::test_3"]),
+            batch(&["c.py::test_4"]),
+            batch(&["d.py::test_5", "d.py::test_6"]),
+        ];
+
+        let results: Vec<WorkerResult> = pool
+            .run(batches)
+            .into_iter()
+            .flat_map(|response| response.results)
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let ids: Vec<&str> = results.iter().map(|r| r.node_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "a.py::test_1",
+                "a.py::test_2",
+                "b.py::test_3",
+                "c.py::test_4",
+                "d.py::test_5",
+                "d.py::test_6"
+            ]
+        );
+        assert!(results.iter().all(|r| r.status == "passed"));
+        // Four batches ran on at most two long-lived processes
+        let pids: std::collections::HashSet<_> = results.iter().map(|r| &r.message).collect();
+        assert!(pids.len() <= 2, "{:?}", pids);
+    }
+
+    #[test]
+    fn requeues_a_crashed_batch_once() {
+        let (pool, dir) = fake_pool("rustest_pool_crash");
+        let batches = vec![
+            batch(&["a.py::test_ok"]),
+            batch(&["b.py::test_crash"]),
+            batch(&["c.py::test_always_crash", "c.py::test_other"]),
+        ];
+
+        let results: Vec<WorkerResult> = pool
+            .run(batches)
+            .into_iter()
+            .flat_map(|response| response.results)
+            .collect();
+        std::fs::remove_dir_all(&dir).ok();
+
+        let status: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.node_id.as_str(), r.status.as_str()))
+            .collect();
+        assert_eq!(
+            status,
+            [
+                ("a.py::test_ok", "passed"),
+                ("b.py::test_crash", "passed"),
+                ("c.py::test_always_crash", "error"),
+                ("c.py::test_other", "error"),
+            ]
+        );
+        assert!(results[2]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("crashed twice"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Wire format between the supervisor and its workers
+//!
+//! Every message is a 4-byte big-endian length followed by that many bytes
+//! of UTF-8 JSON. Closing the worker's stdin asks it to exit.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::{self, Read, Write};
+
+/// Upper bound on a single message, to fail fast on a corrupted stream
+const MAX_MESSAGE_LEN: u32 = 256 * 1024 * 1024;
+
+/// A batch of tests for a worker to run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub batch_id: usize,
+    pub node_ids: Vec<String>,
+    /// Keyword arguments for the worker's `rustest.rust.run` call
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub options: Map<String, Value>,
+}
+
+/// The results of one batch, in any order, and what its session reported
+/// besides test outcomes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchResponse {
+    pub batch_id: usize,
+    pub results: Vec<WorkerResult>,
+    /// `(node_id, message)` for each warning a test raised
+    #[serde(default)]
+    pub warnings: Vec<(String, String)>,
+    /// `(fixture, scope, message)` for each fixture teardown that raised
+    #[serde(default)]
+    pub teardown_errors: Vec<(String, String, String)>,
+    #[serde(default)]
+    pub fixture_setups: usize,
+    #[serde(default)]
+    pub fixture_teardowns: usize,
+}
+
+/// Outcome of a single test run by a worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerResult {
+    pub node_id: String,
+    /// Same status strings as `PyTestResult` (`passed`, `failed`, `skipped`, `error`)
+    pub status: String,
+    pub duration: f64,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub stdout: Option<String>,
+    #[serde(default)]
+    pub stderr: Option<String>,
+    #[serde(default)]
+    pub fixtures: Vec<String>,
+}
+
+/// Write one framed message and flush it.
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let payload = serde_json::to_vec(message)?;
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_MESSAGE_LEN)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "message too large"))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(&payload)?;
+    writer.flush()
+}
+
+/// Read one framed message. Returns `Ok(None)` if the stream ended cleanly
+/// before a new message started.
+pub fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<Option<T>> {
+    let mut header = [0u8; 4];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let len = u32::from_be_bytes(header);
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message length {} exceeds limit", len),
+        ));
+    }
+
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok(Some(serde_json::from_slice(&payload)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn messages_round_trip_with_length_prefix() {
+        let request = BatchRequest {
+            batch_id: 3,
+            node_ids: vec!["tests/test_a.py::test_x".to_string()],
+            options: Map::from_iter([("capture_output".to_string(), Value::Bool(false))]),
+        };
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &request).unwrap();
+        write_message(&mut buffer, &request).unwrap();
+
+        let payload_len = u32::from_be_bytes(buffer[..4].try_into().unwrap()) as usize;
+        assert_eq!(buffer.len(), 2 * (4 + payload_len));
+
+        let mut reader = Cursor::new(buffer);
+        let first: Option<BatchRequest> = read_message(&mut reader).unwrap();
+        let second: Option<BatchRequest> = read_message(&mut reader).unwrap();
+        let end: Option<BatchRequest> = read_message(&mut reader).unwrap();
+        assert_eq!(first.as_ref(), Some(&request));
+        assert_eq!(second.as_ref(), Some(&request));
+        assert!(end.is_none());
+    }
+
+    #[test]
+    fn truncated_message_is_an_error() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &vec!["a", "b"]).unwrap();
+        buffer.truncate(buffer.len() - 1);
+
+        let result: io::Result<Option<Vec<String>>> = read_message(&mut Cursor::new(buffer));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Splitting modules between worker processes
+
+use std::collections::HashMap;
+
+/// Split items of the given sizes into at most `count` disjoint shards of
+/// similar total size, returning the item indices of each shard.
+///
+/// Largest items are placed first, each into the currently lightest shard.
+/// Indices within a shard stay in their original order, and empty shards are
+/// dropped.
+pub fn shard_by_size(sizes: &[usize], count: usize) -> Vec<Vec<usize>> {
+    let count = count.max(1);
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| sizes[b].cmp(&sizes[a]).then(a.cmp(&b)));
+
+    let mut shards: Vec<(usize, Vec<usize>)> = vec![(0, Vec::new()); count];
+    for index in order {
+        let lightest = shards
+            .iter_mut()
+            .min_by_key(|(load, _)| *load)
+            .expect("at least one shard");
+        lightest.0 += sizes[index];
+        lightest.1.push(index);
+    }
+
+    shards
+        .into_iter()
+        .filter(|(_, indices)| !indices.is_empty())
+        .map(|(_, mut indices)| {
+            indices.sort_unstable();
+            indices
+        })
+        .collect()
+}
+
+/// Like [`shard_by_size`], but items sharing any of their `resources` always
+/// land in the same shard, so a worker runs them one after another.
+///
+/// Items linked through shared resources, directly or transitively, are
+/// balanced as one group.
+pub fn shard_exclusive(
+    sizes: &[usize],
+    resources: &[Vec<String>],
+    count: usize,
+) -> Vec<Vec<usize>> {
+    // Union-find over items, joined through the first item to use a resource
+    let mut parent: Vec<usize> = (0..sizes.len()).collect();
+    fn root(parent: &mut [usize], mut item: usize) -> usize {
+        while parent[item] != item {
+            parent[item] = parent[parent[item]];
+            item = parent[item];
+        }
+        item
+    }
+    let mut owner: HashMap<&str, usize> = HashMap::new();
+    for (item, names) in resources.iter().enumerate() {
+        for name in names {
+            let first = *owner.entry(name.as_str()).or_insert(item);
+            let (a, b) = (root(&mut parent, first), root(&mut parent, item));
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for item in 0..sizes.len() {
+        let group = *group_of.entry(root(&mut parent, item)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(item);
+    }
+
+    let group_sizes: Vec<usize> = groups
+        .iter()
+        .map(|group| group.iter().map(|&item| sizes[item]).sum())
+        .collect();
+    shard_by_size(&group_sizes, count)
+        .into_iter()
+        .map(|shard| {
+            let mut items: Vec<usize> = shard
+                .into_iter()
+                .flat_map(|group| groups[group].iter().copied())
+                .collect();
+            items.sort_unstable();
+            items
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn balances_shards_by_size() {
+        let shards = shard_by_size(&[10, 1, 4, 5, 3], 2);
+        assert_eq!(shards, [vec![0, 1], vec![2, 3, 4]]);
+
+        let loads: Vec<usize> = shards
+            .iter()
+            .map(|shard| shard.iter().map(|&i| [10, 1, 4, 5, 3][i]).sum())
+            .collect();
+        assert_eq!(loads, [11, 12]);
+
+        // Never more shards than items, and every item exactly once
+        let shards = shard_by_size(&[2, 2], 8);
+        assert_eq!(shards, [vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn items_sharing_a_resource_are_never_in_concurrent_shards() {
+        let names = |list: &[&str]| list.iter().map(|name| name.to_string()).collect();
+        let resources: Vec<Vec<String>> = vec![
+            names(&["db"]),
+            names(&[]),
+            names(&["queue"]),
+            names(&["db", "cache"]),
+            names(&[]),
+            names(&["cache"]),
+            names(&["queue"]),
+        ];
+        let shards = shard_exclusive(&[1; 7], &resources, 4);
+
+        let shard_of = |item: usize| shards.iter().position(|shard| shard.contains(&item));
+        // "db" and "cache" are linked through item 3
+        assert_eq!(shard_of(0), shard_of(3));
+        assert_eq!(shard_of(3), shard_of(5));
+        assert_eq!(shard_of(2), shard_of(6));
+        assert_ne!(shard_of(0), shard_of(2));
+        assert_eq!(shards.iter().map(Vec::len).sum::<usize>(), 7);
+        assert_eq!(shards.len(), 4);
+    }
+}
@@ -28,21 +28,52 @@ impl PyPaths {
     }
 
     /// Convert the raw strings into canonicalised [`PathBuf`] values.
+    ///
+    /// Every path is checked before failing, so a single error lists all of
+    /// the missing or unreadable paths rather than just the first one.
     pub fn materialise(&self) -> PyResult<Vec<PathBuf>> {
-        self.raw
-            .iter()
-            .map(|value| {
-                let path = Path::new(value);
-                if path.exists() {
-                    Ok(path.canonicalize()?)
-                } else {
-                    Err(pyo3::exceptions::PyFileNotFoundError::new_err(format!(
-                        "Path '{}' does not exist",
-                        value
-                    )))
+        let mut paths = Vec::with_capacity(self.raw.len());
+        let mut problems = Vec::new();
+        let mut all_missing = true;
+
+        for value in &self.raw {
+            let path = Path::new(value);
+            if !path.exists() {
+                problems.push(format!("Path '{}' does not exist", value));
+                continue;
+            }
+            match check_readable(path).and_then(|_| path.canonicalize()) {
+                Ok(canonical) => paths.push(canonical),
+                Err(err) => {
+                    all_missing = false;
+                    problems.push(format!("Path '{}' is not readable: {}", value, err));
                 }
-            })
-            .collect()
+            }
+        }
+
+        let message = match problems.len() {
+            0 => return Ok(paths),
+            1 => problems.remove(0),
+            count => format!(
+                "{} test paths are invalid:\n  {}",
+                count,
+                problems.join("\n  ")
+            ),
+        };
+        if all_missing {
+            Err(pyo3::exceptions::PyFileNotFoundError::new_err(message))
+        } else {
+            Err(pyo3::exceptions::PyOSError::new_err(message))
+        }
+    }
+}
+
+/// Open a test path the way discovery will, to surface permission errors early.
+fn check_readable(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::read_dir(path).map(|_| ())
+    } else {
+        std::fs::File::open(path).map(|_| ())
     }
 }
 
@@ -230,7 +261,9 @@ pub(crate) fn read_pythonpath_from_pyproject(project_root: &Path) -> Option<Vec<
 /// ```bash
 /// PYTHONPATH=src rustest tests/  # Not needed anymore!
 /// ```
-pub fn setup_python_path(py: Python<'_>, paths: &[PathBuf]) -> PyResult<()> {
+///
+/// Returns the paths that were newly inserted into `sys.path`.
+pub fn setup_python_path(py: Python<'_>, paths: &[PathBuf]) -> PyResult<Vec<PathBuf>> {
     let sys = py.import("sys")?;
     let sys_path: Bound<'_, PyList> = sys.getattr("path")?.extract()?;
 
@@ -272,6 +305,7 @@ pub fn setup_python_path(py: Python<'_>, paths: &[PathBuf]) -> PyResult<()> {
     }
 
     // Add paths to sys.path if not already present
+    let mut added = Vec::new();
     for path in paths_to_add {
         let path_str = path.to_string_lossy();
         let path_str = path_str.as_ref();
@@ -286,8 +320,9 @@ pub fn setup_python_path(py: Python<'_>, paths: &[PathBuf]) -> PyResult<()> {
         if !already_exists {
             // Insert at the beginning like pytest does (prepend mode)
             sys_path.insert(0, path_str)?;
+            added.push(path.clone());
         }
     }
 
-    Ok(())
+    Ok(added)
 }
@@ -62,6 +62,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_pypaths_materialise_reports_every_missing_path() {
+        let existing = env::temp_dir().join("rustest_test_some_missing");
+        fs::create_dir_all(&existing).unwrap();
+
+        let paths = vec![
+            "/nonexistent/tset_api.py".to_string(),
+            existing.to_string_lossy().to_string(),
+            "/nonexistent/tests/unti".to_string(),
+        ];
+        let py_paths = PyPaths::from_vec(paths);
+
+        pyo3::Python::with_gil(|py| {
+            let err = py_paths.materialise().unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyFileNotFoundError>(py));
+            let message = err.to_string();
+            assert!(message.contains("2 test paths are invalid"), "{}", message);
+            assert!(message.contains("'/nonexistent/tset_api.py' does not exist"));
+            assert!(message.contains("'/nonexistent/tests/unti' does not exist"));
+            assert!(!message.contains("rustest_test_some_missing"));
+        });
+
+        fs::remove_dir(&existing).ok();
+    }
+
     #[test]
     fn test_pypaths_materialise_multiple_paths() {
         // Create multiple temporary directories
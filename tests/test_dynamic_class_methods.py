@@ -0,0 +1,49 @@
+"""Test classes whose test methods are attached dynamically."""
+
+
+def add_generated_test(cls):
+    def test_added_by_decorator(self):
+        assert True
+
+    cls.test_added_by_decorator = test_added_by_decorator
+    return cls
+
+
+class GeneratedTestsBase:
+    def __init_subclass__(cls, **kwargs):
+        super().__init_subclass__(**kwargs)
+
+        def test_added_by_init_subclass(self):
+            assert True
+
+        cls.test_added_by_init_subclass = test_added_by_init_subclass
+
+
+class RaisingDescriptor:
+    def __get__(self, instance, owner):
+        raise RuntimeError("descriptor should not break collection")
+
+
+@add_generated_test
+class TestDynamicMethods(GeneratedTestsBase):
+    def __init__(self):
+        self.test_added_on_instance = lambda: None
+
+    broken = RaisingDescriptor()
+
+    @property
+    def test_broken_property(self):
+        raise RuntimeError("property should not break collection")
+
+    def test_defined_in_body(self):
+        assert True
+
+
+class TestConstructorSideEffects:
+    def __init__(self):
+        import sys
+
+        sys.rustest_constructor_calls = getattr(sys, "rustest_constructor_calls", 0) + 1
+
+    def test_defined_in_body(self):
+        assert True
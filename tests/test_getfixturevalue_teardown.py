@@ -0,0 +1,23 @@
+"""Generator fixtures requested via getfixturevalue() are torn down after the test."""
+
+from rustest import fixture
+
+_events = []
+
+
+@fixture
+def resource():
+    _events.append("enter")
+    yield "resource"
+    _events.append("exit")
+
+
+def test_getfixturevalue_enters_generator_fixture(request):
+    assert request.getfixturevalue("resource") == "resource"
+    _events.append("test body")
+    assert _events == ["enter", "test body"]
+
+
+def test_previous_fixture_was_torn_down(request):
+    assert _events == ["enter", "test body", "exit"]
+    request.getfixturevalue("resource")
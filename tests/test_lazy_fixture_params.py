@@ -0,0 +1,15 @@
+"""A parametrized fixture only runs for the params its selected tests use."""
+
+from rustest import fixture
+
+_calls = []
+
+
+@fixture(params=["sqlite", "postgres", "mysql"])
+def backend(request):
+    _calls.append(request.param)
+    return request.param
+
+
+def test_backend(backend):
+    assert backend in ("sqlite", "postgres", "mysql")
@@ -0,0 +1,30 @@
+"""Module-scoped generator fixtures must tear down in reverse dependency order."""
+
+from rustest import fixture
+
+_teardown_order = []
+
+
+@fixture(scope="module")
+def a():
+    yield "a"
+    _teardown_order.append("a")
+
+
+@fixture(scope="module")
+def b(a):
+    yield a + "b"
+    _teardown_order.append("b")
+
+
+def test_uses_a_directly(a):
+    assert a == "a"
+
+
+def test_uses_b(b):
+    assert b == "ab"
+
+
+def test_requests_b_before_a(b, a):
+    assert (a, b) == ("a", "ab")
+    assert _teardown_order == []
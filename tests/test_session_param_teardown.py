@@ -0,0 +1,22 @@
+"""Parametrized session fixtures release each value once no test needs it."""
+
+from rustest import fixture
+
+_events = []
+
+
+@fixture(scope="session", params=["A", "B"])
+def heavy_resource(request):
+    _events.append(f"setup {request.param}")
+    yield request.param
+    _events.append(f"teardown {request.param}")
+
+
+def test_uses_resource(heavy_resource):
+    assert heavy_resource in ("A", "B")
+
+
+def test_values_are_not_alive_together():
+    # "A" must be gone before "B" is created; when "B" is torn down depends
+    # on the runner (right after its last test, or at session end).
+    assert _events[:3] == ["setup A", "teardown A", "setup B"]